@@ -227,6 +227,56 @@ public:
         resvg_options_set_image_rendering_mode(d, mode);
     }
 
+    /**
+     * @brief Adds a CSS style sheet that will be applied to the document.
+     *
+     * Applied in addition to the `style` elements inside the SVG.
+     * Can be called multiple times.
+     */
+    void addStyleSheet(const QString &css)
+    {
+        auto cssC = css.toUtf8();
+        cssC.append('\0');
+        resvg_options_add_stylesheet(d, cssC.constData());
+    }
+
+    /**
+     * @brief Sets the cascade origin of style sheets added via `addStyleSheet`.
+     *
+     * Default: `RESVG_STYLESHEET_ORIGIN_AUTHOR`
+     */
+    void setStyleSheetOrigin(const resvg_stylesheet_origin origin)
+    {
+        resvg_options_set_stylesheet_origin(d, origin);
+    }
+
+    /**
+     * @brief Sets a CSS custom property on the document root.
+     *
+     * The name must include the `--` prefix.
+     * Can be called multiple times.
+     */
+    void addCssVariable(const QString &name, const QString &value)
+    {
+        auto nameC = name.toUtf8();
+        nameC.append('\0');
+        auto valueC = value.toUtf8();
+        valueC.append('\0');
+        resvg_options_add_css_variable(d, nameC.constData(), valueC.constData());
+    }
+
+    /**
+     * @brief Sets the initial `color` property value, i.e. the `currentColor`.
+     *
+     * Default: black
+     */
+    void setCurrentColor(const QString &color)
+    {
+        auto colorC = color.toUtf8();
+        colorC.append('\0');
+        resvg_options_set_current_color(d, colorC.constData());
+    }
+
     /**
      * @brief Loads a font data into the internal fonts database.
      *
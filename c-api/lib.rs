@@ -465,6 +465,89 @@ pub extern "C" fn resvg_options_set_image_rendering_mode(
     }
 }
 
+/// @brief Adds a CSS style sheet that will be applied to the document.
+///
+/// Applied in addition to the `style` elements inside the SVG.
+/// Can be called multiple times.
+///
+/// Must be UTF-8. NULL is not allowed.
+#[no_mangle]
+pub extern "C" fn resvg_options_add_stylesheet(opt: *mut resvg_options, css: *const c_char) {
+    let css = match cstr_to_str(css) {
+        Some(v) => v,
+        None => return,
+    };
+
+    cast_opt(opt).style_sheet.push(css.to_string());
+}
+
+/// @brief A style sheet cascade origin.
+#[repr(C)]
+#[allow(missing_docs)]
+#[derive(Copy, Clone)]
+pub enum resvg_stylesheet_origin {
+    USER_AGENT,
+    AUTHOR,
+}
+
+/// @brief Sets the cascade origin of style sheets added via `resvg_options_add_stylesheet`.
+///
+/// User agent style sheets are applied before the document styles
+/// and author style sheets are applied after them.
+///
+/// Default: `RESVG_STYLESHEET_ORIGIN_AUTHOR`
+#[no_mangle]
+pub extern "C" fn resvg_options_set_stylesheet_origin(
+    opt: *mut resvg_options,
+    origin: resvg_stylesheet_origin,
+) {
+    cast_opt(opt).style_sheet_origin = match origin as i32 {
+        0 => usvg::StyleSheetOrigin::UserAgent,
+        1 => usvg::StyleSheetOrigin::Author,
+        _ => return,
+    }
+}
+
+/// @brief Sets a CSS custom property on the document root.
+///
+/// Allows theming `var()` references inside the SVG.
+/// The name must include the `--` prefix.
+/// Can be called multiple times.
+///
+/// Must be UTF-8. NULL is not allowed.
+#[no_mangle]
+pub extern "C" fn resvg_options_add_css_variable(
+    opt: *mut resvg_options,
+    name: *const c_char,
+    value: *const c_char,
+) {
+    let (name, value) = match (cstr_to_str(name), cstr_to_str(value)) {
+        (Some(name), Some(value)) => (name, value),
+        _ => return,
+    };
+
+    cast_opt(opt)
+        .custom_properties
+        .push((name.to_string(), value.to_string()));
+}
+
+/// @brief Sets the initial `color` property value, i.e. the `currentColor`.
+///
+/// Accepts any CSS color.
+/// Will be used when no `color` attribute is set in the SVG.
+///
+/// Must be UTF-8. Sets the default value when `NULL` is set.
+///
+/// Default: black
+#[no_mangle]
+pub extern "C" fn resvg_options_set_current_color(opt: *mut resvg_options, color: *const c_char) {
+    cast_opt(opt).color = if color.is_null() {
+        None
+    } else {
+        cstr_to_str(color).map(|s| s.to_string())
+    };
+}
+
 /// @brief Loads a font data into the internal fonts database.
 ///
 /// Prints a warning into the log when the data is not a valid TrueType font.
@@ -100,6 +100,14 @@ typedef enum {
     RESVG_SHAPE_RENDERING_GEOMETRIC_PRECISION,
 } resvg_shape_rendering;
 
+/**
+ * @brief A style sheet cascade origin.
+ */
+typedef enum {
+    RESVG_STYLESHEET_ORIGIN_USER_AGENT,
+    RESVG_STYLESHEET_ORIGIN_AUTHOR,
+} resvg_stylesheet_origin;
+
 /**
  * @brief A text rendering method.
  */
@@ -349,6 +357,49 @@ void resvg_options_set_text_rendering_mode(resvg_options *opt, resvg_text_render
  */
 void resvg_options_set_image_rendering_mode(resvg_options *opt, resvg_image_rendering mode);
 
+/**
+ * @brief Adds a CSS style sheet that will be applied to the document.
+ *
+ * Applied in addition to the `style` elements inside the SVG.
+ * Can be called multiple times.
+ *
+ * Must be UTF-8. NULL is not allowed.
+ */
+void resvg_options_add_stylesheet(resvg_options *opt, const char *css);
+
+/**
+ * @brief Sets the cascade origin of style sheets added via `resvg_options_add_stylesheet`.
+ *
+ * User agent style sheets are applied before the document styles
+ * and author style sheets are applied after them.
+ *
+ * Default: `RESVG_STYLESHEET_ORIGIN_AUTHOR`
+ */
+void resvg_options_set_stylesheet_origin(resvg_options *opt, resvg_stylesheet_origin origin);
+
+/**
+ * @brief Sets a CSS custom property on the document root.
+ *
+ * Allows theming `var()` references inside the SVG.
+ * The name must include the `--` prefix.
+ * Can be called multiple times.
+ *
+ * Must be UTF-8. NULL is not allowed.
+ */
+void resvg_options_add_css_variable(resvg_options *opt, const char *name, const char *value);
+
+/**
+ * @brief Sets the initial `color` property value, i.e. the `currentColor`.
+ *
+ * Accepts any CSS color.
+ * Will be used when no `color` attribute is set in the SVG.
+ *
+ * Must be UTF-8. Sets the default value when `NULL` is set.
+ *
+ * Default: black
+ */
+void resvg_options_set_current_color(resvg_options *opt, const char *color);
+
 /**
  * @brief Loads a font data into the internal fonts database.
  *
@@ -11,6 +11,9 @@ fn main() {
             aspect: usvg::AspectRatio::default(),
         },
         root: usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default())),
+        font_faces: Vec::new(),
+        svg_fonts: Vec::new(),
+        diagnostics: Vec::new(),
     };
 
     let gradient = usvg::LinearGradient {
@@ -28,11 +31,13 @@ fn main() {
                     offset: usvg::StopOffset::ZERO,
                     color: usvg::Color::new_rgb(0, 255, 0),
                     opacity: usvg::Opacity::ONE,
+                    specified_color: None,
                 },
                 usvg::Stop {
                     offset: usvg::StopOffset::ONE,
                     color: usvg::Color::new_rgb(0, 255, 0),
                     opacity: usvg::Opacity::ZERO,
+                    specified_color: None,
                 },
             ],
         },
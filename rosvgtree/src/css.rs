@@ -0,0 +1,1924 @@
+//! A minimal CSS cascade implementation.
+//!
+//! Supports selectors level 3 (with a `:not()` from level 4),
+//! `@media`, `@namespace` and `@font-face` rules.
+
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use log::warn;
+
+use crate::{ColorScheme, FontFace, FontFaceSource};
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+const XML_NAMESPACE_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// An environment used to evaluate media queries.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MediaContext {
+    pub width: f64,
+    pub height: f64,
+    pub color_scheme: ColorScheme,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Declaration<'a> {
+    pub name: &'a str,
+    pub value: &'a str,
+    pub important: bool,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Rule<'a> {
+    pub selector: Selector<'a>,
+    pub declarations: Vec<Declaration<'a>>,
+}
+
+#[derive(Clone, Default, Debug)]
+pub(crate) struct StyleSheet<'a> {
+    pub rules: Vec<Rule<'a>>,
+    pub font_faces: Vec<FontFace>,
+    namespaces: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> StyleSheet<'a> {
+    pub fn new() -> Self {
+        StyleSheet::default()
+    }
+
+    /// Parses a style sheet from a text to the current style sheet.
+    ///
+    /// Rules are kept sorted by specificity.
+    /// Rules with the same specificity preserve the parsing order.
+    pub fn parse_more(&mut self, text: &'a str, media: &MediaContext) {
+        self.consume_statements(text, media);
+
+        // Stable sort, so the document order is preserved.
+        self.rules
+            .sort_by_cached_key(|rule| rule.selector.specificity());
+    }
+
+    fn consume_statements(&mut self, text: &'a str, media: &MediaContext) {
+        let mut s = Stream::new(text);
+        loop {
+            s.skip_spaces_and_comments();
+            if s.at_end() {
+                break;
+            }
+
+            if s.curr_byte() == Some(b'@') {
+                s.advance(1);
+                self.consume_at_rule(&mut s, media);
+            } else {
+                self.consume_rule_set(&mut s);
+            }
+        }
+    }
+
+    fn consume_at_rule(&mut self, s: &mut Stream<'a>, media: &MediaContext) {
+        let name = s.consume_ident().unwrap_or_default();
+        let prelude = s.consume_until(b"{;");
+
+        match s.curr_byte() {
+            Some(b'{') => {
+                let block = s.consume_block();
+                if name.eq_ignore_ascii_case("media") {
+                    if media_query_list_matches(prelude, media) {
+                        self.consume_statements(block, media);
+                    }
+                } else if name.eq_ignore_ascii_case("font-face") {
+                    self.consume_font_face(block);
+                } else {
+                    warn!("The @{} rule is not supported. Skipped.", name);
+                }
+            }
+            _ => {
+                s.advance(1);
+                if name.eq_ignore_ascii_case("namespace") {
+                    self.consume_namespace(prelude);
+                } else {
+                    warn!("The @{} rule is not supported. Skipped.", name);
+                }
+            }
+        }
+    }
+
+    fn consume_namespace(&mut self, prelude: &'a str) {
+        let mut s = Stream::new(prelude);
+        s.skip_spaces_and_comments();
+        let prefix = s.consume_ident().unwrap_or_default();
+        s.skip_spaces_and_comments();
+
+        let uri = s.tail().trim();
+        let uri = if let Some(uri) = uri.strip_prefix("url(") {
+            uri.strip_suffix(')').unwrap_or(uri).trim()
+        } else {
+            uri
+        };
+        let uri = uri.trim_matches(|c| c == '"' || c == '\'');
+
+        if prefix.is_empty() {
+            // TODO: default namespace
+            warn!("Default CSS namespaces are not supported.");
+            return;
+        }
+
+        self.namespaces.push((prefix, uri));
+    }
+
+    fn consume_font_face(&mut self, block: &str) {
+        let mut family = None;
+        let mut weight = None;
+        let mut style = None;
+        let mut unicode_range = Vec::new();
+        let mut sources = Vec::new();
+        for declaration in DeclarationTokenizer::from(block) {
+            let name = declaration.name.to_ascii_lowercase();
+            match name.as_str() {
+                "font-family" => family = parse_family_name(declaration.value),
+                "font-weight" => weight = Some(declaration.value.to_string()),
+                "font-style" => style = Some(declaration.value.to_string()),
+                "unicode-range" => match parse_unicode_range(declaration.value) {
+                    Some(v) => unicode_range = v,
+                    None => warn!("Failed to parse a '{}' unicode range.", declaration.value),
+                },
+                "src" => sources = parse_font_face_sources(declaration.value),
+                _ => {}
+            }
+        }
+
+        let family = match family {
+            Some(v) => v,
+            None => {
+                warn!("An @font-face rule without a font family. Skipped.");
+                return;
+            }
+        };
+
+        if sources.is_empty() {
+            warn!(
+                "An @font-face rule for '{}' without sources. Skipped.",
+                family
+            );
+            return;
+        }
+
+        self.font_faces.push(FontFace {
+            family,
+            weight,
+            style,
+            unicode_range,
+            sources,
+        });
+    }
+
+    fn consume_rule_set(&mut self, s: &mut Stream<'a>) {
+        let prelude = s.consume_until(b"{");
+        if s.curr_byte() != Some(b'{') {
+            // Skip an unmatched closing bracket.
+            s.advance(1);
+            return;
+        }
+
+        let block = s.consume_block();
+
+        let selectors = match parse_selector_list(prelude, &self.namespaces) {
+            Some(v) => v,
+            None => {
+                warn!(
+                    "Failed to parse a '{}' selector. Rule skipped.",
+                    prelude.trim()
+                );
+                return;
+            }
+        };
+
+        let declarations: Vec<_> = DeclarationTokenizer::from(block).collect();
+        if declarations.is_empty() {
+            return;
+        }
+
+        for selector in selectors {
+            self.rules.push(Rule {
+                selector,
+                declarations: declarations.clone(),
+            });
+        }
+    }
+}
+
+/// A declarations list tokenizer.
+///
+/// Invalid declarations are skipped.
+pub(crate) struct DeclarationTokenizer<'a> {
+    stream: Stream<'a>,
+}
+
+impl<'a> From<&'a str> for DeclarationTokenizer<'a> {
+    fn from(text: &'a str) -> Self {
+        DeclarationTokenizer {
+            stream: Stream::new(text),
+        }
+    }
+}
+
+impl<'a> Iterator for DeclarationTokenizer<'a> {
+    type Item = Declaration<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.stream.skip_spaces_and_comments();
+            while self.stream.curr_byte() == Some(b';') {
+                self.stream.advance(1);
+                self.stream.skip_spaces_and_comments();
+            }
+
+            if self.stream.at_end() {
+                return None;
+            }
+
+            let text = self.stream.consume_until(b";");
+            if !self.stream.at_end() && self.stream.curr_byte() != Some(b';') {
+                // An unmatched closing bracket. Ignore everything after it.
+                self.stream.pos = self.stream.text.len();
+            }
+
+            if let Some(declaration) = parse_declaration(text) {
+                return Some(declaration);
+            }
+        }
+    }
+}
+
+fn parse_declaration(text: &str) -> Option<Declaration<'_>> {
+    let (name, value) = text.split_once(':')?;
+
+    let name = trim_css(name);
+    // https://snook.ca/archives/html_and_css/targetting_ie7
+    let name = name.strip_prefix('*').unwrap_or(name);
+    if name.is_empty() || !name.bytes().all(is_ident_byte) {
+        return None;
+    }
+
+    let mut value = trim_css(value);
+    let mut important = false;
+    if let Some(idx) = value.rfind('!') {
+        if trim_css(&value[idx + 1..]).eq_ignore_ascii_case("important") {
+            value = trim_css(&value[..idx]);
+            important = true;
+        }
+    }
+
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(Declaration {
+        name,
+        value,
+        important,
+    })
+}
+
+/// Parses a single font family name, either quoted or as a sequence of identifiers.
+fn parse_family_name(text: &str) -> Option<String> {
+    let text = trim_css(text);
+    if text.starts_with(['"', '\'']) {
+        let mut s = Stream::new(text);
+        let name = s.consume_string()?;
+        s.skip_spaces_and_comments();
+        return if s.at_end() {
+            Some(name.to_string())
+        } else {
+            None
+        };
+    }
+
+    let mut name = String::new();
+    for ident in text.split_ascii_whitespace() {
+        if !ident.bytes().all(is_ident_byte) {
+            return None;
+        }
+
+        if !name.is_empty() {
+            name.push(' ');
+        }
+        name.push_str(ident);
+    }
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Parses a `unicode-range` descriptor, like `U+0-7F, U+4??`.
+fn parse_unicode_range(text: &str) -> Option<Vec<std::ops::RangeInclusive<u32>>> {
+    fn parse_hex(text: &str) -> Option<u32> {
+        if text.is_empty() || text.len() > 6 {
+            return None;
+        }
+
+        u32::from_str_radix(text, 16).ok()
+    }
+
+    let mut ranges = Vec::new();
+    for item in text.split(',') {
+        let item = trim_css(item);
+        let item = item
+            .strip_prefix("U+")
+            .or_else(|| item.strip_prefix("u+"))?;
+
+        let (start, end) = if let Some((start, end)) = item.split_once('-') {
+            (parse_hex(start)?, parse_hex(end)?)
+        } else if item.ends_with('?') {
+            let digits = item.trim_end_matches('?');
+            if item.len() > 6 || digits.contains('?') {
+                return None;
+            }
+
+            let start = if digits.is_empty() {
+                0
+            } else {
+                parse_hex(digits)?
+            };
+            let wildcards = (item.len() - digits.len()) as u32;
+            let start = start << (wildcards * 4);
+            (start, start | ((1 << (wildcards * 4)) - 1))
+        } else {
+            let c = parse_hex(item)?;
+            (c, c)
+        };
+
+        if start > end || start > 0x10FFFF {
+            return None;
+        }
+
+        ranges.push(start..=end.min(0x10FFFF));
+    }
+
+    Some(ranges)
+}
+
+/// Parses an `@font-face` `src` descriptor.
+///
+/// Invalid sources are skipped.
+fn parse_font_face_sources(text: &str) -> Vec<FontFaceSource> {
+    let mut sources = Vec::new();
+    let mut s = Stream::new(text);
+    while !s.at_end() {
+        let item = s.consume_until(b",");
+        s.advance(1);
+
+        match parse_font_face_source(item) {
+            Some(source) => sources.push(source),
+            None => warn!("Failed to parse a '{}' font source.", trim_css(item)),
+        }
+    }
+
+    sources
+}
+
+fn parse_font_face_source(text: &str) -> Option<FontFaceSource> {
+    // Consumes a function name with an opening parenthesis
+    // and returns the function arguments.
+    fn consume_function<'a>(s: &mut Stream<'a>) -> Option<(&'a str, &'a str)> {
+        s.skip_spaces_and_comments();
+        let name = s.consume_ident()?;
+        if s.curr_byte() != Some(b'(') {
+            return None;
+        }
+
+        s.advance(1);
+        let args = s.consume_until(b")");
+        if s.curr_byte() != Some(b')') {
+            return None;
+        }
+        s.advance(1);
+
+        Some((name, trim_css(args)))
+    }
+
+    let mut s = Stream::new(text);
+    let (name, args) = consume_function(&mut s)?;
+    let source = if name.eq_ignore_ascii_case("url") {
+        let url = if args.starts_with(['"', '\'']) {
+            Stream::new(args).consume_string()?
+        } else {
+            args
+        };
+
+        let mut format = None;
+        loop {
+            s.skip_spaces_and_comments();
+            if s.at_end() {
+                break;
+            }
+
+            // Other hints, like `tech()`, are ignored.
+            let (name, args) = consume_function(&mut s)?;
+            if name.eq_ignore_ascii_case("format") {
+                format = Some(parse_family_name(args)?.to_ascii_lowercase());
+            }
+        }
+
+        FontFaceSource::Url {
+            url: url.to_string(),
+            format,
+        }
+    } else if name.eq_ignore_ascii_case("local") {
+        FontFaceSource::Local(parse_family_name(args)?)
+    } else {
+        return None;
+    };
+
+    s.skip_spaces_and_comments();
+    if s.at_end() {
+        Some(source)
+    } else {
+        None
+    }
+}
+
+/// Trims spaces and comments at both ends.
+fn trim_css(mut text: &str) -> &str {
+    loop {
+        let prev_len = text.len();
+        text = text.trim();
+
+        if let Some(tail) = text.strip_prefix("/*") {
+            text = match tail.find("*/") {
+                Some(idx) => &tail[idx + 2..],
+                None => "",
+            };
+        }
+
+        if text.ends_with("*/") {
+            if let Some(idx) = text.rfind("/*") {
+                text = &text[..idx];
+            }
+        }
+
+        if text.len() == prev_len {
+            return text;
+        }
+    }
+}
+
+/// Checks that a declaration value contains `var()` functions.
+pub(crate) fn has_vars(value: &str) -> bool {
+    value
+        .as_bytes()
+        .windows(4)
+        .any(|w| w.eq_ignore_ascii_case(b"var("))
+}
+
+/// The maximum length of a value after `var()` substitution.
+///
+/// Like in browsers, longer values are invalid, since they are usually a result
+/// of an exponential expansion, like `--b: var(--a) var(--a)`.
+const MAX_SUBSTITUTED_LEN: usize = 64 * 1024;
+
+/// Substitutes `var()` functions in a declaration value.
+///
+/// `lookup` returns a custom property value by name.
+///
+/// Returns `None` when a referenced custom property is not set and there is no fallback,
+/// or when the result is longer than 64 KiB,
+/// which makes the declaration invalid at computed-value time.
+pub(crate) fn substitute_vars(
+    value: &str,
+    lookup: &mut dyn FnMut(&str) -> Option<Rc<str>>,
+) -> Option<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut last_pos = 0;
+    let mut s = Stream::new(value);
+    while let Some(c) = s.curr_byte() {
+        match c {
+            b'"' | b'\'' => {
+                s.advance(1);
+                s.skip_string_content(c);
+                s.advance(1);
+            }
+            b'/' if s.next_byte() == Some(b'*') => {
+                s.skip_spaces_and_comments();
+            }
+            c if is_ident_start_byte(c) => {
+                let start = s.pos;
+                let ident = s.consume_ident()?;
+                if ident.eq_ignore_ascii_case("var") && s.curr_byte() == Some(b'(') {
+                    s.advance(1);
+                    let args = s.consume_until(b")");
+                    // An unclosed function is closed by the end of the value.
+                    s.advance(1);
+
+                    result.push_str(&value[last_pos..start]);
+                    result.push_str(&resolve_var(args, lookup)?);
+                    last_pos = s.pos;
+
+                    if result.len() > MAX_SUBSTITUTED_LEN {
+                        log::warn!("A value with var() functions is too long.");
+                        return None;
+                    }
+                }
+            }
+            _ => s.advance(1),
+        }
+    }
+
+    result.push_str(&value[last_pos..]);
+    Some(trim_css(&result).to_string())
+}
+
+fn resolve_var(args: &str, lookup: &mut dyn FnMut(&str) -> Option<Rc<str>>) -> Option<Rc<str>> {
+    let (name, fallback) = match args.split_once(',') {
+        Some((name, fallback)) => (name, Some(fallback)),
+        None => (args, None),
+    };
+
+    let name = trim_css(name);
+    if !name.starts_with("--") {
+        log::warn!("'{}' is not a valid custom property name.", name);
+        return None;
+    }
+
+    if let Some(value) = lookup(name) {
+        return Some(value);
+    }
+
+    // An empty fallback, like in `var(--a,)`, is still a valid one.
+    substitute_vars(trim_css(fallback?), lookup).map(Rc::from)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Combinator {
+    None,
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum NamespaceMatch<'a> {
+    Any,
+    None,
+    Uri(&'a str),
+}
+
+impl NamespaceMatch<'_> {
+    fn matches(self, ns: Option<&str>) -> bool {
+        match self {
+            NamespaceMatch::Any => true,
+            NamespaceMatch::None => ns.is_none(),
+            NamespaceMatch::Uri(uri) => ns == Some(uri),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum AttributeOperator<'a> {
+    /// `[attr]`
+    Exists,
+    /// `[attr=value]`
+    Matches(Cow<'a, str>),
+    /// `[attr~=value]`
+    Contains(Cow<'a, str>),
+    /// `[attr|=value]`
+    DashMatches(Cow<'a, str>),
+    /// `[attr^=value]`
+    StartsWith(Cow<'a, str>),
+    /// `[attr$=value]`
+    EndsWith(Cow<'a, str>),
+    /// `[attr*=value]`
+    Substring(Cow<'a, str>),
+}
+
+impl AttributeOperator<'_> {
+    fn matches(&self, value: &str, ignore_case: bool) -> bool {
+        if ignore_case {
+            let value = value.to_ascii_lowercase();
+            return match self {
+                AttributeOperator::Exists => true,
+                AttributeOperator::Matches(v) => value == v.to_ascii_lowercase(),
+                AttributeOperator::Contains(v) => value
+                    .split_ascii_whitespace()
+                    .any(|s| s == v.to_ascii_lowercase()),
+                AttributeOperator::DashMatches(v) => dash_matches(&value, &v.to_ascii_lowercase()),
+                AttributeOperator::StartsWith(v) => {
+                    !v.is_empty() && value.starts_with(&v.to_ascii_lowercase())
+                }
+                AttributeOperator::EndsWith(v) => {
+                    !v.is_empty() && value.ends_with(&v.to_ascii_lowercase())
+                }
+                AttributeOperator::Substring(v) => {
+                    !v.is_empty() && value.contains(&v.to_ascii_lowercase())
+                }
+            };
+        }
+
+        match self {
+            AttributeOperator::Exists => true,
+            AttributeOperator::Matches(v) => value == v,
+            AttributeOperator::Contains(v) => value.split_ascii_whitespace().any(|s| s == v),
+            AttributeOperator::DashMatches(v) => dash_matches(value, v),
+            AttributeOperator::StartsWith(v) => !v.is_empty() && value.starts_with(v.as_ref()),
+            AttributeOperator::EndsWith(v) => !v.is_empty() && value.ends_with(v.as_ref()),
+            AttributeOperator::Substring(v) => !v.is_empty() && value.contains(v.as_ref()),
+        }
+    }
+}
+
+fn dash_matches(value: &str, v: &str) -> bool {
+    value == v || (value.starts_with(v) && value.as_bytes().get(v.len()) == Some(&b'-'))
+}
+
+/// An `an+b` expression.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Nth {
+    a: i32,
+    b: i32,
+}
+
+impl Nth {
+    fn parse(text: &str) -> Option<Self> {
+        let text: String = text
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match text.as_str() {
+            "odd" => return Some(Nth { a: 2, b: 1 }),
+            "even" => return Some(Nth { a: 2, b: 0 }),
+            _ => {}
+        }
+
+        match text.find('n') {
+            Some(idx) => {
+                let a = match &text[..idx] {
+                    "" | "+" => 1,
+                    "-" => -1,
+                    s => s.parse().ok()?,
+                };
+
+                let b_text = &text[idx + 1..];
+                let b = if b_text.is_empty() {
+                    0
+                } else if b_text.starts_with('+') || b_text.starts_with('-') {
+                    b_text.parse().ok()?
+                } else {
+                    return None;
+                };
+
+                Some(Nth { a, b })
+            }
+            None => Some(Nth {
+                a: 0,
+                b: text.parse().ok()?,
+            }),
+        }
+    }
+
+    /// Checks that the 1-based `index` matches the expression.
+    fn matches(self, index: i32) -> bool {
+        // `a` and `b` can be any i32, so compute in i64 to avoid overflows.
+        let (a, b, index) = (i64::from(self.a), i64::from(self.b), i64::from(index));
+        if a == 0 {
+            index == b
+        } else {
+            let diff = index - b;
+            diff % a == 0 && diff / a >= 0
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum PseudoClass<'a> {
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    FirstOfType,
+    LastOfType,
+    OnlyOfType,
+    NthChild(Nth),
+    NthLastChild(Nth),
+    NthOfType(Nth),
+    NthLastOfType(Nth),
+    Root,
+    Empty,
+    Not(Vec<Selector<'a>>),
+    Lang(&'a str),
+    /// `:hover`, `:focus`, etc.
+    ///
+    /// Since we are querying a static SVG we can ignore them.
+    Dynamic,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum SubSelector<'a> {
+    Id(Cow<'a, str>),
+    Class(Cow<'a, str>),
+    Attribute {
+        namespace: NamespaceMatch<'a>,
+        name: Cow<'a, str>,
+        operator: AttributeOperator<'a>,
+        ignore_case: bool,
+    },
+    PseudoClass(PseudoClass<'a>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct CompoundSelector<'a> {
+    namespace: NamespaceMatch<'a>,
+    /// `None` indicates a universal selector.
+    local_name: Option<Cow<'a, str>>,
+    subselectors: Vec<SubSelector<'a>>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Component<'a> {
+    /// A combinator that precede the selector.
+    combinator: Combinator,
+    selector: CompoundSelector<'a>,
+}
+
+/// A complex selector.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Selector<'a> {
+    components: Vec<Component<'a>>,
+}
+
+impl<'a> Selector<'a> {
+    /// Computes the selector's specificity.
+    ///
+    /// Cf. https://www.w3.org/TR/selectors/#specificity.
+    pub fn specificity(&self) -> [u32; 3] {
+        let mut spec = [0u32; 3];
+
+        for selector in self.components.iter().map(|c| &c.selector) {
+            if selector.local_name.is_some() {
+                spec[2] += 1;
+            }
+
+            for sub in &selector.subselectors {
+                match sub {
+                    SubSelector::Id(_) => spec[0] += 1,
+                    SubSelector::PseudoClass(PseudoClass::Not(list)) => {
+                        // `:not()` has the specificity of the most specific selector in its list.
+                        if let Some(max) = list.iter().map(|s| s.specificity()).max() {
+                            for (a, b) in spec.iter_mut().zip(max.iter()) {
+                                *a += *b;
+                            }
+                        }
+                    }
+                    _ => spec[1] += 1,
+                }
+            }
+        }
+
+        spec
+    }
+
+    /// Checks that the provided element matches the current selector.
+    pub fn matches(&self, node: roxmltree::Node) -> bool {
+        debug_assert!(!self.components.is_empty());
+        self.matches_impl(self.components.len() - 1, node)
+    }
+
+    fn matches_impl(&self, idx: usize, node: roxmltree::Node) -> bool {
+        let component = &self.components[idx];
+
+        if !match_compound_selector(&component.selector, node) {
+            return false;
+        }
+
+        match component.combinator {
+            Combinator::None => true,
+            Combinator::Descendant => node
+                .ancestors()
+                .skip(1)
+                .filter(|n| n.is_element())
+                .any(|n| self.matches_impl(idx - 1, n)),
+            Combinator::Child => node
+                .parent_element()
+                .is_some_and(|n| self.matches_impl(idx - 1, n)),
+            Combinator::NextSibling => node
+                .prev_sibling_element()
+                .is_some_and(|n| self.matches_impl(idx - 1, n)),
+            Combinator::SubsequentSibling => {
+                let mut prev = node.prev_sibling_element();
+                while let Some(n) = prev {
+                    if self.matches_impl(idx - 1, n) {
+                        return true;
+                    }
+
+                    prev = n.prev_sibling_element();
+                }
+
+                false
+            }
+        }
+    }
+}
+
+fn match_compound_selector(selector: &CompoundSelector, node: roxmltree::Node) -> bool {
+    if !node.is_element() {
+        return false;
+    }
+
+    if let Some(ref local_name) = selector.local_name {
+        if node.tag_name().name() != local_name {
+            return false;
+        }
+    }
+
+    if !selector.namespace.matches(node.tag_name().namespace()) {
+        return false;
+    }
+
+    selector.subselectors.iter().all(|sub| match sub {
+        SubSelector::Id(id) => node.attribute("id") == Some(id.as_ref()),
+        SubSelector::Class(class) => node
+            .attribute("class")
+            .is_some_and(|v| v.split_ascii_whitespace().any(|c| c == class)),
+        SubSelector::Attribute {
+            namespace,
+            name,
+            operator,
+            ignore_case,
+        } => node
+            .attributes()
+            .find(|a| a.name() == *name && namespace.matches(a.namespace()))
+            .is_some_and(|a| operator.matches(a.value(), *ignore_case)),
+        SubSelector::PseudoClass(class) => match_pseudo_class(class, node),
+    })
+}
+
+fn match_pseudo_class(class: &PseudoClass, node: roxmltree::Node) -> bool {
+    // Element siblings, including the current one.
+    let siblings = || {
+        node.parent()
+            .into_iter()
+            .flat_map(|p| p.children())
+            .filter(|n| n.is_element())
+    };
+    let same_type = |n: &roxmltree::Node| n.tag_name() == node.tag_name();
+
+    let index = || siblings().position(|n| n == node).unwrap_or(0) as i32 + 1;
+    let last_index = || siblings().count() as i32 - index() + 1;
+    let type_index = || {
+        siblings()
+            .filter(same_type)
+            .position(|n| n == node)
+            .unwrap_or(0) as i32
+            + 1
+    };
+    let last_type_index = || siblings().filter(same_type).count() as i32 - type_index() + 1;
+
+    match class {
+        PseudoClass::FirstChild => node.prev_sibling_element().is_none(),
+        PseudoClass::LastChild => node.next_sibling_element().is_none(),
+        PseudoClass::OnlyChild => {
+            node.prev_sibling_element().is_none() && node.next_sibling_element().is_none()
+        }
+        PseudoClass::FirstOfType => type_index() == 1,
+        PseudoClass::LastOfType => last_type_index() == 1,
+        PseudoClass::OnlyOfType => type_index() == 1 && last_type_index() == 1,
+        PseudoClass::NthChild(nth) => nth.matches(index()),
+        PseudoClass::NthLastChild(nth) => nth.matches(last_index()),
+        PseudoClass::NthOfType(nth) => nth.matches(type_index()),
+        PseudoClass::NthLastOfType(nth) => nth.matches(last_type_index()),
+        PseudoClass::Root => node.parent().is_some_and(|p| p.is_root()),
+        PseudoClass::Empty => !node
+            .children()
+            .any(|n| n.is_element() || n.text().is_some_and(|t| !t.is_empty())),
+        PseudoClass::Not(list) => !list.iter().any(|s| s.matches(node)),
+        PseudoClass::Lang(ranges) => element_lang(node).is_some_and(|lang| {
+            ranges
+                .split(',')
+                .map(|range| range.trim().trim_matches(|c| c == '"' || c == '\''))
+                .any(|range| lang_range_matches(range, lang))
+        }),
+        PseudoClass::Dynamic => false,
+    }
+}
+
+/// Returns an element's language, inherited from the nearest ancestor.
+///
+/// `xml:lang` takes precedence over `lang`.
+fn element_lang<'a>(node: roxmltree::Node<'a, '_>) -> Option<&'a str> {
+    node.ancestors().filter(|n| n.is_element()).find_map(|n| {
+        n.attribute((XML_NAMESPACE_NS, "lang"))
+            .or_else(|| n.attribute("lang"))
+    })
+}
+
+/// Checks that a language matches a language range, like `en` for `en-US`.
+///
+/// An empty language never matches.
+fn lang_range_matches(range: &str, lang: &str) -> bool {
+    if range.is_empty() || lang.is_empty() {
+        return false;
+    }
+
+    if range.eq_ignore_ascii_case(lang) {
+        return true;
+    }
+
+    lang.len() > range.len()
+        && lang.as_bytes()[range.len()] == b'-'
+        && lang[..range.len()].eq_ignore_ascii_case(range)
+}
+
+/// Resolves escapes in an identifier or a string.
+///
+/// Cf. https://www.w3.org/TR/css-syntax-3/#consume-escaped-code-point
+fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('\\') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        let mut code = 0;
+        let mut digits = 0;
+        while let Some(d) = chars.peek().and_then(|c| c.to_digit(16)) {
+            if digits == 6 {
+                break;
+            }
+
+            code = code * 16 + d;
+            digits += 1;
+            chars.next();
+        }
+
+        if digits > 0 {
+            // A single whitespace after a hex escape is a part of it.
+            chars.next_if(|c| c.is_ascii_whitespace());
+            let c = char::from_u32(code).filter(|c| *c != '\0');
+            result.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+        } else {
+            match chars.next() {
+                // An escaped newline is removed.
+                Some('\n') | None => {}
+                Some(c) => result.push(c),
+            }
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+fn parse_selector_list<'a>(
+    text: &'a str,
+    namespaces: &[(&'a str, &'a str)],
+) -> Option<Vec<Selector<'a>>> {
+    let mut s = Stream::new(text);
+    let list = parse_selector_list_impl(&mut s, namespaces)?;
+    s.skip_spaces_and_comments();
+    if s.at_end() {
+        Some(list)
+    } else {
+        None
+    }
+}
+
+/// Parses a comma-separated list of complex selectors.
+///
+/// Stops at EOF or `)`.
+fn parse_selector_list_impl<'a>(
+    s: &mut Stream<'a>,
+    namespaces: &[(&'a str, &'a str)],
+) -> Option<Vec<Selector<'a>>> {
+    let mut list = Vec::new();
+    loop {
+        s.skip_spaces_and_comments();
+        list.push(parse_complex_selector(s, namespaces)?);
+
+        s.skip_spaces_and_comments();
+        if s.curr_byte() == Some(b',') {
+            s.advance(1);
+        } else {
+            break;
+        }
+    }
+
+    Some(list)
+}
+
+fn parse_complex_selector<'a>(
+    s: &mut Stream<'a>,
+    namespaces: &[(&'a str, &'a str)],
+) -> Option<Selector<'a>> {
+    let mut components = Vec::new();
+    let mut combinator = Combinator::None;
+    loop {
+        let selector = parse_compound_selector(s, namespaces)?;
+        components.push(Component {
+            combinator,
+            selector,
+        });
+
+        let has_spaces = s.skip_spaces_and_comments();
+        combinator = match s.curr_byte() {
+            None | Some(b',') | Some(b')') => break,
+            Some(b'>') => Combinator::Child,
+            Some(b'+') => Combinator::NextSibling,
+            Some(b'~') => Combinator::SubsequentSibling,
+            _ if has_spaces => Combinator::Descendant,
+            _ => return None,
+        };
+
+        if combinator != Combinator::Descendant {
+            s.advance(1);
+            s.skip_spaces_and_comments();
+        }
+    }
+
+    Some(Selector { components })
+}
+
+fn parse_compound_selector<'a>(
+    s: &mut Stream<'a>,
+    namespaces: &[(&'a str, &'a str)],
+) -> Option<CompoundSelector<'a>> {
+    let mut selector = CompoundSelector {
+        namespace: NamespaceMatch::Any,
+        local_name: None,
+        subselectors: Vec::new(),
+    };
+
+    let mut is_empty = true;
+    if let Some((namespace, name)) = parse_qualified_name(s, namespaces, NamespaceMatch::Any)? {
+        selector.namespace = namespace;
+        selector.local_name = name;
+        is_empty = false;
+    }
+
+    loop {
+        let sub = match s.curr_byte() {
+            Some(b'#') => {
+                s.advance(1);
+                SubSelector::Id(unescape(s.consume_ident()?))
+            }
+            Some(b'.') => {
+                s.advance(1);
+                SubSelector::Class(unescape(s.consume_ident()?))
+            }
+            Some(b'[') => {
+                s.advance(1);
+                parse_attribute_selector(s, namespaces)?
+            }
+            Some(b':') => {
+                s.advance(1);
+                if s.curr_byte() == Some(b':') {
+                    warn!("Pseudo-elements are not supported.");
+                    return None;
+                }
+
+                SubSelector::PseudoClass(parse_pseudo_class(s, namespaces)?)
+            }
+            _ => break,
+        };
+
+        selector.subselectors.push(sub);
+        is_empty = false;
+    }
+
+    if is_empty {
+        None
+    } else {
+        Some(selector)
+    }
+}
+
+/// Parses `ns|name`, `ns|*`, `name` and `*`.
+///
+/// Returns `Ok(None)` when there is no name at all.
+/// `None` name in the result indicates `*`.
+#[allow(clippy::type_complexity)]
+fn parse_qualified_name<'a>(
+    s: &mut Stream<'a>,
+    namespaces: &[(&'a str, &'a str)],
+    default_namespace: NamespaceMatch<'a>,
+) -> Option<Option<(NamespaceMatch<'a>, Option<Cow<'a, str>>)>> {
+    let first = match s.curr_byte() {
+        Some(b'*') => {
+            s.advance(1);
+            Some("*")
+        }
+        Some(b'|') => None,
+        Some(c) if is_ident_start_byte(c) => Some(s.consume_ident()?),
+        _ => return Some(None),
+    };
+
+    let is_namespace_separator = s.curr_byte() == Some(b'|') && s.next_byte() != Some(b'=');
+    if !is_namespace_separator {
+        return Some(first.map(|name| {
+            let name = Some(name).filter(|n| *n != "*").map(unescape);
+            (default_namespace, name)
+        }));
+    }
+
+    s.advance(1); // |
+
+    let namespace = match first {
+        None => NamespaceMatch::None,
+        Some("*") => NamespaceMatch::Any,
+        Some(prefix) => NamespaceMatch::Uri(resolve_namespace(prefix, namespaces)?),
+    };
+
+    let name = if s.curr_byte() == Some(b'*') {
+        s.advance(1);
+        None
+    } else {
+        Some(unescape(s.consume_ident()?))
+    };
+
+    Some(Some((namespace, name)))
+}
+
+fn resolve_namespace<'a>(prefix: &str, namespaces: &[(&'a str, &'a str)]) -> Option<&'a str> {
+    if let Some((_, uri)) = namespaces.iter().rev().find(|(p, _)| *p == prefix) {
+        return Some(uri);
+    }
+
+    // Well-known prefixes can be used without an explicit `@namespace` rule.
+    match prefix {
+        "svg" => Some(SVG_NS),
+        "xlink" => Some(XLINK_NS),
+        "xml" => Some(XML_NAMESPACE_NS),
+        _ => {
+            warn!("Unknown CSS namespace prefix: '{}'.", prefix);
+            None
+        }
+    }
+}
+
+fn parse_attribute_selector<'a>(
+    s: &mut Stream<'a>,
+    namespaces: &[(&'a str, &'a str)],
+) -> Option<SubSelector<'a>> {
+    s.skip_spaces_and_comments();
+
+    // Attribute selectors without a namespace prefix match only attributes without a namespace.
+    let (namespace, name) = parse_qualified_name(s, namespaces, NamespaceMatch::None)??;
+    let name = name?;
+
+    s.skip_spaces_and_comments();
+
+    let mut ignore_case = false;
+    let operator = if s.curr_byte() == Some(b']') {
+        AttributeOperator::Exists
+    } else {
+        let op = match (s.curr_byte()?, s.next_byte()?) {
+            (b'=', _) => {
+                s.advance(1);
+                b'='
+            }
+            (c, b'=') => {
+                s.advance(2);
+                c
+            }
+            _ => return None,
+        };
+
+        s.skip_spaces_and_comments();
+        let value = unescape(s.consume_string()?);
+        s.skip_spaces_and_comments();
+
+        match s.curr_byte() {
+            Some(b'i') | Some(b'I') => {
+                s.advance(1);
+                ignore_case = true;
+            }
+            Some(b's') | Some(b'S') => s.advance(1),
+            _ => {}
+        }
+
+        s.skip_spaces_and_comments();
+
+        match op {
+            b'=' => AttributeOperator::Matches(value),
+            b'~' => AttributeOperator::Contains(value),
+            b'|' => AttributeOperator::DashMatches(value),
+            b'^' => AttributeOperator::StartsWith(value),
+            b'$' => AttributeOperator::EndsWith(value),
+            b'*' => AttributeOperator::Substring(value),
+            _ => return None,
+        }
+    };
+
+    if s.curr_byte() != Some(b']') {
+        return None;
+    }
+    s.advance(1);
+
+    Some(SubSelector::Attribute {
+        namespace,
+        name,
+        operator,
+        ignore_case,
+    })
+}
+
+fn parse_pseudo_class<'a>(
+    s: &mut Stream<'a>,
+    namespaces: &[(&'a str, &'a str)],
+) -> Option<PseudoClass<'a>> {
+    let name = s.consume_ident()?;
+
+    if s.curr_byte() == Some(b'(') {
+        s.advance(1);
+        s.skip_spaces_and_comments();
+
+        let class = if name.eq_ignore_ascii_case("not") {
+            PseudoClass::Not(parse_selector_list_impl(s, namespaces)?)
+        } else {
+            let args = s.consume_until(b")");
+            match name.to_ascii_lowercase().as_str() {
+                "nth-child" => PseudoClass::NthChild(Nth::parse(args)?),
+                "nth-last-child" => PseudoClass::NthLastChild(Nth::parse(args)?),
+                "nth-of-type" => PseudoClass::NthOfType(Nth::parse(args)?),
+                "nth-last-of-type" => PseudoClass::NthLastOfType(Nth::parse(args)?),
+                "lang" => PseudoClass::Lang(args),
+                _ => {
+                    warn!("':{}()' is not supported. Selector skipped.", name);
+                    return None;
+                }
+            }
+        };
+
+        s.skip_spaces_and_comments();
+        if s.curr_byte() != Some(b')') {
+            return None;
+        }
+        s.advance(1);
+
+        return Some(class);
+    }
+
+    let class = match name.to_ascii_lowercase().as_str() {
+        "first-child" => PseudoClass::FirstChild,
+        "last-child" => PseudoClass::LastChild,
+        "only-child" => PseudoClass::OnlyChild,
+        "first-of-type" => PseudoClass::FirstOfType,
+        "last-of-type" => PseudoClass::LastOfType,
+        "only-of-type" => PseudoClass::OnlyOfType,
+        "root" => PseudoClass::Root,
+        "empty" => PseudoClass::Empty,
+        "link" | "visited" | "any-link" | "hover" | "active" | "focus" | "focus-within"
+        | "focus-visible" | "target" => PseudoClass::Dynamic,
+        _ => {
+            warn!("':{}' is not supported. Selector skipped.", name);
+            return None;
+        }
+    };
+
+    Some(class)
+}
+
+/// Checks that a comma-separated media query list matches the provided context.
+///
+/// Supports media types and `and`-joined media features,
+/// including the range syntax from Media Queries Level 4.
+fn media_query_list_matches(text: &str, ctx: &MediaContext) -> bool {
+    // An empty list matches everything.
+    if trim_css(text).is_empty() {
+        return true;
+    }
+
+    let mut s = Stream::new(text);
+    loop {
+        let query = s.consume_until(b",");
+        if media_query_matches(query, ctx) {
+            return true;
+        }
+
+        if s.at_end() {
+            return false;
+        }
+
+        s.advance(1); // ,
+    }
+}
+
+fn media_query_matches(text: &str, ctx: &MediaContext) -> bool {
+    media_query_matches_impl(text, ctx).unwrap_or_else(|| {
+        warn!("Failed to parse a '{}' media query.", text.trim());
+        false
+    })
+}
+
+fn media_query_matches_impl(text: &str, ctx: &MediaContext) -> Option<bool> {
+    let mut s = Stream::new(text);
+    s.skip_spaces_and_comments();
+
+    let mut negate = false;
+    let mut result = true;
+    let mut needs_and = false;
+    if s.curr_byte() != Some(b'(') {
+        let mut ident = s.consume_ident()?;
+        if ident.eq_ignore_ascii_case("not") || ident.eq_ignore_ascii_case("only") {
+            negate = ident.eq_ignore_ascii_case("not");
+            s.skip_spaces_and_comments();
+            ident = s.consume_ident()?;
+        }
+
+        // We are rendering to an image, which is closer to a screen than to a paper.
+        result = ident.eq_ignore_ascii_case("all") || ident.eq_ignore_ascii_case("screen");
+        needs_and = true;
+    }
+
+    loop {
+        s.skip_spaces_and_comments();
+        if s.at_end() {
+            break;
+        }
+
+        if needs_and {
+            if !s.consume_ident()?.eq_ignore_ascii_case("and") {
+                return None;
+            }
+            s.skip_spaces_and_comments();
+        }
+
+        let mut negate_feature = false;
+        if s.curr_byte() != Some(b'(') {
+            if !s.consume_ident()?.eq_ignore_ascii_case("not") {
+                return None;
+            }
+            negate_feature = true;
+            s.skip_spaces_and_comments();
+        }
+
+        if s.curr_byte() != Some(b'(') {
+            return None;
+        }
+        s.advance(1);
+        let feature = s.consume_until(b")");
+        if s.curr_byte() != Some(b')') {
+            return None;
+        }
+        s.advance(1);
+
+        result &= media_feature_matches(feature, ctx)? != negate_feature;
+        needs_and = true;
+    }
+
+    Some(result != negate)
+}
+
+fn media_feature_matches(text: &str, ctx: &MediaContext) -> Option<bool> {
+    let text = trim_css(text);
+
+    if let Some((name, value)) = text.split_once(':') {
+        let name = trim_css(name).to_ascii_lowercase();
+        let value = trim_css(value);
+
+        let (prefix, name) = if let Some(name) = name.strip_prefix("min-") {
+            (Some(std::cmp::Ordering::Greater), name)
+        } else if let Some(name) = name.strip_prefix("max-") {
+            (Some(std::cmp::Ordering::Less), name)
+        } else {
+            (None, name.as_str())
+        };
+
+        return match name {
+            "prefers-color-scheme" if prefix.is_none() => {
+                match value.to_ascii_lowercase().as_str() {
+                    "light" => Some(ctx.color_scheme == ColorScheme::Light),
+                    "dark" => Some(ctx.color_scheme == ColorScheme::Dark),
+                    _ => None,
+                }
+            }
+            "orientation" if prefix.is_none() => match value.to_ascii_lowercase().as_str() {
+                "portrait" => Some(ctx.height >= ctx.width),
+                "landscape" => Some(ctx.width > ctx.height),
+                _ => None,
+            },
+            _ => {
+                let actual = media_feature_value(name, ctx)?;
+                let expected = parse_media_value(name, value)?;
+                let ord = actual.partial_cmp(&expected)?;
+                match prefix {
+                    Some(prefix) => Some(ord == prefix || ord == std::cmp::Ordering::Equal),
+                    None => Some(ord == std::cmp::Ordering::Equal),
+                }
+            }
+        };
+    }
+
+    // Range syntax. Like `(width >= 600px)` or `(400px < width)`.
+    if let Some(idx) = text.find(['<', '>', '=']) {
+        let op_len = if text[idx + 1..].starts_with('=') {
+            2
+        } else {
+            1
+        };
+        let op = &text[idx..idx + op_len];
+        let left = trim_css(&text[..idx]);
+        let right = trim_css(&text[idx + op_len..]);
+
+        let (name, value, op) = if media_feature_value(&left.to_ascii_lowercase(), ctx).is_some() {
+            (left.to_ascii_lowercase(), right, op.to_string())
+        } else {
+            // Mirror the operator, so the feature name is on the left.
+            let op = op.replace('<', "#").replace('>', "<").replace('#', ">");
+            (right.to_ascii_lowercase(), left, op)
+        };
+
+        let actual = media_feature_value(&name, ctx)?;
+        let expected = parse_media_value(&name, value)?;
+        return Some(match op.as_str() {
+            "<" => actual < expected,
+            "<=" => actual <= expected,
+            ">" => actual > expected,
+            ">=" => actual >= expected,
+            "=" => actual == expected,
+            _ => return None,
+        });
+    }
+
+    // Boolean context.
+    match text.to_ascii_lowercase().as_str() {
+        "prefers-color-scheme" | "orientation" | "color" => Some(true),
+        name => Some(media_feature_value(name, ctx)? != 0.0),
+    }
+}
+
+fn media_feature_value(name: &str, ctx: &MediaContext) -> Option<f64> {
+    match name {
+        "width" => Some(ctx.width),
+        "height" => Some(ctx.height),
+        "aspect-ratio" => Some(ctx.width / ctx.height),
+        _ => None,
+    }
+}
+
+fn parse_media_value(name: &str, value: &str) -> Option<f64> {
+    if name == "aspect-ratio" {
+        let (w, h) = value.split_once('/')?;
+        let w: f64 = w.trim().parse().ok()?;
+        let h: f64 = h.trim().parse().ok()?;
+        return Some(w / h);
+    }
+
+    parse_media_length(value)
+}
+
+/// Parses a length and converts it into CSS pixels.
+fn parse_media_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let idx = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(value.len());
+    let n: f64 = value[..idx].parse().ok()?;
+
+    // Relative units are resolved against the initial font size.
+    let factor = match value[idx..].to_ascii_lowercase().as_str() {
+        "" | "px" => 1.0,
+        "em" | "rem" => 16.0,
+        "ex" => 8.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "pt" => 4.0 / 3.0,
+        "pc" => 16.0,
+        _ => return None,
+    };
+
+    Some(n * factor)
+}
+
+#[inline]
+fn is_ident_start_byte(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c == b'-' || c == b'\\' || c >= 0x80
+}
+
+#[inline]
+fn is_ident_byte(c: u8) -> bool {
+    is_ident_start_byte(c) || c.is_ascii_digit()
+}
+
+struct Stream<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Stream<'a> {
+    fn new(text: &'a str) -> Self {
+        Stream { text, pos: 0 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    fn curr_byte(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).cloned()
+    }
+
+    fn next_byte(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos + 1).cloned()
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.pos = (self.pos + n).min(self.text.len());
+    }
+
+    fn tail(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// Skips spaces, comments and HTML comment tokens.
+    ///
+    /// Returns `true` when something was skipped.
+    fn skip_spaces_and_comments(&mut self) -> bool {
+        let start = self.pos;
+        loop {
+            let tail = self.tail();
+            if self.curr_byte().is_some_and(|c| c.is_ascii_whitespace()) {
+                self.advance(1);
+            } else if let Some(comment) = tail.strip_prefix("/*") {
+                match comment.find("*/") {
+                    Some(idx) => self.advance(idx + 4),
+                    None => self.pos = self.text.len(),
+                }
+            } else if tail.starts_with("<!--") {
+                self.advance(4);
+            } else if tail.starts_with("-->") {
+                self.advance(3);
+            } else {
+                break;
+            }
+        }
+
+        self.pos != start
+    }
+
+    fn consume_ident(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        if !self.curr_byte().is_some_and(is_ident_start_byte) {
+            return None;
+        }
+
+        while let Some(c) = self.curr_byte() {
+            if c == b'\\' {
+                // Skip an escaped character or up to 6 hex digits followed by a whitespace.
+                self.advance(1);
+                let hex_len = self
+                    .tail()
+                    .bytes()
+                    .take(6)
+                    .take_while(|c| c.is_ascii_hexdigit())
+                    .count();
+                if hex_len > 0 {
+                    self.advance(hex_len);
+                    if self.curr_byte().is_some_and(|c| c.is_ascii_whitespace()) {
+                        self.advance(1);
+                    }
+                } else {
+                    let len = self.tail().chars().next().map_or(0, |c| c.len_utf8());
+                    self.advance(len);
+                }
+            } else if is_ident_byte(c) {
+                self.advance(1);
+            } else {
+                break;
+            }
+        }
+
+        // Make sure we are not in the middle of a UTF-8 character.
+        while !self.text.is_char_boundary(self.pos) {
+            self.pos += 1;
+        }
+
+        Some(&self.text[start..self.pos])
+    }
+
+    /// Consumes a quoted string or an identifier.
+    fn consume_string(&mut self) -> Option<&'a str> {
+        match self.curr_byte()? {
+            quote @ b'\'' | quote @ b'"' => {
+                self.advance(1);
+                let start = self.pos;
+                self.skip_string_content(quote);
+                let value = &self.text[start..self.pos];
+                if self.curr_byte() != Some(quote) {
+                    return None;
+                }
+                self.advance(1);
+                Some(value)
+            }
+            _ => self.consume_ident(),
+        }
+    }
+
+    fn skip_string_content(&mut self, quote: u8) {
+        while let Some(c) = self.curr_byte() {
+            if c == quote {
+                break;
+            } else if c == b'\\' {
+                self.advance(2);
+            } else {
+                self.advance(1);
+            }
+        }
+    }
+
+    /// Consumes text until one of the `stop` bytes at the current nesting level.
+    ///
+    /// Strings, comments and nested blocks are skipped.
+    /// An unmatched closing bracket acts as a stop byte as well.
+    fn consume_until(&mut self, stop: &[u8]) -> &'a str {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(c) = self.curr_byte() {
+            if depth == 0 && stop.contains(&c) {
+                break;
+            }
+
+            match c {
+                b'"' | b'\'' => {
+                    self.advance(1);
+                    self.skip_string_content(c);
+                }
+                b'/' if self.next_byte() == Some(b'*') => {
+                    match self.tail()[2..].find("*/") {
+                        Some(idx) => self.advance(idx + 4),
+                        None => self.pos = self.text.len(),
+                    }
+                    continue;
+                }
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => {
+                    if depth == 0 {
+                        break;
+                    }
+
+                    depth -= 1;
+                }
+                _ => {}
+            }
+
+            self.advance(1);
+        }
+
+        &self.text[start..self.pos]
+    }
+
+    /// Consumes a `{}` block and returns its content.
+    fn consume_block(&mut self) -> &'a str {
+        debug_assert_eq!(self.curr_byte(), Some(b'{'));
+        self.advance(1);
+        let content = self.consume_until(b"}");
+        self.advance(1);
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEDIA: MediaContext = MediaContext {
+        width: 200.0,
+        height: 100.0,
+        color_scheme: ColorScheme::Light,
+    };
+
+    fn selector(text: &str) -> Option<Selector<'_>> {
+        let mut list = parse_selector_list(text, &[])?;
+        assert_eq!(list.len(), 1);
+        list.pop()
+    }
+
+    fn specificity(text: &str) -> [u32; 3] {
+        selector(text).unwrap().specificity()
+    }
+
+    /// Returns IDs of elements that match a selector.
+    fn select(xml: &str, selector_text: &str) -> Vec<String> {
+        let doc = roxmltree::Document::parse(xml).unwrap();
+        let list = parse_selector_list(selector_text, &[("x", XLINK_NS)]).unwrap();
+        doc.descendants()
+            .filter(|n| n.is_element() && list.iter().any(|s| s.matches(*n)))
+            .filter_map(|n| n.attribute("id"))
+            .map(|id| id.to_string())
+            .collect()
+    }
+
+    const DOC: &str = "\
+<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' id='svg'>
+    <g id='g1' class='a b' xml:lang='en-US'>
+        <rect id='r1' class='a' width='10'/>
+        <rect id='r2' data-x='foo-bar'/>
+        <circle id='c1' xlink:href='#r1'/>
+    </g>
+    <g id='g2' lang='de'>
+        <rect id='r3' class='b'/>
+    </g>
+</svg>";
+
+    #[test]
+    fn parse_selectors() {
+        assert!(selector("*").is_some());
+        assert!(selector("rect").is_some());
+        assert!(selector("g > rect + circle ~ path").is_some());
+        assert!(selector("[width='10' i]").is_some());
+        assert!(selector(":nth-child(2n + 1)").is_some());
+        assert!(selector(":not(.a, #b)").is_some());
+        assert!(selector("|rect").is_some());
+        assert_eq!(parse_selector_list("rect, circle", &[]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn parse_invalid_selectors() {
+        assert!(selector("").is_none());
+        assert!(selector("rect >").is_none());
+        assert!(selector("[width").is_none());
+        assert!(selector(":nth-child(x)").is_none());
+        assert!(selector("::before").is_none());
+        // Undeclared namespace prefix.
+        assert!(selector("foo|rect").is_none());
+        assert!(parse_selector_list("rect,", &[]).is_none());
+    }
+
+    #[test]
+    fn selectors_specificity() {
+        assert_eq!(specificity("*"), [0, 0, 0]);
+        assert_eq!(specificity("rect"), [0, 0, 1]);
+        assert_eq!(specificity("g rect"), [0, 0, 2]);
+        assert_eq!(specificity(".a"), [0, 1, 0]);
+        assert_eq!(specificity("rect.a[width]:first-child"), [0, 3, 1]);
+        assert_eq!(specificity("#r1"), [1, 0, 0]);
+        assert_eq!(specificity("g#g1 > .a"), [1, 1, 1]);
+        // `:not()` takes the most specific selector of its argument.
+        assert_eq!(specificity(":not(.a, #b)"), [1, 0, 0]);
+        assert_eq!(specificity("rect:not(g)"), [0, 0, 2]);
+    }
+
+    #[test]
+    fn rules_are_sorted_by_specificity() {
+        let mut sheet = StyleSheet::new();
+        sheet.parse_more(
+            "#a {fill:red} .b {fill:green} c {fill:blue} .d {fill:black}",
+            &MEDIA,
+        );
+        let values: Vec<_> = sheet
+            .rules
+            .iter()
+            .map(|r| r.declarations[0].value)
+            .collect();
+        assert_eq!(values, vec!["blue", "green", "black", "red"]);
+    }
+
+    #[test]
+    fn match_simple_selectors() {
+        assert_eq!(select(DOC, "rect"), vec!["r1", "r2", "r3"]);
+        assert_eq!(select(DOC, ".a"), vec!["g1", "r1"]);
+        assert_eq!(select(DOC, ".a.b"), vec!["g1"]);
+        assert_eq!(select(DOC, "#c1"), vec!["c1"]);
+        assert_eq!(select(DOC, "RECT"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn match_combinators() {
+        assert_eq!(select(DOC, "svg rect"), vec!["r1", "r2", "r3"]);
+        assert_eq!(select(DOC, "#g2 > rect"), vec!["r3"]);
+        assert_eq!(select(DOC, "rect + rect"), vec!["r2"]);
+        assert_eq!(select(DOC, "#r1 ~ *"), vec!["r2", "c1"]);
+    }
+
+    #[test]
+    fn match_attributes() {
+        assert_eq!(select(DOC, "[width]"), vec!["r1"]);
+        assert_eq!(select(DOC, "[width='10']"), vec!["r1"]);
+        assert_eq!(select(DOC, "[class~=b]"), vec!["g1", "r3"]);
+        assert_eq!(select(DOC, "[data-x|=foo]"), vec!["r2"]);
+        assert_eq!(select(DOC, "[data-x^=foo]"), vec!["r2"]);
+        assert_eq!(select(DOC, "[data-x$=bar]"), vec!["r2"]);
+        assert_eq!(select(DOC, "[data-x*='o-b']"), vec!["r2"]);
+        assert_eq!(select(DOC, "[data-x='FOO-BAR' i]"), vec!["r2"]);
+        assert_eq!(select(DOC, "[x|href]"), vec!["c1"]);
+        assert_eq!(select(DOC, "[xlink|href='#r1']"), vec!["c1"]);
+        assert_eq!(select(DOC, "[href]"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn match_pseudo_classes() {
+        assert_eq!(select(DOC, "rect:first-child"), vec!["r1", "r3"]);
+        // The root element has no siblings.
+        assert_eq!(select(DOC, ":last-child"), vec!["svg", "c1", "g2", "r3"]);
+        assert_eq!(select(DOC, "rect:only-child"), vec!["r3"]);
+        assert_eq!(select(DOC, "#g1 > :nth-child(2n+1)"), vec!["r1", "c1"]);
+        assert_eq!(select(DOC, "#g1 > :nth-of-type(2)"), vec!["r2"]);
+        assert_eq!(select(DOC, "rect:not(.a)"), vec!["r2", "r3"]);
+        assert_eq!(select(DOC, "rect:lang(en)"), vec!["r1", "r2"]);
+        assert_eq!(select(DOC, "rect:lang(de)"), vec!["r3"]);
+    }
+
+    #[test]
+    fn match_nth_with_extreme_values() {
+        assert_eq!(
+            select(DOC, "#g1 > :nth-child(n-2147483648)"),
+            vec!["r1", "r2", "c1"]
+        );
+        assert_eq!(
+            select(DOC, "#g1 > :nth-child(-n+2147483647)"),
+            vec!["r1", "r2", "c1"]
+        );
+        assert_eq!(
+            select(DOC, "#g1 > :nth-child(-1n-2147483648)"),
+            Vec::<String>::new()
+        );
+        assert_eq!(select(DOC, "#g1 > :nth-child(2147483647n+2)"), vec!["r2"]);
+        assert_eq!(select(DOC, "#g1 > :nth-child(-2147483648n+3)"), vec!["c1"]);
+    }
+
+    #[test]
+    fn match_escaped_identifiers() {
+        let doc = "<svg xmlns='http://www.w3.org/2000/svg' id='svg'>
+            <rect id='a:b' class='1st'/>
+            <rect id='c' data-x='\"q\"'/>
+        </svg>";
+        assert_eq!(select(doc, "#a\\:b"), vec!["a:b"]);
+        assert_eq!(select(doc, ".\\31 st"), vec!["a:b"]);
+        assert_eq!(select(doc, "r\\65 ct#c"), vec!["c"]);
+        assert_eq!(select(doc, "[data\\-x='\\\"q\\\"']"), vec!["c"]);
+    }
+
+    #[test]
+    fn parse_declarations() {
+        let list: Vec<_> =
+            DeclarationTokenizer::from("fill: red; ; stroke:blue !important;invalid;*x:1")
+                .collect();
+        assert_eq!(
+            list,
+            vec![
+                Declaration {
+                    name: "fill",
+                    value: "red",
+                    important: false,
+                },
+                Declaration {
+                    name: "stroke",
+                    value: "blue",
+                    important: true,
+                },
+                Declaration {
+                    name: "x",
+                    value: "1",
+                    important: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn media_queries() {
+        let matches = |text: &str| media_query_list_matches(text, &MEDIA);
+        assert!(matches(""));
+        assert!(matches("all"));
+        assert!(matches("screen and (min-width: 100px)"));
+        assert!(!matches("print"));
+        assert!(matches("print, (max-width: 200px)"));
+        assert!(!matches("(min-width: 201px)"));
+        assert!(matches("not print"));
+        assert!(matches("(orientation: landscape)"));
+        assert!(matches("(prefers-color-scheme: light)"));
+        assert!(!matches("(prefers-color-scheme: dark)"));
+        assert!(!matches("screen (width: 200px)"));
+    }
+
+    #[test]
+    fn namespace_rules() {
+        let mut sheet = StyleSheet::new();
+        sheet.parse_more(
+            "@namespace l url(http://www.w3.org/1999/xlink); [l|href] {fill:red} [q|href] {}",
+            &MEDIA,
+        );
+        assert_eq!(sheet.rules.len(), 1);
+    }
+
+    #[test]
+    fn media_rules() {
+        let mut sheet = StyleSheet::new();
+        sheet.parse_more(
+            "@media print { a {} b {fill:red} } @media screen { c {fill:red} }",
+            &MEDIA,
+        );
+        assert_eq!(sheet.rules.len(), 1);
+        assert_eq!(sheet.rules[0].selector, selector("c").unwrap());
+    }
+}
@@ -19,6 +19,7 @@ XML, to make SVG parsing easier.
 use std::collections::HashMap;
 use std::num::NonZeroU32;
 
+mod css;
 #[rustfmt::skip] mod names;
 mod parse;
 mod text;
@@ -27,6 +28,79 @@ pub use names::{AttributeId, ElementId};
 
 pub use roxmltree::{self, Error};
 
+/// A cascade origin of user-provided style sheets.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum StyleSheetOrigin {
+    /// Style sheets are applied before the presentation attributes and document styles,
+    /// like a user agent style sheet.
+    ///
+    /// `!important` declarations still override everything else.
+    UserAgent,
+    /// Style sheets are applied after the document styles, as if they were
+    /// the last `style` element in the document.
+    ///
+    /// The `style` attribute still has a higher priority.
+    #[default]
+    Author,
+}
+
+/// A preferred color scheme.
+///
+/// Used to evaluate `prefers-color-scheme` CSS media queries.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ColorScheme {
+    /// Light color scheme.
+    #[default]
+    Light,
+    /// Dark color scheme.
+    Dark,
+}
+
+/// Document parsing options.
+#[derive(Clone, Debug)]
+pub struct ParseOptions<'a> {
+    /// A list of user-provided CSS style sheets.
+    ///
+    /// Applied in addition to the `style` elements inside the document.
+    pub style_sheet: Vec<&'a str>,
+
+    /// A cascade origin of `style_sheet`.
+    pub style_sheet_origin: StyleSheetOrigin,
+
+    /// A color scheme used to evaluate `prefers-color-scheme` media queries.
+    pub color_scheme: ColorScheme,
+
+    /// A viewport size used to evaluate media queries when the root element
+    /// size cannot be resolved.
+    ///
+    /// Default: `(100, 100)`
+    pub default_viewport_size: (f64, f64),
+
+    /// A list of CSS custom properties, like `("--main-color", "green")`,
+    /// that will be set on the document root.
+    ///
+    /// Names must include the `--` prefix.
+    /// Properties declared inside the document take precedence.
+    pub custom_properties: Vec<(&'a str, &'a str)>,
+
+    /// An initial `color` property value. Defines `currentColor`
+    /// unless the document sets `color` itself.
+    pub color: Option<&'a str>,
+}
+
+impl Default for ParseOptions<'_> {
+    fn default() -> Self {
+        ParseOptions {
+            style_sheet: Vec::new(),
+            style_sheet_origin: StyleSheetOrigin::default(),
+            color_scheme: ColorScheme::default(),
+            default_viewport_size: (100.0, 100.0),
+            custom_properties: Vec::new(),
+            color: None,
+        }
+    }
+}
+
 /// An SVG tree container.
 ///
 /// Contains only element and text nodes.
@@ -35,6 +109,46 @@ pub struct Document<'input> {
     nodes: Vec<NodeData>,
     attrs: Vec<Attribute<'input>>,
     links: HashMap<String, NodeId>,
+    font_faces: Vec<FontFace>,
+    // Used only during parsing.
+    custom_properties: HashMap<NodeId, parse::CustomProperties>,
+}
+
+/// An `@font-face` rule.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FontFace {
+    /// A `font-family` descriptor value. Unquoted.
+    pub family: String,
+
+    /// A raw `font-weight` descriptor value.
+    ///
+    /// Can be a range, like `100 900`.
+    pub weight: Option<String>,
+
+    /// A raw `font-style` descriptor value.
+    pub style: Option<String>,
+
+    /// A `unicode-range` descriptor value.
+    ///
+    /// Empty when the descriptor is not set, which means all code points.
+    pub unicode_range: Vec<std::ops::RangeInclusive<u32>>,
+
+    /// A `src` descriptor value. In the preference order.
+    pub sources: Vec<FontFaceSource>,
+}
+
+/// A font source from the `@font-face` `src` descriptor.
+#[derive(Clone, PartialEq, Debug)]
+pub enum FontFaceSource {
+    /// A `url()` source with an optional `format()` hint.
+    Url {
+        /// A URL. Unquoted.
+        url: String,
+        /// A lowercase `format()` hint value.
+        format: Option<String>,
+    },
+    /// A `local()` font name.
+    Local(String),
 }
 
 impl<'input> Document<'input> {
@@ -73,6 +187,14 @@ impl<'input> Document<'input> {
         Some(self.get(*node_id))
     }
 
+    /// Returns all `@font-face` rules from the document and user style sheets.
+    ///
+    /// Rules are in the parsing order, including ones from matching `@media` rules.
+    #[inline]
+    pub fn font_faces(&self) -> &[FontFace] {
+        &self.font_faces
+    }
+
     #[inline]
     fn get<'a>(&'a self, id: NodeId) -> Node<'a, 'input> {
         Node {
@@ -159,7 +281,7 @@ impl ShortRange {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 struct NodeId(NonZeroU32);
 
 impl NodeId {
@@ -676,19 +798,30 @@ impl AttributeId {
                 | AttributeId::FloodColor
                 | AttributeId::FloodOpacity
                 | AttributeId::FontFamily
+                | AttributeId::FontFeatureSettings // technically not presentation
                 | AttributeId::FontKerning // technically not presentation
+                | AttributeId::FontOpticalSizing // technically not presentation
                 | AttributeId::FontSize
                 | AttributeId::FontSizeAdjust
                 | AttributeId::FontStretch
                 | AttributeId::FontStyle
+                | AttributeId::FontSynthesis // technically not presentation
                 | AttributeId::FontVariant
+                | AttributeId::FontVariantCaps
+                | AttributeId::FontVariantEastAsian
+                | AttributeId::FontVariantLigatures
+                | AttributeId::FontVariantNumeric
+                | AttributeId::FontVariantPosition
+                | AttributeId::FontVariationSettings // technically not presentation
                 | AttributeId::FontWeight
                 | AttributeId::GlyphOrientationHorizontal
                 | AttributeId::GlyphOrientationVertical
                 | AttributeId::ImageRendering
+                | AttributeId::InlineSize
                 | AttributeId::Isolation // technically not presentation
                 | AttributeId::LetterSpacing
                 | AttributeId::LightingColor
+                | AttributeId::LineHeight
                 | AttributeId::MarkerEnd
                 | AttributeId::MarkerMid
                 | AttributeId::MarkerStart
@@ -697,7 +830,11 @@ impl AttributeId {
                 | AttributeId::Opacity
                 | AttributeId::Overflow
                 | AttributeId::PaintOrder
+                | AttributeId::ShapeInside
+                | AttributeId::ShapeMargin
+                | AttributeId::ShapePadding
                 | AttributeId::ShapeRendering
+                | AttributeId::ShapeSubtract
                 | AttributeId::StopColor
                 | AttributeId::StopOpacity
                 | AttributeId::Stroke
@@ -710,8 +847,18 @@ impl AttributeId {
                 | AttributeId::StrokeWidth
                 | AttributeId::TextAnchor
                 | AttributeId::TextDecoration
+                | AttributeId::TextDecorationColor // technically not presentation
+                | AttributeId::TextDecorationFill
+                | AttributeId::TextDecorationLine // technically not presentation
+                | AttributeId::TextDecorationStroke
+                | AttributeId::TextDecorationStyle // technically not presentation
+                | AttributeId::TextDecorationThickness // technically not presentation
+                | AttributeId::TextOrientation
                 | AttributeId::TextOverflow
                 | AttributeId::TextRendering
+                | AttributeId::TextTransform // technically not presentation
+                | AttributeId::TextUnderlineOffset // technically not presentation
+                | AttributeId::TextUnderlinePosition // technically not presentation
                 | AttributeId::Transform
                 | AttributeId::UnicodeBidi
                 | AttributeId::VectorEffect
@@ -750,15 +897,25 @@ impl AttributeId {
                 | AttributeId::FloodColor
                 | AttributeId::FloodOpacity
                 | AttributeId::FontFamily
+                | AttributeId::FontFeatureSettings
                 | AttributeId::FontKerning
+                | AttributeId::FontOpticalSizing
                 | AttributeId::FontSize
                 | AttributeId::FontStretch
                 | AttributeId::FontStyle
+                | AttributeId::FontSynthesis
                 | AttributeId::FontVariant
+                | AttributeId::FontVariantCaps
+                | AttributeId::FontVariantEastAsian
+                | AttributeId::FontVariantLigatures
+                | AttributeId::FontVariantNumeric
+                | AttributeId::FontVariantPosition
+                | AttributeId::FontVariationSettings
                 | AttributeId::FontWeight
                 | AttributeId::ImageRendering
                 | AttributeId::Kerning
                 | AttributeId::LetterSpacing
+                | AttributeId::LineHeight
                 | AttributeId::MarkerEnd
                 | AttributeId::MarkerMid
                 | AttributeId::MarkerStart
@@ -778,8 +935,19 @@ impl AttributeId {
                 | AttributeId::StrokeWidth
                 | AttributeId::TextAnchor
                 | AttributeId::TextDecoration
+                | AttributeId::TextDecorationColor
+                | AttributeId::TextDecorationFill
+                | AttributeId::TextDecorationLine
+                | AttributeId::TextDecorationStroke
+                | AttributeId::TextDecorationStyle
+                | AttributeId::TextDecorationThickness
+                | AttributeId::TextOrientation
                 | AttributeId::TextRendering
+                | AttributeId::TextTransform
+                | AttributeId::TextUnderlineOffset
+                | AttributeId::TextUnderlinePosition
                 | AttributeId::Visibility
+                | AttributeId::WhiteSpace
                 | AttributeId::WordSpacing
                 | AttributeId::WritingMode
         )
@@ -797,13 +965,24 @@ fn is_non_inheritable(id: AttributeId) -> bool {
             | AttributeId::Filter
             | AttributeId::FloodColor
             | AttributeId::FloodOpacity
+            | AttributeId::InlineSize
             | AttributeId::Mask
             | AttributeId::Opacity
             | AttributeId::Overflow
             | AttributeId::LightingColor
+            | AttributeId::ShapeInside
+            | AttributeId::ShapeMargin
+            | AttributeId::ShapePadding
+            | AttributeId::ShapeSubtract
             | AttributeId::StopColor
             | AttributeId::StopOpacity
             | AttributeId::TextDecoration
+            | AttributeId::TextDecorationColor
+            | AttributeId::TextDecorationFill
+            | AttributeId::TextDecorationLine
+            | AttributeId::TextDecorationStroke
+            | AttributeId::TextDecorationStyle
+            | AttributeId::TextDecorationThickness
             | AttributeId::Transform
     )
 }
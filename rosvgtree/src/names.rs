@@ -36,12 +36,17 @@ pub enum ElementId {
     FeTile,
     FeTurbulence,
     Filter,
+    Font,
+    FontFace,
     G,
+    Glyph,
+    Hkern,
     Image,
     Line,
     LinearGradient,
     Marker,
     Mask,
+    MissingGlyph,
     Path,
     Pattern,
     Polygon,
@@ -57,78 +62,86 @@ pub enum ElementId {
     TextPath,
     Tref,
     Tspan,
-    Use
+    Use,
+    Vkern
 }
 
 static ELEMENTS: Map<ElementId> = Map {
     key: 732231254413039614,
     disps: &[
-        (0, 12),
-        (1, 11),
-        (10, 26),
-        (2, 42),
-        (1, 19),
-        (0, 5),
-        (1, 13),
-        (8, 50),
+        (0, 36),
+        (2, 32),
+        (2, 52),
         (0, 0),
-        (1, 0),
-        (7, 45),
+        (0, 1),
+        (2, 25),
+        (11, 45),
+        (1, 57),
+        (7, 0),
+        (1, 14),
+        (36, 52),
+        (0, 1),
     ],
     entries: &[
-        ("feFlood", ElementId::FeFlood),
-        ("radialGradient", ElementId::RadialGradient),
-        ("feImage", ElementId::FeImage),
-        ("stop", ElementId::Stop),
+        ("feDisplacementMap", ElementId::FeDisplacementMap),
+        ("feSpecularLighting", ElementId::FeSpecularLighting),
+        ("line", ElementId::Line),
+        ("a", ElementId::A),
+        ("feMergeNode", ElementId::FeMergeNode),
+        ("g", ElementId::G),
         ("fePointLight", ElementId::FePointLight),
-        ("feConvolveMatrix", ElementId::FeConvolveMatrix),
-        ("feComposite", ElementId::FeComposite),
-        ("clipPath", ElementId::ClipPath),
-        ("feMerge", ElementId::FeMerge),
-        ("defs", ElementId::Defs),
-        ("mask", ElementId::Mask),
-        ("svg", ElementId::Svg),
+        ("polygon", ElementId::Polygon),
+        ("path", ElementId::Path),
+        ("style", ElementId::Style),
         ("symbol", ElementId::Symbol),
+        ("polyline", ElementId::Polyline),
+        ("feTile", ElementId::FeTile),
+        ("missing-glyph", ElementId::MissingGlyph),
+        ("mask", ElementId::Mask),
+        ("feImage", ElementId::FeImage),
+        ("switch", ElementId::Switch),
+        ("text", ElementId::Text),
+        ("feTurbulence", ElementId::FeTurbulence),
+        ("ellipse", ElementId::Ellipse),
+        ("feGaussianBlur", ElementId::FeGaussianBlur),
+        ("feComposite", ElementId::FeComposite),
+        ("vkern", ElementId::Vkern),
+        ("glyph", ElementId::Glyph),
+        ("feFlood", ElementId::FeFlood),
+        ("feColorMatrix", ElementId::FeColorMatrix),
+        ("image", ElementId::Image),
+        ("tspan", ElementId::Tspan),
+        ("circle", ElementId::Circle),
         ("linearGradient", ElementId::LinearGradient),
-        ("feSpecularLighting", ElementId::FeSpecularLighting),
+        ("feDropShadow", ElementId::FeDropShadow),
+        ("marker", ElementId::Marker),
+        ("feFuncR", ElementId::FeFuncR),
+        ("stop", ElementId::Stop),
         ("feFuncB", ElementId::FeFuncB),
-        ("filter", ElementId::Filter),
-        ("feFuncG", ElementId::FeFuncG),
-        ("circle", ElementId::Circle),
-        ("g", ElementId::G),
-        ("tref", ElementId::Tref),
+        ("feMorphology", ElementId::FeMorphology),
+        ("clipPath", ElementId::ClipPath),
+        ("svg", ElementId::Svg),
+        ("radialGradient", ElementId::RadialGradient),
+        ("feComponentTransfer", ElementId::FeComponentTransfer),
         ("feFuncA", ElementId::FeFuncA),
-        ("image", ElementId::Image),
-        ("text", ElementId::Text),
-        ("line", ElementId::Line),
+        ("font", ElementId::Font),
+        ("feMerge", ElementId::FeMerge),
+        ("feConvolveMatrix", ElementId::FeConvolveMatrix),
+        ("rect", ElementId::Rect),
+        ("feFuncG", ElementId::FeFuncG),
+        ("feDistantLight", ElementId::FeDistantLight),
+        ("defs", ElementId::Defs),
         ("pattern", ElementId::Pattern),
+        ("filter", ElementId::Filter),
+        ("textPath", ElementId::TextPath),
+        ("hkern", ElementId::Hkern),
+        ("font-face", ElementId::FontFace),
         ("use", ElementId::Use),
-        ("feDropShadow", ElementId::FeDropShadow),
         ("feSpotLight", ElementId::FeSpotLight),
-        ("marker", ElementId::Marker),
-        ("style", ElementId::Style),
-        ("switch", ElementId::Switch),
-        ("tspan", ElementId::Tspan),
-        ("feColorMatrix", ElementId::FeColorMatrix),
+        ("tref", ElementId::Tref),
         ("feOffset", ElementId::FeOffset),
-        ("path", ElementId::Path),
-        ("feGaussianBlur", ElementId::FeGaussianBlur),
-        ("feTile", ElementId::FeTile),
-        ("feTurbulence", ElementId::FeTurbulence),
-        ("feMergeNode", ElementId::FeMergeNode),
-        ("feMorphology", ElementId::FeMorphology),
-        ("a", ElementId::A),
-        ("textPath", ElementId::TextPath),
-        ("ellipse", ElementId::Ellipse),
-        ("feComponentTransfer", ElementId::FeComponentTransfer),
-        ("feDistantLight", ElementId::FeDistantLight),
-        ("polyline", ElementId::Polyline),
-        ("polygon", ElementId::Polygon),
         ("feBlend", ElementId::FeBlend),
-        ("feDisplacementMap", ElementId::FeDisplacementMap),
         ("feDiffuseLighting", ElementId::FeDiffuseLighting),
-        ("rect", ElementId::Rect),
-        ("feFuncR", ElementId::FeFuncR),
     ],
 };
 
@@ -162,6 +175,8 @@ impl std::fmt::Display for ElementId {
 pub enum AttributeId {
     AlignmentBaseline,
     Amplitude,
+    ArabicForm,
+    Ascent,
     Azimuth,
     BaseFrequency,
     BaselineShift,
@@ -179,6 +194,7 @@ pub enum AttributeId {
     Cx,
     Cy,
     D,
+    Descent,
     DiffuseConstant,
     Direction,
     Display,
@@ -201,6 +217,7 @@ pub enum AttributeId {
     FontFamily,
     FontFeatureSettings,
     FontKerning,
+    FontOpticalSizing,
     FontSize,
     FontSizeAdjust,
     FontStretch,
@@ -212,14 +229,19 @@ pub enum AttributeId {
     FontVariantLigatures,
     FontVariantNumeric,
     FontVariantPosition,
+    FontVariationSettings,
     FontWeight,
     Fx,
     Fy,
+    G1,
+    G2,
+    GlyphName,
     GlyphOrientationHorizontal,
     GlyphOrientationVertical,
     GradientTransform,
     GradientUnits,
     Height,
+    HorizAdvX,
     Href,
     Id,
     ImageRendering,
@@ -228,6 +250,7 @@ pub enum AttributeId {
     InlineSize,
     Intercept,
     Isolation,
+    K,
     K1,
     K2,
     K3,
@@ -235,6 +258,7 @@ pub enum AttributeId {
     KernelMatrix,
     KernelUnitLength,
     Kerning,
+    Lang,
     LengthAdjust,
     LetterSpacing,
     LightingColor,
@@ -323,18 +347,25 @@ pub enum AttributeId {
     TextDecorationLine,
     TextDecorationStroke,
     TextDecorationStyle,
+    TextDecorationThickness,
     TextIndent,
     TextOrientation,
     TextOverflow,
     TextRendering,
+    TextTransform,
+    TextUnderlineOffset,
     TextUnderlinePosition,
     TextLength,
     Transform,
     TransformBox,
     TransformOrigin,
     Type,
+    U1,
+    U2,
+    Unicode,
     UnicodeBidi,
     UnicodeRange,
+    UnitsPerEm,
     Values,
     VectorEffect,
     ViewBox,
@@ -355,241 +386,262 @@ pub enum AttributeId {
 }
 
 static ATTRIBUTES: Map<AttributeId> = Map {
-    key: 732231254413039614,
+    key: 1789006226184284512,
     disps: &[
-        (0, 103),
-        (1, 31),
-        (0, 1),
-        (0, 2),
-        (0, 31),
-        (3, 20),
-        (0, 164),
-        (0, 97),
+        (0, 162),
+        (0, 6),
         (0, 2),
-        (0, 4),
-        (0, 12),
-        (0, 166),
-        (0, 1),
-        (4, 174),
-        (2, 158),
-        (0, 13),
-        (0, 18),
-        (0, 0),
-        (0, 138),
-        (7, 16),
-        (1, 100),
-        (5, 19),
-        (10, 59),
-        (0, 5),
-        (0, 146),
         (0, 44),
-        (0, 72),
-        (0, 26),
-        (0, 1),
-        (3, 147),
         (0, 7),
+        (1, 84),
+        (0, 5),
+        (1, 132),
+        (0, 161),
+        (0, 2),
+        (0, 0),
         (0, 11),
-        (0, 10),
-        (5, 91),
+        (0, 2),
+        (0, 43),
+        (4, 112),
+        (0, 25),
+        (8, 203),
+        (0, 17),
+        (0, 1),
+        (4, 49),
+        (0, 27),
+        (2, 23),
+        (3, 91),
+        (1, 7),
+        (0, 25),
+        (6, 209),
+        (2, 1),
+        (0, 189),
+        (0, 0),
+        (8, 43),
+        (0, 32),
+        (0, 109),
+        (5, 207),
+        (0, 110),
+        (0, 17),
         (0, 74),
-        (4, 37),
-        (0, 138),
-        (0, 3),
-        (6, 164),
+        (0, 159),
+        (0, 71),
+        (42, 109),
+        (21, 26),
+        (20, 60),
+        (0, 1),
     ],
     entries: &[
-        ("color-interpolation", AttributeId::ColorInterpolation),
-        ("id", AttributeId::Id),
-        ("inline-size", AttributeId::InlineSize),
-        ("flood-opacity", AttributeId::FloodOpacity),
-        ("kernelMatrix", AttributeId::KernelMatrix),
-        ("font-stretch", AttributeId::FontStretch),
-        ("mask", AttributeId::Mask),
-        ("order", AttributeId::Order),
-        ("kerning", AttributeId::Kerning),
-        ("font-size-adjust", AttributeId::FontSizeAdjust),
-        ("limitingConeAngle", AttributeId::LimitingConeAngle),
+        ("radius", AttributeId::Radius),
         ("azimuth", AttributeId::Azimuth),
-        ("k4", AttributeId::K4),
-        ("fill", AttributeId::Fill),
-        ("intercept", AttributeId::Intercept),
-        ("text-align-last", AttributeId::TextAlignLast),
-        ("text-decoration-fill", AttributeId::TextDecorationFill),
-        ("textLength", AttributeId::TextLength),
+        ("g1", AttributeId::G1),
         ("patternUnits", AttributeId::PatternUnits),
-        ("space", AttributeId::Space),
-        ("side", AttributeId::Side),
-        ("line-height", AttributeId::LineHeight),
-        ("stroke-linejoin", AttributeId::StrokeLinejoin),
-        ("xChannelSelector", AttributeId::XChannelSelector),
-        ("kernelUnitLength", AttributeId::KernelUnitLength),
-        ("k3", AttributeId::K3),
-        ("font-feature-settings", AttributeId::FontFeatureSettings),
-        ("fill-opacity", AttributeId::FillOpacity),
-        ("transform-box", AttributeId::TransformBox),
-        ("lengthAdjust", AttributeId::LengthAdjust),
-        ("marker-end", AttributeId::MarkerEnd),
-        ("y1", AttributeId::Y1),
-        ("specularExponent", AttributeId::SpecularExponent),
-        ("direction", AttributeId::Direction),
-        ("stroke-linecap", AttributeId::StrokeLinecap),
-        ("display", AttributeId::Display),
-        ("text-overflow", AttributeId::TextOverflow),
-        ("text-decoration-color", AttributeId::TextDecorationColor),
-        ("transform-origin", AttributeId::TransformOrigin),
-        ("lighting-color", AttributeId::LightingColor),
-        ("systemLanguage", AttributeId::SystemLanguage),
-        ("pointsAtZ", AttributeId::PointsAtZ),
-        ("cy", AttributeId::Cy),
-        ("k2", AttributeId::K2),
-        ("specularConstant", AttributeId::SpecularConstant),
-        ("tableValues", AttributeId::TableValues),
-        ("dominant-baseline", AttributeId::DominantBaseline),
-        ("stroke-width", AttributeId::StrokeWidth),
-        ("dx", AttributeId::Dx),
+        ("stop-color", AttributeId::StopColor),
+        ("targetY", AttributeId::TargetY),
+        ("stroke-miterlimit", AttributeId::StrokeMiterlimit),
+        ("text-underline-offset", AttributeId::TextUnderlineOffset),
         ("font-variant-caps", AttributeId::FontVariantCaps),
-        ("shape-subtract", AttributeId::ShapeSubtract),
-        ("shape-image-threshold", AttributeId::ShapeImageThreshold),
-        ("gradientTransform", AttributeId::GradientTransform),
+        ("path", AttributeId::Path),
+        ("style", AttributeId::Style),
+        ("diffuseConstant", AttributeId::DiffuseConstant),
+        ("stitchTiles", AttributeId::StitchTiles),
+        ("paint-order", AttributeId::PaintOrder),
+        ("filter", AttributeId::Filter),
+        ("id", AttributeId::Id),
+        ("values", AttributeId::Values),
+        ("color-interpolation", AttributeId::ColorInterpolation),
+        ("bias", AttributeId::Bias),
+        ("color-profile", AttributeId::ColorProfile),
+        ("mode", AttributeId::Mode),
+        ("font-variant-position", AttributeId::FontVariantPosition),
         ("pointsAtX", AttributeId::PointsAtX),
-        ("cx", AttributeId::Cx),
-        ("filterUnits", AttributeId::FilterUnits),
-        ("image-rendering", AttributeId::ImageRendering),
-        ("fx", AttributeId::Fx),
-        ("gradientUnits", AttributeId::GradientUnits),
-        ("text-orientation", AttributeId::TextOrientation),
-        ("exponent", AttributeId::Exponent),
+        ("r", AttributeId::R),
+        ("markerUnits", AttributeId::MarkerUnits),
+        ("clip-path", AttributeId::ClipPath),
+        ("orient", AttributeId::Orient),
+        ("shape-margin", AttributeId::ShapeMargin),
+        ("offset", AttributeId::Offset),
         ("rotate", AttributeId::Rotate),
-        ("d", AttributeId::D),
-        ("mix-blend-mode", AttributeId::MixBlendMode),
-        ("rx", AttributeId::Rx),
-        ("text-rendering", AttributeId::TextRendering),
-        ("glyph-orientation-horizontal", AttributeId::GlyphOrientationHorizontal),
-        ("enable-background", AttributeId::EnableBackground),
-        ("scale", AttributeId::Scale),
-        ("in", AttributeId::In),
-        ("font-variant-numeric", AttributeId::FontVariantNumeric),
-        ("font-variant-east-asian", AttributeId::FontVariantEastAsian),
-        ("text-anchor", AttributeId::TextAnchor),
-        ("glyph-orientation-vertical", AttributeId::GlyphOrientationVertical),
-        ("dy", AttributeId::Dy),
-        ("color-interpolation-filters", AttributeId::ColorInterpolationFilters),
-        ("clip-rule", AttributeId::ClipRule),
-        ("stroke-miterlimit", AttributeId::StrokeMiterlimit),
-        ("isolation", AttributeId::Isolation),
-        ("transform", AttributeId::Transform),
-        ("stroke-dashoffset", AttributeId::StrokeDashoffset),
-        ("z", AttributeId::Z),
-        ("refX", AttributeId::RefX),
-        ("width", AttributeId::Width),
-        ("requiredExtensions", AttributeId::RequiredExtensions),
-        ("font-synthesis", AttributeId::FontSynthesis),
-        ("radius", AttributeId::Radius),
-        ("white-space", AttributeId::WhiteSpace),
-        ("patternTransform", AttributeId::PatternTransform),
         ("maskContentUnits", AttributeId::MaskContentUnits),
-        ("stop-color", AttributeId::StopColor),
-        ("amplitude", AttributeId::Amplitude),
-        ("visibility", AttributeId::Visibility),
-        ("yChannelSelector", AttributeId::YChannelSelector),
-        ("color-rendering", AttributeId::ColorRendering),
-        ("diffuseConstant", AttributeId::DiffuseConstant),
+        ("transform-origin", AttributeId::TransformOrigin),
+        ("tableValues", AttributeId::TableValues),
+        ("primitiveUnits", AttributeId::PrimitiveUnits),
+        ("marker-start", AttributeId::MarkerStart),
+        ("refX", AttributeId::RefX),
+        ("font-family", AttributeId::FontFamily),
+        ("glyph-orientation-vertical", AttributeId::GlyphOrientationVertical),
+        ("points", AttributeId::Points),
         ("stroke", AttributeId::Stroke),
-        ("text-decoration-style", AttributeId::TextDecorationStyle),
-        ("y2", AttributeId::Y2),
-        ("operator", AttributeId::Operator),
-        ("font-variant", AttributeId::FontVariant),
-        ("clip-path", AttributeId::ClipPath),
-        ("edgeMode", AttributeId::EdgeMode),
-        ("text-indent", AttributeId::TextIndent),
-        ("numOctaves", AttributeId::NumOctaves),
-        ("font-size", AttributeId::FontSize),
-        ("alignment-baseline", AttributeId::AlignmentBaseline),
-        ("stroke-opacity", AttributeId::StrokeOpacity),
-        ("class", AttributeId::Class),
-        ("refY", AttributeId::RefY),
-        ("stitchTiles", AttributeId::StitchTiles),
+        ("side", AttributeId::Side),
+        ("clip", AttributeId::Clip),
+        ("font-kerning", AttributeId::FontKerning),
+        ("systemLanguage", AttributeId::SystemLanguage),
+        ("inline-size", AttributeId::InlineSize),
+        ("gradientUnits", AttributeId::GradientUnits),
         ("x2", AttributeId::X2),
-        ("unicode-bidi", AttributeId::UnicodeBidi),
-        ("targetY", AttributeId::TargetY),
-        ("vector-effect", AttributeId::VectorEffect),
-        ("fy", AttributeId::Fy),
-        ("clipPathUnits", AttributeId::ClipPathUnits),
+        ("k1", AttributeId::K1),
+        ("width", AttributeId::Width),
+        ("text-decoration-stroke", AttributeId::TextDecorationStroke),
+        ("lang", AttributeId::Lang),
         ("href", AttributeId::Href),
-        ("x1", AttributeId::X1),
-        ("result", AttributeId::Result),
+        ("targetX", AttributeId::TargetX),
+        ("pointsAtZ", AttributeId::PointsAtZ),
+        ("font-feature-settings", AttributeId::FontFeatureSettings),
+        ("flood-opacity", AttributeId::FloodOpacity),
+        ("markerWidth", AttributeId::MarkerWidth),
+        ("text-decoration-style", AttributeId::TextDecorationStyle),
+        ("fy", AttributeId::Fy),
+        ("shape-rendering", AttributeId::ShapeRendering),
+        ("requiredFeatures", AttributeId::RequiredFeatures),
+        ("viewBox", AttributeId::ViewBox),
+        ("isolation", AttributeId::Isolation),
+        ("word-spacing", AttributeId::WordSpacing),
+        ("markerHeight", AttributeId::MarkerHeight),
+        ("clip-rule", AttributeId::ClipRule),
+        ("seed", AttributeId::Seed),
+        ("font-size", AttributeId::FontSize),
+        ("rx", AttributeId::Rx),
+        ("overflow", AttributeId::Overflow),
+        ("color-interpolation-filters", AttributeId::ColorInterpolationFilters),
         ("fill-rule", AttributeId::FillRule),
-        ("color-profile", AttributeId::ColorProfile),
-        ("font", AttributeId::Font),
-        ("font-weight", AttributeId::FontWeight),
+        ("kernelMatrix", AttributeId::KernelMatrix),
+        ("dy", AttributeId::Dy),
+        ("font-style", AttributeId::FontStyle),
+        ("text-underline-position", AttributeId::TextUnderlinePosition),
+        ("k4", AttributeId::K4),
         ("shape-padding", AttributeId::ShapePadding),
-        ("primitiveUnits", AttributeId::PrimitiveUnits),
-        ("height", AttributeId::Height),
-        ("bias", AttributeId::Bias),
-        ("maskUnits", AttributeId::MaskUnits),
+        ("spreadMethod", AttributeId::SpreadMethod),
+        ("stroke-linecap", AttributeId::StrokeLinecap),
+        ("yChannelSelector", AttributeId::YChannelSelector),
+        ("text-decoration-thickness", AttributeId::TextDecorationThickness),
         ("pointsAtY", AttributeId::PointsAtY),
-        ("paint-order", AttributeId::PaintOrder),
-        ("text-decoration-line", AttributeId::TextDecorationLine),
+        ("arabic-form", AttributeId::ArabicForm),
+        ("color", AttributeId::Color),
+        ("surfaceScale", AttributeId::SurfaceScale),
+        ("stroke-width", AttributeId::StrokeWidth),
+        ("fill", AttributeId::Fill),
         ("preserveAlpha", AttributeId::PreserveAlpha),
-        ("shape-rendering", AttributeId::ShapeRendering),
-        ("text-underline-position", AttributeId::TextUnderlinePosition),
-        ("stroke-dasharray", AttributeId::StrokeDasharray),
-        ("ry", AttributeId::Ry),
+        ("vector-effect", AttributeId::VectorEffect),
+        ("maskUnits", AttributeId::MaskUnits),
+        ("specularConstant", AttributeId::SpecularConstant),
+        ("shape-subtract", AttributeId::ShapeSubtract),
+        ("k", AttributeId::K),
         ("preserveAspectRatio", AttributeId::PreserveAspectRatio),
-        ("markerWidth", AttributeId::MarkerWidth),
-        ("letter-spacing", AttributeId::LetterSpacing),
-        ("font-variant-position", AttributeId::FontVariantPosition),
-        ("y", AttributeId::Y),
-        ("style", AttributeId::Style),
-        ("requiredFeatures", AttributeId::RequiredFeatures),
+        ("text-decoration-line", AttributeId::TextDecorationLine),
+        ("stdDeviation", AttributeId::StdDeviation),
+        ("color-rendering", AttributeId::ColorRendering),
+        ("text-orientation", AttributeId::TextOrientation),
+        ("order", AttributeId::Order),
+        ("lengthAdjust", AttributeId::LengthAdjust),
+        ("unicode-range", AttributeId::UnicodeRange),
+        ("units-per-em", AttributeId::UnitsPerEm),
+        ("filterUnits", AttributeId::FilterUnits),
+        ("white-space", AttributeId::WhiteSpace),
+        ("glyph-name", AttributeId::GlyphName),
+        ("text-decoration-fill", AttributeId::TextDecorationFill),
+        ("exponent", AttributeId::Exponent),
         ("text-align", AttributeId::TextAlign),
-        ("divisor", AttributeId::Divisor),
-        ("offset", AttributeId::Offset),
-        ("overflow", AttributeId::Overflow),
-        ("font-kerning", AttributeId::FontKerning),
-        ("slope", AttributeId::Slope),
-        ("marker-mid", AttributeId::MarkerMid),
-        ("mode", AttributeId::Mode),
-        ("x", AttributeId::X),
-        ("elevation", AttributeId::Elevation),
-        ("k1", AttributeId::K1),
+        ("direction", AttributeId::Direction),
+        ("horiz-adv-x", AttributeId::HorizAdvX),
+        ("cy", AttributeId::Cy),
+        ("u2", AttributeId::U2),
+        ("clipPathUnits", AttributeId::ClipPathUnits),
+        ("mix-blend-mode", AttributeId::MixBlendMode),
+        ("transform-box", AttributeId::TransformBox),
+        ("font-variant-ligatures", AttributeId::FontVariantLigatures),
         ("startOffset", AttributeId::StartOffset),
-        ("clip", AttributeId::Clip),
-        ("surfaceScale", AttributeId::SurfaceScale),
-        ("points", AttributeId::Points),
-        ("targetX", AttributeId::TargetX),
-        ("text-decoration", AttributeId::TextDecoration),
-        ("font-style", AttributeId::FontStyle),
-        ("seed", AttributeId::Seed),
-        ("stop-opacity", AttributeId::StopOpacity),
-        ("values", AttributeId::Values),
-        ("stdDeviation", AttributeId::StdDeviation),
-        ("spreadMethod", AttributeId::SpreadMethod),
+        ("patternTransform", AttributeId::PatternTransform),
+        ("y", AttributeId::Y),
+        ("u1", AttributeId::U1),
+        ("space", AttributeId::Space),
+        ("text-align-last", AttributeId::TextAlignLast),
+        ("pathLength", AttributeId::PathLength),
+        ("requiredExtensions", AttributeId::RequiredExtensions),
+        ("amplitude", AttributeId::Amplitude),
+        ("line-height", AttributeId::LineHeight),
+        ("transform", AttributeId::Transform),
+        ("scale", AttributeId::Scale),
+        ("d", AttributeId::D),
+        ("operator", AttributeId::Operator),
+        ("font-weight", AttributeId::FontWeight),
+        ("opacity", AttributeId::Opacity),
+        ("unicode", AttributeId::Unicode),
+        ("font", AttributeId::Font),
+        ("limitingConeAngle", AttributeId::LimitingConeAngle),
         ("flood-color", AttributeId::FloodColor),
-        ("color", AttributeId::Color),
+        ("font-synthesis", AttributeId::FontSynthesis),
+        ("stop-opacity", AttributeId::StopOpacity),
+        ("text-decoration", AttributeId::TextDecoration),
+        ("alignment-baseline", AttributeId::AlignmentBaseline),
+        ("y1", AttributeId::Y1),
+        ("fx", AttributeId::Fx),
+        ("specularExponent", AttributeId::SpecularExponent),
+        ("y2", AttributeId::Y2),
+        ("g2", AttributeId::G2),
+        ("font-stretch", AttributeId::FontStretch),
+        ("xChannelSelector", AttributeId::XChannelSelector),
+        ("visibility", AttributeId::Visibility),
+        ("edgeMode", AttributeId::EdgeMode),
+        ("cx", AttributeId::Cx),
+        ("in", AttributeId::In),
+        ("elevation", AttributeId::Elevation),
         ("shape-inside", AttributeId::ShapeInside),
-        ("marker-start", AttributeId::MarkerStart),
-        ("text-decoration-stroke", AttributeId::TextDecorationStroke),
+        ("descent", AttributeId::Descent),
+        ("z", AttributeId::Z),
+        ("font-size-adjust", AttributeId::FontSizeAdjust),
+        ("letter-spacing", AttributeId::LetterSpacing),
+        ("lighting-color", AttributeId::LightingColor),
+        ("stroke-opacity", AttributeId::StrokeOpacity),
+        ("text-decoration-color", AttributeId::TextDecorationColor),
+        ("x1", AttributeId::X1),
+        ("refY", AttributeId::RefY),
+        ("ry", AttributeId::Ry),
         ("patternContentUnits", AttributeId::PatternContentUnits),
-        ("word-spacing", AttributeId::WordSpacing),
-        ("orient", AttributeId::Orient),
         ("in2", AttributeId::In2),
-        ("unicode-range", AttributeId::UnicodeRange),
-        ("opacity", AttributeId::Opacity),
-        ("baseFrequency", AttributeId::BaseFrequency),
-        ("path", AttributeId::Path),
-        ("markerHeight", AttributeId::MarkerHeight),
-        ("pathLength", AttributeId::PathLength),
-        ("r", AttributeId::R),
-        ("writing-mode", AttributeId::WritingMode),
-        ("shape-margin", AttributeId::ShapeMargin),
-        ("viewBox", AttributeId::ViewBox),
-        ("font-family", AttributeId::FontFamily),
-        ("filter", AttributeId::Filter),
-        ("markerUnits", AttributeId::MarkerUnits),
+        ("class", AttributeId::Class),
+        ("text-anchor", AttributeId::TextAnchor),
         ("type", AttributeId::Type),
+        ("font-variation-settings", AttributeId::FontVariationSettings),
+        ("display", AttributeId::Display),
+        ("writing-mode", AttributeId::WritingMode),
+        ("height", AttributeId::Height),
+        ("textLength", AttributeId::TextLength),
+        ("shape-image-threshold", AttributeId::ShapeImageThreshold),
+        ("intercept", AttributeId::Intercept),
+        ("glyph-orientation-horizontal", AttributeId::GlyphOrientationHorizontal),
+        ("numOctaves", AttributeId::NumOctaves),
+        ("stroke-linejoin", AttributeId::StrokeLinejoin),
+        ("font-variant", AttributeId::FontVariant),
+        ("fill-opacity", AttributeId::FillOpacity),
+        ("x", AttributeId::X),
+        ("marker-mid", AttributeId::MarkerMid),
+        ("text-rendering", AttributeId::TextRendering),
+        ("result", AttributeId::Result),
+        ("mask", AttributeId::Mask),
+        ("slope", AttributeId::Slope),
+        ("divisor", AttributeId::Divisor),
+        ("baseFrequency", AttributeId::BaseFrequency),
+        ("dominant-baseline", AttributeId::DominantBaseline),
+        ("ascent", AttributeId::Ascent),
+        ("stroke-dasharray", AttributeId::StrokeDasharray),
+        ("text-transform", AttributeId::TextTransform),
+        ("kernelUnitLength", AttributeId::KernelUnitLength),
+        ("k3", AttributeId::K3),
+        ("gradientTransform", AttributeId::GradientTransform),
+        ("font-variant-east-asian", AttributeId::FontVariantEastAsian),
+        ("dx", AttributeId::Dx),
+        ("text-overflow", AttributeId::TextOverflow),
+        ("kerning", AttributeId::Kerning),
+        ("enable-background", AttributeId::EnableBackground),
+        ("unicode-bidi", AttributeId::UnicodeBidi),
+        ("stroke-dashoffset", AttributeId::StrokeDashoffset),
+        ("font-optical-sizing", AttributeId::FontOpticalSizing),
+        ("font-variant-numeric", AttributeId::FontVariantNumeric),
+        ("k2", AttributeId::K2),
+        ("text-indent", AttributeId::TextIndent),
+        ("image-rendering", AttributeId::ImageRendering),
         ("baseline-shift", AttributeId::BaselineShift),
-        ("font-variant-ligatures", AttributeId::FontVariantLigatures),
+        ("marker-end", AttributeId::MarkerEnd),
     ],
 };
 
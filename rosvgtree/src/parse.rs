@@ -1,8 +1,13 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use roxmltree::Error;
 
-use crate::{Attribute, AttributeId, Document, ElementId, NodeData, NodeId, NodeKind, ShortRange};
+use crate::css;
+use crate::{
+    Attribute, AttributeId, Document, ElementId, NodeData, NodeId, NodeKind, ParseOptions,
+    ShortRange, StyleSheetOrigin,
+};
 
 const SVG_NS: &str = "http://www.w3.org/2000/svg";
 const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
@@ -11,13 +16,29 @@ const XML_NAMESPACE_NS: &str = "http://www.w3.org/XML/1998/namespace";
 impl<'input> Document<'input> {
     /// Parses a [`Document`] from a string.
     pub fn parse_str(text: &'input str) -> Result<Document<'input>, Error> {
+        Self::parse_str_with_options(text, &ParseOptions::default())
+    }
+
+    /// Parses a [`Document`] from a string using the provided options.
+    pub fn parse_str_with_options(
+        text: &'input str,
+        opt: &ParseOptions,
+    ) -> Result<Document<'input>, Error> {
         let xml = roxmltree::Document::parse(text)?;
-        parse(&xml)
+        parse(&xml, opt)
     }
 
     /// Parses a [`Document`] from a [`roxmltree::Document`].
     pub fn parse_tree(xml: &roxmltree::Document<'input>) -> Result<Document<'input>, Error> {
-        parse(xml)
+        parse(xml, &ParseOptions::default())
+    }
+
+    /// Parses a [`Document`] from a [`roxmltree::Document`] using the provided options.
+    pub fn parse_tree_with_options(
+        xml: &roxmltree::Document<'input>,
+        opt: &ParseOptions,
+    ) -> Result<Document<'input>, Error> {
+        parse(xml, opt)
     }
 
     pub(crate) fn append(&mut self, parent_id: NodeId, kind: NodeKind) -> NodeId {
@@ -51,11 +72,16 @@ impl<'input> Document<'input> {
     }
 }
 
-fn parse<'input>(xml: &roxmltree::Document<'input>) -> Result<Document<'input>, Error> {
+fn parse<'input>(
+    xml: &roxmltree::Document<'input>,
+    opt: &ParseOptions,
+) -> Result<Document<'input>, Error> {
     let mut doc = Document {
         nodes: Vec::new(),
         attrs: Vec::new(),
         links: HashMap::new(),
+        font_faces: Vec::new(),
+        custom_properties: HashMap::new(),
     };
 
     // Add a root node.
@@ -66,13 +92,24 @@ fn parse<'input>(xml: &roxmltree::Document<'input>) -> Result<Document<'input>,
         kind: NodeKind::Root,
     });
 
-    let style_sheet = resolve_css(xml);
+    // User-provided custom properties are inherited by the whole document.
+    if !opt.custom_properties.is_empty() {
+        let vars = opt
+            .custom_properties
+            .iter()
+            .map(|(name, value)| (name.to_string(), Rc::from(value.trim())))
+            .collect();
+        doc.custom_properties.insert(doc.root().id, Rc::new(vars));
+    }
+
+    let style_sheets = resolve_css(xml, opt);
+    doc.font_faces = style_sheets.font_faces();
 
     parse_xml_node_children(
         xml.root(),
         xml.root(),
         doc.root().id,
-        &style_sheet,
+        &style_sheets,
         false,
         0,
         &mut doc,
@@ -103,6 +140,9 @@ fn parse<'input>(xml: &roxmltree::Document<'input>) -> Result<Document<'input>,
     fix_recursive_links(ElementId::Filter, AttributeId::Filter, &mut doc);
     fix_recursive_fe_image(&mut doc);
 
+    // Custom properties are already substituted and no longer needed.
+    doc.custom_properties = HashMap::new();
+
     Ok(doc)
 }
 
@@ -122,13 +162,21 @@ fn parse_xml_node_children<'input>(
     parent: roxmltree::Node<'_, 'input>,
     origin: roxmltree::Node,
     parent_id: NodeId,
-    style_sheet: &simplecss::StyleSheet,
+    style_sheets: &StyleSheets,
     ignore_ids: bool,
     depth: u32,
     doc: &mut Document<'input>,
 ) -> Result<(), Error> {
     for node in parent.children() {
-        parse_xml_node(node, origin, parent_id, style_sheet, ignore_ids, depth, doc)?;
+        parse_xml_node(
+            node,
+            origin,
+            parent_id,
+            style_sheets,
+            ignore_ids,
+            depth,
+            doc,
+        )?;
     }
 
     Ok(())
@@ -138,7 +186,7 @@ fn parse_xml_node<'input>(
     node: roxmltree::Node<'_, 'input>,
     origin: roxmltree::Node,
     parent_id: NodeId,
-    style_sheet: &simplecss::StyleSheet,
+    style_sheets: &StyleSheets,
     ignore_ids: bool,
     depth: u32,
     doc: &mut Document<'input>,
@@ -162,17 +210,17 @@ fn parse_xml_node<'input>(
         tag_name = ElementId::G;
     }
 
-    let node_id = parse_svg_element(node, parent_id, tag_name, style_sheet, ignore_ids, doc)?;
+    let node_id = parse_svg_element(node, parent_id, tag_name, style_sheets, ignore_ids, doc)?;
     if tag_name == ElementId::Text {
-        crate::text::parse_svg_text_element(node, node_id, style_sheet, doc)?;
+        crate::text::parse_svg_text_element(node, node_id, style_sheets, doc)?;
     } else if tag_name == ElementId::Use {
-        parse_svg_use_element(node, origin, node_id, style_sheet, depth + 1, doc)?;
+        parse_svg_use_element(node, origin, node_id, style_sheets, depth + 1, doc)?;
     } else {
         parse_xml_node_children(
             node,
             origin,
             node_id,
-            style_sheet,
+            style_sheets,
             ignore_ids,
             depth + 1,
             doc,
@@ -186,13 +234,89 @@ pub(crate) fn parse_svg_element<'input>(
     xml_node: roxmltree::Node<'_, 'input>,
     parent_id: NodeId,
     tag_name: ElementId,
-    style_sheet: &simplecss::StyleSheet,
+    style_sheets: &StyleSheets,
     ignore_ids: bool,
     doc: &mut Document<'input>,
 ) -> Result<NodeId, Error> {
+    // Collect CSS declarations in the cascade order.
+    let mut ua_declarations = Vec::new();
+    collect_style_sheet(
+        &style_sheets.user_agent,
+        xml_node,
+        false,
+        &mut ua_declarations,
+    );
+
+    let mut declarations = Vec::new();
+    collect_style_sheet(&style_sheets.author, xml_node, false, &mut declarations);
+    collect_style_attribute(xml_node, false, &mut declarations);
+    // `!important` declarations are applied in the reversed cascade order.
+    collect_style_sheet(&style_sheets.author, xml_node, true, &mut declarations);
+    collect_style_attribute(xml_node, true, &mut declarations);
+    collect_style_sheet(&style_sheets.user_agent, xml_node, true, &mut declarations);
+
+    let inherited_vars = doc
+        .get(parent_id)
+        .ancestors()
+        .find_map(|n| doc.custom_properties.get(&n.id))
+        .cloned()
+        .unwrap_or_default();
+    let own_vars = resolve_custom_properties(
+        ua_declarations.iter().chain(declarations.iter()),
+        &inherited_vars,
+    );
+    let vars = own_vars.clone().unwrap_or(inherited_vars);
+
+    let is_root_element = parent_id == doc.root().id;
     let attrs_start_idx = doc.attrs.len();
 
-    // Copy presentational attributes first.
+    let mut insert_attribute = |aid, value: Option<roxmltree::StringStorage<'input>>| {
+        // Check that attribute already exists.
+        let idx = doc.attrs[attrs_start_idx..]
+            .iter_mut()
+            .position(|a| a.name == aid);
+
+        match value {
+            Some(value) => {
+                // Append an attribute as usual.
+                let added = append_attribute(parent_id, tag_name, aid, value, doc);
+
+                // Check that attribute was actually added, because it could be skipped.
+                if added {
+                    if let Some(idx) = idx {
+                        // Swap the last attribute with an existing one.
+                        let last_idx = doc.attrs.len() - 1;
+                        doc.attrs.swap(attrs_start_idx + idx, last_idx);
+                        // Remove last.
+                        doc.attrs.pop();
+                    }
+                }
+            }
+            None => {
+                // Reset to the initial value, which is the same as not having an attribute.
+                if let Some(idx) = idx {
+                    doc.attrs.remove(attrs_start_idx + idx);
+                }
+            }
+        }
+    };
+
+    // The initial `color` value acts like a user agent style for the root element.
+    if is_root_element {
+        if let Some(color) = style_sheets.color {
+            insert_attribute(
+                AttributeId::Color,
+                Some(roxmltree::StringStorage::new_owned(color)),
+            );
+        }
+    }
+
+    // Apply user agent styles first, so everything else could override them.
+    for declaration in &ua_declarations {
+        apply_declaration(declaration, &vars, &mut insert_attribute);
+    }
+
+    // Copy presentational attributes.
     for attr in xml_node.attributes() {
         match attr.namespace() {
             None | Some(SVG_NS) | Some(XLINK_NS) | Some(XML_NAMESPACE_NS) => {}
@@ -204,6 +328,14 @@ pub(crate) fn parse_svg_element<'input>(
             None => continue,
         };
 
+        // `xml:lang` takes precedence over `lang`.
+        if aid == AttributeId::Lang
+            && attr.namespace().is_none()
+            && xml_node.has_attribute((XML_NAMESPACE_NS, "lang"))
+        {
+            continue;
+        }
+
         // During a `use` resolving, all `id` attributes must be ignored.
         // Otherwise we will get elements with duplicated id's.
         if ignore_ids && aid == AttributeId::Id {
@@ -218,81 +350,208 @@ pub(crate) fn parse_svg_element<'input>(
             continue;
         }
 
-        append_attribute(parent_id, tag_name, aid, attr.value_storage().clone(), doc);
+        insert_attribute(aid, Some(attr.value_storage().clone()));
     }
 
-    let mut insert_attribute = |aid, value: &str| {
-        // Check that attribute already exists.
-        let idx = doc.attrs[attrs_start_idx..]
-            .iter_mut()
-            .position(|a| a.name == aid);
+    // Apply CSS and the `style` attribute.
+    for declaration in &declarations {
+        apply_declaration(declaration, &vars, &mut insert_attribute);
+    }
 
-        // Append an attribute as usual.
-        let added = append_attribute(
-            parent_id,
+    if doc.nodes.len() > 1_000_000 {
+        return Err(Error::NodesLimitReached);
+    }
+
+    let node_id = doc.append(
+        parent_id,
+        NodeKind::Element {
             tag_name,
-            aid,
-            roxmltree::StringStorage::new_owned(value),
-            doc,
-        );
+            attributes: ShortRange::new(attrs_start_idx as u32, doc.attrs.len() as u32),
+        },
+    );
 
-        // Check that attribute was actually added, because it could be skipped.
-        if added {
-            if let Some(idx) = idx {
-                // Swap the last attribute with an existing one.
-                let last_idx = doc.attrs.len() - 1;
-                doc.attrs.swap(attrs_start_idx + idx, last_idx);
-                // Remove last.
-                doc.attrs.pop();
-            }
-        }
-    };
+    if let Some(vars) = own_vars {
+        doc.custom_properties.insert(node_id, vars);
+    }
 
-    // Apply CSS.
+    Ok(node_id)
+}
+
+fn collect_style_sheet<'a>(
+    style_sheet: &'a css::StyleSheet,
+    xml_node: roxmltree::Node,
+    important: bool,
+    declarations: &mut Vec<css::Declaration<'a>>,
+) {
     for rule in &style_sheet.rules {
-        if rule.selector.matches(&XmlNode(xml_node)) {
+        if rule.selector.matches(xml_node) {
             for declaration in &rule.declarations {
-                // TODO: perform XML attribute normalization
-                if let Some(aid) = AttributeId::from_str(declaration.name) {
-                    // Parse only the presentation attributes.
-                    if aid.is_presentation() {
-                        insert_attribute(aid, declaration.value);
-                    }
-                } else if declaration.name == "marker" {
-                    insert_attribute(AttributeId::MarkerStart, declaration.value);
-                    insert_attribute(AttributeId::MarkerMid, declaration.value);
-                    insert_attribute(AttributeId::MarkerEnd, declaration.value);
+                if declaration.important == important {
+                    declarations.push(*declaration);
                 }
             }
         }
     }
+}
 
-    // Split a `style` attribute.
+fn collect_style_attribute<'a>(
+    xml_node: roxmltree::Node<'a, '_>,
+    important: bool,
+    declarations: &mut Vec<css::Declaration<'a>>,
+) {
     if let Some(value) = xml_node.attribute("style") {
-        for declaration in simplecss::DeclarationTokenizer::from(value) {
-            // TODO: preform XML attribute normalization
-            if let Some(aid) = AttributeId::from_str(declaration.name) {
-                // Parse only the presentation attributes.
-                if aid.is_presentation() {
-                    insert_attribute(aid, declaration.value);
+        for declaration in css::DeclarationTokenizer::from(value) {
+            if declaration.important == important {
+                declarations.push(declaration);
+            }
+        }
+    }
+}
+
+fn apply_declaration<'input>(
+    declaration: &css::Declaration,
+    vars: &CustomProperties,
+    insert_attribute: &mut impl FnMut(AttributeId, Option<roxmltree::StringStorage<'input>>),
+) {
+    let aids: &[AttributeId] = if declaration.name == "marker" {
+        &[
+            AttributeId::MarkerStart,
+            AttributeId::MarkerMid,
+            AttributeId::MarkerEnd,
+        ]
+    } else {
+        // TODO: perform XML attribute normalization
+        match AttributeId::from_str(declaration.name) {
+            // Parse only the presentation attributes.
+            Some(aid) if aid.is_presentation() => &[aid],
+            _ => return,
+        }
+    };
+
+    let value = if css::has_vars(declaration.value) {
+        css::substitute_vars(declaration.value, &mut |name| vars.get(name).cloned())
+    } else {
+        Some(declaration.value.to_string())
+    };
+
+    for aid in aids.iter().cloned() {
+        match value {
+            Some(ref value) => {
+                insert_attribute(
+                    aid,
+                    Some(roxmltree::StringStorage::new_owned(value.as_str())),
+                );
+            }
+            None => {
+                // A declaration that is invalid at computed-value time
+                // acts like `unset`.
+                if aid.is_inheritable() && aid.allows_inherit_value() {
+                    insert_attribute(aid, Some(roxmltree::StringStorage::Borrowed("inherit")));
+                } else {
+                    insert_attribute(aid, None);
                 }
             }
         }
     }
+}
 
-    if doc.nodes.len() > 1_000_000 {
-        return Err(Error::NodesLimitReached);
+/// Computed custom properties of an element, including the inherited ones.
+///
+/// Values are shared, since most of them are simply inherited.
+pub(crate) type CustomProperties = Rc<HashMap<String, Rc<str>>>;
+
+/// Resolves custom properties declared on an element.
+///
+/// Returns `None` when the element doesn't declare any,
+/// which means that it simply inherits the parent ones.
+fn resolve_custom_properties<'a>(
+    declarations: impl Iterator<Item = &'a css::Declaration<'a>>,
+    inherited: &CustomProperties,
+) -> Option<CustomProperties> {
+    // Declarations are already in the cascade order, so the last one wins.
+    let mut specified: HashMap<&str, &str> = HashMap::new();
+    for declaration in declarations {
+        if declaration.name.starts_with("--") {
+            specified.insert(declaration.name, declaration.value);
+        }
     }
 
-    let node_id = doc.append(
-        parent_id,
-        NodeKind::Element {
-            tag_name,
-            attributes: ShortRange::new(attrs_start_idx as u32, doc.attrs.len() as u32),
+    if specified.is_empty() {
+        return None;
+    }
+
+    let mut computed = HashMap::clone(&**inherited);
+    let mut state = ResolvingState {
+        specified: &specified,
+        inherited,
+        resolved: HashMap::new(),
+        resolving: Vec::new(),
+    };
+    for name in specified.keys() {
+        match resolve_custom_property(name, &mut state) {
+            Some(value) => computed.insert(name.to_string(), value),
+            // An invalid custom property is reset to the initial value.
+            None => computed.remove(*name),
+        };
+    }
+
+    Some(Rc::new(computed))
+}
+
+struct ResolvingState<'a, 'b> {
+    specified: &'b HashMap<&'a str, &'a str>,
+    inherited: &'b CustomProperties,
+    /// Already resolved properties.
+    ///
+    /// Each property must be resolved only once, otherwise properties that reference
+    /// the same property multiple times will be expanded exponentially.
+    resolved: HashMap<&'a str, Option<Rc<str>>>,
+    /// Properties that are being resolved. Used to detect dependency cycles.
+    resolving: Vec<&'a str>,
+}
+
+fn resolve_custom_property<'a>(
+    name: &'a str,
+    state: &mut ResolvingState<'a, '_>,
+) -> Option<Rc<str>> {
+    let value = match state.specified.get(name) {
+        Some(value) => *value,
+        None => return state.inherited.get(name).cloned(),
+    };
+
+    if value == "inherit" {
+        return state.inherited.get(name).cloned();
+    } else if value == "initial" || value == "unset" {
+        return None;
+    }
+
+    if !css::has_vars(value) {
+        return Some(Rc::from(value));
+    }
+
+    if let Some(value) = state.resolved.get(name) {
+        return value.clone();
+    }
+
+    if state.resolving.contains(&name) {
+        log::warn!("Custom property '{}' has a dependency cycle.", name);
+        return None;
+    }
+
+    state.resolving.push(name);
+    let specified = state.specified;
+    let value = css::substitute_vars(
+        value,
+        &mut |var_name| match specified.get_key_value(var_name) {
+            Some((var_name, _)) => resolve_custom_property(var_name, state),
+            None => state.inherited.get(var_name).cloned(),
         },
-    );
+    )
+    .map(Rc::from);
+    state.resolving.pop();
 
-    Ok(node_id)
+    state.resolved.insert(name, value.clone());
+    value
 }
 
 fn append_attribute<'input>(
@@ -445,7 +704,7 @@ fn parse_svg_use_element<'input>(
     node: roxmltree::Node<'_, 'input>,
     origin: roxmltree::Node,
     parent_id: NodeId,
-    style_sheet: &simplecss::StyleSheet,
+    style_sheets: &StyleSheets,
     depth: u32,
     doc: &mut Document<'input>,
 ) -> Result<(), Error> {
@@ -506,11 +765,86 @@ fn parse_svg_use_element<'input>(
         return Ok(());
     }
 
-    parse_xml_node(link, node, parent_id, style_sheet, true, depth + 1, doc)
+    parse_xml_node(link, node, parent_id, style_sheets, true, depth + 1, doc)
+}
+
+/// Resolves the root element size in CSS pixels.
+///
+/// This is a simplified version of what `usvg` does,
+/// but it's good enough for media queries evaluation.
+fn resolve_viewport_size(xml: &roxmltree::Document, opt: &ParseOptions) -> (f64, f64) {
+    let root = xml.root_element();
+    let view_box = root
+        .attribute("viewBox")
+        .and_then(|v| v.parse::<svgtypes::ViewBox>().ok())
+        .filter(|vb| vb.w > 0.0 && vb.h > 0.0);
+
+    let resolve = |name: &str, vb_side: Option<f64>, default: f64| {
+        let length = root
+            .attribute(name)
+            .and_then(|v| v.parse::<svgtypes::Length>().ok())
+            .unwrap_or(svgtypes::Length::new(100.0, svgtypes::LengthUnit::Percent));
+
+        let n = length.number;
+        let side = match length.unit {
+            svgtypes::LengthUnit::None | svgtypes::LengthUnit::Px => n,
+            svgtypes::LengthUnit::Em => n * 16.0,
+            svgtypes::LengthUnit::Ex => n * 8.0,
+            svgtypes::LengthUnit::In => n * 96.0,
+            svgtypes::LengthUnit::Cm => n * 96.0 / 2.54,
+            svgtypes::LengthUnit::Mm => n * 96.0 / 25.4,
+            svgtypes::LengthUnit::Pt => n * 4.0 / 3.0,
+            svgtypes::LengthUnit::Pc => n * 16.0,
+            svgtypes::LengthUnit::Percent => vb_side.unwrap_or(default) * n / 100.0,
+        };
+
+        if side > 0.0 {
+            side
+        } else {
+            default
+        }
+    };
+
+    (
+        resolve(
+            "width",
+            view_box.map(|vb| vb.w),
+            opt.default_viewport_size.0,
+        ),
+        resolve(
+            "height",
+            view_box.map(|vb| vb.h),
+            opt.default_viewport_size.1,
+        ),
+    )
 }
 
-fn resolve_css<'a>(xml: &'a roxmltree::Document<'a>) -> simplecss::StyleSheet<'a> {
-    let mut sheet = simplecss::StyleSheet::new();
+/// Style sheets split by a cascade origin.
+pub(crate) struct StyleSheets<'a> {
+    user_agent: css::StyleSheet<'a>,
+    author: css::StyleSheet<'a>,
+    /// The initial `color` value.
+    color: Option<&'a str>,
+}
+
+impl StyleSheets<'_> {
+    fn font_faces(&self) -> Vec<crate::FontFace> {
+        let mut faces = self.author.font_faces.clone();
+        faces.extend_from_slice(&self.user_agent.font_faces);
+        faces
+    }
+}
+
+fn resolve_css<'a>(xml: &'a roxmltree::Document<'a>, opt: &ParseOptions<'a>) -> StyleSheets<'a> {
+    let viewport_size = resolve_viewport_size(xml, opt);
+    let media = css::MediaContext {
+        width: viewport_size.0,
+        height: viewport_size.1,
+        color_scheme: opt.color_scheme,
+    };
+
+    let mut user_agent = css::StyleSheet::new();
+    let mut author = css::StyleSheet::new();
 
     for node in xml.descendants().filter(|n| n.has_tag_name("style")) {
         match node.attribute("type") {
@@ -524,40 +858,24 @@ fn resolve_css<'a>(xml: &'a roxmltree::Document<'a>) -> simplecss::StyleSheet<'a
             None => continue,
         };
 
-        sheet.parse_more(text);
-    }
-
-    sheet
-}
-
-struct XmlNode<'a, 'input: 'a>(roxmltree::Node<'a, 'input>);
-
-impl simplecss::Element for XmlNode<'_, '_> {
-    fn parent_element(&self) -> Option<Self> {
-        self.0.parent_element().map(XmlNode)
-    }
-
-    fn prev_sibling_element(&self) -> Option<Self> {
-        self.0.prev_sibling_element().map(XmlNode)
+        author.parse_more(text, &media);
     }
 
-    fn has_local_name(&self, local_name: &str) -> bool {
-        self.0.tag_name().name() == local_name
-    }
+    // Rules with the same specificity are applied in order,
+    // so author-level user styles must be parsed after the document ones.
+    let user_sheet = match opt.style_sheet_origin {
+        StyleSheetOrigin::UserAgent => &mut user_agent,
+        StyleSheetOrigin::Author => &mut author,
+    };
 
-    fn attribute_matches(&self, local_name: &str, operator: simplecss::AttributeOperator) -> bool {
-        match self.0.attribute(local_name) {
-            Some(value) => operator.matches(value),
-            None => false,
-        }
+    for text in &opt.style_sheet {
+        user_sheet.parse_more(text, &media);
     }
 
-    fn pseudo_class_matches(&self, class: simplecss::PseudoClass) -> bool {
-        match class {
-            simplecss::PseudoClass::FirstChild => self.prev_sibling_element().is_none(),
-            // TODO: lang
-            _ => false, // Since we are querying a static SVG we can ignore other pseudo-classes.
-        }
+    StyleSheets {
+        user_agent,
+        author,
+        color: opt.color,
     }
 }
 
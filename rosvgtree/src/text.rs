@@ -9,26 +9,23 @@ const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
 pub(crate) fn parse_svg_text_element<'input>(
     parent: roxmltree::Node<'_, 'input>,
     parent_id: NodeId,
-    style_sheet: &simplecss::StyleSheet,
+    style_sheets: &crate::parse::StyleSheets,
     doc: &mut Document<'input>,
 ) -> Result<(), Error> {
     debug_assert_eq!(parent.tag_name().name(), "text");
 
-    let space = if doc.get(parent_id).has_attribute(AttributeId::Space) {
-        get_xmlspace(doc, parent_id, XmlSpace::Default)
-    } else {
-        if let Some(node) = doc
-            .get(parent_id)
-            .ancestors()
-            .find(|n| n.has_attribute(AttributeId::Space))
-        {
-            get_xmlspace(doc, node.id, XmlSpace::Default)
-        } else {
-            XmlSpace::Default
-        }
-    };
+    let (space, newlines) =
+        match doc.get(parent_id).ancestors().find(|n| {
+            n.has_attribute(AttributeId::Space) || n.has_attribute(AttributeId::WhiteSpace)
+        }) {
+            Some(node) => (
+                get_xmlspace(doc, node.id, XmlSpace::Default),
+                get_preserve_newlines(doc, node.id, false),
+            ),
+            None => (XmlSpace::Default, false),
+        };
 
-    parse_svg_text_element_impl(parent, parent_id, style_sheet, space, doc)?;
+    parse_svg_text_element_impl(parent, parent_id, style_sheets, space, newlines, doc)?;
 
     trim_text_nodes(parent_id, space, doc);
     Ok(())
@@ -37,13 +34,14 @@ pub(crate) fn parse_svg_text_element<'input>(
 fn parse_svg_text_element_impl<'input>(
     parent: roxmltree::Node<'_, 'input>,
     parent_id: NodeId,
-    style_sheet: &simplecss::StyleSheet,
+    style_sheets: &crate::parse::StyleSheets,
     space: XmlSpace,
+    newlines: bool,
     doc: &mut Document<'input>,
 ) -> Result<(), Error> {
     for node in parent.children() {
         if node.is_text() {
-            let text = trim_text(node.text().unwrap(), space);
+            let text = trim_text(node.text().unwrap(), space, newlines);
             doc.append(parent_id, NodeKind::Text(text));
             continue;
         }
@@ -78,8 +76,9 @@ fn parse_svg_text_element_impl<'input>(
         }
 
         let node_id =
-            crate::parse::parse_svg_element(node, parent_id, tag_name, style_sheet, false, doc)?;
+            crate::parse::parse_svg_element(node, parent_id, tag_name, style_sheets, false, doc)?;
         let space = get_xmlspace(doc, node_id, space);
+        let newlines = get_preserve_newlines(doc, node_id, newlines);
 
         if is_tref {
             let link_value = node
@@ -88,12 +87,12 @@ fn parse_svg_text_element_impl<'input>(
 
             if let Some(href) = link_value {
                 if let Some(text) = resolve_tref_text(node.document(), href) {
-                    let text = trim_text(&text, space);
+                    let text = trim_text(&text, space, newlines);
                     doc.append(node_id, NodeKind::Text(text));
                 }
             }
         } else {
-            parse_svg_text_element_impl(node, node_id, style_sheet, space, doc)?;
+            parse_svg_text_element_impl(node, node_id, style_sheets, space, newlines, doc)?;
         }
     }
 
@@ -135,6 +134,13 @@ enum XmlSpace {
 }
 
 fn get_xmlspace(doc: &Document, node_id: NodeId, default: XmlSpace) -> XmlSpace {
+    // `white-space` takes precedence over the deprecated `xml:space`.
+    match doc.get(node_id).attribute(AttributeId::WhiteSpace) {
+        Some("pre") | Some("pre-wrap") | Some("break-spaces") => return XmlSpace::Preserve,
+        Some("normal") | Some("nowrap") | Some("pre-line") => return XmlSpace::Default,
+        _ => {}
+    }
+
     match doc.get(node_id).attribute(AttributeId::Space) {
         Some("preserve") => XmlSpace::Preserve,
         Some(_) => XmlSpace::Default,
@@ -142,6 +148,18 @@ fn get_xmlspace(doc: &Document, node_id: NodeId, default: XmlSpace) -> XmlSpace
     }
 }
 
+/// Checks that newlines should be preserved and not converted into spaces.
+///
+/// Unlike `xml:space="preserve"`, `white-space` preserves newlines,
+/// which are forced line breaks during the text layout.
+fn get_preserve_newlines(doc: &Document, node_id: NodeId, default: bool) -> bool {
+    match doc.get(node_id).attribute(AttributeId::WhiteSpace) {
+        Some("pre") | Some("pre-wrap") | Some("break-spaces") | Some("pre-line") => true,
+        Some(_) => false,
+        None => default,
+    }
+}
+
 trait StrTrim {
     fn remove_first_space(&mut self);
     fn remove_last_space(&mut self);
@@ -341,13 +359,14 @@ fn collect_text_nodes(parent: Node, depth: usize, nodes: &mut Vec<(NodeId, usize
     }
 }
 
-fn trim_text(text: &str, space: XmlSpace) -> String {
+fn trim_text(text: &str, space: XmlSpace, newlines: bool) -> String {
     let mut s = String::with_capacity(text.len());
 
     let mut prev = '0';
     for c in text.chars() {
         // \r, \n and \t should be converted into spaces.
         let c = match c {
+            '\n' if newlines => '\n',
             '\r' | '\n' | '\t' => ' ',
             _ => c,
         };
@@ -357,6 +376,17 @@ fn trim_text(text: &str, space: XmlSpace) -> String {
             continue;
         }
 
+        // Spaces around a preserved newline are collapsed as well.
+        if space == XmlSpace::Default && newlines {
+            if c == ' ' && prev == '\n' {
+                continue;
+            }
+
+            if c == '\n' && prev == ' ' {
+                s.pop();
+            }
+        }
+
         prev = c;
 
         s.push(c);
@@ -112,7 +112,18 @@ fn process() -> Result<(), String> {
             }
         }
 
-        timed(args.perf, "Text Conversion", || tree.convert_text(&fontdb));
+        timed(args.perf, "Text Conversion", || {
+            if args.hint_text {
+                convert_hinted_text(&args, &mut tree, &fontdb)
+            } else {
+                tree.convert_text(&fontdb);
+                Ok(())
+            }
+        })?;
+    }
+
+    for diagnostic in &tree.diagnostics {
+        log::warn!("{}", diagnostic);
     }
 
     if args.query_all {
@@ -185,6 +196,20 @@ OPTIONS:
                                 Expected to be the same as the directory that
                                 contains the SVG file, but can be set to any.
                                 [default: input file directory]
+  --stylesheet PATH             Applies a CSS style sheet to the input SVG.
+                                This option can be set multiple times
+  --stylesheet-origin ORIGIN    Sets the cascade origin of the '--stylesheet' files.
+                                'user-agent' style sheets are applied before
+                                the document styles, 'author' ones - after
+                                [default: author] [possible values: user-agent, author]
+  --color-scheme SCHEME         Sets the preferred color scheme used by
+                                'prefers-color-scheme' CSS media queries
+                                [default: light] [possible values: light, dark]
+  --css-variable NAME=VALUE     Sets a CSS custom property on the root element,
+                                like '--css-variable --main-color=green'.
+                                This option can be set multiple times
+  --current-color COLOR         Sets the initial 'color' property value
+                                used by 'currentColor' [default: black]
 
   --font-family FAMILY          Sets the default font family that will be
                                 used when no 'font-family' is present
@@ -215,6 +240,8 @@ OPTIONS:
                                 Otherwise, text elements will not be processes
   --list-fonts                  Lists successfully loaded font faces.
                                 Useful for debugging
+  --hint-text                   Fits glyphs of small 'optimizeLegibility' text
+                                to the pixel grid. Has no effect with --export-id
 
 
   --query-all                   Queries all valid SVG ids with bounding boxes
@@ -245,6 +272,11 @@ struct CliArgs {
     text_rendering: usvg::TextRendering,
     image_rendering: usvg::ImageRendering,
     resources_dir: Option<path::PathBuf>,
+    style_sheets: Vec<path::PathBuf>,
+    style_sheet_origin: usvg::StyleSheetOrigin,
+    color_scheme: usvg::ColorScheme,
+    custom_properties: Vec<(String, String)>,
+    current_color: Option<String>,
 
     font_family: Option<String>,
     font_size: u32,
@@ -257,6 +289,7 @@ struct CliArgs {
     font_dirs: Vec<path::PathBuf>,
     skip_system_fonts: bool,
     list_fonts: bool,
+    hint_text: bool,
 
     query_all: bool,
     export_id: Option<String>,
@@ -306,6 +339,15 @@ fn collect_args() -> Result<CliArgs, pico_args::Error> {
         resources_dir: input
             .opt_value_from_str("--resources-dir")
             .unwrap_or_default(),
+        style_sheets: input.values_from_str("--stylesheet")?,
+        style_sheet_origin: input
+            .opt_value_from_fn("--stylesheet-origin", parse_style_sheet_origin)?
+            .unwrap_or_default(),
+        color_scheme: input
+            .opt_value_from_fn("--color-scheme", parse_color_scheme)?
+            .unwrap_or_default(),
+        custom_properties: input.values_from_fn("--css-variable", parse_custom_property)?,
+        current_color: input.opt_value_from_str("--current-color")?,
 
         font_family: input.opt_value_from_str("--font-family")?,
         font_size: input
@@ -320,6 +362,7 @@ fn collect_args() -> Result<CliArgs, pico_args::Error> {
         font_dirs: input.values_from_str("--use-fonts-dir")?,
         skip_system_fonts: input.contains("--skip-system-fonts"),
         list_fonts: input.contains("--list-fonts"),
+        hint_text: input.contains("--hint-text"),
 
         query_all: input.contains("--query-all"),
         export_id: input.opt_value_from_str("--export-id")?,
@@ -388,6 +431,29 @@ fn parse_languages(s: &str) -> Result<Vec<String>, String> {
     Ok(langs)
 }
 
+fn parse_style_sheet_origin(s: &str) -> Result<usvg::StyleSheetOrigin, String> {
+    match s {
+        "user-agent" => Ok(usvg::StyleSheetOrigin::UserAgent),
+        "author" => Ok(usvg::StyleSheetOrigin::Author),
+        _ => Err("invalid ORIGIN value".to_string()),
+    }
+}
+
+fn parse_custom_property(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => Ok((name.to_string(), value.to_string())),
+        _ => Err("invalid NAME=VALUE value".to_string()),
+    }
+}
+
+fn parse_color_scheme(s: &str) -> Result<usvg::ColorScheme, String> {
+    match s {
+        "light" => Ok(usvg::ColorScheme::Light),
+        "dark" => Ok(usvg::ColorScheme::Dark),
+        _ => Err("invalid SCHEME value".to_string()),
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 enum InputFrom {
     Stdin,
@@ -422,6 +488,7 @@ struct Args {
     font_dirs: Vec<path::PathBuf>,
     skip_system_fonts: bool,
     list_fonts: bool,
+    hint_text: bool,
 }
 
 fn parse_args() -> Result<Args, String> {
@@ -467,6 +534,10 @@ fn parse_args() -> Result<Args, String> {
         println!("Warning: --export-area-drawing has no effect when --export-id is set.");
     }
 
+    if args.hint_text && args.export_id.is_some() {
+        println!("Warning: --hint-text has no effect when --export-id is set.");
+    }
+
     let export_id = args.export_id.as_ref().map(|v| v.to_string());
 
     let mut fit_to = resvg::FitTo::Original;
@@ -495,6 +566,13 @@ fn parse_args() -> Result<Args, String> {
         None => None,
     };
 
+    let mut style_sheet = Vec::new();
+    for path in &args.style_sheets {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read '{}' cause {}", path.display(), e))?;
+        style_sheet.push(text);
+    }
+
     let usvg = usvg::Options {
         resources_dir,
         dpi: args.dpi as f64,
@@ -509,6 +587,11 @@ fn parse_args() -> Result<Args, String> {
         image_rendering: args.image_rendering,
         default_size,
         image_href_resolver: usvg::ImageHrefResolver::default(),
+        style_sheet,
+        style_sheet_origin: args.style_sheet_origin,
+        color_scheme: args.color_scheme,
+        custom_properties: args.custom_properties,
+        color: args.current_color,
     };
 
     Ok(Args {
@@ -532,6 +615,7 @@ fn parse_args() -> Result<Args, String> {
         font_dirs: args.font_dirs,
         skip_system_fonts: args.skip_system_fonts,
         list_fonts: args.list_fonts,
+        hint_text: args.hint_text && args.export_id.is_none(),
     })
 }
 
@@ -595,6 +679,33 @@ fn query_all(tree: &usvg::Tree) -> Result<(), String> {
     Ok(())
 }
 
+fn convert_hinted_text(
+    args: &Args,
+    tree: &mut usvg::Tree,
+    fontdb: &fontdb::Database,
+) -> Result<(), String> {
+    let size = args
+        .fit_to
+        .fit_to(tree.size.to_screen_size())
+        .ok_or_else(|| "target size is zero".to_string())?;
+
+    let ts = usvg::utils::view_box_to_transform(
+        tree.view_box.rect,
+        tree.view_box.aspect,
+        size.to_size(),
+    );
+
+    let hinting = usvg::TextHinting::new(ts);
+    tree.convert_text_with_options(
+        fontdb,
+        &usvg::TextConversionOptions {
+            hinting: Some(&hinting),
+            ..usvg::TextConversionOptions::default()
+        },
+    );
+    Ok(())
+}
+
 fn render_svg(args: &Args, tree: &usvg::Tree) -> Result<tiny_skia::Pixmap, String> {
     let now = std::time::Instant::now();
 
@@ -0,0 +1,393 @@
+use usvg::TreeParsing;
+
+#[test]
+fn text_layout() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text x='20' y='100' font-family='Noto Sans' font-size='20'>Te<tspan>xt</tspan></text>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+    let node = tree.root.first_child().unwrap();
+    let layout = match *node.borrow() {
+        usvg::NodeKind::Text(ref text) => {
+            let db = super::GLOBAL_FONTDB.lock().unwrap();
+            usvg::layout(
+                text,
+                &db,
+                &usvg::DocumentFonts::default(),
+                &usvg::FontFallback::default(),
+                usvg::Transform::default(),
+            )
+        }
+        _ => unreachable!(),
+    };
+
+    assert_eq!(layout.fragments.len(), 2);
+    assert_eq!(layout.fragments[0].span, 0);
+    assert_eq!(layout.fragments[1].span, 1);
+
+    let ranges: Vec<_> = layout
+        .fragments
+        .iter()
+        .flat_map(|f| f.clusters.iter().map(|c| c.byte_range.clone()))
+        .collect();
+    assert_eq!(ranges, vec![0..1, 1..2, 2..3, 3..4]);
+
+    // Clusters are positioned one after another, starting from the text position.
+    let mut x = 20.0;
+    for cluster in layout.fragments.iter().flat_map(|f| f.clusters.iter()) {
+        assert_eq!(cluster.glyphs.len(), 1);
+        assert!((cluster.transform.e - x).abs() < 0.001);
+        assert!((cluster.transform.f - 100.0).abs() < 0.001);
+        assert!(cluster.glyphs[0].id.0 != 0);
+        assert_eq!(cluster.glyphs[0].font, layout.fragments[0].font);
+        x += cluster.advance;
+    }
+}
+
+fn layout_text(svg: &str, fallback: &usvg::FontFallback) -> usvg::TextLayout {
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+    let node = tree.root.first_child().unwrap();
+    let layout = match *node.borrow() {
+        usvg::NodeKind::Text(ref text) => {
+            let db = super::GLOBAL_FONTDB.lock().unwrap();
+            let fonts = usvg::DocumentFonts::new(&db, &tree);
+            usvg::layout(text, &db, &fonts, fallback, usvg::Transform::default())
+        }
+        _ => unreachable!(),
+    };
+    layout
+}
+
+#[test]
+fn document_fonts_layout() {
+    let svg = std::fs::read_to_string("tests/svg/e-style-024.svg").unwrap();
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let node = tree
+        .root
+        .descendants()
+        .find(|node| matches!(*node.borrow(), usvg::NodeKind::Text(_)))
+        .unwrap();
+
+    let db = super::GLOBAL_FONTDB.lock().unwrap();
+    let fonts = usvg::DocumentFonts::new(&db, &tree);
+    let layout = match *node.borrow() {
+        usvg::NodeKind::Text(ref text) => usvg::layout(
+            text,
+            &db,
+            &fonts,
+            &usvg::FontFallback::default(),
+            usvg::Transform::default(),
+        ),
+        _ => unreachable!(),
+    };
+
+    // The `@font-face` font is used, even though it's not in the fonts database.
+    let font = layout.fragments[0].font;
+    assert!(db.face(font).is_none());
+    let face = fonts.face(&db, font).unwrap();
+    assert_eq!(face.families[0].0, "My Web Font");
+    assert!(layout.diagnostics.is_empty());
+
+    let has_data = fonts.with_face_data(&db, font, |data, _| !data.is_empty());
+    assert_eq!(has_data, Some(true));
+}
+
+#[test]
+fn font_fallback() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='20' y='100' font-family='Noto Sans' font-size='20'>A日&#x12000;</text>
+    </svg>
+    ";
+
+    let mut fallback = usvg::FontFallback {
+        use_database: false,
+        ..usvg::FontFallback::default()
+    };
+    fallback
+        .script_families
+        .insert(usvg::Script::Han, vec!["Mplus 1p".to_string()]);
+    let layout = layout_text(svg, &fallback);
+
+    assert_eq!(
+        layout.diagnostics,
+        vec![
+            usvg::Diagnostic::FontFallback {
+                element_id: "text1".to_string(),
+                character: '日',
+                font_family: "Noto Sans".to_string(),
+                fallback_family: "Mplus 1p".to_string(),
+            },
+            usvg::Diagnostic::MissingGlyph {
+                element_id: "text1".to_string(),
+                character: '\u{12000}',
+            },
+        ]
+    );
+
+    let glyph_fonts: Vec<_> = layout.fragments[0]
+        .clusters
+        .iter()
+        .map(|c| c.glyphs[0].font)
+        .collect();
+    assert_eq!(glyph_fonts[0], layout.fragments[0].font);
+    assert_ne!(glyph_fonts[1], layout.fragments[0].font);
+
+    // A callback has the highest priority.
+    let db = super::GLOBAL_FONTDB.lock().unwrap();
+    let font_id = db
+        .query(&usvg::fontdb::Query {
+            families: &[usvg::fontdb::Family::Name("Mplus 1p")],
+            ..Default::default()
+        })
+        .unwrap();
+    drop(db);
+
+    let fallback = usvg::FontFallback {
+        callback: Some(std::rc::Rc::new(move |c, font, script| {
+            assert_eq!(font.families, vec!["Noto Sans".to_string()]);
+            if c == '日' && script == usvg::Script::Han {
+                Some(font_id)
+            } else {
+                None
+            }
+        })),
+        use_database: false,
+        ..usvg::FontFallback::default()
+    };
+    let layout = layout_text(svg, &fallback);
+    assert_eq!(layout.fragments[0].clusters[1].glyphs[0].font, font_id);
+    assert_eq!(layout.diagnostics.len(), 2);
+}
+
+#[test]
+fn grapheme_font_fallback() {
+    // A joiner doesn't join a letter with a following non-emoji character.
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='20' y='100' font-family='Noto Sans' font-size='20'>A&#x200D;&#x3042;</text>
+    </svg>
+    ";
+
+    let mut fallback = usvg::FontFallback {
+        use_database: false,
+        ..usvg::FontFallback::default()
+    };
+    fallback
+        .script_families
+        .insert(usvg::Script::Hiragana, vec!["Mplus 1p".to_string()]);
+    let layout = layout_text(svg, &fallback);
+
+    let glyph_fonts: Vec<_> = layout
+        .fragments
+        .iter()
+        .flat_map(|f| f.clusters.iter())
+        .map(|c| c.glyphs[0].font)
+        .collect();
+    assert_eq!(glyph_fonts.len(), 2);
+    assert_eq!(glyph_fonts[0], layout.fragments[0].font);
+    assert_ne!(glyph_fonts[1], layout.fragments[0].font);
+}
+
+#[test]
+fn language_font_fallback() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='20' y='100' font-family='Yellowtail' font-size='20'>
+            Ж<tspan xml:lang='ru' lang='en'>Д</tspan><tspan lang='ru-RU'>Л</tspan>
+        </text>
+    </svg>
+    ";
+
+    let mut fallback = usvg::FontFallback {
+        use_database: false,
+        ..usvg::FontFallback::default()
+    };
+    fallback
+        .script_families
+        .insert(usvg::Script::Cyrillic, vec!["Noto Sans".to_string()]);
+    fallback
+        .language_families
+        .insert("ru".to_string(), vec!["Noto Serif".to_string()]);
+    fallback
+        .language_families
+        .insert("ru-RU".to_string(), vec!["Noto Sans".to_string()]);
+    let layout = layout_text(svg, &fallback);
+
+    // The span's language is preferred over the script,
+    // and more specific language tags are preferred over less specific ones.
+    let db = super::GLOBAL_FONTDB.lock().unwrap();
+    let families: Vec<_> = layout
+        .fragments
+        .iter()
+        .flat_map(|f| f.clusters.iter())
+        .filter(|c| !c.glyphs.is_empty())
+        .map(|c| db.face(c.glyphs[0].font).unwrap().families[0].0.clone())
+        .collect();
+    assert_eq!(families, vec!["Noto Sans", "Noto Serif", "Noto Sans"]);
+}
+
+#[test]
+fn line_breaks() {
+    // A hyphen-minus before a number is a sign, not a break opportunity.
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text x='20' y='100' font-family='Noto Sans' font-size='20' inline-size='1'>a -5 b</text>
+    </svg>
+    ";
+
+    let layout = layout_text(svg, &usvg::FontFallback::default());
+    let mut lines: Vec<_> = layout
+        .fragments
+        .iter()
+        .flat_map(|f| f.clusters.iter())
+        .map(|c| c.transform.f)
+        .collect();
+    lines.dedup();
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn text_orientation() {
+    let layout = |orientation: &str| {
+        let svg = format!(
+            "
+            <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+                <text x='100' y='20' font-family='Mplus 1p' font-size='20'
+                      writing-mode='tb' text-orientation='{}'>日i</text>
+            </svg>
+            ",
+            orientation
+        );
+        let layout = layout_text(&svg, &usvg::FontFallback::default());
+        let advances: Vec<_> = layout
+            .fragments
+            .iter()
+            .flat_map(|f| f.clusters.iter())
+            .map(|c| c.advance)
+            .collect();
+        advances
+    };
+
+    // Upright glyphs use vertical advances, which are 1em in this font,
+    // while sideways glyphs use horizontal ones.
+    let mixed = layout("mixed");
+    assert!((mixed[0] - 20.0).abs() < 0.001);
+    assert!(mixed[1] < 10.0);
+
+    let upright = layout("upright");
+    assert!((upright[0] - 20.0).abs() < 0.001);
+    assert!((upright[1] - 20.0).abs() < 0.001);
+
+    let sideways = layout("sideways");
+    assert!((sideways[1] - mixed[1]).abs() < 0.001);
+}
+
+#[test]
+fn glyph_cache() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text x='20' y='100' font-family='Noto Sans' font-size='20'>Tee</text>
+    </svg>
+    ";
+
+    let convert = |cache: Option<&usvg::GlyphCache>| {
+        use usvg::{TreeTextToPath, TreeWriting};
+
+        let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+        let db = super::GLOBAL_FONTDB.lock().unwrap();
+        let fallback = usvg::FontFallback::default();
+        let options = usvg::TextConversionOptions {
+            fallback: Some(&fallback),
+            cache,
+            hinting: None,
+        };
+        tree.convert_text_with_options(&db, &options);
+        tree.to_string(&usvg::XmlOptions::default())
+    };
+
+    let cache = usvg::GlyphCache::new();
+    let expected = convert(None);
+    assert_eq!(convert(Some(&cache)), expected);
+    // Each glyph is outlined only once.
+    assert_eq!(cache.outlines_count(), 2);
+    // The text is a single run.
+    assert_eq!(cache.runs_count(), 1);
+
+    // Cached outlines are reused.
+    let memory_usage = cache.memory_usage();
+    assert_eq!(convert(Some(&cache)), expected);
+    assert_eq!(cache.memory_usage(), memory_usage);
+
+    // Outlines that don't fit the limit are evicted.
+    let mut cache = usvg::GlyphCache::with_memory_limit(memory_usage);
+    assert_eq!(convert(Some(&cache)), expected);
+    assert!(cache.memory_usage() <= memory_usage);
+    assert!(cache.outlines_count() < 2);
+
+    cache.clear();
+    assert_eq!(cache.outlines_count(), 0);
+    assert_eq!(cache.runs_count(), 0);
+}
+
+#[test]
+fn text_hinting() {
+    let bbox = |attrs: &str, use_hinting: bool| {
+        use usvg::TreeTextToPath;
+
+        let svg = format!(
+            "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+                <text x='20.2' y='50.3' font-family='Noto Sans' {}>H</text>
+            </svg>",
+            attrs
+        );
+        let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+        let db = super::GLOBAL_FONTDB.lock().unwrap();
+        let fallback = usvg::FontFallback::default();
+        let cache = usvg::GlyphCache::new();
+        // Rendered at 200x200.
+        let hinting = usvg::TextHinting::new(usvg::Transform::new_scale(2.0, 2.0));
+        let options = usvg::TextConversionOptions {
+            fallback: Some(&fallback),
+            cache: Some(&cache),
+            hinting: Some(&hinting).filter(|_| use_hinting),
+        };
+        tree.convert_text_with_options(&db, &options);
+
+        let bbox = tree
+            .root
+            .descendants()
+            .find_map(|node| match *node.borrow() {
+                usvg::NodeKind::Path(ref path) => path.data.bbox(),
+                _ => None,
+            });
+        bbox.unwrap()
+    };
+
+    let is_pixel_aligned = |v: f64| ((v * 2.0).round() - v * 2.0).abs() < 0.001;
+
+    // The baseline and the cap-height are snapped to device pixels.
+    let hinted = bbox("font-size='5'", true);
+    assert!(is_pixel_aligned(hinted.bottom()));
+    assert!(is_pixel_aligned(hinted.top()));
+
+    // Horizontal positions are preserved.
+    let unhinted = bbox("font-size='5'", false);
+    assert!(!is_pixel_aligned(unhinted.bottom()));
+    assert!((hinted.left() - unhinted.left()).abs() < 0.001);
+    assert!((hinted.right() - unhinted.right()).abs() < 0.001);
+
+    // Large and `geometricPrecision` text is not hinted.
+    for attrs in [
+        "font-size='20'",
+        "font-size='5' text-rendering='geometricPrecision'",
+    ] {
+        let hinted = bbox(attrs, true);
+        let unhinted = bbox(attrs, false);
+        assert!((hinted.bottom() - unhinted.bottom()).abs() < 0.001);
+        assert!((hinted.top() - unhinted.top()).abs() < 0.001);
+    }
+}
@@ -2,6 +2,7 @@ use once_cell::sync::Lazy;
 use rgb::FromSlice;
 use usvg::{fontdb, TreeParsing, TreeTextToPath};
 
+mod layout;
 #[rustfmt::skip]
 mod render;
 
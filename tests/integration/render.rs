@@ -53,6 +53,7 @@ use crate::render;
 #[test] fn a_color_interpolation_filters_001() { assert_eq!(render("a-color-interpolation-filters-001"), 0); }
 #[test] fn a_direction_001() { assert_eq!(render("a-direction-001"), 0); }
 #[test] fn a_direction_002() { assert_eq!(render("a-direction-002"), 0); }
+#[test] fn a_direction_003() { assert_eq!(render("a-direction-003"), 0); }
 #[test] fn a_display_001() { assert_eq!(render("a-display-001"), 0); }
 #[test] fn a_display_002() { assert_eq!(render("a-display-002"), 0); }
 #[test] fn a_display_003() { assert_eq!(render("a-display-003"), 0); }
@@ -163,6 +164,12 @@ use crate::render;
 #[test] fn a_fill_057() { assert_eq!(render("a-fill-057"), 0); }
 #[test] fn a_fill_058() { assert_eq!(render("a-fill-058"), 0); }
 #[test] fn a_fill_059() { assert_eq!(render("a-fill-059"), 0); }
+#[test] fn a_fill_060() { assert_eq!(render("a-fill-060"), 0); }
+#[test] fn a_fill_061() { assert_eq!(render("a-fill-061"), 0); }
+#[test] fn a_fill_062() { assert_eq!(render("a-fill-062"), 0); }
+#[test] fn a_fill_063() { assert_eq!(render("a-fill-063"), 0); }
+#[test] fn a_fill_064() { assert_eq!(render("a-fill-064"), 0); }
+#[test] fn a_fill_065() { assert_eq!(render("a-fill-065"), 0); }
 #[test] fn a_fill_opacity_001() { assert_eq!(render("a-fill-opacity-001"), 0); }
 #[test] fn a_fill_opacity_002() { assert_eq!(render("a-fill-opacity-002"), 0); }
 #[test] fn a_fill_opacity_003() { assert_eq!(render("a-fill-opacity-003"), 0); }
@@ -223,6 +230,7 @@ use crate::render;
 #[test] fn a_flood_color_005() { assert_eq!(render("a-flood-color-005"), 0); }
 #[test] fn a_flood_color_006() { assert_eq!(render("a-flood-color-006"), 0); }
 #[test] fn a_flood_color_007() { assert_eq!(render("a-flood-color-007"), 0); }
+#[test] fn a_flood_color_008() { assert_eq!(render("a-flood-color-008"), 0); }
 #[test] fn a_flood_opacity_001() { assert_eq!(render("a-flood-opacity-001"), 0); }
 #[test] fn a_flood_opacity_002() { assert_eq!(render("a-flood-opacity-002"), 0); }
 #[test] fn a_font_001() { assert_eq!(render("a-font-001"), 0); }
@@ -237,9 +245,18 @@ use crate::render;
 #[test] fn a_font_family_009() { assert_eq!(render("a-font-family-009"), 0); }
 #[test] fn a_font_family_010() { assert_eq!(render("a-font-family-010"), 0); }
 #[test] fn a_font_family_011() { assert_eq!(render("a-font-family-011"), 0); }
+#[test] fn a_font_feature_settings_001() { assert_eq!(render("a-font-feature-settings-001"), 0); }
+#[test] fn a_font_feature_settings_002() { assert_eq!(render("a-font-feature-settings-002"), 0); }
+#[test] fn a_font_feature_settings_003() { assert_eq!(render("a-font-feature-settings-003"), 0); }
+#[test] fn a_font_feature_settings_004() { assert_eq!(render("a-font-feature-settings-004"), 0); }
+#[test] fn a_font_feature_settings_005() { assert_eq!(render("a-font-feature-settings-005"), 0); }
+#[test] fn a_font_feature_settings_006() { assert_eq!(render("a-font-feature-settings-006"), 0); }
+#[test] fn a_font_feature_settings_007() { assert_eq!(render("a-font-feature-settings-007"), 0); }
 #[test] fn a_font_kerning_001() { assert_eq!(render("a-font-kerning-001"), 0); }
 #[test] fn a_font_kerning_002() { assert_eq!(render("a-font-kerning-002"), 0); }
 #[test] fn a_font_kerning_003() { assert_eq!(render("a-font-kerning-003"), 0); }
+#[test] fn a_font_optical_sizing_001() { assert_eq!(render("a-font-optical-sizing-001"), 0); }
+#[test] fn a_font_optical_sizing_002() { assert_eq!(render("a-font-optical-sizing-002"), 0); }
 #[test] fn a_font_size_001() { assert_eq!(render("a-font-size-001"), 0); }
 #[test] fn a_font_size_002() { assert_eq!(render("a-font-size-002"), 0); }
 #[test] fn a_font_size_003() { assert_eq!(render("a-font-size-003"), 0); }
@@ -264,11 +281,36 @@ use crate::render;
 #[test] fn a_font_stretch_001() { assert_eq!(render("a-font-stretch-001"), 0); }
 #[test] fn a_font_stretch_002() { assert_eq!(render("a-font-stretch-002"), 0); }
 #[test] fn a_font_stretch_003() { assert_eq!(render("a-font-stretch-003"), 0); }
+#[test] fn a_font_stretch_004() { assert_eq!(render("a-font-stretch-004"), 0); }
+#[test] fn a_font_stretch_005() { assert_eq!(render("a-font-stretch-005"), 0); }
+#[test] fn a_font_stretch_006() { assert_eq!(render("a-font-stretch-006"), 0); }
 #[test] fn a_font_style_001() { assert_eq!(render("a-font-style-001"), 0); }
 #[test] fn a_font_style_002() { assert_eq!(render("a-font-style-002"), 0); }
 #[test] fn a_font_style_003() { assert_eq!(render("a-font-style-003"), 0); }
+#[test] fn a_font_synthesis_001() { assert_eq!(render("a-font-synthesis-001"), 0); }
+#[test] fn a_font_synthesis_002() { assert_eq!(render("a-font-synthesis-002"), 0); }
+#[test] fn a_font_synthesis_003() { assert_eq!(render("a-font-synthesis-003"), 0); }
+#[test] fn a_font_synthesis_004() { assert_eq!(render("a-font-synthesis-004"), 0); }
+#[test] fn a_font_synthesis_005() { assert_eq!(render("a-font-synthesis-005"), 0); }
+#[test] fn a_font_synthesis_006() { assert_eq!(render("a-font-synthesis-006"), 0); }
+#[test] fn a_font_synthesis_007() { assert_eq!(render("a-font-synthesis-007"), 0); }
+#[test] fn a_font_synthesis_008() { assert_eq!(render("a-font-synthesis-008"), 0); }
 #[test] fn a_font_variant_001() { assert_eq!(render("a-font-variant-001"), 0); }
 #[test] fn a_font_variant_002() { assert_eq!(render("a-font-variant-002"), 0); }
+#[test] fn a_font_variant_003() { assert_eq!(render("a-font-variant-003"), 0); }
+#[test] fn a_font_variant_004() { assert_eq!(render("a-font-variant-004"), 0); }
+#[test] fn a_font_variant_caps_001() { assert_eq!(render("a-font-variant-caps-001"), 0); }
+#[test] fn a_font_variant_ligatures_001() { assert_eq!(render("a-font-variant-ligatures-001"), 0); }
+#[test] fn a_font_variant_numeric_001() { assert_eq!(render("a-font-variant-numeric-001"), 0); }
+#[test] fn a_font_variant_numeric_002() { assert_eq!(render("a-font-variant-numeric-002"), 0); }
+#[test] fn a_font_variant_numeric_003() { assert_eq!(render("a-font-variant-numeric-003"), 0); }
+#[test] fn a_font_variant_position_001() { assert_eq!(render("a-font-variant-position-001"), 0); }
+#[test] fn a_font_variation_settings_001() { assert_eq!(render("a-font-variation-settings-001"), 0); }
+#[test] fn a_font_variation_settings_002() { assert_eq!(render("a-font-variation-settings-002"), 0); }
+#[test] fn a_font_variation_settings_003() { assert_eq!(render("a-font-variation-settings-003"), 0); }
+#[test] fn a_font_variation_settings_004() { assert_eq!(render("a-font-variation-settings-004"), 0); }
+#[test] fn a_font_variation_settings_005() { assert_eq!(render("a-font-variation-settings-005"), 0); }
+#[test] fn a_font_variation_settings_006() { assert_eq!(render("a-font-variation-settings-006"), 0); }
 #[test] fn a_font_weight_001() { assert_eq!(render("a-font-weight-001"), 0); }
 #[test] fn a_font_weight_002() { assert_eq!(render("a-font-weight-002"), 0); }
 #[test] fn a_font_weight_003() { assert_eq!(render("a-font-weight-003"), 0); }
@@ -281,15 +323,32 @@ use crate::render;
 #[test] fn a_font_weight_010() { assert_eq!(render("a-font-weight-010"), 0); }
 #[test] fn a_font_weight_011() { assert_eq!(render("a-font-weight-011"), 0); }
 #[test] fn a_font_weight_012() { assert_eq!(render("a-font-weight-012"), 0); }
+#[test] fn a_font_weight_013() { assert_eq!(render("a-font-weight-013"), 0); }
+#[test] fn a_font_weight_014() { assert_eq!(render("a-font-weight-014"), 0); }
+#[test] fn a_font_weight_015() { assert_eq!(render("a-font-weight-015"), 0); }
+#[test] fn a_font_weight_016() { assert_eq!(render("a-font-weight-016"), 0); }
 #[test] fn a_glyph_orientation_horizontal_001() { assert_eq!(render("a-glyph-orientation-horizontal-001"), 0); }
 #[test] fn a_glyph_orientation_vertical_001() { assert_eq!(render("a-glyph-orientation-vertical-001"), 0); }
+#[test] fn a_glyph_orientation_vertical_002() { assert_eq!(render("a-glyph-orientation-vertical-002"), 0); }
 #[test] fn a_image_rendering_001() { assert_eq!(render("a-image-rendering-001"), 0); }
 #[test] fn a_image_rendering_002() { assert_eq!(render("a-image-rendering-002"), 0); }
 #[test] fn a_image_rendering_003() { assert_eq!(render("a-image-rendering-003"), 0); }
+#[test] fn a_inline_size_001() { assert_eq!(render("a-inline-size-001"), 0); }
+#[test] fn a_inline_size_002() { assert_eq!(render("a-inline-size-002"), 0); }
+#[test] fn a_inline_size_003() { assert_eq!(render("a-inline-size-003"), 0); }
+#[test] fn a_inline_size_004() { assert_eq!(render("a-inline-size-004"), 0); }
+#[test] fn a_inline_size_005() { assert_eq!(render("a-inline-size-005"), 0); }
+#[test] fn a_inline_size_006() { assert_eq!(render("a-inline-size-006"), 0); }
+#[test] fn a_inline_size_007() { assert_eq!(render("a-inline-size-007"), 0); }
 #[test] fn a_isolation_001() { assert_eq!(render("a-isolation-001"), 0); }
 #[test] fn a_isolation_002() { assert_eq!(render("a-isolation-002"), 0); }
 #[test] fn a_kerning_001() { assert_eq!(render("a-kerning-001"), 0); }
 #[test] fn a_kerning_002() { assert_eq!(render("a-kerning-002"), 0); }
+#[test] fn a_lang_001() { assert_eq!(render("a-lang-001"), 0); }
+#[test] fn a_lang_002() { assert_eq!(render("a-lang-002"), 0); }
+#[test] fn a_lang_003() { assert_eq!(render("a-lang-003"), 0); }
+#[test] fn a_lang_004() { assert_eq!(render("a-lang-004"), 0); }
+#[test] fn a_lang_005() { assert_eq!(render("a-lang-005"), 0); }
 #[test] fn a_lengthAdjust_001() { assert_eq!(render("a-lengthAdjust-001"), 0); }
 #[test] fn a_lengthAdjust_002() { assert_eq!(render("a-lengthAdjust-002"), 0); }
 #[test] fn a_lengthAdjust_003() { assert_eq!(render("a-lengthAdjust-003"), 0); }
@@ -305,6 +364,8 @@ use crate::render;
 #[test] fn a_letter_spacing_009() { assert_eq!(render("a-letter-spacing-009"), 0); }
 #[test] fn a_letter_spacing_010() { assert_eq!(render("a-letter-spacing-010"), 0); }
 #[test] fn a_letter_spacing_011() { assert_eq!(render("a-letter-spacing-011"), 0); }
+#[test] fn a_line_height_001() { assert_eq!(render("a-line-height-001"), 0); }
+#[test] fn a_line_height_002() { assert_eq!(render("a-line-height-002"), 0); }
 #[test] fn a_marker_end_001() { assert_eq!(render("a-marker-end-001"), 0); }
 #[test] fn a_marker_mid_001() { assert_eq!(render("a-marker-mid-001"), 0); }
 #[test] fn a_marker_start_001() { assert_eq!(render("a-marker-start-001"), 0); }
@@ -356,6 +417,14 @@ use crate::render;
 #[test] fn a_paint_order_011() { assert_eq!(render("a-paint-order-011"), 0); }
 #[test] fn a_paint_order_012() { assert_eq!(render("a-paint-order-012"), 0); }
 #[test] fn a_paint_order_013() { assert_eq!(render("a-paint-order-013"), 0); }
+#[test] fn a_shape_inside_001() { assert_eq!(render("a-shape-inside-001"), 0); }
+#[test] fn a_shape_inside_002() { assert_eq!(render("a-shape-inside-002"), 0); }
+#[test] fn a_shape_inside_003() { assert_eq!(render("a-shape-inside-003"), 0); }
+#[test] fn a_shape_inside_004() { assert_eq!(render("a-shape-inside-004"), 0); }
+#[test] fn a_shape_inside_005() { assert_eq!(render("a-shape-inside-005"), 0); }
+#[test] fn a_shape_inside_006() { assert_eq!(render("a-shape-inside-006"), 0); }
+#[test] fn a_shape_inside_007() { assert_eq!(render("a-shape-inside-007"), 0); }
+#[test] fn a_shape_inside_008() { assert_eq!(render("a-shape-inside-008"), 0); }
 #[test] fn a_shape_rendering_001() { assert_eq!(render("a-shape-rendering-001"), 0); }
 #[test] fn a_shape_rendering_002() { assert_eq!(render("a-shape-rendering-002"), 0); }
 #[test] fn a_shape_rendering_003() { assert_eq!(render("a-shape-rendering-003"), 0); }
@@ -365,6 +434,7 @@ use crate::render;
 #[test] fn a_shape_rendering_007() { assert_eq!(render("a-shape-rendering-007"), 0); }
 #[test] fn a_shape_rendering_008() { assert_eq!(render("a-shape-rendering-008"), 0); }
 #[test] fn a_stop_color_001() { assert_eq!(render("a-stop-color-001"), 0); }
+#[test] fn a_stop_color_002() { assert_eq!(render("a-stop-color-002"), 0); }
 #[test] fn a_stop_opacity_001() { assert_eq!(render("a-stop-opacity-001"), 0); }
 #[test] fn a_stop_opacity_002() { assert_eq!(render("a-stop-opacity-002"), 0); }
 #[test] fn a_stroke_001() { assert_eq!(render("a-stroke-001"), 0); }
@@ -485,11 +555,38 @@ use crate::render;
 #[test] fn a_text_decoration_018() { assert_eq!(render("a-text-decoration-018"), 0); }
 #[test] fn a_text_decoration_019() { assert_eq!(render("a-text-decoration-019"), 0); }
 #[test] fn a_text_decoration_020() { assert_eq!(render("a-text-decoration-020"), 0); }
+#[test] fn a_text_decoration_021() { assert_eq!(render("a-text-decoration-021"), 0); }
+#[test] fn a_text_decoration_022() { assert_eq!(render("a-text-decoration-022"), 0); }
+#[test] fn a_text_decoration_023() { assert_eq!(render("a-text-decoration-023"), 0); }
+#[test] fn a_text_decoration_024() { assert_eq!(render("a-text-decoration-024"), 0); }
+#[test] fn a_text_decoration_025() { assert_eq!(render("a-text-decoration-025"), 0); }
+#[test] fn a_text_decoration_026() { assert_eq!(render("a-text-decoration-026"), 0); }
+#[test] fn a_text_decoration_027() { assert_eq!(render("a-text-decoration-027"), 0); }
+#[test] fn a_text_decoration_028() { assert_eq!(render("a-text-decoration-028"), 0); }
+#[test] fn a_text_decoration_029() { assert_eq!(render("a-text-decoration-029"), 0); }
+#[test] fn a_text_decoration_030() { assert_eq!(render("a-text-decoration-030"), 0); }
+#[test] fn a_text_decoration_031() { assert_eq!(render("a-text-decoration-031"), 0); }
+#[test] fn a_text_decoration_032() { assert_eq!(render("a-text-decoration-032"), 0); }
+#[test] fn a_text_orientation_001() { assert_eq!(render("a-text-orientation-001"), 0); }
+#[test] fn a_text_orientation_002() { assert_eq!(render("a-text-orientation-002"), 0); }
+#[test] fn a_text_orientation_003() { assert_eq!(render("a-text-orientation-003"), 0); }
+#[test] fn a_text_orientation_004() { assert_eq!(render("a-text-orientation-004"), 0); }
+#[test] fn a_text_orientation_005() { assert_eq!(render("a-text-orientation-005"), 0); }
+#[test] fn a_text_orientation_006() { assert_eq!(render("a-text-orientation-006"), 0); }
+#[test] fn a_text_orientation_007() { assert_eq!(render("a-text-orientation-007"), 0); }
 #[test] fn a_text_rendering_001() { assert_eq!(render("a-text-rendering-001"), 0); }
 #[test] fn a_text_rendering_002() { assert_eq!(render("a-text-rendering-002"), 0); }
 #[test] fn a_text_rendering_003() { assert_eq!(render("a-text-rendering-003"), 0); }
 #[test] fn a_text_rendering_004() { assert_eq!(render("a-text-rendering-004"), 0); }
 #[test] fn a_text_rendering_005() { assert_eq!(render("a-text-rendering-005"), 0); }
+#[test] fn a_text_transform_001() { assert_eq!(render("a-text-transform-001"), 0); }
+#[test] fn a_text_transform_002() { assert_eq!(render("a-text-transform-002"), 0); }
+#[test] fn a_text_transform_003() { assert_eq!(render("a-text-transform-003"), 0); }
+#[test] fn a_text_transform_004() { assert_eq!(render("a-text-transform-004"), 0); }
+#[test] fn a_text_transform_005() { assert_eq!(render("a-text-transform-005"), 0); }
+#[test] fn a_text_transform_006() { assert_eq!(render("a-text-transform-006"), 0); }
+#[test] fn a_text_transform_007() { assert_eq!(render("a-text-transform-007"), 0); }
+#[test] fn a_text_transform_008() { assert_eq!(render("a-text-transform-008"), 0); }
 #[test] fn a_textLength_001() { assert_eq!(render("a-textLength-001"), 0); }
 #[test] fn a_textLength_002() { assert_eq!(render("a-textLength-002"), 0); }
 #[test] fn a_textLength_003() { assert_eq!(render("a-textLength-003"), 0); }
@@ -522,6 +619,9 @@ use crate::render;
 #[test] fn a_transform_018() { assert_eq!(render("a-transform-018"), 0); }
 #[test] fn a_transform_019() { assert_eq!(render("a-transform-019"), 0); }
 #[test] fn a_unicode_bidi_001() { assert_eq!(render("a-unicode-bidi-001"), 0); }
+#[test] fn a_unicode_bidi_002() { assert_eq!(render("a-unicode-bidi-002"), 0); }
+#[test] fn a_unicode_bidi_003() { assert_eq!(render("a-unicode-bidi-003"), 0); }
+#[test] fn a_unicode_bidi_004() { assert_eq!(render("a-unicode-bidi-004"), 0); }
 #[test] fn a_visibility_001() { assert_eq!(render("a-visibility-001"), 0); }
 #[test] fn a_visibility_002() { assert_eq!(render("a-visibility-002"), 0); }
 #[test] fn a_visibility_003() { assert_eq!(render("a-visibility-003"), 0); }
@@ -529,6 +629,9 @@ use crate::render;
 #[test] fn a_visibility_005() { assert_eq!(render("a-visibility-005"), 0); }
 #[test] fn a_visibility_006() { assert_eq!(render("a-visibility-006"), 0); }
 #[test] fn a_visibility_007() { assert_eq!(render("a-visibility-007"), 0); }
+#[test] fn a_white_space_001() { assert_eq!(render("a-white-space-001"), 0); }
+#[test] fn a_white_space_002() { assert_eq!(render("a-white-space-002"), 0); }
+#[test] fn a_white_space_003() { assert_eq!(render("a-white-space-003"), 0); }
 #[test] fn a_word_spacing_001() { assert_eq!(render("a-word-spacing-001"), 0); }
 #[test] fn a_word_spacing_002() { assert_eq!(render("a-word-spacing-002"), 0); }
 #[test] fn a_word_spacing_003() { assert_eq!(render("a-word-spacing-003"), 0); }
@@ -949,6 +1052,12 @@ use crate::render;
 #[test] fn e_filter_063() { assert_eq!(render("e-filter-063"), 0); }
 #[test] fn e_filter_064() { assert_eq!(render("e-filter-064"), 0); }
 #[test] fn e_filter_065() { assert_eq!(render("e-filter-065"), 0); }
+#[test] fn e_font_001() { assert_eq!(render("e-font-001"), 0); }
+#[test] fn e_font_002() { assert_eq!(render("e-font-002"), 0); }
+#[test] fn e_font_003() { assert_eq!(render("e-font-003"), 0); }
+#[test] fn e_font_004() { assert_eq!(render("e-font-004"), 0); }
+#[test] fn e_font_005() { assert_eq!(render("e-font-005"), 0); }
+#[test] fn e_font_006() { assert_eq!(render("e-font-006"), 0); }
 #[test] fn e_g_001() { assert_eq!(render("e-g-001"), 0); }
 #[test] fn e_g_002() { assert_eq!(render("e-g-002"), 0); }
 #[test] fn e_image_001() { assert_eq!(render("e-image-001"), 0); }
@@ -1366,6 +1475,21 @@ use crate::render;
 #[test] fn e_style_014() { assert_eq!(render("e-style-014"), 0); }
 #[test] fn e_style_015() { assert_eq!(render("e-style-015"), 0); }
 #[test] fn e_style_016() { assert_eq!(render("e-style-016"), 0); }
+#[test] fn e_style_017() { assert_eq!(render("e-style-017"), 0); }
+#[test] fn e_style_018() { assert_eq!(render("e-style-018"), 0); }
+#[test] fn e_style_019() { assert_eq!(render("e-style-019"), 0); }
+#[test] fn e_style_020() { assert_eq!(render("e-style-020"), 0); }
+#[test] fn e_style_021() { assert_eq!(render("e-style-021"), 0); }
+#[test] fn e_style_022() { assert_eq!(render("e-style-022"), 0); }
+#[test] fn e_style_023() { assert_eq!(render("e-style-023"), 0); }
+#[test] fn e_style_024() { assert_eq!(render("e-style-024"), 0); }
+#[test] fn e_style_025() { assert_eq!(render("e-style-025"), 0); }
+#[test] fn e_style_026() { assert_eq!(render("e-style-026"), 0); }
+#[test] fn e_style_027() { assert_eq!(render("e-style-027"), 0); }
+#[test] fn e_style_028() { assert_eq!(render("e-style-028"), 0); }
+#[test] fn e_style_029() { assert_eq!(render("e-style-029"), 0); }
+#[test] fn e_style_030() { assert_eq!(render("e-style-030"), 0); }
+#[test] fn e_style_031() { assert_eq!(render("e-style-031"), 0); }
 #[test] fn e_svg_001() { assert_eq!(render("e-svg-001"), 0); }
 #[test] fn e_svg_002() { assert_eq!(render("e-svg-002"), 0); }
 #[test] fn e_svg_003() { assert_eq!(render("e-svg-003"), 0); }
@@ -1470,6 +1594,17 @@ use crate::render;
 #[test] fn e_text_042() { assert_eq!(render("e-text-042"), 0); }
 #[test] fn e_text_043() { assert_eq!(render("e-text-043"), 0); }
 #[test] fn e_text_044() { assert_eq!(render("e-text-044"), 0); }
+#[test] fn e_text_045() { assert_eq!(render("e-text-045"), 0); }
+#[test] fn e_text_046() { assert_eq!(render("e-text-046"), 0); }
+#[test] fn e_text_047() { assert_eq!(render("e-text-047"), 0); }
+#[test] fn e_text_048() { assert_eq!(render("e-text-048"), 0); }
+#[test] fn e_text_049() { assert_eq!(render("e-text-049"), 0); }
+#[test] fn e_text_050() { assert_eq!(render("e-text-050"), 0); }
+#[test] fn e_text_051() { assert_eq!(render("e-text-051"), 0); }
+#[test] fn e_text_052() { assert_eq!(render("e-text-052"), 0); }
+#[test] fn e_text_053() { assert_eq!(render("e-text-053"), 0); }
+#[test] fn e_text_054() { assert_eq!(render("e-text-054"), 0); }
+#[test] fn e_text_055() { assert_eq!(render("e-text-055"), 0); }
 #[test] fn e_textPath_001() { assert_eq!(render("e-textPath-001"), 0); }
 #[test] fn e_textPath_002() { assert_eq!(render("e-textPath-002"), 0); }
 #[test] fn e_textPath_003() { assert_eq!(render("e-textPath-003"), 0); }
@@ -1514,6 +1649,11 @@ use crate::render;
 #[test] fn e_textPath_042() { assert_eq!(render("e-textPath-042"), 0); }
 #[test] fn e_textPath_043() { assert_eq!(render("e-textPath-043"), 0); }
 #[test] fn e_textPath_044() { assert_eq!(render("e-textPath-044"), 0); }
+#[test] fn e_textPath_045() { assert_eq!(render("e-textPath-045"), 0); }
+#[test] fn e_textPath_046() { assert_eq!(render("e-textPath-046"), 0); }
+#[test] fn e_textPath_047() { assert_eq!(render("e-textPath-047"), 0); }
+#[test] fn e_textPath_048() { assert_eq!(render("e-textPath-048"), 0); }
+#[test] fn e_textPath_049() { assert_eq!(render("e-textPath-049"), 0); }
 #[test] fn e_tref_001() { assert_eq!(render("e-tref-001"), 0); }
 #[test] fn e_tref_002() { assert_eq!(render("e-tref-002"), 0); }
 #[test] fn e_tref_003() { assert_eq!(render("e-tref-003"), 0); }
@@ -0,0 +1,605 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! CSS Color Level 4 functions support.
+//!
+//! `svgtypes` supports only sRGB colors, so `lab()`, `lch()`, `oklab()`, `oklch()`, `hwb()`
+//! and `color(display-p3 ...)` are handled here.
+//! Such colors are converted into sRGB using the CSS Color 4 gamut mapping algorithm,
+//! while the original value is preserved as a `SpecifiedColor`.
+
+use rosvgtree::{self, AttributeId as AId};
+use usvg_tree::filter::Kind;
+use usvg_tree::{
+    Color, ColorSpace, Diagnostic, NodeExt, NodeKind, Opacity, Paint, SpecifiedColor, Tree,
+};
+
+use crate::rosvgtree_ext::{FromValue, SvgColorExt};
+
+/// A parsed color with an optional original color.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ParsedColor {
+    pub color: svgtypes::Color,
+    pub specified: Option<SpecifiedColor>,
+}
+
+impl ParsedColor {
+    pub fn black() -> Self {
+        ParsedColor::from(svgtypes::Color::black())
+    }
+
+    pub fn split_alpha(self) -> (Color, Opacity) {
+        self.color.split_alpha()
+    }
+}
+
+impl From<svgtypes::Color> for ParsedColor {
+    fn from(color: svgtypes::Color) -> Self {
+        ParsedColor {
+            color,
+            specified: None,
+        }
+    }
+}
+
+impl<'a, 'input: 'a> FromValue<'a, 'input> for ParsedColor {
+    fn parse(_: rosvgtree::Node, _: AId, value: &str) -> Option<Self> {
+        parse_color(value)
+    }
+}
+
+/// Parses a color, including CSS Color 4 functions.
+pub(crate) fn parse_color(text: &str) -> Option<ParsedColor> {
+    if let Ok(color) = text.parse::<svgtypes::Color>() {
+        return Some(ParsedColor::from(color));
+    }
+
+    let (specified, alpha) = parse_color_function(text.trim())?;
+    let (r, g, b) = to_srgb(&specified);
+    let color = svgtypes::Color::new_rgba(r, g, b, (alpha * 255.0).round() as u8);
+    Some(ParsedColor {
+        color,
+        specified: Some(specified),
+    })
+}
+
+/// Parses a paint, including CSS Color 4 functions.
+///
+/// Returns an original color for `Paint::Color` and `Paint::FuncIRI` fallback colors.
+pub(crate) fn parse_paint(text: &str) -> Option<(svgtypes::Paint<'_>, Option<SpecifiedColor>)> {
+    if let Ok(paint) = svgtypes::Paint::from_str(text) {
+        return Some((paint, None));
+    }
+
+    let text = text.trim();
+    if text.starts_with("url(") {
+        let idx = text.find(')')?;
+        let (link, fallback) = text.split_at(idx + 1);
+        match svgtypes::Paint::from_str(link) {
+            Ok(svgtypes::Paint::FuncIRI(link, None)) => {
+                let fallback = parse_color(fallback)?;
+                Some((
+                    svgtypes::Paint::FuncIRI(
+                        link,
+                        Some(svgtypes::PaintFallback::Color(fallback.color)),
+                    ),
+                    fallback.specified,
+                ))
+            }
+            _ => None,
+        }
+    } else {
+        let color = parse_color(text)?;
+        Some((svgtypes::Paint::Color(color.color), color.specified))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Token {
+    Number(f64),
+    Percent(f64),
+    Angle(f64),
+    None,
+}
+
+fn parse_color_function(text: &str) -> Option<(SpecifiedColor, f64)> {
+    let open_idx = text.find('(')?;
+    if !text.ends_with(')') {
+        return None;
+    }
+
+    let name = text[..open_idx].trim().to_ascii_lowercase();
+    let mut args = &text[open_idx + 1..text.len() - 1];
+
+    let space = match name.as_str() {
+        "hwb" => ColorSpace::HWB,
+        "lab" => ColorSpace::Lab,
+        "lch" => ColorSpace::LCH,
+        "oklab" => ColorSpace::Oklab,
+        "oklch" => ColorSpace::Oklch,
+        "color" => {
+            let args_start = args.trim_start();
+            let (profile, tail) = args_start.split_at(
+                args_start
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(args_start.len()),
+            );
+            if !profile.eq_ignore_ascii_case("display-p3") {
+                log::warn!("Unsupported color space: '{}'.", profile);
+                return None;
+            }
+
+            args = tail;
+            ColorSpace::DisplayP3
+        }
+        _ => return None,
+    };
+
+    let (components, alpha) = match args.split_once('/') {
+        Some((components, alpha)) => (components, Some(alpha)),
+        None => (args, None),
+    };
+
+    let mut tokens = components.split_ascii_whitespace().map(parse_token);
+    let t1 = tokens.next()??;
+    let t2 = tokens.next()??;
+    let t3 = tokens.next()??;
+    if tokens.next().is_some() {
+        return None;
+    }
+
+    let alpha = match alpha {
+        Some(alpha) => {
+            let mut tokens = alpha.split_ascii_whitespace().map(parse_token);
+            let alpha = match tokens.next()?? {
+                Token::Number(n) => n,
+                Token::Percent(n) => n / 100.0,
+                Token::None => 0.0,
+                Token::Angle(_) => return None,
+            };
+
+            if tokens.next().is_some() {
+                return None;
+            }
+
+            crate::f64_bound(0.0, alpha, 1.0)
+        }
+        None => 1.0,
+    };
+
+    // Resolves a component to a number, where 100% is equal to `percent_ref`.
+    let number = |t: Token, percent_ref: f64| match t {
+        Token::Number(n) => Some(n),
+        Token::Percent(n) => Some(n / 100.0 * percent_ref),
+        Token::None => Some(0.0),
+        Token::Angle(_) => None,
+    };
+
+    let hue = |t: Token| match t {
+        Token::Number(n) | Token::Angle(n) => Some(n.rem_euclid(360.0)),
+        Token::None => Some(0.0),
+        Token::Percent(_) => None,
+    };
+
+    let clamp = |n: f64, max: f64| n.max(0.0).min(max);
+
+    let components = match space {
+        ColorSpace::HWB => [
+            hue(t1)?,
+            clamp(number(t2, 100.0)? / 100.0, 1.0),
+            clamp(number(t3, 100.0)? / 100.0, 1.0),
+        ],
+        ColorSpace::Lab => [
+            clamp(number(t1, 100.0)?, 100.0),
+            number(t2, 125.0)?,
+            number(t3, 125.0)?,
+        ],
+        ColorSpace::LCH => [
+            clamp(number(t1, 100.0)?, 100.0),
+            number(t2, 150.0)?.max(0.0),
+            hue(t3)?,
+        ],
+        ColorSpace::Oklab => [
+            clamp(number(t1, 1.0)?, 1.0),
+            number(t2, 0.4)?,
+            number(t3, 0.4)?,
+        ],
+        ColorSpace::Oklch => [
+            clamp(number(t1, 1.0)?, 1.0),
+            number(t2, 0.4)?.max(0.0),
+            hue(t3)?,
+        ],
+        ColorSpace::DisplayP3 => [number(t1, 1.0)?, number(t2, 1.0)?, number(t3, 1.0)?],
+    };
+
+    Some((SpecifiedColor { space, components }, alpha))
+}
+
+fn parse_token(text: &str) -> Option<Token> {
+    if text.eq_ignore_ascii_case("none") {
+        return Some(Token::None);
+    }
+
+    if let Some(n) = text.strip_suffix('%') {
+        return n
+            .parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite())
+            .map(Token::Percent);
+    }
+
+    let idx = text
+        .find(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E')
+        .unwrap_or(text.len());
+    let (n, unit) = text.split_at(idx);
+    let n: f64 = n.parse().ok()?;
+    if !n.is_finite() {
+        return None;
+    }
+
+    match unit.to_ascii_lowercase().as_str() {
+        "" => Some(Token::Number(n)),
+        "deg" => Some(Token::Angle(n)),
+        "grad" => Some(Token::Angle(n * 0.9)),
+        "rad" => Some(Token::Angle(n.to_degrees())),
+        "turn" => Some(Token::Angle(n * 360.0)),
+        _ => None,
+    }
+}
+
+/// Converts a color into sRGB, applying gamut mapping when needed.
+fn to_srgb(color: &SpecifiedColor) -> (u8, u8, u8) {
+    let rgb = match color.space {
+        ColorSpace::HWB => hwb_to_srgb(color.components),
+        _ => gamut_map(to_oklab(color)),
+    };
+
+    let to_u8 = |c: f64| (crate::f64_bound(0.0, c, 1.0) * 255.0).round() as u8;
+    (to_u8(rgb[0]), to_u8(rgb[1]), to_u8(rgb[2]))
+}
+
+/// Checks that a color lies inside the sRGB gamut.
+fn is_in_srgb_gamut(color: &SpecifiedColor) -> bool {
+    match color.space {
+        // HWB is just another sRGB notation.
+        ColorSpace::HWB => true,
+        _ => in_gamut(oklab_to_srgb(to_oklab(color))),
+    }
+}
+
+fn hwb_to_srgb([hue, white, black]: [f64; 3]) -> [f64; 3] {
+    if white + black >= 1.0 {
+        let gray = white / (white + black);
+        return [gray; 3];
+    }
+
+    // HSL with 100% saturation and 50% lightness.
+    let f = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        0.5 - 0.5 * crate::f64_bound(-1.0, (k - 3.0).min(9.0 - k), 1.0)
+    };
+
+    let scale = 1.0 - white - black;
+    [
+        f(0.0) * scale + white,
+        f(8.0) * scale + white,
+        f(4.0) * scale + white,
+    ]
+}
+
+fn to_oklab(color: &SpecifiedColor) -> [f64; 3] {
+    let [c1, c2, c3] = color.components;
+    match color.space {
+        ColorSpace::HWB => linear_srgb_to_oklab(hwb_to_srgb(color.components).map(to_linear)),
+        ColorSpace::Lab => xyz_d65_to_oklab(lab_to_xyz_d65([c1, c2, c3])),
+        ColorSpace::LCH => xyz_d65_to_oklab(lab_to_xyz_d65(lch_to_lab([c1, c2, c3]))),
+        ColorSpace::Oklab => [c1, c2, c3],
+        ColorSpace::Oklch => lch_to_lab([c1, c2, c3]),
+        ColorSpace::DisplayP3 => xyz_d65_to_oklab(mul(&P3_TO_XYZ_D65, [c1, c2, c3].map(to_linear))),
+    }
+}
+
+fn lch_to_lab([l, c, h]: [f64; 3]) -> [f64; 3] {
+    let h = h.to_radians();
+    [l, c * h.cos(), c * h.sin()]
+}
+
+fn lab_to_lch([l, a, b]: [f64; 3]) -> [f64; 3] {
+    [l, (a * a + b * b).sqrt(), b.atan2(a).to_degrees()]
+}
+
+fn lab_to_xyz_d65([l, a, b]: [f64; 3]) -> [f64; 3] {
+    const KAPPA: f64 = 24389.0 / 27.0;
+    const EPSILON: f64 = 216.0 / 24389.0;
+    const D50_WHITE: [f64; 3] = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+
+    let f1 = (l + 16.0) / 116.0;
+    let f0 = a / 500.0 + f1;
+    let f2 = f1 - b / 200.0;
+
+    let x = if f0.powi(3) > EPSILON {
+        f0.powi(3)
+    } else {
+        (116.0 * f0 - 16.0) / KAPPA
+    };
+
+    let y = if l > KAPPA * EPSILON {
+        f1.powi(3)
+    } else {
+        l / KAPPA
+    };
+
+    let z = if f2.powi(3) > EPSILON {
+        f2.powi(3)
+    } else {
+        (116.0 * f2 - 16.0) / KAPPA
+    };
+
+    let xyz_d50 = [x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]];
+    mul(&D50_TO_D65, xyz_d50)
+}
+
+fn xyz_d65_to_oklab(xyz: [f64; 3]) -> [f64; 3] {
+    linear_srgb_to_oklab(mul(&XYZ_D65_TO_LINEAR_SRGB, xyz))
+}
+
+fn linear_srgb_to_oklab([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+/// Converts Oklab into a non-linear sRGB. The result can be out of gamut.
+fn oklab_to_srgb([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ]
+    .map(to_gamma)
+}
+
+fn to_linear(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs <= 0.04045 {
+        c / 12.92
+    } else {
+        c.signum() * ((abs + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn to_gamma(c: f64) -> f64 {
+    let abs = c.abs();
+    if abs <= 0.0031308 {
+        c * 12.92
+    } else {
+        c.signum() * (1.055 * abs.powf(1.0 / 2.4) - 0.055)
+    }
+}
+
+fn in_gamut(rgb: [f64; 3]) -> bool {
+    // Allow some floating point noise.
+    const EPSILON: f64 = 0.0001;
+    rgb.iter().all(|c| *c >= -EPSILON && *c <= 1.0 + EPSILON)
+}
+
+fn clip(rgb: [f64; 3]) -> [f64; 3] {
+    rgb.map(|c| crate::f64_bound(0.0, c, 1.0))
+}
+
+fn delta_e_ok(lab1: [f64; 3], lab2: [f64; 3]) -> f64 {
+    let dl = lab1[0] - lab2[0];
+    let da = lab1[1] - lab2[1];
+    let db = lab1[2] - lab2[2];
+    (dl * dl + da * da + db * db).sqrt()
+}
+
+/// Maps an Oklab color into the sRGB gamut by reducing its chroma.
+///
+/// Based on https://www.w3.org/TR/css-color-4/#binsearch
+fn gamut_map(oklab: [f64; 3]) -> [f64; 3] {
+    // Just noticeable difference.
+    const JND: f64 = 0.02;
+    const EPSILON: f64 = 0.0001;
+
+    let rgb = oklab_to_srgb(oklab);
+    if in_gamut(rgb) {
+        return clip(rgb);
+    }
+
+    let [l, chroma, hue] = lab_to_lch(oklab);
+    if l >= 1.0 {
+        return [1.0; 3];
+    } else if l <= 0.0 {
+        return [0.0; 3];
+    }
+
+    let clipped = clip(rgb);
+    if delta_e_ok(linear_srgb_to_oklab(clipped.map(to_linear)), oklab) < JND {
+        return clipped;
+    }
+
+    let mut min = 0.0;
+    let mut max = chroma;
+    let mut min_in_gamut = true;
+    let mut current = oklab;
+    while max - min > EPSILON {
+        let chroma = (min + max) / 2.0;
+        current = lch_to_lab([l, chroma, hue]);
+        let rgb = oklab_to_srgb(current);
+
+        if min_in_gamut && in_gamut(rgb) {
+            min = chroma;
+            continue;
+        }
+
+        let clipped = clip(rgb);
+        let e = delta_e_ok(linear_srgb_to_oklab(clipped.map(to_linear)), current);
+        if e < JND {
+            if JND - e < EPSILON {
+                return clipped;
+            }
+
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+
+    clip(oklab_to_srgb(current))
+}
+
+fn mul(m: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+// Bradford chromatic adaptation from D50 to D65.
+#[rustfmt::skip]
+const D50_TO_D65: [[f64; 3]; 3] = [
+    [ 0.955473421488075,    -0.02309845494876471,  0.06325924320057072],
+    [-0.0283697093338637,    1.0099953980813041,   0.021041441191917323],
+    [ 0.012314014864481998, -0.020507649298898964, 1.330365926242124],
+];
+
+#[rustfmt::skip]
+const XYZ_D65_TO_LINEAR_SRGB: [[f64; 3]; 3] = [
+    [ 3.2409699419045226,  -1.537383177570094,   -0.4986107602930034],
+    [-0.9692436362808796,   1.8759675015077202,   0.04155505740717559],
+    [ 0.05563007969699366, -0.20397695888897652,  1.0569715142428786],
+];
+
+#[rustfmt::skip]
+const P3_TO_XYZ_D65: [[f64; 3]; 3] = [
+    [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+    [0.2289745640697488, 0.6917385218365064,  0.079286914093745],
+    [0.0,                0.04511338185890264, 1.043944368900976],
+];
+
+/// Reports colors that are outside of the sRGB gamut.
+pub(crate) fn collect_diagnostics(tree: &mut Tree) {
+    let mut diagnostics = Vec::new();
+    collect_node_diagnostics(&tree.root, &mut diagnostics);
+    tree.diagnostics.extend(diagnostics);
+}
+
+fn collect_node_diagnostics(root: &usvg_tree::Node, diagnostics: &mut Vec<Diagnostic>) {
+    for node in root.descendants() {
+        match *node.borrow() {
+            NodeKind::Group(ref group) => {
+                for filter in &group.filters {
+                    for primitive in &filter.primitives {
+                        let (color, specified) = match primitive.kind {
+                            Kind::DiffuseLighting(ref fe) => {
+                                (fe.lighting_color, fe.specified_lighting_color)
+                            }
+                            Kind::SpecularLighting(ref fe) => {
+                                (fe.lighting_color, fe.specified_lighting_color)
+                            }
+                            Kind::DropShadow(ref fe) => (fe.color, fe.specified_color),
+                            Kind::Flood(ref fe) => (fe.color, fe.specified_color),
+                            _ => continue,
+                        };
+
+                        push_color(&filter.id, specified, color, diagnostics);
+                    }
+                }
+            }
+            NodeKind::Path(ref path) => {
+                if let Some(ref fill) = path.fill {
+                    push_paint(&path.id, &fill.paint, fill.specified_color, diagnostics);
+                }
+
+                if let Some(ref stroke) = path.stroke {
+                    push_paint(&path.id, &stroke.paint, stroke.specified_color, diagnostics);
+                }
+            }
+            NodeKind::Image(_) => {}
+            NodeKind::Text(ref text) => {
+                for chunk in &text.chunks {
+                    for span in &chunk.spans {
+                        if let Some(ref fill) = span.fill {
+                            push_paint(&text.id, &fill.paint, fill.specified_color, diagnostics);
+                        }
+
+                        if let Some(ref stroke) = span.stroke {
+                            push_paint(
+                                &text.id,
+                                &stroke.paint,
+                                stroke.specified_color,
+                                diagnostics,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        node.subroots(|subroot| collect_node_diagnostics(&subroot, diagnostics));
+    }
+}
+
+fn push_paint(
+    element_id: &str,
+    paint: &Paint,
+    specified: Option<SpecifiedColor>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    match paint {
+        Paint::Color(color) => push_color(element_id, specified, *color, diagnostics),
+        Paint::LinearGradient(ref lg) => {
+            for stop in &lg.stops {
+                push_color(&lg.id, stop.specified_color, stop.color, diagnostics);
+            }
+        }
+        Paint::RadialGradient(ref rg) => {
+            for stop in &rg.stops {
+                push_color(&rg.id, stop.specified_color, stop.color, diagnostics);
+            }
+        }
+        // Pattern children are processed as subroots.
+        Paint::Pattern(_) => {}
+    }
+}
+
+fn push_color(
+    element_id: &str,
+    specified: Option<SpecifiedColor>,
+    mapped: Color,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let color = match specified {
+        Some(v) => v,
+        None => return,
+    };
+
+    if is_in_srgb_gamut(&color) {
+        return;
+    }
+
+    let diagnostic = Diagnostic::ColorOutOfGamut {
+        element_id: element_id.to_string(),
+        color,
+        mapped,
+    };
+
+    // The same gradient or filter can be referenced multiple times.
+    if !diagnostics.contains(&diagnostic) {
+        diagnostics.push(diagnostic);
+    }
+}
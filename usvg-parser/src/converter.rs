@@ -38,6 +38,8 @@ pub struct Cache {
     pub all_ids: HashSet<u64>,
     pub clip_path_index: usize,
     pub filter_index: usize,
+    pub text_path_index: usize,
+    pub text_shape_index: usize,
 }
 
 impl Cache {
@@ -52,6 +54,28 @@ impl Cache {
         }
     }
 
+    pub fn gen_text_path_id(&mut self) -> String {
+        loop {
+            self.text_path_index += 1;
+            let new_id = format!("textPath{}", self.text_path_index);
+            let new_hash = string_hash(&new_id);
+            if !self.all_ids.contains(&new_hash) {
+                return new_id;
+            }
+        }
+    }
+
+    pub fn gen_text_shape_id(&mut self) -> String {
+        loop {
+            self.text_shape_index += 1;
+            let new_id = format!("textShape{}", self.text_shape_index);
+            let new_hash = string_hash(&new_id);
+            if !self.all_ids.contains(&new_hash) {
+                return new_id;
+            }
+        }
+    }
+
     pub fn gen_filter_id(&mut self) -> String {
         loop {
             self.filter_index += 1;
@@ -94,12 +118,18 @@ pub(crate) fn convert_doc(svg_doc: &rosvgtree::Document, opt: &Options) -> Resul
         size,
         view_box,
         root: Node::new(NodeKind::Group(Group::default())),
+        font_faces: Vec::new(),
+        svg_fonts: Vec::new(),
+        diagnostics: Vec::new(),
     };
 
     if !svg.is_visible_element(opt) {
         return Ok(tree);
     }
 
+    tree.font_faces = crate::font_face::convert(svg_doc, opt);
+    tree.svg_fonts = crate::svg_font::convert(svg_doc);
+
     let state = State {
         parent_clip_path: None,
         parent_markers: Vec::new(),
@@ -112,7 +142,7 @@ pub(crate) fn convert_doc(svg_doc: &rosvgtree::Document, opt: &Options) -> Resul
     let mut cache = Cache::default();
     for node in svg_doc.descendants() {
         if let Some(tag) = node.tag_name() {
-            if matches!(tag, EId::Filter | EId::ClipPath) {
+            if matches!(tag, EId::Filter | EId::ClipPath | EId::Path) {
                 if !node.element_id().is_empty() {
                     cache.all_ids.insert(string_hash(node.element_id()));
                 }
@@ -128,6 +158,8 @@ pub(crate) fn convert_doc(svg_doc: &rosvgtree::Document, opt: &Options) -> Resul
         calculate_svg_bbox(&mut tree);
     }
 
+    crate::color::collect_diagnostics(&mut tree);
+
     Ok(tree)
 }
 
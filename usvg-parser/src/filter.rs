@@ -6,16 +6,19 @@
 
 use std::collections::HashSet;
 use std::rc::Rc;
-use std::str::FromStr;
 
 use rosvgtree::{self, AttributeId as AId, ElementId as EId};
 use strict_num::PositiveF64;
 use svgtypes::{Length, LengthUnit as Unit};
 use usvg_tree::filter::*;
-use usvg_tree::{Color, FuzzyZero, Group, Node, NodeKind, NonZeroF64, Opacity, Point, Rect, Units};
+use usvg_tree::{
+    Color, FuzzyZero, Group, Node, NodeKind, NonZeroF64, Opacity, Point, Rect, SpecifiedColor,
+    Units,
+};
 
+use crate::color::{self, ParsedColor};
 use crate::paint_server::{convert_units, resolve_number};
-use crate::rosvgtree_ext::{OpacityWrapper, SvgColorExt, SvgNodeExt, SvgNodeExt2};
+use crate::rosvgtree_ext::{OpacityWrapper, SvgNodeExt, SvgNodeExt2};
 use crate::{converter, FromValue, OptionLog};
 
 impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::filter::ColorInterpolation {
@@ -334,6 +337,7 @@ pub(crate) fn create_dummy_primitive() -> Kind {
     Kind::Flood(Flood {
         color: Color::black(),
         opacity: Opacity::ZERO,
+        specified_color: None,
     })
 }
 
@@ -622,10 +626,10 @@ fn convert_displacement_map(fe: rosvgtree::Node, primitives: &[Primitive]) -> Ki
 fn convert_drop_shadow(fe: rosvgtree::Node, primitives: &[Primitive]) -> Kind {
     let (std_dev_x, std_dev_y) = convert_std_dev_attr(fe, "2 2");
 
-    let (color, opacity) = fe
+    let flood_color: ParsedColor = fe
         .parse_attribute(AId::FloodColor)
-        .unwrap_or_else(svgtypes::Color::black)
-        .split_alpha();
+        .unwrap_or_else(ParsedColor::black);
+    let (color, opacity) = flood_color.split_alpha();
 
     let flood_opacity = fe
         .parse_attribute::<OpacityWrapper>(AId::FloodOpacity)
@@ -640,14 +644,15 @@ fn convert_drop_shadow(fe: rosvgtree::Node, primitives: &[Primitive]) -> Kind {
         std_dev_y,
         color,
         opacity: opacity * flood_opacity,
+        specified_color: flood_color.specified,
     })
 }
 
 fn convert_flood(fe: rosvgtree::Node) -> Kind {
-    let (color, opacity) = fe
+    let flood_color: ParsedColor = fe
         .parse_attribute(AId::FloodColor)
-        .unwrap_or_else(svgtypes::Color::black)
-        .split_alpha();
+        .unwrap_or_else(ParsedColor::black);
+    let (color, opacity) = flood_color.split_alpha();
 
     let flood_opacity = fe
         .parse_attribute::<OpacityWrapper>(AId::FloodOpacity)
@@ -657,6 +662,7 @@ fn convert_flood(fe: rosvgtree::Node) -> Kind {
     Kind::Flood(Flood {
         color,
         opacity: opacity * flood_opacity,
+        specified_color: flood_color.specified,
     })
 }
 
@@ -743,11 +749,13 @@ fn convert_image(
 
 fn convert_diffuse_lighting(fe: rosvgtree::Node, primitives: &[Primitive]) -> Option<Kind> {
     let light_source = convert_light_source(fe)?;
+    let (lighting_color, specified_lighting_color) = convert_lighting_color(fe);
     Some(Kind::DiffuseLighting(DiffuseLighting {
         input: resolve_input(fe, AId::In, primitives),
         surface_scale: fe.parse_attribute(AId::SurfaceScale).unwrap_or(1.0),
         diffuse_constant: fe.parse_attribute(AId::DiffuseConstant).unwrap_or(1.0),
-        lighting_color: convert_lighting_color(fe),
+        lighting_color,
+        specified_lighting_color,
         light_source,
     }))
 }
@@ -762,38 +770,40 @@ fn convert_specular_lighting(fe: rosvgtree::Node, primitives: &[Primitive]) -> O
     }
 
     let specular_exponent = crate::f64_bound(1.0, specular_exponent, 128.0);
+    let (lighting_color, specified_lighting_color) = convert_lighting_color(fe);
 
     Some(Kind::SpecularLighting(SpecularLighting {
         input: resolve_input(fe, AId::In, primitives),
         surface_scale: fe.parse_attribute(AId::SurfaceScale).unwrap_or(1.0),
         specular_constant: fe.parse_attribute(AId::SpecularConstant).unwrap_or(1.0),
         specular_exponent,
-        lighting_color: convert_lighting_color(fe),
+        lighting_color,
+        specified_lighting_color,
         light_source,
     }))
 }
 
 #[inline(never)]
-fn convert_lighting_color(node: rosvgtree::Node) -> Color {
+fn convert_lighting_color(node: rosvgtree::Node) -> (Color, Option<SpecifiedColor>) {
     // Color's alpha doesn't affect lighting-color. Simply skip it.
-    match node.attribute(AId::LightingColor) {
+    let color = match node.attribute(AId::LightingColor) {
         Some("currentColor") => {
             node.find_and_parse_attribute(AId::Color)
                 // Yes, a missing `currentColor` resolves to black and not white.
-                .unwrap_or(svgtypes::Color::black())
-                .split_alpha()
-                .0
+                .unwrap_or_else(ParsedColor::black)
         }
         Some(value) => {
-            if let Ok(c) = svgtypes::Color::from_str(value) {
-                c.split_alpha().0
+            if let Some(c) = color::parse_color(value) {
+                c
             } else {
                 log::warn!("Failed to parse lighting-color value: '{}'.", value);
-                Color::white()
+                return (Color::white(), None);
             }
         }
-        _ => Color::white(),
-    }
+        _ => return (Color::white(), None),
+    };
+
+    (color.split_alpha().0, color.specified)
 }
 
 #[inline(never)]
@@ -1119,12 +1129,11 @@ fn convert_drop_shadow_function(
     ))
     .unwrap_or(PositiveF64::ZERO);
 
-    let (color, opacity) = color
-        .unwrap_or_else(|| {
-            node.find_and_parse_attribute(AId::Color)
-                .unwrap_or_else(svgtypes::Color::black)
-        })
-        .split_alpha();
+    let shadow_color = color.map(ParsedColor::from).unwrap_or_else(|| {
+        node.find_and_parse_attribute(AId::Color)
+            .unwrap_or_else(ParsedColor::black)
+    });
+    let (color, opacity) = shadow_color.split_alpha();
 
     Kind::DropShadow(DropShadow {
         input: Input::SourceGraphic,
@@ -1134,5 +1143,6 @@ fn convert_drop_shadow_function(
         std_dev_y: std_dev,
         color,
         opacity,
+        specified_color: shadow_color.specified,
     })
 }
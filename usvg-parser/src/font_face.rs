@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+
+use rosvgtree::FontFaceSource;
+use usvg_tree::{FontFace, FontStyle};
+
+use crate::{OptionLog, Options};
+
+/// Loads fonts from the document's `@font-face` rules.
+///
+/// Faces without a loadable source are skipped.
+pub(crate) fn convert(doc: &rosvgtree::Document, opt: &Options) -> Vec<FontFace> {
+    doc.font_faces()
+        .iter()
+        .filter_map(|face| convert_face(face, opt))
+        .collect()
+}
+
+fn convert_face(face: &rosvgtree::FontFace, opt: &Options) -> Option<FontFace> {
+    // The first source that can be loaded is used.
+    let data = face
+        .sources
+        .iter()
+        .find_map(|source| load_source(source, opt))
+        .log_none(|| log::warn!("Failed to load the '{}' font face. Skipped.", face.family))?;
+
+    Some(FontFace {
+        family: face.family.clone(),
+        style: face.style.as_deref().map(parse_style).unwrap_or_default(),
+        weight: face.weight.as_deref().map(parse_weight).unwrap_or(400),
+        unicode_range: face.unicode_range.clone(),
+        data: Arc::new(data),
+    })
+}
+
+fn load_source(source: &FontFaceSource, opt: &Options) -> Option<Vec<u8>> {
+    let (url, format) = match source {
+        FontFaceSource::Url { url, format } => (url, format.as_deref()),
+        FontFaceSource::Local(name) => {
+            log::warn!("A local('{}') font source is not supported.", name);
+            return None;
+        }
+    };
+
+    // Skip sources with an unsupported format without loading them.
+    if let Some(format) = format {
+        let format = format.strip_suffix("-variations").unwrap_or(format);
+        if !matches!(
+            format,
+            "woff" | "woff2" | "truetype" | "opentype" | "collection"
+        ) {
+            return None;
+        }
+    }
+
+    let data = if let Ok(url) = data_url::DataUrl::process(url) {
+        url.decode_to_vec().ok()?.0
+    } else if url.contains("://") {
+        log::warn!("Remote fonts are not supported. '{}' skipped.", url);
+        return None;
+    } else {
+        let path = opt.get_abs_path(std::path::Path::new(url));
+        match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(_) => {
+                log::warn!("Failed to load '{}'. Skipped.", url);
+                return None;
+            }
+        }
+    };
+
+    if crate::woff::is_woff(&data) {
+        return crate::woff::decode(&data)
+            .log_none(|| log::warn!("Failed to decode a '{}' web font.", url));
+    }
+
+    match data.get(0..4)? {
+        [0x00, 0x01, 0x00, 0x00] | b"OTTO" | b"true" | b"ttcf" => Some(data),
+        _ => {
+            log::warn!("'{}' is not a TrueType, OpenType or WOFF font.", url);
+            None
+        }
+    }
+}
+
+/// Parses a `font-style` descriptor.
+///
+/// An oblique angle is ignored.
+pub(crate) fn parse_style(value: &str) -> FontStyle {
+    match value.split_ascii_whitespace().next() {
+        Some("italic") => FontStyle::Italic,
+        Some("oblique") => FontStyle::Oblique,
+        _ => FontStyle::Normal,
+    }
+}
+
+/// Parses a `font-weight` descriptor.
+///
+/// Uses the lower bound for ranges, like `100 900`.
+pub(crate) fn parse_weight(value: &str) -> u16 {
+    match value.split_ascii_whitespace().next() {
+        Some("bold") => 700,
+        Some(value) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|n| (1.0..=1000.0).contains(n))
+            .map(|n| n.round() as u16)
+            .unwrap_or(400),
+        None => 400,
+    }
+}
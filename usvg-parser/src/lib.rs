@@ -22,8 +22,10 @@
 #![allow(clippy::upper_case_acronyms)]
 
 mod clippath;
+mod color;
 mod converter;
 mod filter;
+mod font_face;
 mod image;
 mod marker;
 mod mask;
@@ -32,14 +34,18 @@ mod paint_server;
 mod rosvgtree_ext;
 mod shapes;
 mod style;
+mod svg_font;
 mod switch;
 mod text;
+mod text_decoration;
+mod text_transform;
 mod units;
 mod use_node;
+mod woff;
 
 pub use crate::options::*;
 pub use image::ImageHrefResolver;
-pub use rosvgtree::{self, roxmltree};
+pub use rosvgtree::{self, roxmltree, ColorScheme, StyleSheetOrigin};
 
 use crate::rosvgtree_ext::{FromValue, SvgNodeExt, SvgNodeExt2};
 
@@ -159,7 +165,7 @@ impl TreeParsing for usvg_tree::Tree {
 
     /// Parses `Tree` from `roxmltree::Document`.
     fn from_xmltree(doc: &roxmltree::Document, opt: &Options) -> Result<Self, Error> {
-        let doc = rosvgtree::Document::parse_tree(doc)?;
+        let doc = rosvgtree::Document::parse_tree_with_options(doc, &opt.to_parse_options())?;
         Self::from_rosvgtree(doc, opt)
     }
 
@@ -5,6 +5,7 @@
 use usvg_tree::{ImageRendering, ShapeRendering, Size, TextRendering};
 
 use crate::ImageHrefResolver;
+use rosvgtree::{ColorScheme, StyleSheetOrigin};
 
 /// Processing options.
 #[derive(Debug)]
@@ -78,6 +79,43 @@ pub struct Options {
     ///
     /// Default: see type's documentation for details
     pub image_href_resolver: ImageHrefResolver,
+
+    /// A list of CSS style sheets that will be applied to the document.
+    ///
+    /// Applied in addition to the `style` elements inside the SVG.
+    ///
+    /// Default: empty
+    pub style_sheet: Vec<String>,
+
+    /// Specifies a cascade origin of `style_sheet`.
+    ///
+    /// Default: Author
+    pub style_sheet_origin: StyleSheetOrigin,
+
+    /// A preferred color scheme.
+    ///
+    /// Will be used to evaluate `prefers-color-scheme` CSS media queries.
+    /// Other media queries are evaluated against the SVG size.
+    ///
+    /// Default: Light
+    pub color_scheme: ColorScheme,
+
+    /// A list of CSS custom properties that will be set on the document root.
+    ///
+    /// Allows theming `var()` references, like `fill: var(--icon-primary, #333)`.
+    /// Names must include the `--` prefix.
+    /// Properties declared inside the SVG take precedence.
+    ///
+    /// Default: empty
+    pub custom_properties: Vec<(String, String)>,
+
+    /// An initial `color` property value, i.e. the `currentColor`.
+    ///
+    /// Accepts any CSS color.
+    /// Will be used when no `color` attribute is set in the SVG.
+    ///
+    /// Default: `None`, which is black
+    pub color: Option<String>,
 }
 
 impl Default for Options {
@@ -94,6 +132,11 @@ impl Default for Options {
             image_rendering: ImageRendering::default(),
             default_size: Size::new(100.0, 100.0).unwrap(),
             image_href_resolver: ImageHrefResolver::default(),
+            style_sheet: Vec::new(),
+            style_sheet_origin: StyleSheetOrigin::default(),
+            color_scheme: ColorScheme::default(),
+            custom_properties: Vec::new(),
+            color: None,
         }
     }
 }
@@ -108,4 +151,19 @@ impl Options {
             None => rel_path.into(),
         }
     }
+
+    pub(crate) fn to_parse_options(&self) -> rosvgtree::ParseOptions<'_> {
+        rosvgtree::ParseOptions {
+            style_sheet: self.style_sheet.iter().map(|s| s.as_str()).collect(),
+            style_sheet_origin: self.style_sheet_origin,
+            color_scheme: self.color_scheme,
+            default_viewport_size: (self.default_size.width(), self.default_size.height()),
+            custom_properties: self
+                .custom_properties
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect(),
+            color: self.color.as_deref(),
+        }
+    }
 }
@@ -3,19 +3,23 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::rc::Rc;
-use std::str::FromStr;
 
 use rosvgtree::{self, AttributeId as AId, ElementId as EId};
 use strict_num::PositiveF64;
 use svgtypes::{Length, LengthUnit as Unit};
 use usvg_tree::*;
 
-use crate::rosvgtree_ext::{OpacityWrapper, SvgColorExt, SvgNodeExt2};
+use crate::color::{self, ParsedColor};
+use crate::rosvgtree_ext::{OpacityWrapper, SvgNodeExt2};
 use crate::{converter, OptionLog, SvgNodeExt};
 
 pub(crate) enum ServerOrColor {
     Server(Paint),
-    Color { color: Color, opacity: Opacity },
+    Color {
+        color: Color,
+        opacity: Opacity,
+        specified_color: Option<SpecifiedColor>,
+    },
 }
 
 pub(crate) fn convert(
@@ -101,6 +105,7 @@ fn convert_radial(node: rosvgtree::Node, state: &converter::State) -> Option<Ser
         return Some(ServerOrColor::Color {
             color: stop.color,
             opacity: stop.opacity,
+            specified_color: stop.specified_color,
         });
     }
 
@@ -276,21 +281,21 @@ fn convert_stops(grad: rosvgtree::Node) -> Vec<Stop> {
             let offset = crate::f64_bound(0.0, offset, 1.0);
             prev_offset = Length::new_number(offset);
 
-            let (color, opacity) = match stop.attribute(AId::StopColor) {
+            let stop_color = match stop.attribute(AId::StopColor) {
                 Some("currentColor") => stop
                     .find_and_parse_attribute(AId::Color)
-                    .unwrap_or_else(svgtypes::Color::black),
+                    .unwrap_or_else(ParsedColor::black),
                 Some(value) => {
-                    if let Ok(c) = svgtypes::Color::from_str(value) {
+                    if let Some(c) = color::parse_color(value) {
                         c
                     } else {
                         log::warn!("Failed to parse stop-color value: '{}'.", value);
-                        svgtypes::Color::black()
+                        ParsedColor::black()
                     }
                 }
-                _ => svgtypes::Color::black(),
-            }
-            .split_alpha();
+                _ => ParsedColor::black(),
+            };
+            let (color, opacity) = stop_color.split_alpha();
 
             let stop_opacity = stop
                 .parse_attribute::<OpacityWrapper>(AId::StopOpacity)
@@ -300,6 +305,7 @@ fn convert_stops(grad: rosvgtree::Node) -> Vec<Stop> {
                 offset: StopOffset::new_clamped(offset),
                 color,
                 opacity: opacity * stop_opacity,
+                specified_color: stop_color.specified,
             });
         }
     }
@@ -537,6 +543,7 @@ fn stops_to_color(stops: &[Stop]) -> Option<ServerOrColor> {
         Some(ServerOrColor::Color {
             color: stops[0].color,
             opacity: stops[0].opacity,
+            specified_color: stops[0].specified_color,
         })
     }
 }
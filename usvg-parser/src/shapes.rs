@@ -26,10 +26,19 @@ pub(crate) fn convert(node: rosvgtree::Node, state: &converter::State) -> Option
 
 pub(crate) fn convert_path(node: rosvgtree::Node) -> Option<Rc<PathData>> {
     let value: &str = node.attribute(AId::D)?;
+    parse_path(value, false)
+}
+
+/// Parses path data.
+///
+/// By default, a path is rendered up to the first error.
+/// In the `strict` mode, any error invalidates the whole path.
+pub(crate) fn parse_path(value: &str, strict: bool) -> Option<Rc<PathData>> {
     let mut path = PathData::new();
     for segment in svgtypes::SimplifyingPathParser::from(value) {
         let segment = match segment {
             Ok(v) => v,
+            Err(_) if strict => return None,
             Err(_) => break,
         };
 
@@ -3,8 +3,11 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use rosvgtree::{self, AttributeId as AId};
-use usvg_tree::{Color, Fill, FuzzyEq, Opacity, Paint, Stroke, StrokeMiterlimit, Units};
+use usvg_tree::{
+    Color, Fill, FuzzyEq, Opacity, Paint, SpecifiedColor, Stroke, StrokeMiterlimit, Units,
+};
 
+use crate::color::{self, ParsedColor};
 use crate::rosvgtree_ext::{FromValue, OpacityWrapper, SvgColorExt, SvgNodeExt2};
 use crate::{converter, paint_server, SvgNodeExt};
 
@@ -54,28 +57,80 @@ pub(crate) fn resolve_fill(
             rule: node
                 .find_and_parse_attribute(AId::ClipRule)
                 .unwrap_or_default(),
+            specified_color: None,
         });
     }
 
     let mut sub_opacity = Opacity::ONE;
+    let mut specified_color = None;
     let paint = if let Some(n) = node.ancestors().find(|n| n.has_attribute(AId::Fill)) {
-        convert_paint(n, AId::Fill, has_bbox, state, &mut sub_opacity, cache)?
+        convert_paint(
+            n,
+            AId::Fill,
+            has_bbox,
+            state,
+            &mut sub_opacity,
+            &mut specified_color,
+            cache,
+        )?
     } else {
         Paint::Color(Color::black())
     };
 
+    Some(fill_with_paint(node, paint, sub_opacity, specified_color))
+}
+
+/// Resolves a fill with a paint from a different property, like `text-decoration-fill`.
+///
+/// `value` is resolved on `paint_node`, while other fill properties are resolved on `node`.
+pub(crate) fn resolve_fill_with(
+    node: rosvgtree::Node,
+    paint_node: rosvgtree::Node,
+    aid: AId,
+    value: &str,
+    has_bbox: bool,
+    state: &converter::State,
+    cache: &mut converter::Cache,
+) -> Option<Fill> {
+    if state.parent_clip_path.is_some() {
+        return resolve_fill(node, has_bbox, state, cache);
+    }
+
+    let mut sub_opacity = Opacity::ONE;
+    let mut specified_color = None;
+    let paint = convert_paint_value(
+        paint_node,
+        aid,
+        value,
+        has_bbox,
+        state,
+        &mut sub_opacity,
+        &mut specified_color,
+        cache,
+    )?;
+
+    Some(fill_with_paint(node, paint, sub_opacity, specified_color))
+}
+
+fn fill_with_paint(
+    node: rosvgtree::Node,
+    paint: Paint,
+    sub_opacity: Opacity,
+    specified_color: Option<SpecifiedColor>,
+) -> Fill {
     let fill_opacity = node
         .find_and_parse_attribute::<OpacityWrapper>(AId::FillOpacity)
         .map(|v| v.0)
         .unwrap_or(Opacity::ONE);
 
-    Some(Fill {
+    Fill {
         paint,
         opacity: sub_opacity * fill_opacity,
         rule: node
             .find_and_parse_attribute(AId::FillRule)
             .unwrap_or_default(),
-    })
+        specified_color,
+    }
 }
 
 pub(crate) fn resolve_stroke(
@@ -90,12 +145,63 @@ pub(crate) fn resolve_stroke(
     }
 
     let mut sub_opacity = Opacity::ONE;
+    let mut specified_color = None;
     let paint = if let Some(n) = node.ancestors().find(|n| n.has_attribute(AId::Stroke)) {
-        convert_paint(n, AId::Stroke, has_bbox, state, &mut sub_opacity, cache)?
+        convert_paint(
+            n,
+            AId::Stroke,
+            has_bbox,
+            state,
+            &mut sub_opacity,
+            &mut specified_color,
+            cache,
+        )?
     } else {
         return None;
     };
 
+    stroke_with_paint(node, paint, sub_opacity, specified_color, state)
+}
+
+/// Resolves a stroke with a paint from a different property, like `text-decoration-stroke`.
+///
+/// `value` is resolved on `paint_node`, while other stroke properties are resolved on `node`.
+pub(crate) fn resolve_stroke_with(
+    node: rosvgtree::Node,
+    paint_node: rosvgtree::Node,
+    aid: AId,
+    value: &str,
+    has_bbox: bool,
+    state: &converter::State,
+    cache: &mut converter::Cache,
+) -> Option<Stroke> {
+    if state.parent_clip_path.is_some() {
+        return None;
+    }
+
+    let mut sub_opacity = Opacity::ONE;
+    let mut specified_color = None;
+    let paint = convert_paint_value(
+        paint_node,
+        aid,
+        value,
+        has_bbox,
+        state,
+        &mut sub_opacity,
+        &mut specified_color,
+        cache,
+    )?;
+
+    stroke_with_paint(node, paint, sub_opacity, specified_color, state)
+}
+
+fn stroke_with_paint(
+    node: rosvgtree::Node,
+    paint: Paint,
+    sub_opacity: Opacity,
+    specified_color: Option<SpecifiedColor>,
+    state: &converter::State,
+) -> Option<Stroke> {
     let width = node.resolve_valid_length(AId::StrokeWidth, state, 1.0)?;
 
     // Must be bigger than 1.
@@ -123,6 +229,7 @@ pub(crate) fn resolve_stroke(
         linejoin: node
             .find_and_parse_attribute(AId::StrokeLinejoin)
             .unwrap_or_default(),
+        specified_color,
     };
 
     Some(stroke)
@@ -134,18 +241,32 @@ fn convert_paint(
     has_bbox: bool,
     state: &converter::State,
     opacity: &mut Opacity,
+    specified: &mut Option<SpecifiedColor>,
     cache: &mut converter::Cache,
 ) -> Option<Paint> {
     let value: &str = node.attribute(aid)?;
-    let paint = match svgtypes::Paint::from_str(value) {
-        Ok(v) => v,
-        Err(_) => {
+    convert_paint_value(node, aid, value, has_bbox, state, opacity, specified, cache)
+}
+
+fn convert_paint_value(
+    node: rosvgtree::Node,
+    aid: AId,
+    value: &str,
+    has_bbox: bool,
+    state: &converter::State,
+    opacity: &mut Opacity,
+    specified: &mut Option<SpecifiedColor>,
+    cache: &mut converter::Cache,
+) -> Option<Paint> {
+    let (paint, specified_paint_color) = match color::parse_paint(value) {
+        Some(v) => v,
+        None => {
             if aid == AId::Fill {
                 log::warn!(
                     "Failed to parse fill value: '{}'. Fallback to black.",
                     value
                 );
-                svgtypes::Paint::Color(svgtypes::Color::black())
+                (svgtypes::Paint::Color(svgtypes::Color::black()), None)
             } else {
                 return None;
             }
@@ -156,16 +277,18 @@ fn convert_paint(
         svgtypes::Paint::None => None,
         svgtypes::Paint::Inherit => None, // already resolved by rosvgtree
         svgtypes::Paint::CurrentColor => {
-            let svg_color: svgtypes::Color = node
+            let svg_color: ParsedColor = node
                 .find_and_parse_attribute(AId::Color)
-                .unwrap_or_else(svgtypes::Color::black);
+                .unwrap_or_else(ParsedColor::black);
             let (color, alpha) = svg_color.split_alpha();
             *opacity = alpha;
+            *specified = svg_color.specified;
             Some(Paint::Color(color))
         }
         svgtypes::Paint::Color(svg_color) => {
             let (color, alpha) = svg_color.split_alpha();
             *opacity = alpha;
+            *specified = specified_paint_color;
             Some(Paint::Color(color))
         }
         svgtypes::Paint::FuncIRI(func_iri, fallback) => {
@@ -179,23 +302,36 @@ fn convert_paint(
                             //
                             // See SVG spec 7.11 for details.
                             if !has_bbox && paint.units() == Some(Units::ObjectBoundingBox) {
-                                from_fallback(node, fallback, opacity)
+                                from_fallback(
+                                    node,
+                                    fallback,
+                                    specified_paint_color,
+                                    opacity,
+                                    specified,
+                                )
                             } else {
                                 Some(paint)
                             }
                         }
-                        Some(paint_server::ServerOrColor::Color { color, opacity: so }) => {
+                        Some(paint_server::ServerOrColor::Color {
+                            color,
+                            opacity: so,
+                            specified_color,
+                        }) => {
                             *opacity = so;
+                            *specified = specified_color;
                             Some(Paint::Color(color))
                         }
-                        None => from_fallback(node, fallback, opacity),
+                        None => {
+                            from_fallback(node, fallback, specified_paint_color, opacity, specified)
+                        }
                     }
                 } else {
                     log::warn!("'{}' cannot be used to {} a shape.", tag_name, aid);
                     None
                 }
             } else {
-                from_fallback(node, fallback, opacity)
+                from_fallback(node, fallback, specified_paint_color, opacity, specified)
             }
         }
     }
@@ -204,21 +340,25 @@ fn convert_paint(
 fn from_fallback(
     node: rosvgtree::Node,
     fallback: Option<svgtypes::PaintFallback>,
+    specified_fallback_color: Option<SpecifiedColor>,
     opacity: &mut Opacity,
+    specified: &mut Option<SpecifiedColor>,
 ) -> Option<Paint> {
     match fallback? {
         svgtypes::PaintFallback::None => None,
         svgtypes::PaintFallback::CurrentColor => {
-            let svg_color: svgtypes::Color = node
+            let svg_color: ParsedColor = node
                 .find_and_parse_attribute(AId::Color)
-                .unwrap_or_else(svgtypes::Color::black);
+                .unwrap_or_else(ParsedColor::black);
             let (color, alpha) = svg_color.split_alpha();
             *opacity = alpha;
+            *specified = svg_color.specified;
             Some(Paint::Color(color))
         }
         svgtypes::PaintFallback::Color(svg_color) => {
             let (color, alpha) = svg_color.split_alpha();
             *opacity = alpha;
+            *specified = specified_fallback_color;
             Some(Paint::Color(color))
         }
     }
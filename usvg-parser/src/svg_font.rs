@@ -0,0 +1,200 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use rosvgtree::{self, AttributeId as AId, ElementId as EId};
+use usvg_tree::{ArabicForm, SvgFont, SvgGlyph, SvgKerning};
+
+use crate::rosvgtree_ext::SvgNodeExt2;
+use crate::OptionLog;
+
+/// Collects the document's SVG fonts.
+///
+/// Fonts without a `font-face` family are skipped.
+pub(crate) fn convert(doc: &rosvgtree::Document) -> Vec<SvgFont> {
+    doc.descendants()
+        .filter(|n| n.tag_name() == Some(EId::Font))
+        .filter_map(convert_font)
+        .collect()
+}
+
+fn convert_font(node: rosvgtree::Node) -> Option<SvgFont> {
+    let face = node
+        .children()
+        .find(|n| n.tag_name() == Some(EId::FontFace))
+        .log_none(|| log::warn!("A font without a 'font-face' element. Skipped."))?;
+
+    let family = face
+        .attribute(AId::FontFamily)
+        .and_then(parse_family)
+        .log_none(|| log::warn!("A font without a family name. Skipped."))?;
+
+    let units_per_em = face
+        .parse_attribute::<f64>(AId::UnitsPerEm)
+        .filter(|n| *n > 0.0)
+        .unwrap_or(1000.0);
+
+    // The default ascent is `units-per-em` minus `vert-origin-y`, which is zero.
+    let ascent = face.parse_attribute(AId::Ascent).unwrap_or(units_per_em);
+
+    // Some fonts use a negative descent, even though it's a distance.
+    let descent = face
+        .parse_attribute::<f64>(AId::Descent)
+        .map(f64::abs)
+        .unwrap_or(0.0);
+
+    let horiz_adv_x = node.parse_attribute(AId::HorizAdvX).unwrap_or(0.0);
+
+    let missing_glyph = match node
+        .children()
+        .find(|n| n.tag_name() == Some(EId::MissingGlyph))
+    {
+        Some(n) => convert_glyph(n, horiz_adv_x),
+        None => SvgGlyph {
+            unicode: String::new(),
+            names: Vec::new(),
+            arabic_form: None,
+            languages: Vec::new(),
+            horiz_adv_x,
+            path: None,
+        },
+    };
+
+    let glyphs: Vec<_> = node
+        .children()
+        .filter(|n| n.tag_name() == Some(EId::Glyph))
+        .map(|n| convert_glyph(n, horiz_adv_x))
+        .collect();
+
+    let kerning = node
+        .children()
+        .filter(|n| n.tag_name() == Some(EId::Hkern))
+        .filter_map(|n| convert_kerning(n, &glyphs))
+        .collect();
+
+    Some(SvgFont {
+        id: node.element_id().to_string(),
+        family,
+        style: face
+            .attribute(AId::FontStyle)
+            .map(crate::font_face::parse_style)
+            .unwrap_or_default(),
+        weight: face
+            .attribute(AId::FontWeight)
+            .map(crate::font_face::parse_weight)
+            .unwrap_or(400),
+        units_per_em,
+        ascent,
+        descent,
+        horiz_adv_x,
+        missing_glyph,
+        glyphs,
+        kerning,
+    })
+}
+
+/// Parses the first family of a `font-family` list.
+fn parse_family(value: &str) -> Option<String> {
+    let family = value.split(',').next()?.trim();
+    let family = family.trim_matches(|c| c == '"' || c == '\'').trim();
+    if family.is_empty() {
+        None
+    } else {
+        Some(family.to_string())
+    }
+}
+
+fn convert_glyph(node: rosvgtree::Node, font_horiz_adv_x: f64) -> SvgGlyph {
+    let arabic_form = match node.attribute(AId::ArabicForm) {
+        Some("isolated") => Some(ArabicForm::Isolated),
+        Some("initial") => Some(ArabicForm::Initial),
+        Some("medial") => Some(ArabicForm::Medial),
+        Some("terminal") => Some(ArabicForm::Terminal),
+        _ => None,
+    };
+
+    SvgGlyph {
+        unicode: node.attribute(AId::Unicode).unwrap_or("").to_string(),
+        names: split_list(node.attribute(AId::GlyphName)),
+        arabic_form,
+        languages: split_list(node.attribute(AId::Lang)),
+        horiz_adv_x: node
+            .parse_attribute(AId::HorizAdvX)
+            .unwrap_or(font_horiz_adv_x),
+        path: crate::shapes::convert_path(node),
+    }
+}
+
+fn convert_kerning(node: rosvgtree::Node, glyphs: &[SvgGlyph]) -> Option<SvgKerning> {
+    let k = node.parse_attribute(AId::K)?;
+
+    let first = find_glyphs(node.attribute(AId::U1), node.attribute(AId::G1), glyphs);
+    let second = find_glyphs(node.attribute(AId::U2), node.attribute(AId::G2), glyphs);
+    if first.is_empty() || second.is_empty() {
+        return None;
+    }
+
+    Some(SvgKerning { first, second, k })
+}
+
+/// Finds glyphs that match any of the characters or glyph names.
+///
+/// Characters can be unicode ranges, like `U+0041-005A`.
+fn find_glyphs(unicode: Option<&str>, names: Option<&str>, glyphs: &[SvgGlyph]) -> Vec<usize> {
+    let unicode: Vec<_> = unicode.map(|s| s.split(',').collect()).unwrap_or_default();
+    let names = split_list(names);
+
+    let matches_unicode = |glyph: &SvgGlyph| {
+        unicode.iter().any(|item| {
+            // Don't trim a whitespace character.
+            let item = match item.trim() {
+                "" => *item,
+                trimmed => trimmed,
+            };
+
+            match parse_unicode_range(item) {
+                Some(range) => {
+                    let mut chars = glyph.unicode.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => range.contains(&u32::from(c)),
+                        _ => false,
+                    }
+                }
+                None => glyph.unicode == item,
+            }
+        })
+    };
+
+    glyphs
+        .iter()
+        .enumerate()
+        .filter(|(_, glyph)| {
+            matches_unicode(glyph) || glyph.names.iter().any(|name| names.contains(name))
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Parses a unicode range, like `U+0041`, `U+00??` or `U+0041-005A`.
+fn parse_unicode_range(value: &str) -> Option<std::ops::RangeInclusive<u32>> {
+    let value = value.strip_prefix("U+")?;
+    if let Some((start, end)) = value.split_once('-') {
+        let start = u32::from_str_radix(start, 16).ok()?;
+        let end = u32::from_str_radix(end, 16).ok()?;
+        Some(start..=end)
+    } else {
+        let start = u32::from_str_radix(&value.replace('?', "0"), 16).ok()?;
+        let end = u32::from_str_radix(&value.replace('?', "F"), 16).ok()?;
+        Some(start..=end)
+    }
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
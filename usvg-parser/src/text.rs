@@ -23,6 +23,56 @@ impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::TextAnchor {
     }
 }
 
+impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::TextDirection {
+    fn parse(_: rosvgtree::Node, _: rosvgtree::AttributeId, value: &str) -> Option<Self> {
+        match value {
+            "ltr" => Some(usvg_tree::TextDirection::LeftToRight),
+            "rtl" => Some(usvg_tree::TextDirection::RightToLeft),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::UnicodeBidi {
+    fn parse(_: rosvgtree::Node, _: rosvgtree::AttributeId, value: &str) -> Option<Self> {
+        match value {
+            "normal" => Some(usvg_tree::UnicodeBidi::Normal),
+            "embed" => Some(usvg_tree::UnicodeBidi::Embed),
+            "isolate" => Some(usvg_tree::UnicodeBidi::Isolate),
+            "bidi-override" => Some(usvg_tree::UnicodeBidi::BidiOverride),
+            "isolate-override" => Some(usvg_tree::UnicodeBidi::IsolateOverride),
+            "plaintext" => Some(usvg_tree::UnicodeBidi::Plaintext),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::TextOrientation {
+    fn parse(_: rosvgtree::Node, _: rosvgtree::AttributeId, value: &str) -> Option<Self> {
+        match value {
+            "mixed" => Some(usvg_tree::TextOrientation::Mixed),
+            "upright" => Some(usvg_tree::TextOrientation::Upright),
+            // `sideways-right` is an alias from the earlier drafts.
+            "sideways" | "sideways-right" => Some(usvg_tree::TextOrientation::Sideways),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::WhiteSpace {
+    fn parse(_: rosvgtree::Node, _: rosvgtree::AttributeId, value: &str) -> Option<Self> {
+        match value {
+            "normal" => Some(usvg_tree::WhiteSpace::Normal),
+            "pre" => Some(usvg_tree::WhiteSpace::Pre),
+            "nowrap" => Some(usvg_tree::WhiteSpace::NoWrap),
+            "pre-wrap" => Some(usvg_tree::WhiteSpace::PreWrap),
+            "break-spaces" => Some(usvg_tree::WhiteSpace::BreakSpaces),
+            "pre-line" => Some(usvg_tree::WhiteSpace::PreLine),
+            _ => None,
+        }
+    }
+}
+
 impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::AlignmentBaseline {
     fn parse(_: rosvgtree::Node, _: rosvgtree::AttributeId, value: &str) -> Option<Self> {
         match value {
@@ -90,11 +140,21 @@ pub(crate) fn convert(
     cache: &mut converter::Cache,
     parent: &mut Node,
 ) {
-    let pos_list = resolve_positions_list(text_node, state);
-    let rotate_list = resolve_rotate_list(text_node);
+    let mut pos_list = resolve_positions_list(text_node, state);
+    let mut rotate_list = resolve_rotate_list(text_node);
     let writing_mode = convert_writing_mode(text_node);
+    let inline_size = resolve_inline_size(text_node, writing_mode, state);
+    let shape_inside = resolve_shape_inside(text_node, state, cache);
+
+    // Auto-wrapped text is positioned only by the first character.
+    if inline_size.is_some() || shape_inside.is_some() {
+        for pos in pos_list.iter_mut().skip(1) {
+            pos.x = None;
+            pos.y = None;
+        }
+    }
 
-    let chunks = collect_text_chunks(text_node, &pos_list, state, cache);
+    let chunks = collect_text_chunks(text_node, &mut pos_list, &mut rotate_list, state, cache);
 
     let rendering_mode: TextRendering = text_node
         .find_and_parse_attribute(AId::TextRendering)
@@ -107,6 +167,11 @@ pub(crate) fn convert(
         positions: pos_list,
         rotate: rotate_list,
         writing_mode,
+        inline_size,
+        white_space: text_node
+            .find_and_parse_attribute(AId::WhiteSpace)
+            .unwrap_or_default(),
+        shape_inside,
         chunks,
     };
     parent.append_kind(NodeKind::Text(text));
@@ -118,11 +183,25 @@ struct IterState {
     split_chunk: bool,
     text_flow: TextFlow,
     chunks: Vec<TextChunk>,
+    /// The last original character. Used by `text-transform`.
+    prev_char: Option<char>,
+    /// Original characters that were replaced by `text-transform`
+    /// with a different number of characters.
+    transformed_chars: Vec<(usize, usize)>,
+    /// An absolute position of characters that were removed by `text-transform`.
+    /// Will be applied to the next character.
+    pending_x: Option<f64>,
+    pending_y: Option<f64>,
 }
 
+/// Collects text chunks.
+///
+/// `pos_list` and `rotate_list` will be remapped to the transformed text
+/// when `text-transform` changes the number of characters.
 fn collect_text_chunks(
     text_node: rosvgtree::Node,
-    pos_list: &[CharacterPosition],
+    pos_list: &mut Vec<CharacterPosition>,
+    rotate_list: &mut Vec<f64>,
     state: &converter::State,
     cache: &mut converter::Cache,
 ) -> Vec<TextChunk> {
@@ -132,6 +211,10 @@ fn collect_text_chunks(
         split_chunk: false,
         text_flow: TextFlow::Linear,
         chunks: Vec::new(),
+        prev_char: None,
+        transformed_chars: Vec::new(),
+        pending_x: None,
+        pending_y: None,
     };
 
     collect_text_chunks_impl(
@@ -143,9 +226,83 @@ fn collect_text_chunks(
         &mut iter_state,
     );
 
+    if !iter_state.transformed_chars.is_empty() {
+        remap_char_lists(pos_list, rotate_list, &iter_state.transformed_chars);
+    }
+
     iter_state.chunks
 }
 
+/// Remaps per-character lists to the transformed text.
+///
+/// Additional characters inherit the rotation, but not the position,
+/// of the original character. Positions of removed characters are applied
+/// to the next character.
+fn remap_char_lists(
+    pos_list: &mut Vec<CharacterPosition>,
+    rotate_list: &mut Vec<f64>,
+    transformed_chars: &[(usize, usize)],
+) {
+    let mut new_pos_list = Vec::with_capacity(pos_list.len());
+    let mut new_rotate_list = Vec::with_capacity(rotate_list.len());
+    let mut transformed_chars = transformed_chars.iter().peekable();
+    let mut pending: Option<CharacterPosition> = None;
+    for (i, (pos, rotate)) in pos_list.iter().zip(rotate_list.iter()).enumerate() {
+        let count = match transformed_chars.peek() {
+            Some((idx, count)) if *idx == i => {
+                transformed_chars.next();
+                *count
+            }
+            _ => 1,
+        };
+
+        let pos = match pending.take() {
+            Some(prev) => merge_char_positions(prev, *pos),
+            None => *pos,
+        };
+
+        if count == 0 {
+            pending = Some(pos);
+            continue;
+        }
+
+        for n in 0..count {
+            new_pos_list.push(if n == 0 {
+                pos
+            } else {
+                CharacterPosition {
+                    x: None,
+                    y: None,
+                    dx: None,
+                    dy: None,
+                }
+            });
+            new_rotate_list.push(*rotate);
+        }
+    }
+
+    *pos_list = new_pos_list;
+    *rotate_list = new_rotate_list;
+}
+
+/// Merges a position of a removed character into the next character's one.
+///
+/// Absolute positions of the next character take precedence,
+/// while relative offsets are accumulated.
+fn merge_char_positions(removed: CharacterPosition, next: CharacterPosition) -> CharacterPosition {
+    let add = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    };
+
+    CharacterPosition {
+        x: next.x.or(removed.x),
+        y: next.y.or(removed.y),
+        dx: add(removed.dx, next.dx),
+        dy: add(removed.dy, next.dy),
+    }
+}
+
 fn collect_text_chunks_impl(
     text_node: rosvgtree::Node,
     parent: rosvgtree::Node,
@@ -163,7 +320,7 @@ fn collect_text_chunks_impl(
                     continue;
                 }
 
-                match resolve_text_flow(child, state) {
+                match resolve_text_flow(child, state, cache) {
                     Some(v) => {
                         iter_state.text_flow = v;
                     }
@@ -200,6 +357,11 @@ fn collect_text_chunks_impl(
             .find_and_parse_attribute(AId::TextAnchor)
             .unwrap_or_default();
 
+        let (direction, unicode_bidi) = resolve_bidi(text_node, parent);
+        let base_direction = text_node
+            .find_and_parse_attribute(AId::Direction)
+            .unwrap_or_default();
+
         // TODO: what to do when <= 0? UB?
         let font_size = crate::units::resolve_font_size(parent, state);
         let font_size = match NonZeroPositiveF64::new(font_size) {
@@ -260,12 +422,15 @@ fn collect_text_chunks_impl(
             paint_order,
             font,
             font_size,
-            small_caps: parent
-                .find_attribute(AId::FontVariant)
-                .and_then(|n| n.attribute(AId::FontVariant))
-                == Some("small-caps"),
+            small_caps: find_font_variant(parent, AId::FontVariantCaps) == ["small-caps"],
             apply_kerning,
-            decoration: resolve_decoration(text_node, parent, state, cache),
+            font_features: resolve_font_features(parent),
+            font_variations: resolve_font_variations(parent),
+            optical_sizing: parent
+                .find_attribute(AId::FontOpticalSizing)
+                .and_then(|n| n.attribute(AId::FontOpticalSizing))
+                != Some("none"),
+            decoration: crate::text_decoration::resolve(text_node, parent, state, cache),
             visibility: parent
                 .find_and_parse_attribute(AId::Visibility)
                 .unwrap_or_default(),
@@ -280,68 +445,159 @@ fn collect_text_chunks_impl(
             length_adjust: parent
                 .find_and_parse_attribute(AId::LengthAdjust)
                 .unwrap_or_default(),
+            direction,
+            unicode_bidi,
+            line_height: resolve_line_height(parent, font_size.get(), state),
+            lang: parent
+                .ancestors()
+                .find_map(|n| n.attribute(AId::Lang))
+                .filter(|lang| !lang.is_empty())
+                .map(|lang| lang.to_string()),
+            text_orientation: resolve_text_orientation(parent),
         };
 
+        let text_transform = crate::text_transform::resolve(parent);
+
         let mut is_new_span = true;
-        for c in child.text().chars() {
-            let char_len = c.len_utf8();
-
-            // Create a new chunk if:
-            // - this is the first span (yes, position can be None)
-            // - text character has an absolute coordinate assigned to it (via x/y attribute)
-            // - `c` is the first char of the `textPath`
-            // - `c` is the first char after `textPath`
-            let is_new_chunk = pos_list[iter_state.chars_count].x.is_some()
-                || pos_list[iter_state.chars_count].y.is_some()
-                || iter_state.split_chunk
-                || iter_state.chunks.is_empty();
-
-            iter_state.split_chunk = false;
-
-            if is_new_chunk {
-                iter_state.chunk_bytes_count = 0;
-
-                let mut span2 = span.clone();
-                span2.start = 0;
-                span2.end = char_len;
-
-                iter_state.chunks.push(TextChunk {
-                    x: pos_list[iter_state.chars_count].x,
-                    y: pos_list[iter_state.chars_count].y,
-                    anchor,
-                    spans: vec![span2],
-                    text_flow: iter_state.text_flow.clone(),
-                    text: c.to_string(),
-                });
-            } else if is_new_span {
-                // Add this span to the last text chunk.
-                let mut span2 = span.clone();
-                span2.start = iter_state.chunk_bytes_count;
-                span2.end = iter_state.chunk_bytes_count + char_len;
-
-                if let Some(chunk) = iter_state.chunks.last_mut() {
-                    chunk.text.push(c);
-                    chunk.spans.push(span2);
+        let mut chars = child.text().chars().peekable();
+        let mut mapped = String::new();
+        while let Some(c) = chars.next() {
+            mapped.clear();
+            if text_transform.is_none() {
+                mapped.push(c);
+            } else {
+                crate::text_transform::apply(
+                    text_transform,
+                    c,
+                    iter_state.prev_char,
+                    chars.peek().cloned(),
+                    span.lang.as_deref(),
+                    &mut mapped,
+                );
+
+                let mapped_count = mapped.chars().count();
+                if mapped_count != 1 {
+                    iter_state
+                        .transformed_chars
+                        .push((iter_state.chars_count, mapped_count));
                 }
+            }
+
+            // A character removed by `text-transform` has nothing to be positioned,
+            // so its position, as well as a chunk split, is applied to the next character.
+            let x = pos_list[iter_state.chars_count].x.or(iter_state.pending_x);
+            let y = pos_list[iter_state.chars_count].y.or(iter_state.pending_y);
+            if mapped.is_empty() {
+                iter_state.pending_x = x;
+                iter_state.pending_y = y;
             } else {
-                // Extend the last span.
-                if let Some(chunk) = iter_state.chunks.last_mut() {
-                    chunk.text.push(c);
-                    if let Some(span) = chunk.spans.last_mut() {
-                        debug_assert_ne!(span.end, 0);
-                        span.end += char_len;
+                iter_state.pending_x = None;
+                iter_state.pending_y = None;
+            }
+
+            for (i, c) in mapped.chars().enumerate() {
+                let char_len = c.len_utf8();
+
+                // Create a new chunk if:
+                // - this is the first span (yes, position can be None)
+                // - text character has an absolute coordinate assigned to it (via x/y attribute)
+                // - `c` is the first char of the `textPath`
+                // - `c` is the first char after `textPath`
+                //
+                // Characters added by `text-transform` can't have a position.
+                let is_new_chunk = (i == 0
+                    && (x.is_some() || y.is_some() || iter_state.split_chunk))
+                    || iter_state.chunks.is_empty();
+
+                iter_state.split_chunk = false;
+
+                if is_new_chunk {
+                    iter_state.chunk_bytes_count = 0;
+
+                    let mut span2 = span.clone();
+                    span2.start = 0;
+                    span2.end = char_len;
+
+                    iter_state.chunks.push(TextChunk {
+                        x,
+                        y,
+                        anchor,
+                        direction: base_direction,
+                        spans: vec![span2],
+                        text_flow: iter_state.text_flow.clone(),
+                        text: c.to_string(),
+                    });
+                } else if is_new_span {
+                    // Add this span to the last text chunk.
+                    let mut span2 = span.clone();
+                    span2.start = iter_state.chunk_bytes_count;
+                    span2.end = iter_state.chunk_bytes_count + char_len;
+
+                    if let Some(chunk) = iter_state.chunks.last_mut() {
+                        chunk.text.push(c);
+                        chunk.spans.push(span2);
+                    }
+                } else {
+                    // Extend the last span.
+                    if let Some(chunk) = iter_state.chunks.last_mut() {
+                        chunk.text.push(c);
+                        if let Some(span) = chunk.spans.last_mut() {
+                            debug_assert_ne!(span.end, 0);
+                            span.end += char_len;
+                        }
                     }
                 }
+
+                is_new_span = false;
+                iter_state.chunk_bytes_count += char_len;
             }
 
-            is_new_span = false;
+            iter_state.prev_char = Some(c);
             iter_state.chars_count += 1;
-            iter_state.chunk_bytes_count += char_len;
         }
     }
 }
 
-fn resolve_text_flow(node: rosvgtree::Node, state: &converter::State) -> Option<TextFlow> {
+fn resolve_text_flow(
+    node: rosvgtree::Node,
+    state: &converter::State,
+    cache: &mut converter::Cache,
+) -> Option<TextFlow> {
+    // The SVG 2 `path` attribute has a priority over `href`, unless it contains an error.
+    let path = match node
+        .attribute(AId::Path)
+        .and_then(|value| crate::shapes::parse_path(value, true))
+    {
+        Some(path) => path,
+        None => resolve_linked_path(node, state)?,
+    };
+
+    // `side=right` simply reverses the path direction.
+    let path = if node.attribute(AId::Side) == Some("right") {
+        Rc::new(reverse_path(&path))
+    } else {
+        path
+    };
+
+    let start_offset: Length = node.parse_attribute(AId::StartOffset).unwrap_or_default();
+    let start_offset = if start_offset.unit == LengthUnit::Percent {
+        // 'If a percentage is given, then the `startOffset` represents
+        // a percentage distance along the entire path.'
+        let path_len = path.length();
+        path_len * (start_offset.number / 100.0)
+    } else {
+        node.resolve_length(AId::StartOffset, state, 0.0)
+    };
+
+    Some(TextFlow::Path(Rc::new(TextPath {
+        id: cache.gen_text_path_id(),
+        start_offset,
+        path,
+    })))
+}
+
+/// Resolves a path or a basic shape referenced by `textPath`.
+fn resolve_linked_path(node: rosvgtree::Node, state: &converter::State) -> Option<Rc<PathData>> {
     let linked_node = node.parse_attribute::<rosvgtree::Node>(AId::Href)?;
     let path = crate::shapes::convert(linked_node, state)?;
 
@@ -355,17 +611,84 @@ fn resolve_text_flow(node: rosvgtree::Node, state: &converter::State) -> Option<
             path
         };
 
-    let start_offset: Length = node.parse_attribute(AId::StartOffset).unwrap_or_default();
-    let start_offset = if start_offset.unit == LengthUnit::Percent {
-        // 'If a percentage is given, then the `startOffset` represents
-        // a percentage distance along the entire path.'
-        let path_len = path.length();
-        path_len * (start_offset.number / 100.0)
-    } else {
-        node.resolve_length(AId::StartOffset, state, 0.0)
-    };
+    Some(path)
+}
+
+/// Reverses the path direction.
+///
+/// Subpaths are reversed as well, while closed subpaths keep their start point.
+fn reverse_path(path: &PathData) -> PathData {
+    #[derive(Clone, Copy)]
+    enum Segment {
+        Line,
+        Curve((f64, f64), (f64, f64)),
+    }
+
+    struct Subpath {
+        points: Vec<(f64, f64)>,
+        segments: Vec<Segment>,
+        closed: bool,
+    }
+
+    let mut subpaths: Vec<Subpath> = Vec::new();
+    let mut points = path.points().chunks(2).map(|p| (p[0], p[1]));
+    for cmd in path.commands() {
+        match cmd {
+            PathCommand::MoveTo => subpaths.push(Subpath {
+                points: vec![points.next().unwrap()],
+                segments: Vec::new(),
+                closed: false,
+            }),
+            PathCommand::LineTo | PathCommand::CurveTo => {
+                let subpath = match subpaths.last_mut() {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                let segment = if *cmd == PathCommand::CurveTo {
+                    Segment::Curve(points.next().unwrap(), points.next().unwrap())
+                } else {
+                    Segment::Line
+                };
+
+                subpath.segments.push(segment);
+                subpath.points.push(points.next().unwrap());
+            }
+            PathCommand::ClosePath => {
+                if let Some(subpath) = subpaths.last_mut() {
+                    subpath.closed = true;
+                }
+            }
+        }
+    }
+
+    let mut new_path = PathData::new();
+    for subpath in subpaths.iter().rev() {
+        let first = subpath.points[0];
+        let last = subpath.points[subpath.points.len() - 1];
+        if subpath.closed {
+            new_path.push_move_to(first.0, first.1);
+            if last != first {
+                new_path.push_line_to(last.0, last.1);
+            }
+        } else {
+            new_path.push_move_to(last.0, last.1);
+        }
+
+        for (i, segment) in subpath.segments.iter().enumerate().rev() {
+            let p = subpath.points[i];
+            match *segment {
+                Segment::Line => new_path.push_line_to(p.0, p.1),
+                Segment::Curve(p1, p2) => new_path.push_curve_to(p2.0, p2.1, p1.0, p1.1, p.0, p.1),
+            }
+        }
 
-    Some(TextFlow::Path(Rc::new(TextPath { start_offset, path })))
+        if subpath.closed {
+            new_path.push_close_path();
+        }
+    }
+
+    new_path
 }
 
 fn convert_font(node: rosvgtree::Node, state: &converter::State) -> Font {
@@ -381,41 +704,113 @@ fn convert_font(node: rosvgtree::Node, state: &converter::State) -> Font {
         ""
     };
 
+    let mut families = parse_font_families(font_family);
+    if families.is_empty() {
+        families.push(state.opt.font_family.clone())
+    }
+
+    Font {
+        families,
+        style,
+        stretch,
+        weight,
+        synthesis: convert_font_synthesis(node),
+    }
+}
+
+/// Parses a `font-family` list.
+///
+/// Family names can be either CSS strings, with either quotes and escapes,
+/// or sequences of identifiers separated by whitespaces.
+fn parse_font_families(text: &str) -> Vec<String> {
     let mut families = Vec::new();
-    for mut family in font_family.split(',') {
-        // TODO: to a proper parser
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut family = String::new();
+        match chars.peek().copied() {
+            None => break,
+            Some(quote @ ('\'' | '"')) => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => family.extend(chars.next()),
+                        c if c == quote => break,
+                        c => family.push(c),
+                    }
+                }
 
-        if family.starts_with('\'') {
-            family = &family[1..];
-        }
+                // Skip anything up to the next family.
+                for c in chars.by_ref() {
+                    if c == ',' {
+                        break;
+                    }
+                }
+            }
+            Some(_) => {
+                for c in chars.by_ref() {
+                    match c {
+                        ',' => break,
+                        '\\' => {}
+                        c => family.push(c),
+                    }
+                }
 
-        if family.ends_with('\'') {
-            family = &family[..family.len() - 1];
+                // Identifiers are separated by a single space.
+                family = family.split_whitespace().collect::<Vec<_>>().join(" ");
+            }
         }
 
-        family = family.trim();
-
         if !family.is_empty() {
-            families.push(family.to_string());
+            families.push(family);
         }
     }
 
-    if families.is_empty() {
-        families.push(state.opt.font_family.clone())
+    families
+}
+
+fn convert_font_synthesis(node: rosvgtree::Node) -> FontSynthesis {
+    let value = match node
+        .find_attribute(AId::FontSynthesis)
+        .and_then(|n| n.attribute(AId::FontSynthesis))
+    {
+        Some(v) => v.trim(),
+        None => return FontSynthesis::default(),
+    };
+
+    let mut synthesis = FontSynthesis {
+        weight: false,
+        style: false,
+    };
+
+    if value == "none" {
+        return synthesis;
     }
 
-    Font {
-        families,
-        style,
-        stretch,
-        weight,
+    // `small-caps` and `position` are not supported, because synthetic
+    // small caps and super/subscripts are not rendered anyway.
+    for token in value.split_whitespace() {
+        match token {
+            "weight" => synthesis.weight = true,
+            "style" => synthesis.style = true,
+            "small-caps" | "position" => {}
+            _ => return FontSynthesis::default(),
+        }
     }
+
+    synthesis
 }
 
 // TODO: properly resolve narrower/wider
 fn conv_font_stretch(node: rosvgtree::Node) -> FontStretch {
     if let Some(n) = node.ancestors().find(|n| n.has_attribute(AId::FontStretch)) {
-        match n.attribute(AId::FontStretch).unwrap_or("") {
+        let value = n.attribute(AId::FontStretch).unwrap_or("");
+        if let Some(percentage) = parse_font_stretch_percentage(value) {
+            return closest_font_stretch(percentage);
+        }
+
+        match value {
             "narrower" | "condensed" => FontStretch::Condensed,
             "ultra-condensed" => FontStretch::UltraCondensed,
             "extra-condensed" => FontStretch::ExtraCondensed,
@@ -431,6 +826,109 @@ fn conv_font_stretch(node: rosvgtree::Node) -> FontStretch {
     }
 }
 
+/// `font-stretch` keywords and their percentages.
+const FONT_STRETCH_PERCENTAGES: &[(FontStretch, f64)] = &[
+    (FontStretch::UltraCondensed, 50.0),
+    (FontStretch::ExtraCondensed, 62.5),
+    (FontStretch::Condensed, 75.0),
+    (FontStretch::SemiCondensed, 87.5),
+    (FontStretch::Normal, 100.0),
+    (FontStretch::SemiExpanded, 112.5),
+    (FontStretch::Expanded, 125.0),
+    (FontStretch::ExtraExpanded, 150.0),
+    (FontStretch::UltraExpanded, 200.0),
+];
+
+fn parse_font_stretch_percentage(value: &str) -> Option<f64> {
+    let n: f64 = value.trim().strip_suffix('%')?.parse().ok()?;
+    // Negative values are invalid.
+    if n.is_finite() && n >= 0.0 {
+        Some(n)
+    } else {
+        None
+    }
+}
+
+fn closest_font_stretch(percentage: f64) -> FontStretch {
+    let mut closest = FONT_STRETCH_PERCENTAGES[0];
+    for item in FONT_STRETCH_PERCENTAGES {
+        if (item.1 - percentage).abs() < (closest.1 - percentage).abs() {
+            closest = *item;
+        }
+    }
+
+    closest.0
+}
+
+/// Resolves font variation axes values.
+///
+/// `font-variation-settings` has the highest precedence, so its values are added last.
+fn resolve_font_variations(node: rosvgtree::Node) -> Vec<FontVariation> {
+    let mut variations = Vec::new();
+
+    // `Font::stretch` is the closest keyword, so the exact value has to be stored separately.
+    let stretch = node
+        .find_attribute(AId::FontStretch)
+        .and_then(|n| n.attribute(AId::FontStretch))
+        .and_then(parse_font_stretch_percentage);
+    if let Some(percentage) = stretch {
+        if !FONT_STRETCH_PERCENTAGES
+            .iter()
+            .any(|item| item.1 == percentage)
+        {
+            variations.push(FontVariation::new(b"wdth", percentage as f32));
+        }
+    }
+
+    let settings = node
+        .find_attribute(AId::FontVariationSettings)
+        .and_then(|n| n.attribute(AId::FontVariationSettings))
+        .and_then(parse_font_variation_settings);
+    if let Some(settings) = settings {
+        variations.extend(settings);
+    }
+
+    variations
+}
+
+/// Parses a `font-variation-settings` property.
+///
+/// Returns `None` for invalid values.
+fn parse_font_variation_settings(value: &str) -> Option<Vec<FontVariation>> {
+    let value = value.trim();
+    if value == "normal" {
+        return Some(Vec::new());
+    }
+
+    let mut variations = Vec::new();
+    for item in value.split(',') {
+        let (tag, value) = parse_quoted_tag(item.trim())?;
+        let value: f32 = value.trim().parse().ok()?;
+        if !value.is_finite() {
+            return None;
+        }
+
+        variations.push(FontVariation::new(&tag, value));
+    }
+
+    Some(variations)
+}
+
+/// Parses a quoted four-letter OpenType tag.
+///
+/// Returns the tag and the rest of the string.
+fn parse_quoted_tag(text: &str) -> Option<([u8; 4], &str)> {
+    let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let end = text[1..].find(quote)? + 1;
+
+    let tag = &text.as_bytes()[1..end];
+    if tag.len() != 4 || !tag.iter().all(|c| (0x20..=0x7E).contains(c)) {
+        return None;
+    }
+
+    Some(([tag[0], tag[1], tag[2], tag[3]], &text[end + 1..]))
+}
+
 fn resolve_font_weight(node: rosvgtree::Node) -> u16 {
     fn bound(min: usize, val: usize, max: usize) -> usize {
         std::cmp::max(min, std::cmp::min(max, val))
@@ -443,15 +941,6 @@ fn resolve_font_weight(node: rosvgtree::Node) -> u16 {
         weight = match n.attribute(AId::FontWeight).unwrap_or("") {
             "normal" => 400,
             "bold" => 700,
-            "100" => 100,
-            "200" => 200,
-            "300" => 300,
-            "400" => 400,
-            "500" => 500,
-            "600" => 600,
-            "700" => 700,
-            "800" => 800,
-            "900" => 900,
             "bolder" => {
                 // By the CSS2 spec the default value should be 400
                 // so `bolder` will result in 500.
@@ -460,7 +949,8 @@ fn resolve_font_weight(node: rosvgtree::Node) -> u16 {
                 // we will follow such behavior for now.
                 let step = if weight == 400 { 300 } else { 100 };
 
-                bound(100, weight + step, 900)
+                // Weights outside the 100..=900 range are never made lighter.
+                std::cmp::max(weight, bound(100, weight + step, 900))
             }
             "lighter" => {
                 // By the CSS2 spec the default value should be 400
@@ -470,9 +960,16 @@ fn resolve_font_weight(node: rosvgtree::Node) -> u16 {
                 // we will follow such behavior for now.
                 let step = if weight == 400 { 200 } else { 100 };
 
-                bound(100, weight - step, 900)
+                // Weights outside the 100..=900 range are never made bolder.
+                std::cmp::min(weight, bound(100, weight.saturating_sub(step), 900))
             }
-            _ => weight,
+            // Any number in the 1..=1000 range is allowed.
+            value => value
+                .parse::<f64>()
+                .ok()
+                .filter(|n| (1.0..=1000.0).contains(n))
+                .map(|n| n.round() as usize)
+                .unwrap_or(weight),
         };
     }
 
@@ -625,75 +1122,358 @@ fn resolve_rotate_list(text_node: rosvgtree::Node) -> Vec<f64> {
     list
 }
 
-/// Resolves node's `text-decoration` property.
+/// Resolves text's `inline-size`.
 ///
-/// `text` and `tspan` can point to the same node.
-fn resolve_decoration(
+/// Percentages are relative to the viewport size along the inline axis.
+/// Zero and negative values disable wrapping.
+fn resolve_inline_size(
+    text_node: rosvgtree::Node,
+    writing_mode: WritingMode,
+    state: &converter::State,
+) -> Option<f64> {
+    let length: Length = text_node.parse_attribute(AId::InlineSize)?;
+    let size = if length.unit == LengthUnit::Percent {
+        let base = match writing_mode {
+            WritingMode::LeftToRight => state.view_box.width(),
+            WritingMode::TopToBottom => state.view_box.height(),
+        };
+
+        base * length.number / 100.0
+    } else {
+        crate::units::convert_length(
+            length,
+            text_node,
+            AId::InlineSize,
+            Units::UserSpaceOnUse,
+            state,
+        )
+    };
+
+    if size > 0.0 {
+        Some(size)
+    } else {
+        None
+    }
+}
+
+/// Resolves text's `shape-inside` and related properties.
+///
+/// Only references to shapes are supported. Basic shapes, like `circle()`, are ignored.
+fn resolve_shape_inside(
     text_node: rosvgtree::Node,
-    tspan: rosvgtree::Node,
     state: &converter::State,
     cache: &mut converter::Cache,
-) -> TextDecoration {
-    // TODO: explain the algorithm
+) -> Option<ShapeInside> {
+    let shapes = resolve_text_shapes(text_node, AId::ShapeInside, state, cache);
+    if shapes.is_empty() {
+        return None;
+    }
 
-    let text_dec = conv_text_decoration(text_node);
-    let tspan_dec = conv_text_decoration2(tspan);
+    let subtract = resolve_text_shapes(text_node, AId::ShapeSubtract, state, cache);
 
-    let mut gen_style = |in_tspan: bool, in_text: bool| {
-        let n = if in_tspan {
-            tspan
-        } else if in_text {
-            text_node
-        } else {
-            return None;
+    let resolve_offset = |aid| {
+        text_node
+            .try_convert_length(aid, Units::UserSpaceOnUse, state)
+            .unwrap_or(0.0)
+            .max(0.0)
+    };
+
+    Some(ShapeInside {
+        shapes,
+        subtract,
+        padding: resolve_offset(AId::ShapePadding),
+        margin: resolve_offset(AId::ShapeMargin),
+    })
+}
+
+/// Resolves a list of `url()` references to shapes.
+fn resolve_text_shapes(
+    text_node: rosvgtree::Node,
+    aid: AId,
+    state: &converter::State,
+    cache: &mut converter::Cache,
+) -> Vec<Rc<TextShape>> {
+    let value = match text_node.attribute(aid) {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+
+    let mut shapes = Vec::new();
+    for item in value.split(')') {
+        let link = match item.trim().strip_prefix("url(") {
+            Some(v) => v.trim().trim_matches(|c| c == '\'' || c == '"'),
+            None => continue,
+        };
+
+        let id = match link.strip_prefix('#') {
+            Some(v) => v,
+            None => continue,
         };
 
-        Some(TextDecorationStyle {
-            fill: style::resolve_fill(n, true, state, cache),
-            stroke: style::resolve_stroke(n, true, state, cache),
-        })
+        let shape_node = match text_node.document().element_by_id(id) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let path = match crate::shapes::convert(shape_node, state) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        // The shape's transform needs to be applied.
+        let path = match shape_node.parse_attribute::<Transform>(AId::Transform) {
+            Some(ts) => {
+                let mut path_copy = path.as_ref().clone();
+                path_copy.transform(ts);
+                Rc::new(path_copy)
+            }
+            None => path,
+        };
+
+        shapes.push(Rc::new(TextShape {
+            id: cache.gen_text_shape_id(),
+            path,
+        }));
+    }
+
+    shapes
+}
+
+/// Resolves span's `line-height`.
+///
+/// Numbers are relative to the span's font size
+/// and percentages to the font size of the element that defines them.
+/// Returns `None` for `normal`.
+fn resolve_line_height(
+    node: rosvgtree::Node,
+    font_size: f64,
+    state: &converter::State,
+) -> Option<f64> {
+    let n = node.find_attribute(AId::LineHeight)?;
+    let value = n.attribute(AId::LineHeight)?;
+    if value == "normal" {
+        return None;
+    }
+
+    // Unlike lengths and percentages, numbers are inherited as is.
+    let length: Length = n.parse_attribute(AId::LineHeight)?;
+    let height = match length.unit {
+        LengthUnit::None => length.number * font_size,
+        LengthUnit::Percent => length.number * crate::units::resolve_font_size(n, state) / 100.0,
+        _ => crate::units::convert_length(length, n, AId::LineHeight, Units::UserSpaceOnUse, state),
     };
 
-    TextDecoration {
-        underline: gen_style(tspan_dec.has_underline, text_dec.has_underline),
-        overline: gen_style(tspan_dec.has_overline, text_dec.has_overline),
-        line_through: gen_style(tspan_dec.has_line_through, text_dec.has_line_through),
+    if height >= 0.0 {
+        Some(height)
+    } else {
+        None
     }
 }
 
-struct TextDecorationTypes {
-    has_underline: bool,
-    has_overline: bool,
-    has_line_through: bool,
+/// Resolves span's embedding direction and `unicode-bidi`.
+///
+/// `unicode-bidi` is not inherited, but affects all the descendants,
+/// so we are looking for the closest element that has one.
+fn resolve_bidi(
+    text_node: rosvgtree::Node,
+    tspan: rosvgtree::Node,
+) -> (TextDirection, UnicodeBidi) {
+    for n in tspan.ancestors() {
+        let unicode_bidi: UnicodeBidi = n.parse_attribute(AId::UnicodeBidi).unwrap_or_default();
+        if unicode_bidi != UnicodeBidi::Normal {
+            let direction = n
+                .find_and_parse_attribute(AId::Direction)
+                .unwrap_or_default();
+            return (direction, unicode_bidi);
+        }
+
+        if n == text_node {
+            break;
+        }
+    }
+
+    let direction = tspan
+        .find_and_parse_attribute(AId::Direction)
+        .unwrap_or_default();
+    (direction, UnicodeBidi::Normal)
 }
 
-/// Resolves the `text` node's `text-decoration` property.
-fn conv_text_decoration(text_node: rosvgtree::Node) -> TextDecorationTypes {
-    fn find_decoration(node: rosvgtree::Node, value: &str) -> bool {
-        node.ancestors().any(|n| {
-            if let Some(str_value) = n.attribute(AId::TextDecoration) {
-                str_value.split(' ').any(|v| v == value)
-            } else {
-                false
+/// Resolves node's `text-decoration` property.
+///
+/// `text` and `tspan` can point to the same node.
+/// Resolves OpenType features set by `font-variant-*` and `font-feature-settings`.
+///
+/// `font-feature-settings` has the highest precedence, so its features are added last.
+fn resolve_font_features(node: rosvgtree::Node) -> Vec<FontFeature> {
+    let mut features = Vec::new();
+    for aid in [
+        AId::FontVariantLigatures,
+        AId::FontVariantCaps,
+        AId::FontVariantNumeric,
+        AId::FontVariantEastAsian,
+        AId::FontVariantPosition,
+    ] {
+        for keyword in find_font_variant(node, aid) {
+            features.extend(
+                font_variant_features(keyword)
+                    .iter()
+                    .map(|(tag, value)| FontFeature::new(tag, *value)),
+            );
+        }
+    }
+
+    let settings = node
+        .find_attribute(AId::FontFeatureSettings)
+        .and_then(|n| n.attribute(AId::FontFeatureSettings))
+        .and_then(parse_font_feature_settings);
+    if let Some(settings) = settings {
+        features.extend(settings);
+    }
+
+    features
+}
+
+/// Resolves a `font-variant-*` property into a list of keywords.
+///
+/// Each property can be set by the `font-variant` shorthand as well,
+/// so the closest element that has either of them is used.
+/// An empty list means `normal`.
+fn find_font_variant<'a>(node: rosvgtree::Node<'a, '_>, aid: AId) -> Vec<&'a str> {
+    for n in node.ancestors() {
+        if let Some(value) = n.attribute(aid) {
+            let keywords: Vec<_> = value.split_whitespace().collect();
+            if keywords == ["normal"] {
+                return Vec::new();
             }
-        })
+
+            // Invalid values are ignored.
+            if keywords
+                .iter()
+                .all(|keyword| font_variant_property(keyword) == Some(aid))
+            {
+                return keywords;
+            }
+        }
+
+        if let Some(value) = n.attribute(AId::FontVariant) {
+            let keywords: Vec<_> = value.split_whitespace().collect();
+            if keywords == ["normal"] {
+                return Vec::new();
+            }
+
+            // Invalid values are ignored.
+            if keywords
+                .iter()
+                .all(|keyword| font_variant_property(keyword).is_some())
+            {
+                return keywords
+                    .into_iter()
+                    .filter(|keyword| font_variant_property(keyword) == Some(aid))
+                    .collect();
+            }
+        }
     }
 
-    TextDecorationTypes {
-        has_underline: find_decoration(text_node, "underline"),
-        has_overline: find_decoration(text_node, "overline"),
-        has_line_through: find_decoration(text_node, "line-through"),
+    Vec::new()
+}
+
+/// Returns a `font-variant-*` property the keyword belongs to.
+fn font_variant_property(keyword: &str) -> Option<AId> {
+    let aid = match keyword {
+        "none"
+        | "common-ligatures"
+        | "no-common-ligatures"
+        | "discretionary-ligatures"
+        | "no-discretionary-ligatures"
+        | "historical-ligatures"
+        | "no-historical-ligatures"
+        | "contextual"
+        | "no-contextual" => AId::FontVariantLigatures,
+        "small-caps" | "all-small-caps" | "petite-caps" | "all-petite-caps" | "unicase"
+        | "titling-caps" => AId::FontVariantCaps,
+        "lining-nums" | "oldstyle-nums" | "proportional-nums" | "tabular-nums"
+        | "diagonal-fractions" | "stacked-fractions" | "ordinal" | "slashed-zero" => {
+            AId::FontVariantNumeric
+        }
+        "jis78" | "jis83" | "jis90" | "jis04" | "simplified" | "traditional" | "full-width"
+        | "proportional-width" | "ruby" => AId::FontVariantEastAsian,
+        "sub" | "super" => AId::FontVariantPosition,
+        _ => return None,
+    };
+
+    Some(aid)
+}
+
+/// Maps a `font-variant-*` keyword to OpenType features.
+///
+/// `small-caps` is stored in `TextSpan::small_caps` instead.
+fn font_variant_features(keyword: &str) -> &'static [(&'static [u8; 4], u32)] {
+    match keyword {
+        "none" => &[
+            (b"liga", 0),
+            (b"clig", 0),
+            (b"dlig", 0),
+            (b"hlig", 0),
+            (b"calt", 0),
+        ],
+        "common-ligatures" => &[(b"liga", 1), (b"clig", 1)],
+        "no-common-ligatures" => &[(b"liga", 0), (b"clig", 0)],
+        "discretionary-ligatures" => &[(b"dlig", 1)],
+        "no-discretionary-ligatures" => &[(b"dlig", 0)],
+        "historical-ligatures" => &[(b"hlig", 1)],
+        "no-historical-ligatures" => &[(b"hlig", 0)],
+        "contextual" => &[(b"calt", 1)],
+        "no-contextual" => &[(b"calt", 0)],
+        "all-small-caps" => &[(b"c2sc", 1), (b"smcp", 1)],
+        "petite-caps" => &[(b"pcap", 1)],
+        "all-petite-caps" => &[(b"c2pc", 1), (b"pcap", 1)],
+        "unicase" => &[(b"unic", 1)],
+        "titling-caps" => &[(b"titl", 1)],
+        "lining-nums" => &[(b"lnum", 1)],
+        "oldstyle-nums" => &[(b"onum", 1)],
+        "proportional-nums" => &[(b"pnum", 1)],
+        "tabular-nums" => &[(b"tnum", 1)],
+        "diagonal-fractions" => &[(b"frac", 1)],
+        "stacked-fractions" => &[(b"afrc", 1)],
+        "ordinal" => &[(b"ordn", 1)],
+        "slashed-zero" => &[(b"zero", 1)],
+        "jis78" => &[(b"jp78", 1)],
+        "jis83" => &[(b"jp83", 1)],
+        "jis90" => &[(b"jp90", 1)],
+        "jis04" => &[(b"jp04", 1)],
+        "simplified" => &[(b"smpl", 1)],
+        "traditional" => &[(b"trad", 1)],
+        "full-width" => &[(b"fwid", 1)],
+        "proportional-width" => &[(b"pwid", 1)],
+        "ruby" => &[(b"ruby", 1)],
+        "sub" => &[(b"subs", 1)],
+        "super" => &[(b"sups", 1)],
+        _ => &[],
     }
 }
 
-/// Resolves the default `text-decoration` property.
-fn conv_text_decoration2(tspan: rosvgtree::Node) -> TextDecorationTypes {
-    let s = tspan.attribute(AId::TextDecoration);
-    TextDecorationTypes {
-        has_underline: s == Some("underline"),
-        has_overline: s == Some("overline"),
-        has_line_through: s == Some("line-through"),
+/// Parses a `font-feature-settings` property.
+///
+/// Returns `None` for invalid values.
+fn parse_font_feature_settings(value: &str) -> Option<Vec<FontFeature>> {
+    let value = value.trim();
+    if value == "normal" {
+        return Some(Vec::new());
+    }
+
+    let mut features = Vec::new();
+    for item in value.split(',') {
+        let (tag, value) = parse_quoted_tag(item.trim())?;
+        let value = match value.trim() {
+            "" | "on" => 1,
+            "off" => 0,
+            n => n.parse().ok()?,
+        };
+
+        features.push(FontFeature::new(&tag, value));
     }
+
+    Some(features)
 }
 
 fn convert_baseline_shift(node: rosvgtree::Node, state: &converter::State) -> Vec<BaselineShift> {
@@ -778,3 +1558,26 @@ fn convert_writing_mode(text_node: rosvgtree::Node) -> WritingMode {
         WritingMode::LeftToRight
     }
 }
+
+/// Resolves a `text-orientation` from the closest element that has it
+/// or the deprecated `glyph-orientation-vertical`.
+///
+/// `glyph-orientation-vertical` is mapped as described in
+/// [CSS Writing Modes Level 3](https://www.w3.org/TR/css-writing-modes-3/#glyph-orientation).
+/// Other angles are not supported.
+fn resolve_text_orientation(node: rosvgtree::Node) -> TextOrientation {
+    for n in node.ancestors() {
+        if let Some(orientation) = n.parse_attribute(AId::TextOrientation) {
+            return orientation;
+        }
+
+        match n.attribute(AId::GlyphOrientationVertical) {
+            Some("auto") => return TextOrientation::Mixed,
+            Some("0") | Some("0deg") => return TextOrientation::Upright,
+            Some("90") | Some("90deg") => return TextOrientation::Sideways,
+            _ => {}
+        }
+    }
+
+    TextOrientation::default()
+}
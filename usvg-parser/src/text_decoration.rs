@@ -0,0 +1,353 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `text-decoration` support.
+//!
+//! Decoration lines are painted by the element that declares them,
+//! while the fill and stroke can be overridden by the SVG 2 `text-decoration-fill`
+//! and `text-decoration-stroke` or by the CSS `text-decoration-color`.
+
+use rosvgtree::{self, AttributeId as AId};
+use svgtypes::{Length, LengthUnit};
+use usvg_tree::{
+    TextDecoration, TextDecorationLineStyle, TextDecorationStyle, TextUnderlinePosition, Units,
+};
+
+use crate::{color, converter, style};
+
+#[derive(Clone, Copy, Default)]
+struct Lines {
+    underline: bool,
+    overline: bool,
+    line_through: bool,
+}
+
+/// `text-decoration` properties specified on a single element.
+///
+/// Longhands override the `text-decoration` shorthand.
+#[derive(Default)]
+struct Properties<'a> {
+    lines: Lines,
+    style: Option<TextDecorationLineStyle>,
+    color: Option<&'a str>,
+    /// `None` indicates `auto` or `from-font`.
+    thickness: Option<Length>,
+}
+
+impl<'a> Properties<'a> {
+    fn from_node(node: rosvgtree::Node<'a, '_>) -> Self {
+        let mut props = node
+            .attribute(AId::TextDecoration)
+            .and_then(parse_shorthand)
+            .unwrap_or_default();
+
+        if let Some(lines) = node
+            .attribute(AId::TextDecorationLine)
+            .and_then(parse_lines)
+        {
+            props.lines = lines;
+        }
+
+        if let Some(style) = node
+            .attribute(AId::TextDecorationStyle)
+            .and_then(|v| parse_line_style(v.trim()))
+        {
+            props.style = Some(style);
+        }
+
+        if let Some(color) = node
+            .attribute(AId::TextDecorationColor)
+            .filter(|v| is_color(v.trim()))
+        {
+            props.color = Some(color.trim());
+        }
+
+        if let Some(thickness) = node
+            .attribute(AId::TextDecorationThickness)
+            .and_then(|v| parse_thickness(v.trim()))
+        {
+            props.thickness = thickness;
+        }
+
+        props
+    }
+}
+
+/// Resolves decoration lines of text inside the `tspan` element.
+///
+/// Lines declared by the `tspan` itself use its style. Otherwise, lines can be
+/// declared by the `text` element or any of its ancestors and use the `text` style.
+pub(crate) fn resolve(
+    text_node: rosvgtree::Node,
+    tspan: rosvgtree::Node,
+    state: &converter::State,
+    cache: &mut converter::Cache,
+) -> TextDecoration {
+    let tspan_props = Properties::from_node(tspan);
+    let text_props: Vec<_> = text_node
+        .ancestors()
+        .map(|n| (n, Properties::from_node(n)))
+        .collect();
+
+    let mut resolve_line = |has_line: fn(&Lines) -> bool| {
+        if has_line(&tspan_props.lines) {
+            return Some(resolve_style(tspan, tspan, &tspan_props, state, cache));
+        }
+
+        let (decorating_node, props) = text_props.iter().find(|(_, p)| has_line(&p.lines))?;
+        Some(resolve_style(
+            *decorating_node,
+            text_node,
+            props,
+            state,
+            cache,
+        ))
+    };
+
+    TextDecoration {
+        underline: resolve_line(|l| l.underline),
+        overline: resolve_line(|l| l.overline),
+        line_through: resolve_line(|l| l.line_through),
+    }
+}
+
+fn resolve_style(
+    decorating_node: rosvgtree::Node,
+    node: rosvgtree::Node,
+    props: &Properties,
+    state: &converter::State,
+    cache: &mut converter::Cache,
+) -> TextDecorationStyle {
+    let decoration_fill = decorating_node
+        .attribute(AId::TextDecorationFill)
+        .filter(|v| color::parse_paint(v).is_some());
+    let fill = if let Some(value) = decoration_fill {
+        style::resolve_fill_with(
+            node,
+            decorating_node,
+            AId::TextDecorationFill,
+            value,
+            true,
+            state,
+            cache,
+        )
+    } else if let Some(value) = props.color {
+        style::resolve_fill_with(
+            node,
+            decorating_node,
+            AId::TextDecorationColor,
+            value,
+            true,
+            state,
+            cache,
+        )
+    } else {
+        style::resolve_fill(node, true, state, cache)
+    };
+
+    let decoration_stroke = decorating_node
+        .attribute(AId::TextDecorationStroke)
+        .filter(|v| color::parse_paint(v).is_some());
+    let stroke = if let Some(value) = decoration_stroke {
+        style::resolve_stroke_with(
+            node,
+            decorating_node,
+            AId::TextDecorationStroke,
+            value,
+            true,
+            state,
+            cache,
+        )
+    } else {
+        style::resolve_stroke(node, true, state, cache)
+    };
+
+    let thickness = props
+        .thickness
+        .map(|len| resolve_length(len, decorating_node, AId::TextDecorationThickness, state));
+
+    TextDecorationStyle {
+        fill,
+        stroke,
+        line_style: props.style.unwrap_or_default(),
+        thickness,
+        underline_offset: resolve_underline_offset(decorating_node, state),
+        underline_position: resolve_underline_position(decorating_node),
+    }
+}
+
+/// Resolves a length, where percentages are relative to the font size.
+fn resolve_length(len: Length, node: rosvgtree::Node, aid: AId, state: &converter::State) -> f64 {
+    if len.unit == LengthUnit::Percent {
+        crate::units::resolve_font_size(node, state) * len.number / 100.0
+    } else {
+        crate::units::convert_length(len, node, aid, Units::UserSpaceOnUse, state)
+    }
+}
+
+fn resolve_underline_offset(node: rosvgtree::Node, state: &converter::State) -> Option<f64> {
+    let n = node.find_attribute(AId::TextUnderlineOffset)?;
+    let value = n.attribute(AId::TextUnderlineOffset)?.trim();
+    if value == "auto" {
+        return None;
+    }
+
+    let len = value.parse::<Length>().ok()?;
+    Some(resolve_length(len, n, AId::TextUnderlineOffset, state))
+}
+
+fn resolve_underline_position(node: rosvgtree::Node) -> TextUnderlinePosition {
+    node.find_attribute(AId::TextUnderlinePosition)
+        .and_then(|n| n.attribute(AId::TextUnderlinePosition))
+        .and_then(parse_underline_position)
+        .unwrap_or_default()
+}
+
+fn parse_underline_position(value: &str) -> Option<TextUnderlinePosition> {
+    let mut position = TextUnderlinePosition::default();
+    match value.trim() {
+        "auto" | "from-font" => return Some(position),
+        _ => {}
+    }
+
+    let mut has_side = false;
+    for token in value.split_whitespace() {
+        match token {
+            "under" if !position.under => position.under = true,
+            "left" if !has_side => has_side = true,
+            "right" if !has_side => {
+                has_side = true;
+                position.right = true;
+            }
+            _ => return None,
+        }
+    }
+
+    Some(position)
+}
+
+/// Parses a `text-decoration` shorthand.
+///
+/// Returns `None` when any of the values is invalid.
+fn parse_shorthand(value: &str) -> Option<Properties<'_>> {
+    let mut props = Properties::default();
+    let mut lines = None;
+    let mut has_none = false;
+    let mut has_thickness = false;
+    for token in split_values(value) {
+        if let Some(v) = parse_lines(token) {
+            // `none` cannot be combined with other lines.
+            if lines.is_some() && (token == "none" || has_none) {
+                return None;
+            }
+
+            has_none = token == "none";
+
+            let lines = lines.get_or_insert(Lines::default());
+            lines.underline |= v.underline;
+            lines.overline |= v.overline;
+            lines.line_through |= v.line_through;
+        } else if let Some(style) = parse_line_style(token) {
+            if props.style.is_some() {
+                return None;
+            }
+
+            props.style = Some(style);
+        } else if let Some(thickness) = parse_thickness(token) {
+            if has_thickness {
+                return None;
+            }
+
+            has_thickness = true;
+            props.thickness = thickness;
+        } else if is_color(token) {
+            if props.color.is_some() {
+                return None;
+            }
+
+            props.color = Some(token);
+        } else {
+            return None;
+        }
+    }
+
+    props.lines = lines.unwrap_or_default();
+    Some(props)
+}
+
+/// Splits a value by whitespace, while keeping color functions intact.
+fn split_values(value: &str) -> Vec<&str> {
+    let mut values = Vec::new();
+    let mut depth = 0;
+    let mut start = None;
+    for (idx, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    values.push(&value[start..idx]);
+                }
+
+                continue;
+            }
+            _ => {}
+        }
+
+        start.get_or_insert(idx);
+    }
+
+    if let Some(start) = start {
+        values.push(&value[start..]);
+    }
+
+    values
+}
+
+fn parse_lines(value: &str) -> Option<Lines> {
+    let mut lines = Lines::default();
+    if value.trim() == "none" {
+        return Some(lines);
+    }
+
+    for token in value.split_whitespace() {
+        match token {
+            "underline" => lines.underline = true,
+            "overline" => lines.overline = true,
+            "line-through" => lines.line_through = true,
+            "blink" => {}
+            _ => return None,
+        }
+    }
+
+    Some(lines)
+}
+
+fn parse_line_style(value: &str) -> Option<TextDecorationLineStyle> {
+    match value {
+        "solid" => Some(TextDecorationLineStyle::Solid),
+        "double" => Some(TextDecorationLineStyle::Double),
+        "dotted" => Some(TextDecorationLineStyle::Dotted),
+        "dashed" => Some(TextDecorationLineStyle::Dashed),
+        "wavy" => Some(TextDecorationLineStyle::Wavy),
+        _ => None,
+    }
+}
+
+/// Parses a `text-decoration-thickness` value.
+///
+/// `Some(None)` indicates `auto` or `from-font`.
+fn parse_thickness(value: &str) -> Option<Option<Length>> {
+    match value {
+        "auto" | "from-font" => Some(None),
+        _ => value.parse::<Length>().ok().map(Some),
+    }
+}
+
+fn is_color(value: &str) -> bool {
+    matches!(
+        color::parse_paint(value),
+        Some((svgtypes::Paint::Color(_), _)) | Some((svgtypes::Paint::CurrentColor, _))
+    )
+}
@@ -0,0 +1,221 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `text-transform` support.
+//!
+//! Relies on the Unicode case mapping from `std`, which already handles
+//! special cases like `ß` to `SS`. Language-sensitive mappings are supported
+//! for Turkish, Azerbaijani and Greek.
+
+use rosvgtree::{self, AttributeId as AId};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Case {
+    Uppercase,
+    Lowercase,
+    Capitalize,
+}
+
+/// A resolved `text-transform` property.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub(crate) struct TextTransform {
+    case: Option<Case>,
+    full_width: bool,
+}
+
+impl TextTransform {
+    pub(crate) fn is_none(&self) -> bool {
+        self.case.is_none() && !self.full_width
+    }
+}
+
+/// Resolves an inherited `text-transform`.
+///
+/// An invalid value is treated as `none`.
+pub(crate) fn resolve(node: rosvgtree::Node) -> TextTransform {
+    let value = match node
+        .find_attribute(AId::TextTransform)
+        .and_then(|n| n.attribute(AId::TextTransform))
+    {
+        Some(v) => v,
+        None => return TextTransform::default(),
+    };
+
+    parse(value).unwrap_or_default()
+}
+
+fn parse(value: &str) -> Option<TextTransform> {
+    let mut transform = TextTransform::default();
+    if value.trim() == "none" {
+        return Some(transform);
+    }
+
+    for token in value.split_whitespace() {
+        let case = match token {
+            "uppercase" => Case::Uppercase,
+            "lowercase" => Case::Lowercase,
+            "capitalize" => Case::Capitalize,
+            "full-width" if !transform.full_width => {
+                transform.full_width = true;
+                continue;
+            }
+            _ => return None,
+        };
+
+        if transform.case.is_some() {
+            return None;
+        }
+
+        transform.case = Some(case);
+    }
+
+    Some(transform)
+}
+
+/// Transforms a single character.
+///
+/// `prev` and `next` are the neighbouring characters in the original text.
+/// They are used to detect words and contextual mappings.
+/// The result can be empty or contain multiple characters.
+pub(crate) fn apply(
+    transform: TextTransform,
+    c: char,
+    prev: Option<char>,
+    next: Option<char>,
+    lang: Option<&str>,
+    out: &mut String,
+) {
+    let start = out.len();
+
+    let language = Language::from_tag(lang);
+    match transform.case {
+        Some(Case::Uppercase) => to_uppercase(c, language, out),
+        Some(Case::Lowercase) => to_lowercase(c, prev, next, language, out),
+        Some(Case::Capitalize) if is_word_start(prev) && c.is_alphabetic() => {
+            to_titlecase(c, language, out)
+        }
+        Some(Case::Capitalize) | None => out.push(c),
+    }
+
+    if transform.full_width {
+        let mapped: String = out[start..].chars().map(to_full_width).collect();
+        out.truncate(start);
+        out.push_str(&mapped);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Language {
+    Turkic,
+    Greek,
+    Other,
+}
+
+impl Language {
+    fn from_tag(tag: Option<&str>) -> Self {
+        let primary = tag
+            .and_then(|tag| tag.split(['-', '_']).next())
+            .unwrap_or("");
+
+        if primary.eq_ignore_ascii_case("tr") || primary.eq_ignore_ascii_case("az") {
+            Language::Turkic
+        } else if primary.eq_ignore_ascii_case("el") {
+            Language::Greek
+        } else {
+            Language::Other
+        }
+    }
+}
+
+fn to_uppercase(c: char, language: Language, out: &mut String) {
+    match (language, c) {
+        (Language::Turkic, 'i') => out.push('\u{0130}'),
+        // Greek uppercase drops accents.
+        (Language::Greek, '\u{0301}') | (Language::Greek, '\u{0342}') => {}
+        (Language::Greek, _) => out.extend(remove_tonos(c).to_uppercase()),
+        _ => out.extend(c.to_uppercase()),
+    }
+}
+
+fn to_lowercase(
+    c: char,
+    prev: Option<char>,
+    next: Option<char>,
+    language: Language,
+    out: &mut String,
+) {
+    match (language, c) {
+        // `I` followed by a combining dot above is a dotted `i`.
+        (Language::Turkic, 'I') if next == Some('\u{0307}') => out.push('i'),
+        (Language::Turkic, '\u{0307}') if prev == Some('I') => {}
+        (Language::Turkic, 'I') => out.push('\u{0131}'),
+        (Language::Turkic, '\u{0130}') => out.push('i'),
+        (_, '\u{03A3}') if is_final_sigma(prev, next) => out.push('\u{03C2}'),
+        _ => out.extend(c.to_lowercase()),
+    }
+}
+
+fn to_titlecase(c: char, language: Language, out: &mut String) {
+    let title = match c {
+        // Digraphs have dedicated titlecase forms.
+        '\u{01C4}' | '\u{01C5}' | '\u{01C6}' => '\u{01C5}',
+        '\u{01C7}' | '\u{01C8}' | '\u{01C9}' => '\u{01C8}',
+        '\u{01CA}' | '\u{01CB}' | '\u{01CC}' => '\u{01CB}',
+        '\u{01F1}' | '\u{01F2}' | '\u{01F3}' => '\u{01F2}',
+        '\u{00DF}' => {
+            out.push_str("Ss");
+            return;
+        }
+        _ => return to_uppercase(c, language, out),
+    };
+
+    out.push(title);
+}
+
+/// Checks that a character after `prev` starts a new word.
+///
+/// Marks and apostrophes don't split words, so `don't` is a single word.
+fn is_word_start(prev: Option<char>) -> bool {
+    match prev {
+        Some(c) => !(c.is_alphanumeric() || is_mark(c) || c == '\'' || c == '\u{2019}'),
+        None => true,
+    }
+}
+
+/// Checks that a capital sigma is at the end of a word.
+fn is_final_sigma(prev: Option<char>, next: Option<char>) -> bool {
+    prev.is_some_and(|c| c.is_alphabetic()) && !next.is_some_and(|c| c.is_alphabetic())
+}
+
+/// Checks for combining diacritical marks.
+fn is_mark(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE20..=0xFE2F
+    )
+}
+
+fn remove_tonos(c: char) -> char {
+    match c {
+        'ά' | 'Ά' => 'α',
+        'έ' | 'Έ' => 'ε',
+        'ή' | 'Ή' => 'η',
+        'ί' | 'Ί' => 'ι',
+        'ό' | 'Ό' => 'ο',
+        'ύ' | 'Ύ' => 'υ',
+        'ώ' | 'Ώ' => 'ω',
+        'ΐ' => 'ϊ',
+        'ΰ' => 'ϋ',
+        _ => c,
+    }
+}
+
+/// Maps ASCII characters to their full-width forms.
+fn to_full_width(c: char) -> char {
+    match c {
+        ' ' => '\u{3000}',
+        '!'..='~' => char::from_u32(c as u32 + 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
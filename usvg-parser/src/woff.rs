@@ -0,0 +1,722 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! [WOFF](https://www.w3.org/TR/WOFF/) and [WOFF2](https://www.w3.org/TR/WOFF2/)
+//! fonts decoding.
+//!
+//! Font collections inside WOFF2 are not supported.
+
+use std::convert::TryFrom;
+use std::io::Read;
+
+const WOFF_SIGNATURE: &[u8] = b"wOFF";
+const WOFF2_SIGNATURE: &[u8] = b"wOF2";
+
+/// Checks that data is a WOFF or a WOFF2 font.
+pub(crate) fn is_woff(data: &[u8]) -> bool {
+    data.starts_with(WOFF_SIGNATURE) || data.starts_with(WOFF2_SIGNATURE)
+}
+
+/// Decodes a WOFF or a WOFF2 font into a TrueType or an OpenType one.
+pub(crate) fn decode(data: &[u8]) -> Option<Vec<u8>> {
+    if data.starts_with(WOFF_SIGNATURE) {
+        decode_woff(data)
+    } else if data.starts_with(WOFF2_SIGNATURE) {
+        decode_woff2(data)
+    } else {
+        None
+    }
+}
+
+struct Table {
+    tag: [u8; 4],
+    data: Vec<u8>,
+}
+
+fn decode_woff(data: &[u8]) -> Option<Vec<u8>> {
+    let mut s = Stream::new(data);
+    s.skip(4); // signature
+    let flavor = s.read_u32()?;
+    s.skip(4); // length
+    let num_tables = s.read_u16()?;
+    // The rest of the header is not needed.
+    s.skip(2 + 4 + 2 + 2 + 4 * 5);
+
+    let mut tables = Vec::with_capacity(usize::from(num_tables));
+    for _ in 0..num_tables {
+        let tag = s.read_tag()?;
+        let offset = s.read_u32()? as usize;
+        let comp_length = s.read_u32()? as usize;
+        let orig_length = s.read_u32()? as usize;
+        s.skip(4); // origChecksum
+
+        let comp_data = data.get(offset..offset.checked_add(comp_length)?)?;
+        let table_data = if comp_length < orig_length {
+            // Do not preallocate, since the length comes from an untrusted header.
+            let mut table_data = Vec::new();
+            flate2::read::ZlibDecoder::new(comp_data)
+                .take(orig_length as u64)
+                .read_to_end(&mut table_data)
+                .ok()?;
+            table_data
+        } else {
+            comp_data.to_vec()
+        };
+
+        if table_data.len() != orig_length {
+            return None;
+        }
+
+        tables.push(Table {
+            tag,
+            data: table_data,
+        });
+    }
+
+    build_sfnt(flavor, tables)
+}
+
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+struct Woff2Entry {
+    tag: [u8; 4],
+    orig_length: usize,
+    /// The length of the table in the decompressed stream.
+    length: usize,
+    transformed: bool,
+}
+
+fn decode_woff2(data: &[u8]) -> Option<Vec<u8>> {
+    let mut s = Stream::new(data);
+    s.skip(4); // signature
+    let flavor = s.read_u32()?;
+    s.skip(4); // length
+    let num_tables = s.read_u16()?;
+    s.skip(2 + 4); // reserved, totalSfntSize
+    let compressed_size = s.read_u32()? as usize;
+    // The rest of the header is not needed.
+    s.skip(2 + 2 + 4 * 5);
+
+    if &flavor.to_be_bytes() == b"ttcf" {
+        log::warn!("WOFF2 font collections are not supported.");
+        return None;
+    }
+
+    let mut entries = Vec::with_capacity(usize::from(num_tables));
+    for _ in 0..num_tables {
+        let flags = s.read_u8()?;
+        let tag = match flags & 0x3F {
+            63 => s.read_tag()?,
+            idx => *KNOWN_TAGS[usize::from(idx)],
+        };
+
+        let transform_version = flags >> 6;
+        // `glyf` and `loca` use version 3 for the null transform, unlike other tables.
+        let transformed = if &tag == b"glyf" || &tag == b"loca" {
+            transform_version != 3
+        } else {
+            transform_version != 0
+        };
+
+        let orig_length = s.read_base128()? as usize;
+        let length = if transformed {
+            s.read_base128()? as usize
+        } else {
+            orig_length
+        };
+
+        entries.push(Woff2Entry {
+            tag,
+            orig_length,
+            length,
+            transformed,
+        });
+    }
+
+    let compressed = s.read_bytes(compressed_size)?;
+    let total_length = entries
+        .iter()
+        .try_fold(0usize, |acc, e| acc.checked_add(e.length))?;
+    let mut stream = Vec::new();
+    brotli_decompressor::Decompressor::new(compressed, 4096)
+        .take(total_length as u64)
+        .read_to_end(&mut stream)
+        .ok()?;
+    if stream.len() != total_length {
+        return None;
+    }
+
+    let mut offset = 0;
+    let mut raw_tables = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        raw_tables.push(&stream[offset..offset + entry.length]);
+        offset += entry.length;
+    }
+
+    let find = |tag: &[u8; 4]| entries.iter().position(|e| &e.tag == tag);
+
+    let mut tables = Vec::with_capacity(entries.len());
+
+    // `hmtx` depends on `glyf`, so it must be reconstructed first.
+    let mut x_mins = None;
+    if let Some(idx) = find(b"glyf").filter(|idx| entries[*idx].transformed) {
+        let (glyf, loca, glyph_x_mins) = reconstruct_glyf(raw_tables[idx])?;
+        let loca_entry = entries.get(find(b"loca")?)?;
+        if !loca_entry.transformed || loca.len() != loca_entry.orig_length {
+            return None;
+        }
+
+        tables.push(Table {
+            tag: *b"glyf",
+            data: glyf,
+        });
+        tables.push(Table {
+            tag: *b"loca",
+            data: loca,
+        });
+        x_mins = Some(glyph_x_mins);
+    }
+
+    for (entry, raw) in entries.iter().zip(&raw_tables) {
+        if !entry.transformed {
+            tables.push(Table {
+                tag: entry.tag,
+                data: raw.to_vec(),
+            });
+            continue;
+        }
+
+        match &entry.tag {
+            // Already reconstructed.
+            b"glyf" | b"loca" if x_mins.is_some() => {}
+            b"hmtx" => {
+                let hhea = raw_tables[find(b"hhea")?];
+                let num_h_metrics = Stream::new(hhea.get(34..36)?).read_u16()?;
+                let hmtx = reconstruct_hmtx(raw, num_h_metrics, x_mins.as_ref()?)?;
+                if hmtx.len() != entry.orig_length {
+                    return None;
+                }
+
+                tables.push(Table {
+                    tag: *b"hmtx",
+                    data: hmtx,
+                });
+            }
+            _ => return None,
+        }
+    }
+
+    build_sfnt(flavor, tables)
+}
+
+/// Reconstructs `glyf` and `loca` tables from a transformed `glyf` table.
+///
+/// Also returns `xMin` of each glyph, which is needed by the `hmtx` transform.
+fn reconstruct_glyf(data: &[u8]) -> Option<(Vec<u8>, Vec<u8>, Vec<i16>)> {
+    let mut s = Stream::new(data);
+    s.skip(2); // reserved
+    let option_flags = s.read_u16()?;
+    let num_glyphs = usize::from(s.read_u16()?);
+    let index_format = s.read_u16()?;
+
+    let mut sizes = [0usize; 7];
+    for size in &mut sizes {
+        *size = s.read_u32()? as usize;
+    }
+
+    let mut n_contours_stream = Stream::new(s.read_bytes(sizes[0])?);
+    let mut n_points_stream = Stream::new(s.read_bytes(sizes[1])?);
+    let mut flag_stream = Stream::new(s.read_bytes(sizes[2])?);
+    let mut glyph_stream = Stream::new(s.read_bytes(sizes[3])?);
+    let mut composite_stream = Stream::new(s.read_bytes(sizes[4])?);
+    let bbox_data = s.read_bytes(sizes[5])?;
+    let mut instruction_stream = Stream::new(s.read_bytes(sizes[6])?);
+
+    let bitmap_len = ((num_glyphs + 31) >> 5) << 2;
+    let bbox_bitmap = bbox_data.get(..bitmap_len)?;
+    let mut bbox_stream = Stream::new(&bbox_data[bitmap_len..]);
+
+    let overlap_bitmap = if option_flags & 1 != 0 {
+        Some(s.read_bytes((num_glyphs + 7) >> 3)?)
+    } else {
+        None
+    };
+
+    let is_bit_set = |bitmap: &[u8], idx: usize| bitmap[idx >> 3] & (0x80 >> (idx & 7)) != 0;
+
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(num_glyphs + 1);
+    let mut x_mins = Vec::with_capacity(num_glyphs);
+    for glyph_idx in 0..num_glyphs {
+        offsets.push(glyf.len());
+
+        let has_bbox = is_bit_set(bbox_bitmap, glyph_idx);
+        let n_contours = n_contours_stream.read_i16()?;
+        if n_contours == 0 {
+            // An empty glyph cannot have a bounding box.
+            if has_bbox {
+                return None;
+            }
+
+            x_mins.push(0);
+            continue;
+        }
+
+        let x_min;
+        if n_contours > 0 {
+            let mut end_points = Vec::with_capacity(n_contours as usize);
+            let mut n_points = 0usize;
+            for _ in 0..n_contours {
+                n_points = n_points.checked_add(usize::from(n_points_stream.read_u16_255()?))?;
+                end_points.push(u16::try_from(n_points.checked_sub(1)?).ok()?);
+            }
+
+            let flags = flag_stream.read_bytes(n_points)?;
+            let points = decode_triplets(flags, &mut glyph_stream)?;
+
+            let instructions_len = usize::from(glyph_stream.read_u16_255()?);
+            let instructions = instruction_stream.read_bytes(instructions_len)?;
+
+            let bbox = if has_bbox {
+                read_bbox(&mut bbox_stream)?
+            } else {
+                calc_bbox(&points)
+            };
+            x_min = bbox[0];
+
+            let overlap = overlap_bitmap.is_some_and(|bitmap| is_bit_set(bitmap, glyph_idx));
+
+            write_i16(&mut glyf, n_contours);
+            for v in &bbox {
+                write_i16(&mut glyf, *v);
+            }
+            for end_point in end_points {
+                write_u16(&mut glyf, end_point);
+            }
+            write_u16(&mut glyf, instructions_len as u16);
+            glyf.extend_from_slice(instructions);
+            write_simple_glyph_points(&points, overlap, &mut glyf);
+        } else {
+            let (components, has_instructions) = read_composite_glyph(&mut composite_stream)?;
+
+            // Composite glyphs must have an explicit bounding box.
+            if !has_bbox {
+                return None;
+            }
+            let bbox = read_bbox(&mut bbox_stream)?;
+            x_min = bbox[0];
+
+            write_i16(&mut glyf, -1);
+            for v in &bbox {
+                write_i16(&mut glyf, *v);
+            }
+            glyf.extend_from_slice(components);
+
+            if has_instructions {
+                let instructions_len = glyph_stream.read_u16_255()?;
+                write_u16(&mut glyf, instructions_len);
+                glyf.extend_from_slice(
+                    instruction_stream.read_bytes(usize::from(instructions_len))?,
+                );
+            }
+        }
+
+        x_mins.push(x_min);
+
+        // Glyphs must be aligned for the short `loca` format.
+        while glyf.len() % 4 != 0 {
+            glyf.push(0);
+        }
+    }
+    offsets.push(glyf.len());
+
+    let mut loca = Vec::new();
+    for offset in offsets {
+        if index_format == 0 {
+            write_u16(&mut loca, u16::try_from(offset / 2).ok()?);
+        } else {
+            write_u32(&mut loca, u32::try_from(offset).ok()?);
+        }
+    }
+
+    Some((glyf, loca, x_mins))
+}
+
+fn read_bbox(s: &mut Stream) -> Option<[i16; 4]> {
+    Some([s.read_i16()?, s.read_i16()?, s.read_i16()?, s.read_i16()?])
+}
+
+fn calc_bbox(points: &[(i32, i32, bool)]) -> [i16; 4] {
+    let mut bbox = [i16::MAX, i16::MAX, i16::MIN, i16::MIN];
+    for &(x, y, _) in points {
+        let (x, y) = (x as i16, y as i16);
+        bbox[0] = bbox[0].min(x);
+        bbox[1] = bbox[1].min(y);
+        bbox[2] = bbox[2].max(x);
+        bbox[3] = bbox[3].max(y);
+    }
+
+    if points.is_empty() {
+        [0; 4]
+    } else {
+        bbox
+    }
+}
+
+/// Decodes absolute points coordinates from the triplet encoding.
+fn decode_triplets(flags: &[u8], s: &mut Stream) -> Option<Vec<(i32, i32, bool)>> {
+    fn with_sign(flag: u8, value: i32) -> i32 {
+        if flag & 1 != 0 {
+            value
+        } else {
+            -value
+        }
+    }
+
+    let mut points = Vec::with_capacity(flags.len());
+    let (mut x, mut y) = (0i32, 0i32);
+    for &flag in flags {
+        let on_curve = flag & 0x80 == 0;
+        let flag = flag & 0x7F;
+
+        let (dx, dy) = if flag < 10 {
+            let b0 = i32::from(s.read_u8()?);
+            (0, with_sign(flag, (i32::from(flag & 14) << 7) + b0))
+        } else if flag < 20 {
+            let b0 = i32::from(s.read_u8()?);
+            (with_sign(flag, (i32::from((flag - 10) & 14) << 7) + b0), 0)
+        } else if flag < 84 {
+            let b0 = i32::from(flag - 20);
+            let b1 = i32::from(s.read_u8()?);
+            (
+                with_sign(flag, 1 + (b0 & 0x30) + (b1 >> 4)),
+                with_sign(flag >> 1, 1 + ((b0 & 0x0C) << 2) + (b1 & 0x0F)),
+            )
+        } else if flag < 120 {
+            let b0 = i32::from(flag - 84);
+            let b1 = i32::from(s.read_u8()?);
+            let b2 = i32::from(s.read_u8()?);
+            (
+                with_sign(flag, 1 + ((b0 / 12) << 8) + b1),
+                with_sign(flag >> 1, 1 + (((b0 % 12) >> 2) << 8) + b2),
+            )
+        } else if flag < 124 {
+            let b1 = i32::from(s.read_u8()?);
+            let b2 = i32::from(s.read_u8()?);
+            let b3 = i32::from(s.read_u8()?);
+            (
+                with_sign(flag, (b1 << 4) + (b2 >> 4)),
+                with_sign(flag >> 1, ((b2 & 0x0F) << 8) + b3),
+            )
+        } else {
+            let b1 = i32::from(s.read_u8()?);
+            let b2 = i32::from(s.read_u8()?);
+            let b3 = i32::from(s.read_u8()?);
+            let b4 = i32::from(s.read_u8()?);
+            (
+                with_sign(flag, (b1 << 8) + b2),
+                with_sign(flag >> 1, (b3 << 8) + b4),
+            )
+        };
+
+        x += dx;
+        y += dy;
+        points.push((x, y, on_curve));
+    }
+
+    Some(points)
+}
+
+/// Writes flags and coordinates of a simple glyph.
+fn write_simple_glyph_points(points: &[(i32, i32, bool)], overlap: bool, glyf: &mut Vec<u8>) {
+    const ON_CURVE_POINT: u8 = 0x01;
+    const X_SHORT_VECTOR: u8 = 0x02;
+    const Y_SHORT_VECTOR: u8 = 0x04;
+    const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+    const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+    const OVERLAP_SIMPLE: u8 = 0x40;
+
+    let mut flags = Vec::with_capacity(points.len());
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let (mut prev_x, mut prev_y) = (0, 0);
+    for (i, &(x, y, on_curve)) in points.iter().enumerate() {
+        let mut flag = if on_curve { ON_CURVE_POINT } else { 0 };
+        if i == 0 && overlap {
+            flag |= OVERLAP_SIMPLE;
+        }
+
+        let dx = x - prev_x;
+        if dx == 0 {
+            flag |= X_IS_SAME_OR_POSITIVE;
+        } else if dx.abs() < 256 {
+            flag |= X_SHORT_VECTOR;
+            if dx > 0 {
+                flag |= X_IS_SAME_OR_POSITIVE;
+            }
+            xs.push(dx.unsigned_abs() as u8);
+        } else {
+            write_i16(&mut xs, dx as i16);
+        }
+
+        let dy = y - prev_y;
+        if dy == 0 {
+            flag |= Y_IS_SAME_OR_POSITIVE;
+        } else if dy.abs() < 256 {
+            flag |= Y_SHORT_VECTOR;
+            if dy > 0 {
+                flag |= Y_IS_SAME_OR_POSITIVE;
+            }
+            ys.push(dy.unsigned_abs() as u8);
+        } else {
+            write_i16(&mut ys, dy as i16);
+        }
+
+        flags.push(flag);
+        prev_x = x;
+        prev_y = y;
+    }
+
+    glyf.extend_from_slice(&flags);
+    glyf.extend_from_slice(&xs);
+    glyf.extend_from_slice(&ys);
+}
+
+/// Reads composite glyph components, which are stored as is.
+///
+/// Returns the components data and whether the glyph has instructions.
+fn read_composite_glyph<'a>(s: &mut Stream<'a>) -> Option<(&'a [u8], bool)> {
+    const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+    const WE_HAVE_A_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+    const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+    const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+    let start = s.offset;
+    let mut has_instructions = false;
+    loop {
+        let flags = s.read_u16()?;
+        has_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+
+        let mut len = 2; // glyphIndex
+        len += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            len += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            len += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            len += 8;
+        }
+        s.read_bytes(len)?;
+
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+
+    Some((&s.data[start..s.offset], has_instructions))
+}
+
+/// Reconstructs an `hmtx` table from a transformed one.
+fn reconstruct_hmtx(data: &[u8], num_h_metrics: u16, x_mins: &[i16]) -> Option<Vec<u8>> {
+    let num_h_metrics = usize::from(num_h_metrics);
+    let num_glyphs = x_mins.len();
+    if num_h_metrics == 0 || num_h_metrics > num_glyphs {
+        return None;
+    }
+
+    let mut s = Stream::new(data);
+    let flags = s.read_u8()?;
+    let has_proportional_lsbs = flags & 1 == 0;
+    let has_monospace_lsbs = flags & 2 == 0;
+
+    let mut advances = Vec::with_capacity(num_h_metrics);
+    for _ in 0..num_h_metrics {
+        advances.push(s.read_u16()?);
+    }
+
+    let mut lsbs = Vec::with_capacity(num_glyphs);
+    for (i, x_min) in x_mins.iter().enumerate() {
+        let is_present = if i < num_h_metrics {
+            has_proportional_lsbs
+        } else {
+            has_monospace_lsbs
+        };
+
+        lsbs.push(if is_present { s.read_i16()? } else { *x_min });
+    }
+
+    let mut hmtx = Vec::with_capacity(num_h_metrics * 4 + (num_glyphs - num_h_metrics) * 2);
+    for (i, lsb) in lsbs.into_iter().enumerate() {
+        if i < num_h_metrics {
+            write_u16(&mut hmtx, advances[i]);
+        }
+        write_i16(&mut hmtx, lsb);
+    }
+
+    Some(hmtx)
+}
+
+/// Builds a TrueType or an OpenType font from tables.
+///
+/// Returns `None` when tables cannot be represented by the sfnt header.
+fn build_sfnt(flavor: u32, mut tables: Vec<Table>) -> Option<Vec<u8>> {
+    tables.sort_by_key(|t| t.tag);
+
+    // `searchRange` is `2^entrySelector * 16` and must fit into u16.
+    let num_tables = u32::try_from(tables.len()).ok()?;
+    if num_tables >= 4096 {
+        return None;
+    }
+
+    let entry_selector = if num_tables == 0 {
+        0
+    } else {
+        31 - num_tables.leading_zeros()
+    };
+    let search_range = (1u32 << entry_selector) * 16;
+    let range_shift = num_tables * 16 - search_range;
+
+    let mut font = Vec::new();
+    write_u32(&mut font, flavor);
+    write_u16(&mut font, num_tables as u16);
+    write_u16(&mut font, search_range as u16);
+    write_u16(&mut font, entry_selector as u16);
+    write_u16(&mut font, range_shift as u16);
+
+    let mut offset = 12 + 16 * num_tables;
+    for table in &tables {
+        let length = u32::try_from(table.data.len()).ok()?;
+        font.extend_from_slice(&table.tag);
+        write_u32(&mut font, checksum(&table.data));
+        write_u32(&mut font, offset);
+        write_u32(&mut font, length);
+        offset = offset.checked_add(length.checked_add(3)? & !3)?;
+    }
+
+    for table in &tables {
+        font.extend_from_slice(&table.data);
+        while font.len() % 4 != 0 {
+            font.push(0);
+        }
+    }
+
+    Some(font)
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn write_u16(buf: &mut Vec<u8>, n: u16) {
+    buf.extend_from_slice(&n.to_be_bytes());
+}
+
+fn write_i16(buf: &mut Vec<u8>, n: i16) {
+    buf.extend_from_slice(&n.to_be_bytes());
+}
+
+fn write_u32(buf: &mut Vec<u8>, n: u32) {
+    buf.extend_from_slice(&n.to_be_bytes());
+}
+
+struct Stream<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Stream<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Stream { data, offset: 0 }
+    }
+
+    fn skip(&mut self, len: usize) {
+        self.offset += len;
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.offset.checked_add(len)?;
+        let bytes = self.data.get(self.offset..end)?;
+        self.offset = end;
+        Some(bytes)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        Some(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Option<u16> {
+        let bytes = self.read_bytes(2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_i16(&mut self) -> Option<i16> {
+        Some(self.read_u16()? as i16)
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        let bytes = self.read_bytes(4)?;
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_tag(&mut self) -> Option<[u8; 4]> {
+        Some(self.read_u32()?.to_be_bytes())
+    }
+
+    /// Reads a `UIntBase128` value.
+    fn read_base128(&mut self) -> Option<u32> {
+        let mut value = 0u32;
+        for i in 0..5 {
+            let byte = self.read_u8()?;
+            // Leading zeros are not allowed.
+            if i == 0 && byte == 0x80 {
+                return None;
+            }
+
+            // Overflow check.
+            if value & 0xFE00_0000 != 0 {
+                return None;
+            }
+
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    /// Reads a `255UInt16` value.
+    fn read_u16_255(&mut self) -> Option<u16> {
+        const WORD_CODE: u8 = 253;
+        const ONE_MORE_BYTE_CODE_2: u8 = 254;
+        const ONE_MORE_BYTE_CODE_1: u8 = 255;
+        const LOWEST_U_CODE: u16 = 253;
+
+        match self.read_u8()? {
+            WORD_CODE => self.read_u16(),
+            ONE_MORE_BYTE_CODE_1 => Some(u16::from(self.read_u8()?) + LOWEST_U_CODE),
+            ONE_MORE_BYTE_CODE_2 => Some(u16::from(self.read_u8()?) + LOWEST_U_CODE * 2),
+            code => Some(u16::from(code)),
+        }
+    }
+}
@@ -97,3 +97,432 @@ fn invalid_size_1() {
     let result = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default());
     assert!(result.is_err());
 }
+
+fn first_path_fill(tree: &usvg_tree::Tree) -> Option<usvg_tree::Color> {
+    let node = tree.root.first_child()?;
+    let kind = node.borrow();
+    match *kind {
+        usvg_tree::NodeKind::Path(ref path) => match path.fill.as_ref()?.paint {
+            usvg_tree::Paint::Color(c) => Some(c),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[test]
+fn user_style_sheet_author() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <style>.accent { fill: red }</style>
+        <rect class='accent' fill='blue' width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        style_sheet: vec![".accent { fill: green }".to_string()],
+        ..usvg_parser::Options::default()
+    };
+
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn user_style_sheet_user_agent() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect fill='blue' width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        style_sheet: vec!["rect { fill: green }".to_string()],
+        style_sheet_origin: usvg_parser::StyleSheetOrigin::UserAgent,
+        ..usvg_parser::Options::default()
+    };
+
+    // Presentation attributes have a higher priority than user agent styles.
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 0, 255))
+    );
+}
+
+#[test]
+fn user_style_sheet_important() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <style>rect { fill: red !important }</style>
+        <rect style='fill: blue !important' width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        style_sheet: vec!["rect { fill: green !important }".to_string()],
+        style_sheet_origin: usvg_parser::StyleSheetOrigin::UserAgent,
+        ..usvg_parser::Options::default()
+    };
+
+    // Important user agent declarations override everything.
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn dark_color_scheme() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <style>
+            rect { fill: red }
+            @media (prefers-color-scheme: dark) {
+                rect { fill: green }
+            }
+        </style>
+        <rect width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        color_scheme: usvg_parser::ColorScheme::Dark,
+        ..usvg_parser::Options::default()
+    };
+
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn custom_properties() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect style='fill: var(--icon-primary, red)' width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        custom_properties: vec![("--icon-primary".to_string(), "green".to_string())],
+        ..usvg_parser::Options::default()
+    };
+
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn custom_properties_override() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1' style='--icon-primary: green'>
+        <rect style='fill: var(--icon-primary)' width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        custom_properties: vec![("--icon-primary".to_string(), "red".to_string())],
+        ..usvg_parser::Options::default()
+    };
+
+    // Properties declared inside the document take precedence.
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn chained_custom_properties() {
+    // Each property doubles the previous one, so a naive expansion would be exponential.
+    let mut vars = String::from("--v0: red;");
+    for i in 1..=28 {
+        vars.push_str(&format!("--v{}: var(--v{1}) var(--v{1});", i, i - 1));
+    }
+
+    let svg = format!(
+        "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1' style='{}'>
+        <rect style='fill: var(--v28, green)' width='10' height='10'/>
+    </svg>
+    ",
+        vars
+    );
+
+    // A too long value is invalid, therefore the fallback is used.
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn inherited_chained_custom_properties() {
+    // Each group doubles a property from its parent.
+    let depth = 40;
+    let mut groups = String::new();
+    for i in 0..depth {
+        groups.push_str(&format!(
+            "<g style='--v{}: var(--v{1}) var(--v{1})'>",
+            i % 2,
+            (i + 1) % 2
+        ));
+    }
+
+    let svg = format!(
+        "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1' style='--v1: red'>
+        {}<rect style='fill: var(--v1, green)' width='10' height='10'/>{}
+    </svg>
+    ",
+        groups,
+        "</g>".repeat(depth)
+    );
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    let fill = tree
+        .root
+        .descendants()
+        .find_map(|node| match *node.borrow() {
+            usvg_tree::NodeKind::Path(ref path) => path.fill.clone(),
+            _ => None,
+        });
+    assert_eq!(
+        fill.map(|fill| fill.paint),
+        Some(usvg_tree::Paint::Color(usvg_tree::Color::new_rgb(
+            0, 128, 0
+        )))
+    );
+}
+
+#[test]
+fn current_color() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect fill='currentColor' width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        color: Some("green".to_string()),
+        ..usvg_parser::Options::default()
+    };
+
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn current_color_override() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1' color='green'>
+        <rect fill='currentColor' width='10' height='10'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        color: Some("red".to_string()),
+        ..usvg_parser::Options::default()
+    };
+
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn recolor() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <filter id='filter1'>
+            <feFlood flood-color='black'/>
+        </filter>
+        <rect fill='black' filter='url(#filter1)' width='10' height='10'/>
+    </svg>
+    ";
+
+    let mut tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    tree.recolor(|c| {
+        if c == usvg_tree::Color::black() {
+            usvg_tree::Color::new_rgb(0, 128, 0)
+        } else {
+            c
+        }
+    });
+
+    let group = tree.root.first_child().unwrap();
+    match *group.borrow() {
+        usvg_tree::NodeKind::Group(ref g) => match g.filters[0].primitives[0].kind {
+            usvg_tree::filter::Kind::Flood(ref fe) => {
+                assert_eq!(fe.color, usvg_tree::Color::new_rgb(0, 128, 0));
+            }
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+
+    let path = group.first_child().unwrap();
+    match *path.borrow() {
+        usvg_tree::NodeKind::Path(ref path) => match path.fill.as_ref().unwrap().paint {
+            usvg_tree::Paint::Color(c) => assert_eq!(c, usvg_tree::Color::new_rgb(0, 128, 0)),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+}
+
+#[test]
+fn recolor_shared_gradient() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='black'/>
+            <stop offset='1' stop-color='white'/>
+        </linearGradient>
+        <rect fill='url(#lg1)' width='10' height='10'/>
+        <rect fill='url(#lg1)' width='10' height='10'/>
+    </svg>
+    ";
+
+    let mut tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+
+    let mut calls = 0;
+    tree.recolor(|c| {
+        calls += 1;
+        usvg_tree::Color::new_rgb(255 - c.red, 255 - c.green, 255 - c.blue)
+    });
+
+    // Each stop must be processed only once.
+    assert_eq!(calls, 2);
+
+    let mut gradients = Vec::new();
+    tree.paint_servers(|paint| {
+        if let usvg_tree::Paint::LinearGradient(ref lg) = paint {
+            gradients.push(lg.clone());
+        }
+    });
+
+    assert_eq!(gradients.len(), 2);
+    assert!(std::rc::Rc::ptr_eq(&gradients[0], &gradients[1]));
+    assert_eq!(gradients[0].stops[0].color, usvg_tree::Color::white());
+    assert_eq!(gradients[0].stops[1].color, usvg_tree::Color::black());
+}
+
+#[test]
+fn specified_color() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect fill='lab(46.28 -47.55 48.59)' width='10' height='10'/>
+    </svg>
+    ";
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    assert_eq!(
+        first_path_fill(&tree),
+        Some(usvg_tree::Color::new_rgb(0, 128, 0))
+    );
+    assert!(tree.diagnostics.is_empty());
+
+    let path = tree.root.first_child().unwrap();
+    match *path.borrow() {
+        usvg_tree::NodeKind::Path(ref path) => {
+            let color = path.fill.as_ref().unwrap().specified_color.unwrap();
+            assert_eq!(color.space, usvg_tree::ColorSpace::Lab);
+            assert_eq!(color.components, [46.28, -47.55, 48.59]);
+        }
+        _ => unreachable!(),
+    };
+}
+
+#[test]
+fn out_of_gamut_color() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect id='rect1' fill='color(display-p3 0 1 0)' width='10' height='10'/>
+    </svg>
+    ";
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    let fill = first_path_fill(&tree).unwrap();
+    assert_eq!(tree.diagnostics.len(), 1);
+    match tree.diagnostics[0] {
+        usvg_tree::Diagnostic::ColorOutOfGamut {
+            ref element_id,
+            ref color,
+            mapped,
+        } => {
+            assert_eq!(element_id, "rect1");
+            assert_eq!(color.space, usvg_tree::ColorSpace::DisplayP3);
+            assert_eq!(mapped, fill);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn woff_with_too_many_tables() {
+    // A WOFF header with 4096 empty tables, which cannot be represented by an sfnt header.
+    let mut font = b"wOFF\x00\x01\x00\x00".to_vec();
+    font.extend_from_slice(&[0; 4]); // length
+    font.extend_from_slice(&4096u16.to_be_bytes());
+    font.extend_from_slice(&[0; 2 + 4 + 2 + 2 + 4 * 5]);
+    font.extend_from_slice(&[0; 20 * 4096]);
+
+    let url: String = font.iter().map(|b| format!("%{:02X}", b)).collect();
+    let svg = format!(
+        "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <style>
+            @font-face {{ font-family: 'My Font'; src: url(data:font/woff,{}); }}
+        </style>
+    </svg>
+    ",
+        url
+    );
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    assert!(tree.font_faces.is_empty());
+}
+
+#[test]
+fn text_transform_removed_char_position() {
+    // A dot above is removed by the Turkish lowercase mapping,
+    // so its position must be applied to the next character.
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+        <text x='10 50' y='20' dx='0 3 4' text-transform='lowercase' xml:lang='tr'>I&#x307;A</text>
+    </svg>
+    ";
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    let node = tree.root.first_child().unwrap();
+    let text = match *node.borrow() {
+        usvg_tree::NodeKind::Text(ref text) => text.clone(),
+        _ => unreachable!(),
+    };
+
+    assert_eq!(text.chunks.len(), 2);
+    assert_eq!(text.chunks[0].text, "i");
+    assert_eq!(text.chunks[1].text, "a");
+    assert_eq!(text.chunks[1].x, Some(50.0));
+    assert_eq!(text.positions.len(), 2);
+    assert_eq!(text.positions[1].x, Some(50.0));
+    assert_eq!(text.positions[1].dx, Some(7.0));
+}
@@ -0,0 +1,434 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A font data, metrics, character coverage, shaping and glyph outlines cache
+//! that can be reused between conversions.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use fontdb::{Database, ID};
+use rustybuzz::ttf_parser;
+use usvg_tree::{FontVariation, PathData};
+
+use crate::{GlyphId, ResolvedFont, ShapedGlyph, ShapingRequest};
+
+/// The default memory limit in bytes.
+const DEFAULT_MEMORY_LIMIT: usize = 32 * 1024 * 1024;
+
+/// Font data shared with the fonts database or loaded from a file.
+type FaceData = (Arc<dyn AsRef<[u8]> + Send + Sync>, u32);
+
+/// A font data, metrics, character coverage, shaping and glyph outlines cache.
+///
+/// Text conversion loads font data, resolves metrics, checks which characters
+/// a font supports, shapes text runs and outlines each glyph again on every call.
+/// When many documents are converted in one process, pass the same cache via
+/// [`TextConversionOptions::cache`](crate::TextConversionOptions::cache)
+/// to reuse them.
+///
+/// Font data is loaded once per face. Faces from `Source::Binary` share data
+/// with the database, while faces from files are read into memory.
+/// Font faces are parsed from the loaded data only when a text run or a glyph is not cached.
+///
+/// Shaping results are keyed by a font ID, variation coordinates, a text run,
+/// its direction and language and OpenType features.
+/// Outlines are keyed by a font ID, variation coordinates and a glyph ID.
+/// Font IDs are unique only within a [`fontdb::Database`], therefore a cache must be used
+/// with a single database and cleared after faces were removed from it.
+/// Document fonts, like `@font-face` and SVG fonts, are never cached.
+///
+/// Shaping results and outlines take at most `memory_limit` bytes, half of it each.
+/// Entries that were not used recently are evicted first.
+/// Font data, face metrics and character coverage are kept until the cache is cleared.
+///
+/// The cache is updated through a shared reference, but it cannot be shared between threads.
+pub struct GlyphCache {
+    data: RefCell<CacheData>,
+}
+
+#[derive(Default)]
+struct CacheData {
+    memory_limit: usize,
+    data: HashMap<ID, Option<FaceData>>,
+    faces: HashMap<ID, Option<CachedFace>>,
+    /// Characters that are present in a face. Doesn't depend on variations.
+    coverage: HashMap<ID, HashMap<char, bool>>,
+    runs: Generations<RunKey, Option<Rc<[ShapedGlyph]>>>,
+    outlines: Generations<OutlineKey, Option<PathData>>,
+}
+
+#[derive(Clone, Copy)]
+struct CachedFace {
+    font: ResolvedFont,
+    is_variable: bool,
+}
+
+/// Variation tags and values bits. Empty for non-variable fonts.
+type VariationsKey = Vec<([u8; 4], u32)>;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct RunKey {
+    font: ID,
+    variations: VariationsKey,
+    text: String,
+    direction: rustybuzz::Direction,
+    language: Option<String>,
+    /// Feature tags and values. Features are always applied to the whole run.
+    features: Vec<(u32, u32)>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct OutlineKey {
+    font: ID,
+    glyph_id: GlyphId,
+    variations: VariationsKey,
+}
+
+/// Entries split into two generations.
+///
+/// When the recent generation reaches the limit, it replaces the old one,
+/// and entries from the old generation are dropped.
+/// Entries that are used from the old generation are moved back to the recent one.
+struct Generations<K, V> {
+    recent: Generation<K, V>,
+    old: Generation<K, V>,
+}
+
+struct Generation<K, V> {
+    /// Entries with their approximate memory size.
+    entries: HashMap<K, (V, usize)>,
+    size: usize,
+}
+
+impl GlyphCache {
+    /// Creates a new cache with a 32 MiB memory limit.
+    pub fn new() -> Self {
+        Self::with_memory_limit(DEFAULT_MEMORY_LIMIT)
+    }
+
+    /// Creates a new cache with a shaping results and outlines memory limit in bytes.
+    pub fn with_memory_limit(memory_limit: usize) -> Self {
+        GlyphCache {
+            data: RefCell::new(CacheData {
+                memory_limit,
+                ..CacheData::default()
+            }),
+        }
+    }
+
+    /// Returns an approximate size of the cached shaping results and outlines in bytes.
+    pub fn memory_usage(&self) -> usize {
+        let data = self.data.borrow();
+        data.runs.size() + data.outlines.size()
+    }
+
+    /// Returns the number of cached outlines.
+    pub fn outlines_count(&self) -> usize {
+        self.data.borrow().outlines.len()
+    }
+
+    /// Returns the number of cached shaping results.
+    pub fn runs_count(&self) -> usize {
+        self.data.borrow().runs.len()
+    }
+
+    /// Removes all cached data.
+    pub fn clear(&mut self) {
+        let data = self.data.get_mut();
+        data.data.clear();
+        data.faces.clear();
+        data.coverage.clear();
+        data.runs = Generations::default();
+        data.outlines = Generations::default();
+    }
+
+    pub(crate) fn load_font(&self, fontdb: &Database, id: ID) -> Option<ResolvedFont> {
+        self.face(fontdb, id).map(|face| face.font)
+    }
+
+    /// Checks that a face has all the characters.
+    pub(crate) fn has_chars(&self, fontdb: &Database, id: ID, chars: &[char]) -> bool {
+        let missing: Vec<_> = {
+            let data = self.data.borrow();
+            let coverage = data.coverage.get(&id);
+            chars
+                .iter()
+                .copied()
+                .filter(|c| coverage.map(|coverage| coverage.contains_key(c)) != Some(true))
+                .collect()
+        };
+
+        if !missing.is_empty() {
+            // A face that cannot be parsed doesn't have any characters.
+            let res = self
+                .with_face_data(fontdb, id, |data, face_index| {
+                    crate::check_chars(data, face_index, &missing)
+                })
+                .flatten()
+                .unwrap_or_else(|| vec![false; missing.len()]);
+
+            let mut data = self.data.borrow_mut();
+            let coverage = data.coverage.entry(id).or_default();
+            coverage.extend(missing.into_iter().zip(res));
+        }
+
+        let data = self.data.borrow();
+        match data.coverage.get(&id) {
+            Some(coverage) => chars.iter().all(|c| coverage[c]),
+            None => true,
+        }
+    }
+
+    pub(crate) fn shape(
+        &self,
+        fontdb: &Database,
+        id: ID,
+        request: &ShapingRequest,
+    ) -> Option<Rc<[ShapedGlyph]>> {
+        let is_variable = self.face(fontdb, id)?.is_variable;
+
+        let key = RunKey {
+            font: id,
+            variations: if is_variable {
+                request
+                    .variations
+                    .iter()
+                    .map(|v| (v.tag.0.to_be_bytes(), v.value.to_bits()))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            text: request.text.to_string(),
+            direction: request.direction,
+            language: request.language.map(|s| s.to_string()),
+            features: request
+                .features
+                .iter()
+                .map(|feature| (feature.tag.0, feature.value))
+                .collect(),
+        };
+
+        let limit = self.data.borrow().memory_limit / 2;
+        if let Some(glyphs) = self.data.borrow_mut().runs.get(&key, limit) {
+            return glyphs;
+        }
+
+        let glyphs = self
+            .with_face_data(fontdb, id, |data, face_index| {
+                crate::shape_face(data, face_index, request)
+            })
+            .flatten()
+            .map(Rc::from);
+
+        let size = run_entry_size(&key, &glyphs);
+        self.data
+            .borrow_mut()
+            .runs
+            .insert(key, glyphs.clone(), size, limit);
+        glyphs
+    }
+
+    pub(crate) fn outline(
+        &self,
+        fontdb: &Database,
+        id: ID,
+        glyph_id: GlyphId,
+        variations: &[FontVariation],
+    ) -> Option<PathData> {
+        let is_variable = self.face(fontdb, id)?.is_variable;
+
+        let key = OutlineKey {
+            font: id,
+            glyph_id,
+            variations: if is_variable {
+                variations
+                    .iter()
+                    .map(|v| (v.tag, v.value.to_bits()))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+        };
+
+        let limit = self.data.borrow().memory_limit / 2;
+        if let Some(path) = self.data.borrow_mut().outlines.get(&key, limit) {
+            return path;
+        }
+
+        let path = self
+            .with_face_data(fontdb, id, |data, face_index| {
+                crate::outline_glyph(data, face_index, glyph_id, variations)
+            })
+            .flatten();
+
+        let size = outline_entry_size(&key, &path);
+        self.data
+            .borrow_mut()
+            .outlines
+            .insert(key, path.clone(), size, limit);
+        path
+    }
+
+    fn face(&self, fontdb: &Database, id: ID) -> Option<CachedFace> {
+        if let Some(face) = self.data.borrow().faces.get(&id) {
+            return *face;
+        }
+
+        let face = self
+            .with_face_data(fontdb, id, |data, face_index| {
+                let font = crate::parse_font_metrics(id, data, face_index)?;
+                let is_variable = ttf_parser::Face::parse(data, face_index)
+                    .map(|face| face.is_variable())
+                    .unwrap_or(false);
+                Some(CachedFace { font, is_variable })
+            })
+            .flatten();
+        self.data.borrow_mut().faces.insert(id, face);
+        face
+    }
+
+    /// Executes a closure with cached font data.
+    ///
+    /// Returns `None` when font data cannot be loaded.
+    fn with_face_data<P, T>(&self, fontdb: &Database, id: ID, p: P) -> Option<T>
+    where
+        P: FnOnce(&[u8], u32) -> T,
+    {
+        let cached = self.data.borrow().data.get(&id).cloned();
+        let face_data = match cached {
+            Some(face_data) => face_data,
+            None => {
+                let face_data = load_face_data(fontdb, id);
+                self.data.borrow_mut().data.insert(id, face_data.clone());
+                face_data
+            }
+        };
+
+        // The cache is not borrowed while the closure is running.
+        let (data, face_index) = face_data?;
+        Some(p((*data).as_ref(), face_index))
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Generations<K, V> {
+    /// Returns a cached value.
+    ///
+    /// A value from the old generation is moved to the recent one.
+    fn get(&mut self, key: &K, limit: usize) -> Option<V> {
+        if let Some((value, _)) = self.recent.entries.get(key) {
+            return Some(value.clone());
+        }
+
+        let (key, (value, size)) = self.old.entries.remove_entry(key)?;
+        self.old.size -= size;
+        self.insert(key, value.clone(), size, limit);
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V, size: usize, limit: usize) {
+        if self.recent.size + size > limit / 2 {
+            self.old = std::mem::take(&mut self.recent);
+        }
+
+        // An entry that doesn't fit even into an empty generation is not cached.
+        if size <= limit / 2 {
+            self.recent.size += size;
+            self.recent.entries.insert(key, (value, size));
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.recent.size + self.old.size
+    }
+
+    fn len(&self) -> usize {
+        self.recent.entries.len() + self.old.entries.len()
+    }
+}
+
+impl<K, V> Default for Generations<K, V> {
+    fn default() -> Self {
+        Generations {
+            recent: Generation::default(),
+            old: Generation::default(),
+        }
+    }
+}
+
+impl<K, V> Default for Generation<K, V> {
+    fn default() -> Self {
+        Generation {
+            entries: HashMap::new(),
+            size: 0,
+        }
+    }
+}
+
+impl Default for GlyphCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for GlyphCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let data = self.data.borrow();
+        f.debug_struct("GlyphCache")
+            .field("memory_limit", &data.memory_limit)
+            .field("memory_usage", &self.memory_usage())
+            .field("faces", &data.faces.len())
+            .field(
+                "coverage",
+                &data.coverage.values().map(|c| c.len()).sum::<usize>(),
+            )
+            .field("runs", &data.runs.len())
+            .field("outlines", &data.outlines.len())
+            .finish()
+    }
+}
+
+/// Loads face data.
+///
+/// Binary sources are shared with the database. Other sources are copied,
+/// since they are backed by files that would be read again on each access otherwise.
+fn load_face_data(fontdb: &Database, id: ID) -> Option<FaceData> {
+    match fontdb.face_source(id)? {
+        (fontdb::Source::Binary(data), face_index) => Some((data, face_index)),
+        _ => fontdb.with_face_data(id, |data, face_index| {
+            let data: Arc<dyn AsRef<[u8]> + Send + Sync> = Arc::new(data.to_vec());
+            (data, face_index)
+        }),
+    }
+}
+
+/// Returns an approximate memory size of a shaping result entry.
+fn run_entry_size(key: &RunKey, glyphs: &Option<Rc<[ShapedGlyph]>>) -> usize {
+    let glyphs_size = glyphs
+        .as_ref()
+        .map_or(0, |glyphs| std::mem::size_of_val(&**glyphs));
+
+    std::mem::size_of::<RunKey>()
+        + key.variations.len() * std::mem::size_of::<([u8; 4], u32)>()
+        + key.text.len()
+        + key.language.as_ref().map_or(0, |s| s.len())
+        + key.features.len() * std::mem::size_of::<(u32, u32)>()
+        + std::mem::size_of::<Option<Rc<[ShapedGlyph]>>>()
+        + glyphs_size
+}
+
+/// Returns an approximate memory size of an outline entry.
+fn outline_entry_size(key: &OutlineKey, path: &Option<PathData>) -> usize {
+    let path_size = path.as_ref().map_or(0, |path| {
+        std::mem::size_of_val(path.commands()) + std::mem::size_of_val(path.points())
+    });
+
+    std::mem::size_of::<OutlineKey>()
+        + key.variations.len() * std::mem::size_of::<([u8; 4], u32)>()
+        + std::mem::size_of::<Option<PathData>>()
+        + path_size
+}
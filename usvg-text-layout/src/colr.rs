@@ -0,0 +1,792 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `COLR` and `CPAL` tables support.
+//!
+//! `ttf-parser` doesn't support color glyphs yet, so we have to parse tables manually.
+//! Both `COLR` versions are supported, but variable paints use their default values.
+
+use std::rc::Rc;
+
+use rustybuzz::ttf_parser;
+use usvg_tree::*;
+
+use crate::{IdGenerator, PathBuilder};
+
+/// A palette index that references the text foreground color.
+const FOREGROUND_INDEX: u16 = 0xFFFF;
+
+/// Limits nested paints.
+const MAX_DEPTH: u8 = 64;
+
+/// Limits the number of paints per glyph.
+///
+/// Paints can be shared, so a small table can describe an exponentially large graph.
+/// Each paint emits only a few nodes, so this limits the number of nodes as well.
+const MAX_PAINTS: u32 = 10_000;
+
+/// Converts a `COLR` glyph into nodes.
+///
+/// Returns a group in font units with the Y axis pointing up, just like glyph outlines.
+/// `foreground` is used for layers that reference the text color.
+/// Layers are skipped when it's `None`.
+pub(crate) fn convert_glyph(
+    face: &ttf_parser::Face,
+    glyph_id: ttf_parser::GlyphId,
+    foreground: Option<&Fill>,
+    ids: &mut IdGenerator,
+) -> Option<Node> {
+    let tag = ttf_parser::Tag::from_bytes;
+    let colr = Colr::parse(face.raw_face().table(tag(b"COLR"))?)?;
+    let cpal = face.raw_face().table(tag(b"CPAL")).and_then(Cpal::parse);
+
+    let mut painter = Painter {
+        face,
+        colr,
+        cpal,
+        foreground,
+        ids,
+        active_glyphs: vec![glyph_id],
+        active_layers: Vec::new(),
+        paints: 0,
+    };
+
+    let group = Node::new(NodeKind::Group(Group::default()));
+    if let Some(offset) = painter.colr.base_paint(glyph_id) {
+        painter.paint(offset, &group, 0)?;
+
+        // Do not render a partially painted glyph.
+        if painter.paints >= MAX_PAINTS {
+            return None;
+        }
+    } else {
+        let (first, count) = painter.colr.base_layers(glyph_id)?;
+        for i in first..first.checked_add(count)? {
+            let (layer_glyph, palette_index) = painter.colr.layer(i)?;
+            let fill = match painter.solid(palette_index, 1.0) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            if let Some(path) = painter.glyph_path(layer_glyph, fill) {
+                group.append_kind(NodeKind::Path(path));
+            }
+        }
+    }
+
+    if group.has_children() {
+        Some(group)
+    } else {
+        None
+    }
+}
+
+struct Stream<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Stream<'a> {
+    fn new_at(data: &'a [u8], offset: usize) -> Option<Self> {
+        if offset <= data.len() {
+            Some(Stream { data, offset })
+        } else {
+            None
+        }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let v = self.data.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(v)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        self.read_bytes(1).map(|v| v[0])
+    }
+
+    fn read_u16(&mut self) -> Option<u16> {
+        self.read_bytes(2).map(|v| u16::from_be_bytes([v[0], v[1]]))
+    }
+
+    fn read_i16(&mut self) -> Option<i16> {
+        self.read_u16().map(|v| v as i16)
+    }
+
+    fn read_u24(&mut self) -> Option<u32> {
+        self.read_bytes(3)
+            .map(|v| u32::from_be_bytes([0, v[0], v[1], v[2]]))
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        self.read_bytes(4)
+            .map(|v| u32::from_be_bytes([v[0], v[1], v[2], v[3]]))
+    }
+
+    fn read_f2dot14(&mut self) -> Option<f64> {
+        self.read_i16().map(|v| v as f64 / 16384.0)
+    }
+
+    fn read_fixed(&mut self) -> Option<f64> {
+        self.read_u32().map(|v| v as i32 as f64 / 65536.0)
+    }
+
+    /// Reads an `Offset24` relative to `base` and returns an absolute one.
+    fn read_offset24(&mut self, base: usize) -> Option<usize> {
+        let offset = self.read_u24()?;
+        if offset == 0 {
+            return None;
+        }
+
+        base.checked_add(offset as usize)
+    }
+}
+
+struct Colr<'a> {
+    data: &'a [u8],
+    base_glyphs_offset: usize,
+    base_glyphs_count: u16,
+    layers_offset: usize,
+    layers_count: u16,
+    base_glyph_list_offset: Option<usize>,
+    layer_list_offset: Option<usize>,
+}
+
+impl<'a> Colr<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        let mut s = Stream::new_at(data, 0)?;
+        let version = s.read_u16()?;
+        let base_glyphs_count = s.read_u16()?;
+        let base_glyphs_offset = s.read_u32()? as usize;
+        let layers_offset = s.read_u32()? as usize;
+        let layers_count = s.read_u16()?;
+
+        let mut colr = Colr {
+            data,
+            base_glyphs_offset,
+            base_glyphs_count,
+            layers_offset,
+            layers_count,
+            base_glyph_list_offset: None,
+            layer_list_offset: None,
+        };
+
+        if version > 0 {
+            let non_zero = |v: u32| if v != 0 { Some(v as usize) } else { None };
+            colr.base_glyph_list_offset = non_zero(s.read_u32()?);
+            colr.layer_list_offset = non_zero(s.read_u32()?);
+        }
+
+        Some(colr)
+    }
+
+    /// Returns the first layer index and the number of layers of a version 0 glyph.
+    fn base_layers(&self, glyph_id: ttf_parser::GlyphId) -> Option<(u16, u16)> {
+        const RECORD_SIZE: usize = 6;
+        let record = self.find_record(
+            self.base_glyphs_offset,
+            self.base_glyphs_count as u32,
+            RECORD_SIZE,
+            glyph_id,
+        )?;
+
+        let mut s = Stream::new_at(self.data, record + 2)?;
+        Some((s.read_u16()?, s.read_u16()?))
+    }
+
+    /// Returns a glyph ID and a palette index of a version 0 layer.
+    fn layer(&self, index: u16) -> Option<(ttf_parser::GlyphId, u16)> {
+        if index >= self.layers_count {
+            return None;
+        }
+
+        let mut s = Stream::new_at(self.data, self.layers_offset + index as usize * 4)?;
+        Some((ttf_parser::GlyphId(s.read_u16()?), s.read_u16()?))
+    }
+
+    /// Returns an absolute offset to a version 1 glyph's root paint.
+    fn base_paint(&self, glyph_id: ttf_parser::GlyphId) -> Option<usize> {
+        const RECORD_SIZE: usize = 6;
+        let list = self.base_glyph_list_offset?;
+        let count = Stream::new_at(self.data, list)?.read_u32()?;
+        let record = self.find_record(list + 4, count, RECORD_SIZE, glyph_id)?;
+        let offset = Stream::new_at(self.data, record + 2)?.read_u32()?;
+        list.checked_add(offset as usize)
+    }
+
+    /// Returns an absolute offset to a paint from the version 1 layers list.
+    fn layer_paint(&self, index: u32) -> Option<usize> {
+        let list = self.layer_list_offset?;
+        let mut s = Stream::new_at(self.data, list)?;
+        if index >= s.read_u32()? {
+            return None;
+        }
+
+        s.skip(index as usize * 4)?;
+        list.checked_add(s.read_u32()? as usize)
+    }
+
+    /// Performs a binary search over records sorted by a glyph ID.
+    fn find_record(
+        &self,
+        offset: usize,
+        count: u32,
+        record_size: usize,
+        glyph_id: ttf_parser::GlyphId,
+    ) -> Option<usize> {
+        let mut start = 0;
+        let mut end = count as usize;
+        while start < end {
+            let mid = (start + end) / 2;
+            let record = offset.checked_add(mid * record_size)?;
+            let id = Stream::new_at(self.data, record)?.read_u16()?;
+            match id.cmp(&glyph_id.0) {
+                std::cmp::Ordering::Less => start = mid + 1,
+                std::cmp::Ordering::Greater => end = mid,
+                std::cmp::Ordering::Equal => return Some(record),
+            }
+        }
+
+        None
+    }
+}
+
+struct Cpal<'a> {
+    data: &'a [u8],
+    entries_count: u16,
+    records_offset: usize,
+    first_record: u16,
+}
+
+impl<'a> Cpal<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        let mut s = Stream::new_at(data, 0)?;
+        s.skip(2)?; // version
+        let entries_count = s.read_u16()?;
+        let palettes_count = s.read_u16()?;
+        s.skip(2)?; // color records count
+        let records_offset = s.read_u32()? as usize;
+        if palettes_count == 0 {
+            return None;
+        }
+
+        // Only the first palette is used.
+        let first_record = s.read_u16()?;
+
+        Some(Cpal {
+            data,
+            entries_count,
+            records_offset,
+            first_record,
+        })
+    }
+
+    fn color(&self, index: u16) -> Option<(Color, f64)> {
+        if index >= self.entries_count {
+            return None;
+        }
+
+        let record = (self.first_record as usize + index as usize) * 4;
+        let mut s = Stream::new_at(self.data, self.records_offset + record)?;
+        let blue = s.read_u8()?;
+        let green = s.read_u8()?;
+        let red = s.read_u8()?;
+        let alpha = s.read_u8()?;
+        Some((Color::new_rgb(red, green, blue), alpha as f64 / 255.0))
+    }
+}
+
+struct Painter<'a, 'b> {
+    face: &'a ttf_parser::Face<'a>,
+    colr: Colr<'a>,
+    cpal: Option<Cpal<'a>>,
+    foreground: Option<&'a Fill>,
+    ids: &'b mut IdGenerator,
+    /// `PaintColrGlyph` glyphs that are being painted.
+    active_glyphs: Vec<ttf_parser::GlyphId>,
+    /// `PaintColrLayers` slices that are being painted.
+    active_layers: Vec<(u32, u32)>,
+    /// The number of visited paints.
+    paints: u32,
+}
+
+impl Painter<'_, '_> {
+    /// Appends a paint to `parent`.
+    ///
+    /// Like the specification requires, paints that reference a glyph or a layers slice
+    /// that is already being painted are skipped, since they would create a cycle.
+    fn paint(&mut self, offset: usize, parent: &Node, depth: u8) -> Option<()> {
+        if depth > MAX_DEPTH || self.paints >= MAX_PAINTS {
+            return None;
+        }
+
+        self.paints += 1;
+
+        let mut s = Stream::new_at(self.colr.data, offset)?;
+        let format = s.read_u8()?;
+        match format {
+            // PaintColrLayers
+            1 => {
+                let count = s.read_u8()? as u32;
+                let first = s.read_u32()?;
+                let end = first.checked_add(count)?;
+                if self.active_layers.contains(&(first, count)) {
+                    return None;
+                }
+
+                self.active_layers.push((first, count));
+                for i in first..end {
+                    if let Some(layer) = self.colr.layer_paint(i) {
+                        self.paint(layer, parent, depth + 1);
+                    }
+                }
+                self.active_layers.pop();
+            }
+            // PaintGlyph
+            10 => {
+                let child = s.read_offset24(offset)?;
+                let glyph_id = ttf_parser::GlyphId(s.read_u16()?);
+
+                if let Some(fill) = self.brush(child, Transform::default(), depth + 1) {
+                    if let Some(path) = self.glyph_path(glyph_id, fill) {
+                        parent.append_kind(NodeKind::Path(path));
+                    }
+                } else {
+                    // Not a simple fill, so we have to use a clip path instead.
+                    let clip_path = ClipPath::default();
+                    let path = self.glyph_path(glyph_id, Fill::default())?;
+                    clip_path.root.append_kind(NodeKind::Path(path));
+
+                    let group = parent.append_kind(NodeKind::Group(Group {
+                        clip_path: Some(Rc::new(ClipPath {
+                            id: self.ids.gen_clip_path_id(),
+                            ..clip_path
+                        })),
+                        ..Group::default()
+                    }));
+                    self.paint(child, &group, depth + 1);
+                }
+            }
+            // PaintColrGlyph
+            11 => {
+                let glyph_id = ttf_parser::GlyphId(s.read_u16()?);
+                if self.active_glyphs.contains(&glyph_id) {
+                    return None;
+                }
+
+                let child = self.colr.base_paint(glyph_id)?;
+                self.active_glyphs.push(glyph_id);
+                let result = self.paint(child, parent, depth + 1);
+                self.active_glyphs.pop();
+                return result;
+            }
+            // Transforms
+            12..=31 => {
+                let (child, ts) = parse_transform(format, offset, &mut s, self.colr.data)?;
+                let group = parent.append_kind(NodeKind::Group(Group {
+                    transform: ts,
+                    ..Group::default()
+                }));
+                self.paint(child, &group, depth + 1);
+            }
+            // PaintComposite
+            32 => {
+                let source = s.read_offset24(offset)?;
+                let mode = s.read_u8()?;
+                let backdrop = s.read_offset24(offset)?;
+                self.composite(source, mode, backdrop, parent, depth + 1);
+            }
+            // A brush outside of a glyph. Fill everything, since it will be clipped anyway.
+            _ => {
+                let fill = self.brush(offset, Transform::default(), depth)?;
+                let mut data = PathData::new();
+                data.push_rect(Rect::new(-32768.0, -32768.0, 65536.0, 65536.0)?);
+                parent.append_kind(NodeKind::Path(Path {
+                    fill: Some(fill),
+                    data: Rc::new(data),
+                    ..Path::default()
+                }));
+            }
+        }
+
+        Some(())
+    }
+
+    fn composite(
+        &mut self,
+        source: usize,
+        mode: u8,
+        backdrop: usize,
+        parent: &Node,
+        depth: u8,
+    ) -> Option<()> {
+        let blend_mode = match mode {
+            // Clear
+            0 => return Some(()),
+            // Source
+            1 => return self.paint(source, parent, depth),
+            // Destination
+            2 => return self.paint(backdrop, parent, depth),
+            // Destination over
+            4 => {
+                self.paint(source, parent, depth);
+                return self.paint(backdrop, parent, depth);
+            }
+            13 => BlendMode::Screen,
+            14 => BlendMode::Overlay,
+            15 => BlendMode::Darken,
+            16 => BlendMode::Lighten,
+            17 => BlendMode::ColorDodge,
+            18 => BlendMode::ColorBurn,
+            19 => BlendMode::HardLight,
+            20 => BlendMode::SoftLight,
+            21 => BlendMode::Difference,
+            22 => BlendMode::Exclusion,
+            23 => BlendMode::Multiply,
+            24 => BlendMode::Hue,
+            25 => BlendMode::Saturation,
+            26 => BlendMode::Color,
+            27 => BlendMode::Luminosity,
+            // Other Porter-Duff modes cannot be expressed in SVG
+            // and are rendered as source over.
+            _ => BlendMode::Normal,
+        };
+
+        let group = parent.append_kind(NodeKind::Group(Group {
+            isolate: true,
+            ..Group::default()
+        }));
+        self.paint(backdrop, &group, depth);
+
+        let source_group = group.append_kind(NodeKind::Group(Group {
+            blend_mode,
+            ..Group::default()
+        }));
+        self.paint(source, &source_group, depth)
+    }
+
+    /// Resolves a paint into a fill.
+    ///
+    /// Transforms are applied to gradients.
+    /// Returns `None` when the paint is not a solid color or a gradient.
+    fn brush(&mut self, offset: usize, ts: Transform, depth: u8) -> Option<Fill> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let mut s = Stream::new_at(self.colr.data, offset)?;
+        let format = s.read_u8()?;
+        let is_var = format % 2 == 1;
+        match format {
+            // PaintSolid and PaintVarSolid
+            2 | 3 => {
+                let palette_index = s.read_u16()?;
+                let alpha = s.read_f2dot14()?;
+                Some(
+                    self.solid(palette_index, alpha)
+                        .unwrap_or_else(invisible_fill),
+                )
+            }
+            // PaintLinearGradient and PaintVarLinearGradient
+            4 | 5 => {
+                let color_line = s.read_offset24(offset)?;
+                let x0 = s.read_i16()? as f64;
+                let y0 = s.read_i16()? as f64;
+                let x1 = s.read_i16()? as f64;
+                let y1 = s.read_i16()? as f64;
+                let x2 = s.read_i16()? as f64;
+                let y2 = s.read_i16()? as f64;
+
+                // The gradient vector is perpendicular to the `p0p2` line.
+                let (px, py) = (y2 - y0, x0 - x2);
+                let len = px * px + py * py;
+                let (x1, y1) = if len.is_fuzzy_zero() {
+                    (x1, y1)
+                } else {
+                    let k = ((x1 - x0) * px + (y1 - y0) * py) / len;
+                    (x0 + px * k, y0 + py * k)
+                };
+
+                let (spread_method, stops) = self.color_line(color_line, is_var, 0.0)?;
+                if stops.len() < 2 {
+                    return stops_fill(&stops);
+                }
+
+                Some(Fill::from_paint(Paint::LinearGradient(Rc::new(
+                    LinearGradient {
+                        id: self.ids.gen_gradient_id(),
+                        x1: x0,
+                        y1: y0,
+                        x2: x1,
+                        y2: y1,
+                        base: BaseGradient {
+                            units: Units::UserSpaceOnUse,
+                            transform: ts,
+                            spread_method,
+                            stops,
+                        },
+                    },
+                ))))
+            }
+            // PaintRadialGradient and PaintVarRadialGradient
+            6 | 7 => {
+                let color_line = s.read_offset24(offset)?;
+                let x0 = s.read_i16()? as f64;
+                let y0 = s.read_i16()? as f64;
+                let r0 = s.read_u16()? as f64;
+                let x1 = s.read_i16()? as f64;
+                let y1 = s.read_i16()? as f64;
+                let r1 = s.read_u16()? as f64;
+
+                // SVG doesn't support a start circle radius,
+                // so we shift stops to the start circle instead.
+                let start = if r1 > r0 { r0 / r1 } else { 0.0 };
+                let (spread_method, stops) = self.color_line(color_line, is_var, start)?;
+                if stops.len() < 2 {
+                    return stops_fill(&stops);
+                }
+
+                let r = match PositiveF64::new(r1) {
+                    Some(r) if r1 > 0.0 => r,
+                    // A zero radius gradient is rendered using the last stop color.
+                    _ => return stops_fill(&stops[stops.len() - 1..]),
+                };
+
+                Some(Fill::from_paint(Paint::RadialGradient(Rc::new(
+                    RadialGradient {
+                        id: self.ids.gen_gradient_id(),
+                        cx: x1,
+                        cy: y1,
+                        r,
+                        fx: x0,
+                        fy: y0,
+                        base: BaseGradient {
+                            units: Units::UserSpaceOnUse,
+                            transform: ts,
+                            spread_method,
+                            stops,
+                        },
+                    },
+                ))))
+            }
+            // PaintSweepGradient and PaintVarSweepGradient
+            8 | 9 => {
+                // SVG doesn't support sweep gradients, therefore we fallback
+                // to the first stop color.
+                let color_line = s.read_offset24(offset)?;
+                let (_, stops) = self.color_line(color_line, is_var, 0.0)?;
+                stops_fill(&stops[..stops.len().min(1)])
+            }
+            // Transforms
+            12..=31 => {
+                let (child, child_ts) = parse_transform(format, offset, &mut s, self.colr.data)?;
+                let mut ts = ts;
+                ts.append(&child_ts);
+                self.brush(child, ts, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    fn color_line(
+        &self,
+        offset: usize,
+        is_var: bool,
+        start: f64,
+    ) -> Option<(SpreadMethod, Vec<Stop>)> {
+        let mut s = Stream::new_at(self.colr.data, offset)?;
+        let spread_method = match s.read_u8()? {
+            1 => SpreadMethod::Repeat,
+            2 => SpreadMethod::Reflect,
+            _ => SpreadMethod::Pad,
+        };
+
+        let count = s.read_u16()?;
+        let mut stops = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let offset = s.read_f2dot14()?;
+            let palette_index = s.read_u16()?;
+            let alpha = s.read_f2dot14()?;
+            if is_var {
+                s.skip(4)?; // varIndexBase
+            }
+
+            let (color, opacity) = self.stop_color(palette_index, alpha);
+            stops.push(Stop {
+                offset: StopOffset::new_clamped(start + offset * (1.0 - start)),
+                color,
+                opacity: Opacity::new_clamped(opacity),
+                specified_color: None,
+            });
+        }
+
+        // Stops in a color line are not required to be sorted.
+        stops.sort_by(|a, b| a.offset.get().partial_cmp(&b.offset.get()).unwrap());
+
+        Some((spread_method, stops))
+    }
+
+    /// Returns a palette color.
+    ///
+    /// Returns `None` for foreground layers when there is no foreground fill.
+    fn solid(&self, palette_index: u16, alpha: f64) -> Option<Fill> {
+        if palette_index == FOREGROUND_INDEX {
+            let mut fill = self.foreground?.clone();
+            fill.opacity = Opacity::new_clamped(fill.opacity.get() * alpha);
+            fill.rule = FillRule::NonZero;
+            return Some(fill);
+        }
+
+        let (color, opacity) = self.palette_color(palette_index);
+        Some(Fill {
+            paint: Paint::Color(color),
+            opacity: Opacity::new_clamped(opacity * alpha),
+            ..Fill::default()
+        })
+    }
+
+    fn stop_color(&self, palette_index: u16, alpha: f64) -> (Color, f64) {
+        if palette_index == FOREGROUND_INDEX {
+            return match self.foreground {
+                Some(Fill {
+                    paint: Paint::Color(c),
+                    opacity,
+                    ..
+                }) => (*c, opacity.get() * alpha),
+                Some(_) => (Color::black(), alpha),
+                None => (Color::black(), 0.0),
+            };
+        }
+
+        let (color, opacity) = self.palette_color(palette_index);
+        (color, opacity * alpha)
+    }
+
+    fn palette_color(&self, palette_index: u16) -> (Color, f64) {
+        self.cpal
+            .as_ref()
+            .and_then(|cpal| cpal.color(palette_index))
+            .unwrap_or((Color::black(), 1.0))
+    }
+
+    fn glyph_path(&self, glyph_id: ttf_parser::GlyphId, fill: Fill) -> Option<Path> {
+        if fill.opacity.get().is_fuzzy_zero() {
+            return None;
+        }
+
+        let mut builder = PathBuilder {
+            path: PathData::new(),
+        };
+        self.face.outline_glyph(glyph_id, &mut builder)?;
+
+        Some(Path {
+            fill: Some(fill),
+            data: Rc::new(builder.path),
+            ..Path::default()
+        })
+    }
+}
+
+fn invisible_fill() -> Fill {
+    Fill {
+        opacity: Opacity::ZERO,
+        ..Fill::default()
+    }
+}
+
+/// Creates a solid fill from a single stop.
+fn stops_fill(stops: &[Stop]) -> Option<Fill> {
+    Some(match stops.first() {
+        Some(stop) => Fill {
+            paint: Paint::Color(stop.color),
+            opacity: stop.opacity,
+            ..Fill::default()
+        },
+        None => invisible_fill(),
+    })
+}
+
+/// Parses a transform paint.
+///
+/// Returns an absolute offset to the child paint and the transform.
+fn parse_transform(
+    format: u8,
+    offset: usize,
+    s: &mut Stream,
+    data: &[u8],
+) -> Option<(usize, Transform)> {
+    let child = s.read_offset24(offset)?;
+
+    // Variable formats have the same layout, but with a trailing `varIndexBase`.
+    let ts = match format {
+        // PaintTransform and PaintVarTransform
+        12 | 13 => {
+            let mut s = Stream::new_at(data, s.read_offset24(offset)?)?;
+            Transform::new(
+                s.read_fixed()?,
+                s.read_fixed()?,
+                s.read_fixed()?,
+                s.read_fixed()?,
+                s.read_fixed()?,
+                s.read_fixed()?,
+            )
+        }
+        // PaintTranslate and PaintVarTranslate
+        14 | 15 => Transform::new_translate(s.read_i16()? as f64, s.read_i16()? as f64),
+        // PaintScale and PaintVarScale
+        16 | 17 => Transform::new_scale(s.read_f2dot14()?, s.read_f2dot14()?),
+        // PaintScaleAroundCenter and PaintVarScaleAroundCenter
+        18 | 19 => {
+            let ts = Transform::new_scale(s.read_f2dot14()?, s.read_f2dot14()?);
+            around_center(ts, s)?
+        }
+        // PaintScaleUniform and PaintVarScaleUniform
+        20 | 21 => {
+            let scale = s.read_f2dot14()?;
+            Transform::new_scale(scale, scale)
+        }
+        // PaintScaleUniformAroundCenter and PaintVarScaleUniformAroundCenter
+        22 | 23 => {
+            let scale = s.read_f2dot14()?;
+            around_center(Transform::new_scale(scale, scale), s)?
+        }
+        // PaintRotate and PaintVarRotate
+        24 | 25 => Transform::new_rotate(s.read_f2dot14()? * 180.0),
+        // PaintRotateAroundCenter and PaintVarRotateAroundCenter
+        26 | 27 => {
+            let ts = Transform::new_rotate(s.read_f2dot14()? * 180.0);
+            around_center(ts, s)?
+        }
+        // PaintSkew and PaintVarSkew
+        28 | 29 => skew(s.read_f2dot14()?, s.read_f2dot14()?),
+        // PaintSkewAroundCenter and PaintVarSkewAroundCenter
+        30 | 31 => {
+            let ts = skew(s.read_f2dot14()?, s.read_f2dot14()?);
+            around_center(ts, s)?
+        }
+        _ => return None,
+    };
+
+    Some((child, ts))
+}
+
+fn around_center(ts: Transform, s: &mut Stream) -> Option<Transform> {
+    let cx = s.read_i16()? as f64;
+    let cy = s.read_i16()? as f64;
+
+    let mut new_ts = Transform::new_translate(cx, cy);
+    new_ts.append(&ts);
+    new_ts.translate(-cx, -cy);
+    Some(new_ts)
+}
+
+/// Creates a skew transform. Angles are in half turns, counter-clockwise.
+fn skew(x_angle: f64, y_angle: f64) -> Transform {
+    let tan = |a: f64| (a * std::f64::consts::PI).tan();
+    Transform::new(1.0, tan(y_angle), -tan(x_angle), 1.0, 0.0, 0.0)
+}
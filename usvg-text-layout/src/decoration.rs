@@ -0,0 +1,160 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `text-decoration-style` geometry.
+//!
+//! All lines are generated as filled outlines, so they can be filled and stroked
+//! like the text itself. Proportions are relative to the line thickness.
+
+use std::f64::consts::PI;
+
+use usvg_tree::{PathData, Rect, TextDecorationLineStyle};
+
+/// A dash length.
+const DASH_LENGTH: f64 = 3.0;
+
+/// A gap between dashes.
+const DASH_GAP: f64 = 2.0;
+
+/// A distance between dot centers.
+const DOT_SPACING: f64 = 2.0;
+
+/// A wave amplitude.
+const WAVE_AMPLITUDE: f64 = 1.0;
+
+/// A wave length.
+const WAVE_LENGTH: f64 = 6.0;
+
+/// Number of segments per wave length.
+const WAVE_SEGMENTS: usize = 16;
+
+/// Appends a decoration line along the X axis, centered at `y = 0`.
+///
+/// `direction` points away from the text: `1` for underlines, `-1` for overlines
+/// and `0` for line-through. Double and wavy lines grow in this direction.
+///
+/// `phase` is a total width of the preceding lines. It is used to continue a pattern
+/// across lines that were split by a relative shift or a text path.
+pub(crate) fn push_line(
+    style: TextDecorationLineStyle,
+    width: f64,
+    thickness: f64,
+    direction: f64,
+    phase: f64,
+    path: &mut PathData,
+) {
+    match style {
+        TextDecorationLineStyle::Solid => push_rect(0.0, width, 0.0, thickness, path),
+        TextDecorationLineStyle::Double => {
+            let (y1, y2) = if direction == 0.0 {
+                (-thickness, thickness)
+            } else {
+                (0.0, direction * thickness * 2.0)
+            };
+
+            push_rect(0.0, width, y1, thickness, path);
+            push_rect(0.0, width, y2, thickness, path);
+        }
+        TextDecorationLineStyle::Dotted => {
+            let spacing = thickness * DOT_SPACING;
+            let radius = thickness / 2.0;
+            let mut x = first_pattern_start(phase, spacing) + radius - phase;
+            while x < width {
+                if x >= 0.0 {
+                    push_circle(x, 0.0, radius, path);
+                }
+
+                x += spacing;
+            }
+        }
+        TextDecorationLineStyle::Dashed => {
+            let dash = thickness * DASH_LENGTH;
+            let period = dash + thickness * DASH_GAP;
+            let mut x = first_pattern_start(phase, period) - phase;
+            while x < width {
+                let x1 = x.max(0.0);
+                let x2 = (x + dash).min(width);
+                if x2 > x1 {
+                    push_rect(x1, x2, 0.0, thickness, path);
+                }
+
+                x += period;
+            }
+        }
+        TextDecorationLineStyle::Wavy => {
+            let amplitude = thickness * WAVE_AMPLITUDE;
+            push_wave(
+                width,
+                thickness,
+                amplitude,
+                direction * amplitude,
+                phase,
+                path,
+            );
+        }
+    }
+}
+
+/// Returns the start of a pattern period that contains `phase`.
+fn first_pattern_start(phase: f64, period: f64) -> f64 {
+    (phase / period).floor() * period
+}
+
+fn push_rect(x1: f64, x2: f64, y: f64, thickness: f64, path: &mut PathData) {
+    match Rect::new(x1, y - thickness / 2.0, x2 - x1, thickness) {
+        Some(rect) => path.push_rect(rect),
+        None => log::warn!("a decoration span has a malformed bbox"),
+    }
+}
+
+fn push_circle(cx: f64, cy: f64, r: f64, path: &mut PathData) {
+    // A cubic Bézier approximation of a quarter circle.
+    let k = r * 0.5522847498;
+    path.push_move_to(cx + r, cy);
+    path.push_curve_to(cx + r, cy + k, cx + k, cy + r, cx, cy + r);
+    path.push_curve_to(cx - k, cy + r, cx - r, cy + k, cx - r, cy);
+    path.push_curve_to(cx - r, cy - k, cx - k, cy - r, cx, cy - r);
+    path.push_curve_to(cx + k, cy - r, cx + r, cy - k, cx + r, cy);
+    path.push_close_path();
+}
+
+/// Appends a sine wave outline with a constant thickness.
+fn push_wave(
+    width: f64,
+    thickness: f64,
+    amplitude: f64,
+    shift: f64,
+    phase: f64,
+    path: &mut PathData,
+) {
+    let wave_length = thickness * WAVE_LENGTH;
+    let step = wave_length / WAVE_SEGMENTS as f64;
+    let count = (width / step).ceil().max(1.0) as usize;
+
+    let k = 2.0 * PI / wave_length;
+    let mut top = Vec::with_capacity(count + 1);
+    let mut bottom = Vec::with_capacity(count + 1);
+    for i in 0..=count {
+        let x = (i as f64 * step).min(width);
+        let angle = (x + phase) * k;
+        let y = shift - amplitude * angle.sin();
+
+        // Offset along the normal, so the line has the same thickness on slopes.
+        let dy = -amplitude * k * angle.cos();
+        let len = (1.0 + dy * dy).sqrt();
+        let (nx, ny) = (-dy / len, 1.0 / len);
+        let half = thickness / 2.0;
+        top.push((x - nx * half, y - ny * half));
+        bottom.push((x + nx * half, y + ny * half));
+    }
+
+    let mut points = top.into_iter().chain(bottom.into_iter().rev());
+    if let Some((x, y)) = points.next() {
+        path.push_move_to(x, y);
+    }
+    for (x, y) in points {
+        path.push_line_to(x, y);
+    }
+    path.push_close_path();
+}
@@ -0,0 +1,129 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Grid fitting of glyph outlines for small raster sizes.
+//!
+//! Mimics the FreeType "light" autohinter: only vertical positions are adjusted,
+//! so advances and the text layout stay unchanged.
+//! The baseline is snapped to a pixel boundary, while the x-height and the cap-height
+//! are rounded to whole pixels. Points in between are interpolated linearly.
+
+use usvg_tree::{PathData, PathSegment, Transform};
+
+use crate::ResolvedFont;
+
+/// The default largest hinted font size in pixels.
+const DEFAULT_MAX_FONT_SIZE: f64 = 16.0;
+
+/// Text hinting options.
+///
+/// Hinting aligns glyph outlines to the pixel grid of the target raster,
+/// which makes small text sharper at the cost of glyph shape precision.
+/// Only text with `text-rendering: optimizeLegibility` is hinted.
+/// Rotated text is never hinted.
+#[derive(Clone, Copy, Debug)]
+pub struct TextHinting {
+    /// A transform from the tree root coordinates to device pixels.
+    ///
+    /// Usually, the view box transform scaled to the target raster size.
+    pub transform: Transform,
+
+    /// The largest font size in device pixels that is still hinted.
+    ///
+    /// Default: 16
+    pub max_font_size: f64,
+}
+
+impl TextHinting {
+    /// Creates new hinting options for the specified device transform.
+    pub fn new(transform: Transform) -> Self {
+        TextHinting {
+            transform,
+            max_font_size: DEFAULT_MAX_FONT_SIZE,
+        }
+    }
+}
+
+impl Default for TextHinting {
+    fn default() -> Self {
+        Self::new(Transform::default())
+    }
+}
+
+/// Fits a glyph outline in font units to the pixel grid.
+///
+/// `ts` is a transform from font units to device pixels.
+///
+/// Returns `None` when the glyph should not be hinted.
+pub(crate) fn hint_outline(
+    outline: &PathData,
+    font: &ResolvedFont,
+    ts: Transform,
+    max_font_size: f64,
+) -> Option<PathData> {
+    // Font units must map to device pixels vertically without a rotation.
+    if ts.b.abs() > f64::EPSILON || ts.d.abs() <= f64::EPSILON {
+        return None;
+    }
+
+    let scale = ts.d.abs();
+    if scale * font.units_per_em.get() as f64 > max_font_size {
+        return None;
+    }
+
+    // A device `y` of the baseline is the transform's `f`.
+    let baseline_delta = (ts.f.round() - ts.f) / ts.d;
+
+    let mut zones = vec![(0.0, 0.0)];
+    for height in [font.x_height.get(), font.cap_height.get()] {
+        let height = height as f64;
+        if height > zones.last().unwrap().0 {
+            let pixels = height * scale;
+            zones.push((height, (pixels.round().max(1.0) - pixels) / scale));
+        }
+    }
+
+    let fit = |y: f64| y + baseline_delta + zone_delta(&zones, y);
+
+    let mut path = PathData::new();
+    for segment in outline.segments() {
+        match segment {
+            PathSegment::MoveTo { x, y } => path.push_move_to(x, fit(y)),
+            PathSegment::LineTo { x, y } => path.push_line_to(x, fit(y)),
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => path.push_curve_to(x1, fit(y1), x2, fit(y2), x, fit(y)),
+            PathSegment::ClosePath => path.push_close_path(),
+        }
+    }
+
+    Some(path)
+}
+
+/// Interpolates a vertical shift between blue zones.
+///
+/// Points below the baseline are not moved, points above the topmost zone
+/// are moved together with it.
+fn zone_delta(zones: &[(f64, f64)], y: f64) -> f64 {
+    let mut prev = zones[0];
+    if y <= prev.0 {
+        return prev.1;
+    }
+
+    for &(height, delta) in &zones[1..] {
+        if y <= height {
+            let t = (y - prev.0) / (height - prev.0);
+            return prev.1 + (delta - prev.1) * t;
+        }
+
+        prev = (height, delta);
+    }
+
+    prev.1
+}
@@ -21,199 +21,1840 @@ An [SVG] text layout implementation on top of [usvg] crate.
 #![allow(clippy::question_mark)]
 #![allow(clippy::upper_case_acronyms)]
 
+pub use cache::GlyphCache;
 pub use fontdb;
+pub use hinting::TextHinting;
+pub use rustybuzz::ttf_parser::GlyphId;
+pub use unicode_script::Script;
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::num::NonZeroU16;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use fontdb::{Database, ID};
 use kurbo::{ParamCurve, ParamCurveArclen, ParamCurveDeriv};
 use rustybuzz::ttf_parser;
-use ttf_parser::GlyphId;
 use unicode_script::UnicodeScript;
+use unicode_segmentation::UnicodeSegmentation;
 use usvg_tree::*;
 
+mod cache;
+mod colr;
+mod decoration;
+mod hinting;
+mod svg_font;
+mod synthesis;
+
+/// Text to path conversion options.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct TextConversionOptions<'a> {
+    /// A font fallback policy.
+    ///
+    /// Used fallback fonts and missing glyphs will be reported via `Tree::diagnostics`.
+    ///
+    /// `None` means the default [`FontFallback`].
+    pub fallback: Option<&'a FontFallback>,
+
+    /// A glyph cache.
+    ///
+    /// The same cache can be reused between trees that use the same fonts database.
+    pub cache: Option<&'a GlyphCache>,
+
+    /// Glyph hinting.
+    ///
+    /// Glyphs are fitted to the pixel grid of the target raster, therefore the tree
+    /// should be rendered only with the [`TextHinting::transform`].
+    pub hinting: Option<&'a TextHinting>,
+}
+
 /// A `usvg::Tree` extension trait.
 pub trait TreeTextToPath {
     /// Converts text nodes into paths.
+    ///
+    /// Uses the default [`TextConversionOptions`].
     fn convert_text(&mut self, fontdb: &fontdb::Database);
+
+    /// Converts text nodes into paths using custom options.
+    ///
+    /// Fonts from `Tree::font_faces` and `Tree::svg_fonts` are used before the `fontdb` ones.
+    fn convert_text_with_options(
+        &mut self,
+        fontdb: &fontdb::Database,
+        options: &TextConversionOptions,
+    );
+}
+
+impl TreeTextToPath for usvg_tree::Tree {
+    fn convert_text(&mut self, fontdb: &fontdb::Database) {
+        self.convert_text_with_options(fontdb, &TextConversionOptions::default());
+    }
+
+    fn convert_text_with_options(
+        &mut self,
+        fontdb: &fontdb::Database,
+        options: &TextConversionOptions,
+    ) {
+        let default_fallback = FontFallback::default();
+        let fallback = options.fallback.unwrap_or(&default_fallback);
+
+        let mut ids = IdGenerator::new(self);
+        let mut fonts = DocumentFonts::from_fonts(fontdb, &self.font_faces, &self.svg_fonts);
+        fonts.cache = options.cache;
+        convert_text(
+            self.root.clone(),
+            fontdb,
+            fallback,
+            &fonts,
+            options.hinting,
+            &mut ids,
+            &mut self.diagnostics,
+        );
+    }
+}
+
+/// A `usvg::Text` extension trait.
+pub trait TextToPath {
+    /// Converts the text node into path(s).
+    ///
+    /// `absolute_ts` is node's absolute transform. Used primarily during text-on-path resolving.
+    ///
+    /// Uses the default [`FontFallback`] and no [`DocumentFonts`]. Diagnostics are discarded.
+    /// Use [`layout`] to get them.
+    ///
+    /// Color glyphs can create gradients and clip paths. Their IDs are unique
+    /// only within the returned node.
+    fn convert(&self, fontdb: &fontdb::Database, absolute_ts: Transform) -> Option<Node>;
+}
+
+impl TextToPath for Text {
+    fn convert(&self, fontdb: &fontdb::Database, absolute_ts: Transform) -> Option<Node> {
+        let fonts = DocumentFonts::default();
+        let layout = layout(self, fontdb, &fonts, &FontFallback::default(), absolute_ts);
+        convert_layout(
+            self,
+            &layout,
+            fontdb,
+            &fonts,
+            None,
+            &mut IdGenerator::default(),
+        )
+    }
+}
+
+/// A font fallback callback.
+///
+/// Accepts a character that is missing in the span's font, the span's font
+/// and the character's script. Returns a font that should be used instead.
+pub type FallbackCallback = Rc<dyn Fn(char, &Font, Script) -> Option<fontdb::ID>>;
+
+/// A font fallback policy.
+///
+/// Used when the span's font doesn't have a glyph for a character.
+/// Fallback sources are checked in the following order:
+///
+/// 1. `callback`
+/// 2. `language_families` for the span's language, see `TextSpan::lang`
+/// 3. `script_families` for the character's script
+/// 4. `language_families` for each language in `languages`
+/// 5. `families`
+/// 6. all fonts in the database, when `use_database` is set
+///
+/// Fonts that were already used for the current text run are skipped.
+/// A family from a list is resolved using the span's font style, weight and stretch.
+#[derive(Clone)]
+pub struct FontFallback {
+    /// A custom fallback callback.
+    pub callback: Option<FallbackCallback>,
+
+    /// Ordered fallback families for specific scripts.
+    ///
+    /// Family names can be generic, like `serif`.
+    pub script_families: HashMap<Script, Vec<String>>,
+
+    /// Ordered fallback families for specific languages.
+    ///
+    /// Keys are language tags, like `ja` or `zh-Hant`.
+    /// A key matches a language when it's equal to it or is its prefix, like `zh` for `zh-Hant`.
+    /// When several keys match, longer ones are checked first.
+    pub language_families: HashMap<String, Vec<String>>,
+
+    /// Preferred languages used to select `language_families`, in order.
+    ///
+    /// Default: `["en"]`
+    pub languages: Vec<String>,
+
+    /// Ordered fallback families for any character.
+    pub families: Vec<String>,
+
+    /// Scan the whole fonts database when nothing else matched.
+    ///
+    /// Fonts with the same style, weight and stretch as the span's font are preferred.
+    ///
+    /// Default: true
+    pub use_database: bool,
+}
+
+impl Default for FontFallback {
+    fn default() -> Self {
+        FontFallback {
+            callback: None,
+            script_families: HashMap::new(),
+            language_families: HashMap::new(),
+            languages: vec!["en".to_string()],
+            families: Vec::new(),
+            use_database: true,
+        }
+    }
+}
+
+impl std::fmt::Debug for FontFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FontFallback")
+            .field("callback", &self.callback.as_ref().map(|_| "Fn"))
+            .field("script_families", &self.script_families)
+            .field("language_families", &self.language_families)
+            .field("languages", &self.languages)
+            .field("families", &self.families)
+            .field("use_database", &self.use_database)
+            .finish()
+    }
+}
+
+fn convert_layout(
+    text: &Text,
+    layout: &TextLayout,
+    fontdb: &fontdb::Database,
+    fonts: &DocumentFonts,
+    hinting: Option<&Hinting>,
+    ids: &mut IdGenerator,
+) -> Option<Node> {
+    let (new_nodes, bbox) = text_to_nodes(text, layout, fontdb, fonts, hinting, ids);
+    if new_nodes.is_empty() {
+        return None;
+    }
+
+    // Create a group will all paths that was created during text-to-path conversion.
+    let group = Node::new(NodeKind::Group(Group {
+        id: text.id.clone(),
+        transform: text.transform,
+        ..Group::default()
+    }));
+
+    let rendering_mode = resolve_rendering_mode(text);
+    for node in new_nodes {
+        if let NodeKind::Path(ref mut path) = *node.borrow_mut() {
+            fix_obj_bounding_box(path, bbox);
+            path.rendering_mode = rendering_mode;
+        }
+
+        group.append(node);
+    }
+
+    Some(group)
+}
+
+/// A text layout.
+///
+/// Contains positioned glyphs instead of outlines, therefore can be used to implement
+/// text selection or to render text using a custom glyphs cache.
+#[derive(Clone, Debug)]
+pub struct TextLayout {
+    /// Text fragments in the rendering order.
+    ///
+    /// Each text span with a resolved font produces exactly one fragment per line.
+    pub fragments: Vec<TextFragment>,
+
+    /// Used fallback fonts and missing glyphs.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A laid out text span.
+#[derive(Clone, Debug)]
+pub struct TextFragment {
+    /// An index of the text chunk in `Text::chunks`.
+    pub chunk: usize,
+
+    /// An index of the text span in `TextChunk::spans`.
+    pub span: usize,
+
+    /// A span's resolved font.
+    ///
+    /// Individual glyphs can still use a different font because of the font fallback.
+    pub font: fontdb::ID,
+
+    /// Font variation axes values used for shaping.
+    ///
+    /// Must be applied to variable fonts when outlining glyphs as well.
+    pub variations: Vec<FontVariation>,
+
+    /// Visible clusters of the span in the visual order.
+    pub clusters: Vec<PositionedCluster>,
+
+    /// An underline shape in the text element coordinates.
+    pub underline: Option<PathData>,
+
+    /// An overline shape in the text element coordinates.
+    pub overline: Option<PathData>,
+
+    /// A line-through shape in the text element coordinates.
+    pub line_through: Option<PathData>,
+}
+
+/// A positioned glyph cluster.
+///
+/// Cluster/grapheme is a single, unbroken, renderable character.
+#[derive(Clone, Debug)]
+pub struct PositionedCluster {
+    /// A cluster's range in bytes in `TextChunk::text`.
+    pub byte_range: std::ops::Range<usize>,
+
+    /// An advance along the X axis.
+    ///
+    /// Includes `letter-spacing` and `word-spacing`. Can be negative.
+    pub advance: f64,
+
+    /// A cluster's transform in the text element coordinates.
+    ///
+    /// Maps cluster's origin, which is on the baseline, to its position.
+    pub transform: Transform,
+
+    /// A cluster's bounding box in cluster's coordinates.
+    ///
+    /// Calculated using font metrics and not glyph outlines.
+    pub bbox: Option<Rect>,
+
+    /// Cluster's glyphs.
+    ///
+    /// Can be empty.
+    pub glyphs: Vec<PositionedGlyph>,
+}
+
+/// A positioned glyph.
+#[derive(Clone, Copy, Debug)]
+pub struct PositionedGlyph {
+    /// The glyph ID in the font.
+    pub id: GlyphId,
+
+    /// The glyph's font.
+    pub font: fontdb::ID,
+
+    /// The glyph advance along the X axis in the text element coordinates.
+    pub advance: f64,
+
+    /// A glyph's transform.
+    ///
+    /// Maps a glyph outline in font units into the text element coordinates.
+    pub transform: Transform,
+
+    /// An outline offset in font units.
+    ///
+    /// Non-zero when a synthetic bold is used. See `font-synthesis`.
+    pub embolden: f64,
+}
+
+/// Lays out a text node.
+///
+/// `absolute_ts` is node's absolute transform. Used primarily during text-on-path resolving.
+///
+/// All coordinates are in the text element coordinates, i.e. `Text::transform` is not applied.
+///
+/// `fonts` are the document fonts of the tree the text belongs to, created via
+/// [`DocumentFonts::new`]. They are used before the `fontdb` ones,
+/// just like during [`TreeTextToPath::convert_text`].
+pub fn layout(
+    text: &Text,
+    fontdb: &fontdb::Database,
+    fonts: &DocumentFonts,
+    fallback: &FontFallback,
+    absolute_ts: Transform,
+) -> TextLayout {
+    let mut fonts_cache: FontsCache = HashMap::new();
+    for chunk in &text.chunks {
+        for span in &chunk.spans {
+            if !fonts_cache.contains_key(&span.font) {
+                if let Some(font) = resolve_font(&span.font, fontdb, fonts) {
+                    fonts_cache.insert(span.font.clone(), Rc::new(font));
+                }
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut fallback = FallbackState {
+        policy: fallback,
+        fonts,
+        element_id: &text.id,
+        diagnostics: &mut diagnostics,
+    };
+
+    let layout_chunks = collect_layout_chunks(text, &fonts_cache, fontdb, &mut fallback);
+
+    let mut fragments = Vec::new();
+    let mut last_x = 0.0;
+    let mut last_y = 0.0;
+    let mut line_origin = (0.0, 0.0);
+    for layout_chunk in &layout_chunks {
+        let chunk = &*layout_chunk.chunk;
+        let char_offset = layout_chunk.char_offset;
+        let (x, y) = match chunk.text_flow {
+            // Lines are stacked along the block axis, which goes right-to-left in vertical text.
+            TextFlow::Linear
+                if layout_chunk.line != 0 && chunk.x.is_none() && chunk.y.is_none() =>
+            {
+                match text.writing_mode {
+                    WritingMode::LeftToRight => {
+                        (line_origin.0, line_origin.1 + layout_chunk.block_offset)
+                    }
+                    WritingMode::TopToBottom => {
+                        (line_origin.0 - layout_chunk.block_offset, line_origin.1)
+                    }
+                }
+            }
+            TextFlow::Linear => (chunk.x.unwrap_or(last_x), chunk.y.unwrap_or(last_y)),
+            TextFlow::Path(_) => (0.0, 0.0),
+        };
+
+        if layout_chunk.line == 0 {
+            line_origin = (x, y);
+        }
+
+        let mut clusters = shape_chunk(
+            chunk,
+            text.writing_mode,
+            &fonts_cache,
+            fontdb,
+            &mut fallback,
+        );
+        if clusters.is_empty() {
+            continue;
+        }
+
+        apply_writing_mode(text.writing_mode, &mut clusters);
+        apply_letter_spacing(chunk, &mut clusters);
+        apply_word_spacing(chunk, &mut clusters);
+        apply_length_adjust(chunk, &mut clusters);
+        let mut curr_pos = resolve_clusters_positions(
+            chunk,
+            char_offset,
+            &text.positions,
+            &text.rotate,
+            text.writing_mode,
+            absolute_ts,
+            &fonts_cache,
+            &mut clusters,
+        );
+
+        let mut text_ts = Transform::default();
+        if text.writing_mode == WritingMode::TopToBottom {
+            if let TextFlow::Linear = chunk.text_flow {
+                text_ts.rotate_at(90.0, x, y);
+            }
+        }
+
+        // Clusters are in the visual order, so we have to sort them to find cluster ends.
+        let mut cluster_starts: Vec<_> = clusters.iter().map(|c| c.byte_idx.value()).collect();
+        cluster_starts.sort_unstable();
+        cluster_starts.dedup();
+
+        for (span_idx, span) in chunk.spans.iter().enumerate() {
+            let font = match fonts_cache.get(&span.font) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            let decoration_spans = collect_decoration_spans(span, &clusters);
+
+            let mut span_ts = text_ts;
+            span_ts.translate(x, y);
+            if let TextFlow::Linear = chunk.text_flow {
+                let shift = resolve_baseline(span, font, text.writing_mode);
+
+                // In case of a horizontal flow, shift transform and not clusters,
+                // because clusters can be rotated and an additional shift will lead
+                // to invalid results.
+                span_ts.translate(0.0, shift);
+            }
+
+            let underline = span.decoration.underline.as_ref().and_then(|style| {
+                let font_size = span.font_size.get();
+                let thickness = decoration_thickness(style, span, font);
+                let position = style.underline_position;
+                let (offset, direction) = match text.writing_mode {
+                    WritingMode::LeftToRight => {
+                        let offset = match (style.underline_offset, position.under) {
+                            (Some(offset), true) => {
+                                -font.descent(font_size) + offset + thickness / 2.0
+                            }
+                            (Some(offset), false) => offset + thickness / 2.0,
+                            (None, true) => -font.descent(font_size) + thickness / 2.0,
+                            (None, false) => -font.underline_position(font_size),
+                        };
+
+                        (offset, 1.0)
+                    }
+                    // The left side is used by default.
+                    // https://www.w3.org/TR/css-text-decor-3/#text-underline-position-property
+                    WritingMode::TopToBottom => {
+                        let side = if position.right { -1.0 } else { 1.0 };
+                        let offset =
+                            font.height(font_size) / 2.0 + style.underline_offset.unwrap_or(0.0);
+                        (side * offset, side)
+                    }
+                };
+
+                layout_decoration(
+                    offset,
+                    direction,
+                    style,
+                    span,
+                    font,
+                    &decoration_spans,
+                    span_ts,
+                )
+            });
+
+            let overline = span.decoration.overline.as_ref().and_then(|style| {
+                let offset = match text.writing_mode {
+                    WritingMode::LeftToRight => -font.ascent(span.font_size.get()),
+                    WritingMode::TopToBottom => -font.height(span.font_size.get()) / 2.0,
+                };
+
+                layout_decoration(offset, -1.0, style, span, font, &decoration_spans, span_ts)
+            });
+
+            let line_through = span.decoration.line_through.as_ref().and_then(|style| {
+                let offset = match text.writing_mode {
+                    WritingMode::LeftToRight => -font.line_through_position(span.font_size.get()),
+                    WritingMode::TopToBottom => 0.0,
+                };
+
+                layout_decoration(offset, 0.0, style, span, font, &decoration_spans, span_ts)
+            });
+
+            let mut span_clusters = Vec::new();
+            for cluster in &clusters {
+                if !cluster.visible || !span_contains(span, cluster.byte_idx) {
+                    continue;
+                }
+
+                let start = cluster.byte_idx.value();
+                let end = cluster_starts
+                    .iter()
+                    .find(|idx| **idx > start)
+                    .cloned()
+                    .unwrap_or(chunk.text.len());
+
+                let mut transform = span_ts;
+                transform.append(&cluster.transform);
+
+                let glyphs = cluster
+                    .glyphs
+                    .iter()
+                    .map(|glyph| {
+                        let mut glyph_ts = transform;
+                        glyph_ts.append(&glyph.transform);
+                        PositionedGlyph {
+                            id: glyph.id,
+                            font: glyph.font,
+                            advance: glyph.advance,
+                            transform: glyph_ts,
+                            embolden: glyph.embolden,
+                        }
+                    })
+                    .collect();
+
+                let offset = layout_chunk.byte_offset;
+                span_clusters.push(PositionedCluster {
+                    byte_range: start + offset..end + offset,
+                    advance: cluster.advance,
+                    transform,
+                    bbox: Rect::new(0.0, -cluster.ascent, cluster.advance, cluster.height()),
+                    glyphs,
+                });
+            }
+
+            fragments.push(TextFragment {
+                chunk: layout_chunk.index,
+                span: layout_chunk.spans[span_idx],
+                font: font.id,
+                variations: collect_variations(span),
+                clusters: span_clusters,
+                underline,
+                overline,
+                line_through,
+            });
+        }
+
+        if text.writing_mode == WritingMode::TopToBottom {
+            if let TextFlow::Linear = chunk.text_flow {
+                std::mem::swap(&mut curr_pos.0, &mut curr_pos.1);
+            }
+        }
+
+        last_x = x + curr_pos.0;
+        last_y = y + curr_pos.1;
+    }
+
+    TextLayout {
+        fragments,
+        diagnostics,
+    }
+}
+
+/// A text chunk prepared for layout.
+///
+/// Usually, this is just a `Text::chunks` item, but wrapped text
+/// and text with preserved newlines will have a chunk per line.
+struct LayoutChunk<'a> {
+    chunk: Cow<'a, TextChunk>,
+    /// An index of the original chunk in `Text::chunks`.
+    index: usize,
+    /// Indices of the chunk spans in the original chunk.
+    spans: Vec<usize>,
+    /// A chunk offset in the original chunk text, in bytes.
+    byte_offset: usize,
+    /// A chunk offset in `Text::positions`, in characters.
+    char_offset: usize,
+    /// A line index in the original chunk.
+    line: usize,
+    /// A line offset from the first line along the block axis.
+    block_offset: f64,
+}
+
+/// Splits text chunks into lines.
+///
+/// Lines are split at newlines preserved by `white-space`
+/// and wrapped using `shape-inside` or `inline-size`. Text-on-path is never split.
+fn collect_layout_chunks<'a>(
+    text: &'a Text,
+    fonts_cache: &FontsCache,
+    fontdb: &fontdb::Database,
+    fallback: &mut FallbackState,
+) -> Vec<LayoutChunk<'a>> {
+    let mut layout_chunks = Vec::new();
+    let mut char_offset = 0;
+    for (index, chunk) in text.chunks.iter().enumerate() {
+        let is_linear = matches!(chunk.text_flow, TextFlow::Linear);
+        let inline_size = text
+            .inline_size
+            .filter(|_| is_linear && text.white_space.allows_wrapping());
+
+        // Text flow into shapes is supported only for horizontal text.
+        let shape_inside = text
+            .shape_inside
+            .as_ref()
+            .filter(|_| is_linear && text.writing_mode == WritingMode::LeftToRight);
+
+        if !is_linear
+            || (shape_inside.is_none() && inline_size.is_none() && !chunk.text.contains('\n'))
+        {
+            layout_chunks.push(LayoutChunk {
+                chunk: Cow::Borrowed(chunk),
+                index,
+                spans: (0..chunk.spans.len()).collect(),
+                byte_offset: 0,
+                char_offset,
+                line: 0,
+                block_offset: 0.0,
+            });
+
+            char_offset += chunk.text.chars().count();
+            continue;
+        }
+
+        // Shape the whole chunk to measure it. Each line will be shaped separately later.
+        let mut clusters = shape_chunk(chunk, text.writing_mode, fonts_cache, fontdb, fallback);
+        apply_letter_spacing(chunk, &mut clusters);
+        apply_word_spacing(chunk, &mut clusters);
+
+        if let Some(shape_inside) = shape_inside {
+            let parts = flow_into_shapes(chunk, &clusters, shape_inside, fonts_cache);
+            for (line, (range, x, y)) in parts.into_iter().enumerate() {
+                let (mut line_chunk, spans) = slice_chunk(chunk, range.clone());
+                line_chunk.x = Some(x);
+                line_chunk.y = Some(y);
+                layout_chunks.push(LayoutChunk {
+                    chunk: Cow::Owned(line_chunk),
+                    index,
+                    spans,
+                    byte_offset: range.start,
+                    char_offset: char_offset + chunk.text[..range.start].chars().count(),
+                    line,
+                    block_offset: 0.0,
+                });
+            }
+
+            char_offset += chunk.text.chars().count();
+            continue;
+        }
+
+        let mut block_offset = 0.0;
+        for (line, range) in break_lines(chunk, &clusters, inline_size)
+            .into_iter()
+            .enumerate()
+        {
+            if line != 0 {
+                block_offset += line_height(chunk, range.clone(), fonts_cache);
+            }
+
+            let (line_chunk, spans) = slice_chunk(chunk, range.clone());
+            layout_chunks.push(LayoutChunk {
+                chunk: Cow::Owned(line_chunk),
+                index,
+                spans,
+                byte_offset: range.start,
+                char_offset: char_offset + chunk.text[..range.start].chars().count(),
+                line,
+                block_offset,
+            });
+        }
+
+        char_offset += chunk.text.chars().count();
+    }
+
+    layout_chunks
+}
+
+/// A text between two line break opportunities.
+struct TextSegment {
+    range: std::ops::Range<usize>,
+    /// A segment advance.
+    width: f64,
+    /// A segment advance without trailing spaces, which are allowed to overflow the line.
+    trimmed_width: f64,
+    /// Indicates that the segment ends with a newline, like `\n` or `\r\n`.
+    is_mandatory_break: bool,
+}
+
+/// Splits chunk's text into segments using line break opportunities.
+fn collect_text_segments(chunk: &TextChunk, clusters: &[GlyphCluster]) -> Vec<TextSegment> {
+    let text = &chunk.text;
+    let advance_at = |range: std::ops::Range<usize>| -> f64 {
+        clusters
+            .iter()
+            .filter(|c| range.contains(&c.byte_idx.value()))
+            .map(|c| c.advance)
+            .sum()
+    };
+
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let breaks = line_break_opportunities(text);
+    let last_break = if breaks.last().map(|b| b.0) == Some(text.len()) {
+        None
+    } else {
+        Some((text.len(), false))
+    };
+
+    for (end, is_mandatory_break) in breaks.into_iter().chain(last_break) {
+        let trimmed_end = start + text[start..end].trim_end().len();
+        segments.push(TextSegment {
+            range: start..end,
+            width: advance_at(start..end),
+            trimmed_width: advance_at(start..trimmed_end),
+            is_mandatory_break,
+        });
+
+        start = end;
+    }
+
+    segments
+}
+
+/// Returns a line's end without a newline or trailing spaces.
+fn line_end(text: &str, line_start: usize, last_segment: &TextSegment) -> usize {
+    if last_segment.is_mandatory_break {
+        line_start
+            + text[line_start..last_segment.range.end]
+                .trim_end_matches(is_newline)
+                .len()
+    } else {
+        line_start + text[line_start..last_segment.range.end].trim_end().len()
+    }
+}
+
+/// Splits chunk's text into lines.
+///
+/// Breaks lines at newlines and, when `inline_size` is set, at soft wrap opportunities.
+/// Returns line ranges in bytes. Newlines and spaces at the end of a wrapped line are excluded.
+fn break_lines(
+    chunk: &TextChunk,
+    clusters: &[GlyphCluster],
+    inline_size: Option<f64>,
+) -> Vec<std::ops::Range<usize>> {
+    let segments = collect_text_segments(chunk, clusters);
+
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    let mut line_width = 0.0;
+    for (i, segment) in segments.iter().enumerate() {
+        if let Some(inline_size) = inline_size {
+            if segment.range.start != line_start && line_width + segment.trimmed_width > inline_size
+            {
+                lines.push(line_start..line_end(&chunk.text, line_start, &segments[i - 1]));
+                line_start = segment.range.start;
+                line_width = 0.0;
+            }
+        }
+
+        line_width += segment.width;
+
+        if segment.is_mandatory_break {
+            lines.push(line_start..line_end(&chunk.text, line_start, segment));
+            line_start = segment.range.end;
+            line_width = 0.0;
+        }
+    }
+
+    lines.push(line_start..chunk.text.len());
+    lines
+}
+
+/// Flows chunk's text into shapes, line by line.
+///
+/// Each line can be split into multiple parts by the shape's geometry.
+/// Returns text ranges in bytes and their positions. Text that doesn't fit is not rendered.
+fn flow_into_shapes(
+    chunk: &TextChunk,
+    clusters: &[GlyphCluster],
+    shape_inside: &ShapeInside,
+    fonts_cache: &FontsCache,
+) -> Vec<(std::ops::Range<usize>, f64, f64)> {
+    let segments = collect_text_segments(chunk, clusters);
+    let anchor = resolve_anchor(chunk, WritingMode::LeftToRight);
+
+    let mut parts = Vec::new();
+    let mut idx = 0;
+    for shape in &shape_inside.shapes {
+        let bbox = match shape.path.bbox() {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let polygons = flatten_path(&shape.path);
+        let max_width = bbox.width() - shape_inside.padding * 2.0;
+        let mut top = bbox.top();
+        while idx < segments.len() {
+            let (height, baseline) = line_metrics(chunk, segments[idx].range.start, fonts_cache);
+            if !(height > 0.0) || top + height > bbox.bottom() {
+                break;
+            }
+
+            let intervals = flow_intervals(&polygons, shape_inside, top, top + height);
+            for (left, right) in intervals {
+                let first = idx;
+                let mut width = 0.0;
+                while let Some(segment) = segments.get(idx) {
+                    // A segment that is wider than the shape itself will never fit,
+                    // so it overflows the line instead.
+                    let is_too_wide = segment.trimmed_width > max_width;
+                    if width + segment.trimmed_width > right - left
+                        && !(idx == first && is_too_wide)
+                    {
+                        break;
+                    }
+
+                    width += segment.width;
+                    idx += 1;
+
+                    if segment.is_mandatory_break {
+                        break;
+                    }
+                }
+
+                // Nothing fits this interval.
+                if idx == first {
+                    continue;
+                }
+
+                let start = segments[first].range.start;
+                let last = &segments[idx - 1];
+                let x = match anchor {
+                    TextAnchor::Start => left,
+                    TextAnchor::Middle => (left + right) / 2.0,
+                    TextAnchor::End => right,
+                };
+
+                parts.push((start..line_end(&chunk.text, start, last), x, top + baseline));
+
+                if last.is_mandatory_break {
+                    break;
+                }
+            }
+
+            top += height;
+        }
+    }
+
+    parts
+}
+
+/// Returns line height and baseline offset from the line top for a line
+/// starting at the specified byte offset.
+fn line_metrics(chunk: &TextChunk, byte_offset: usize, fonts_cache: &FontsCache) -> (f64, f64) {
+    let height = line_height(chunk, byte_offset..byte_offset, fonts_cache);
+
+    let span = chunk
+        .spans
+        .iter()
+        .rev()
+        .find(|span| span.start <= byte_offset)
+        .or_else(|| chunk.spans.first());
+    let (ascent, descent) = span
+        .and_then(|span| {
+            let font = fonts_cache.get(&span.font)?;
+            let font_size = span.font_size.get();
+            Some((font.ascent(font_size), font.descent(font_size)))
+        })
+        .unwrap_or((height, 0.0));
+
+    // Extra space is distributed evenly above and below the glyphs.
+    let half_leading = (height - (ascent - descent)) / 2.0;
+    (height, half_leading + ascent)
+}
+
+/// Returns horizontal intervals available for text inside a horizontal band.
+fn flow_intervals(
+    polygons: &[Vec<(f64, f64)>],
+    shape_inside: &ShapeInside,
+    top: f64,
+    bottom: f64,
+) -> Vec<(f64, f64)> {
+    let padding = shape_inside.padding;
+    let mut intervals: Vec<_> = band_intervals(polygons, top - padding, bottom + padding, true)
+        .into_iter()
+        .map(|(l, r)| (l + padding, r - padding))
+        .filter(|(l, r)| l < r)
+        .collect();
+
+    let margin = shape_inside.margin;
+    for shape in &shape_inside.subtract {
+        let polygons = flatten_path(&shape.path);
+        let excluded: Vec<_> = band_intervals(&polygons, top - margin, bottom + margin, false)
+            .into_iter()
+            .map(|(l, r)| (l - margin, r + margin))
+            .collect();
+        intervals = subtract_intervals(&intervals, &excluded);
+    }
+
+    intervals
+}
+
+/// Returns horizontal intervals of a shape inside a horizontal band.
+///
+/// When `inside` is set, returns intervals that are inside the shape for the whole band.
+/// Otherwise, returns intervals that intersect the shape at any point of the band.
+fn band_intervals(
+    polygons: &[Vec<(f64, f64)>],
+    top: f64,
+    bottom: f64,
+    inside: bool,
+) -> Vec<(f64, f64)> {
+    // Edges are straight lines, so it's enough to check the band edges and all the vertices.
+    // Band edges are moved inwards a bit to ignore touching edges.
+    let top = top + 0.001;
+    let bottom = bottom - 0.001;
+    let mut rows = vec![top, bottom];
+    rows.extend(
+        polygons
+            .iter()
+            .flatten()
+            .map(|p| p.1)
+            .filter(|y| *y > top && *y < bottom),
+    );
+
+    let mut result: Option<Vec<(f64, f64)>> = None;
+    for y in rows {
+        let intervals = scanline_intervals(polygons, y);
+        result = Some(match result {
+            None => intervals,
+            Some(prev) if inside => intersect_intervals(&prev, &intervals),
+            Some(prev) => union_intervals(&prev, &intervals),
+        });
+    }
+
+    result.unwrap_or_default()
+}
+
+/// Returns horizontal intervals that are inside the shape using the nonzero fill rule.
+fn scanline_intervals(polygons: &[Vec<(f64, f64)>], y: f64) -> Vec<(f64, f64)> {
+    let mut crossings = Vec::new();
+    for polygon in polygons {
+        for (i, p0) in polygon.iter().enumerate() {
+            let p1 = polygon[(i + 1) % polygon.len()];
+            let winding = if p0.1 <= y && p1.1 > y {
+                1
+            } else if p1.1 <= y && p0.1 > y {
+                -1
+            } else {
+                continue;
+            };
+
+            let x = p0.0 + (y - p0.1) * (p1.0 - p0.0) / (p1.1 - p0.1);
+            crossings.push((x, winding));
+        }
+    }
+
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut intervals = Vec::new();
+    let mut winding = 0;
+    let mut start = 0.0;
+    for (x, w) in crossings {
+        let prev = winding;
+        winding += w;
+        if prev == 0 && winding != 0 {
+            start = x;
+        } else if prev != 0 && winding == 0 && x > start {
+            intervals.push((start, x));
+        }
+    }
+
+    intervals
+}
+
+fn intersect_intervals(a: &[(f64, f64)], b: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut result = Vec::new();
+    for &(l1, r1) in a {
+        for &(l2, r2) in b {
+            let (l, r) = (l1.max(l2), r1.min(r2));
+            if l < r {
+                result.push((l, r));
+            }
+        }
+    }
+
+    result
+}
+
+fn union_intervals(a: &[(f64, f64)], b: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut all: Vec<_> = a.iter().chain(b).cloned().collect();
+    all.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut result: Vec<(f64, f64)> = Vec::new();
+    for (l, r) in all {
+        match result.last_mut() {
+            Some(last) if l <= last.1 => last.1 = last.1.max(r),
+            _ => result.push((l, r)),
+        }
+    }
+
+    result
+}
+
+fn subtract_intervals(a: &[(f64, f64)], b: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut result = a.to_vec();
+    for &(l2, r2) in b {
+        let mut next = Vec::new();
+        for (l1, r1) in result {
+            if l1 < l2 {
+                next.push((l1, r1.min(l2)));
+            }
+
+            if r1 > r2 {
+                next.push((l1.max(r2), r1));
+            }
+        }
+
+        result = next.into_iter().filter(|(l, r)| l < r).collect();
+    }
+
+    result
+}
+
+/// Converts a path into a list of polygons.
+///
+/// Curves are approximated using line segments.
+fn flatten_path(path: &PathData) -> Vec<Vec<(f64, f64)>> {
+    let mut polygons = Vec::new();
+    let mut polygon: Vec<(f64, f64)> = Vec::new();
+    for segment in path.segments() {
+        match segment {
+            PathSegment::MoveTo { x, y } => {
+                if polygon.len() > 2 {
+                    polygons.push(std::mem::take(&mut polygon));
+                }
+
+                polygon.clear();
+                polygon.push((x, y));
+            }
+            PathSegment::LineTo { x, y } => polygon.push((x, y)),
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                let (px, py) = polygon.last().cloned().unwrap_or((x, y));
+                let curve = kurbo::CubicBez::new((px, py), (x1, y1), (x2, y2), (x, y));
+                for i in 1..=16 {
+                    let p = curve.eval(i as f64 / 16.0);
+                    polygon.push((p.x, p.y));
+                }
+            }
+            PathSegment::ClosePath => {
+                if polygon.len() > 2 {
+                    polygons.push(polygon.clone());
+                }
+
+                // A new subpath starts at the same point.
+                polygon.truncate(1);
+            }
+        }
+    }
+
+    if polygon.len() > 2 {
+        polygons.push(polygon);
+    }
+
+    polygons
+}
+
+/// Returns line break opportunities as byte offsets, including mandatory ones.
+///
+/// Uses the [Unicode Line Breaking Algorithm](https://www.unicode.org/reports/tr14/).
+/// The end of the text is a mandatory break only after a newline.
+fn line_break_opportunities(text: &str) -> Vec<(usize, bool)> {
+    unicode_linebreak::linebreaks(text)
+        .map(|(idx, opportunity)| {
+            let is_mandatory = opportunity == unicode_linebreak::BreakOpportunity::Mandatory
+                && text[..idx].ends_with(is_newline);
+            (idx, is_mandatory)
+        })
+        .collect()
+}
+
+/// Checks for characters that end a line, like `\n` or `U+2028`.
+fn is_newline(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{0B}' | '\u{0C}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+/// Returns a line's height.
+///
+/// This is the maximum `line-height` of all the spans in this line.
+fn line_height(chunk: &TextChunk, range: std::ops::Range<usize>, fonts_cache: &FontsCache) -> f64 {
+    let span_height = |span: &TextSpan| -> f64 {
+        span.line_height.unwrap_or_else(|| {
+            fonts_cache
+                .get(&span.font)
+                .map(|font| font.line_height(span.font_size.get()))
+                .unwrap_or(span.font_size.get())
+        })
+    };
+
+    // An empty line uses the previous span.
+    let height = chunk
+        .spans
+        .iter()
+        .filter(|span| span.start < range.end.max(range.start + 1) && span.end > range.start)
+        .map(span_height)
+        .fold(None, |max: Option<f64>, h| {
+            Some(max.map_or(h, |max| max.max(h)))
+        });
+
+    height
+        .or_else(|| {
+            chunk
+                .spans
+                .iter()
+                .rev()
+                .find(|span| span.start <= range.start)
+                .map(span_height)
+        })
+        .unwrap_or(0.0)
+}
+
+/// Creates a new text chunk from a part of the original one.
+///
+/// Returns the new chunk and indices of its spans in the original chunk.
+fn slice_chunk(chunk: &TextChunk, range: std::ops::Range<usize>) -> (TextChunk, Vec<usize>) {
+    let mut spans = Vec::new();
+    let mut indices = Vec::new();
+    for (idx, span) in chunk.spans.iter().enumerate() {
+        let start = span.start.max(range.start);
+        let end = span.end.min(range.end);
+        if start < end {
+            let mut span = span.clone();
+            span.start = start - range.start;
+            span.end = end - range.start;
+            spans.push(span);
+            indices.push(idx);
+        }
+    }
+
+    // Only the first line has an absolute position.
+    let is_first = range.start == 0;
+    let new_chunk = TextChunk {
+        x: if is_first { chunk.x } else { None },
+        y: if is_first { chunk.y } else { None },
+        anchor: chunk.anchor,
+        direction: chunk.direction,
+        spans,
+        text_flow: chunk.text_flow.clone(),
+        text: chunk.text[range].to_string(),
+    };
+
+    (new_chunk, indices)
 }
 
-impl TreeTextToPath for usvg_tree::Tree {
-    fn convert_text(&mut self, fontdb: &fontdb::Database) {
-        convert_text(self.root.clone(), fontdb);
+fn convert_text(
+    root: Node,
+    fontdb: &fontdb::Database,
+    fallback: &FontFallback,
+    fonts: &DocumentFonts,
+    hinting: Option<&TextHinting>,
+    ids: &mut IdGenerator,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut text_nodes = Vec::new();
+    // We have to update text nodes in clipPaths, masks and patterns as well.
+    for node in root.descendants() {
+        if let NodeKind::Text(_) = *node.borrow() {
+            text_nodes.push(node.clone());
+        }
+
+        node.subroots(|subroot| {
+            convert_text(subroot, fontdb, fallback, fonts, hinting, ids, diagnostics)
+        })
+    }
+
+    if text_nodes.is_empty() {
+        return;
+    }
+
+    for node in &text_nodes {
+        let mut new_node = None;
+        if let NodeKind::Text(ref text) = *node.borrow() {
+            let mut absolute_ts = node.parent().unwrap().abs_transform();
+            absolute_ts.append(&text.transform);
+            let layout = layout(text, fontdb, fonts, fallback, absolute_ts);
+            let hinting = hinting
+                .filter(|_| text.rendering_mode == TextRendering::OptimizeLegibility)
+                .map(|hinting| {
+                    let mut ts = hinting.transform;
+                    ts.append(&absolute_ts);
+                    Hinting {
+                        transform: ts,
+                        max_font_size: hinting.max_font_size,
+                    }
+                });
+            new_node = convert_layout(text, &layout, fontdb, fonts, hinting.as_ref(), ids);
+            diagnostics.extend(layout.diagnostics);
+        }
+
+        if let Some(new_node) = new_node {
+            node.insert_after(new_node);
+        }
+    }
+
+    text_nodes.iter().for_each(|n| n.detach());
+}
+
+/// Resolved hinting options of a text node.
+struct Hinting {
+    /// A transform from the text node coordinates to device pixels.
+    transform: Transform,
+    max_font_size: f64,
+}
+
+/// Code points supported by a font face.
+type UnicodeRange = (ID, Vec<std::ops::RangeInclusive<u32>>);
+
+/// Fonts defined by a document: `Tree::font_faces` and `Tree::svg_fonts`.
+///
+/// The fonts database is never modified or copied. Document faces are stored separately
+/// and their families replace the fonts database families with the same name.
+///
+/// Document face IDs never match the fonts database ones, so a [`TextLayout`] can reference
+/// faces from both. Use [`DocumentFonts::face`] and [`DocumentFonts::with_face_data`]
+/// to access them. SVG fonts are represented by faces without font data.
+///
+/// `Default` creates an instance without document fonts.
+#[derive(Default, Debug)]
+pub struct DocumentFonts<'a> {
+    /// `@font-face` faces and SVG fonts placeholder faces.
+    ///
+    /// Their IDs never match the fonts database ones, see [`push_document_face`].
+    database: Database,
+    /// Families defined by the document.
+    families: Vec<String>,
+    /// `@font-face` faces that declare a `unicode-range`, in the document order.
+    unicode_ranges: Vec<UnicodeRange>,
+    /// SVG fonts by their placeholder faces.
+    svg_fonts: HashMap<ID, &'a SvgFont>,
+    /// A cache for the fonts database faces.
+    cache: Option<&'a GlyphCache>,
+}
+
+impl<'a> DocumentFonts<'a> {
+    /// Loads tree's document fonts.
+    ///
+    /// Must be used only with the same fonts database.
+    pub fn new(fontdb: &Database, tree: &'a usvg_tree::Tree) -> Self {
+        Self::from_fonts(fontdb, &tree.font_faces, &tree.svg_fonts)
+    }
+
+    fn from_fonts(fontdb: &Database, faces: &[FontFace], svg_fonts: &'a [SvgFont]) -> Self {
+        let mut fonts = DocumentFonts::default();
+        if faces.is_empty() && svg_fonts.is_empty() {
+            return fonts;
+        }
+
+        fonts.families = faces
+            .iter()
+            .map(|face| face.family.clone())
+            .chain(svg_fonts.iter().map(|font| font.family.clone()))
+            .collect();
+
+        for face in faces {
+            // Parse the font metadata, like stretch, using a temporary database.
+            let mut tmp_db = Database::new();
+            tmp_db.load_font_source(fontdb::Source::Binary(face.data.clone()));
+
+            for info in tmp_db.faces() {
+                let mut info = info.clone();
+                info.families = vec![(face.family.clone(), fontdb::Language::English_UnitedStates)];
+                info.weight = fontdb::Weight(face.weight);
+                info.style = convert_style(face.style);
+
+                if let Some(id) = push_document_face(&mut fonts.database, fontdb, info) {
+                    if !face.unicode_range.is_empty() {
+                        fonts.unicode_ranges.push((id, face.unicode_range.clone()));
+                    }
+                }
+            }
+        }
+
+        for font in svg_fonts {
+            // SVG fonts have no font data, so only the metadata is used for font matching.
+            let info = fontdb::FaceInfo {
+                id: ID::dummy(),
+                source: fontdb::Source::Binary(Arc::new(Vec::<u8>::new())),
+                index: 0,
+                families: vec![(font.family.clone(), fontdb::Language::English_UnitedStates)],
+                post_script_name: String::new(),
+                style: convert_style(font.style),
+                weight: fontdb::Weight(font.weight),
+                stretch: fontdb::Stretch::Normal,
+                monospaced: false,
+            };
+
+            if let Some(id) = push_document_face(&mut fonts.database, fontdb, info) {
+                fonts.svg_fonts.insert(id, font);
+            }
+        }
+
+        fonts
     }
-}
 
-/// A `usvg::Text` extension trait.
-pub trait TextToPath {
-    /// Converts the text node into path(s).
+    /// Returns a database that contains the face.
+    fn database<'b>(&'b self, fontdb: &'b Database, id: ID) -> &'b Database {
+        if self.database.face(id).is_some() {
+            &self.database
+        } else {
+            fontdb
+        }
+    }
+
+    /// Selects a face from the document fonts or from the fonts database.
+    pub fn face<'b>(&'b self, fontdb: &'b Database, id: ID) -> Option<&'b fontdb::FaceInfo> {
+        self.database(fontdb, id).face(id)
+    }
+
+    /// Executes a closure with a font's data from the document fonts or from the fonts database.
     ///
-    /// `absolute_ts` is node's absolute transform. Used primarily during text-on-path resolving.
-    fn convert(&self, fontdb: &fontdb::Database, absolute_ts: Transform) -> Option<Node>;
-}
+    /// Returns `None` when font data loading failed.
+    pub fn with_face_data<P, T>(&self, fontdb: &Database, id: ID, p: P) -> Option<T>
+    where
+        P: FnOnce(&[u8], u32) -> T,
+    {
+        self.database(fontdb, id).with_face_data(id, p)
+    }
 
-impl TextToPath for Text {
-    fn convert(&self, fontdb: &fontdb::Database, absolute_ts: Transform) -> Option<Node> {
-        let (new_paths, bbox) = text_to_paths(self, fontdb, absolute_ts);
-        if new_paths.is_empty() {
-            return None;
+    /// Returns faces from both databases.
+    ///
+    /// The fonts database faces of families defined by the document are skipped.
+    fn faces<'b>(&'b self, fontdb: &'b Database) -> impl Iterator<Item = &'b fontdb::FaceInfo> {
+        fontdb
+            .faces()
+            .filter(move |face| !face.families.iter().any(|(name, _)| self.has_family(name)))
+            .chain(self.database.faces())
+    }
+
+    /// Performs a CSS-like query over both databases.
+    ///
+    /// Families defined by the document are looked up only in the document faces.
+    fn query(&self, fontdb: &Database, query: &fontdb::Query) -> Option<ID> {
+        for family in query.families {
+            // Generic families are resolved by the fonts database.
+            let families = [fontdb::Family::Name(fontdb.family_name(family))];
+            let family_query = fontdb::Query {
+                families: &families,
+                ..*query
+            };
+
+            let id = if self.has_family(fontdb.family_name(family)) {
+                self.database.query(&family_query)
+            } else {
+                fontdb.query(&family_query)
+            };
+
+            if id.is_some() {
+                return id;
+            }
         }
 
-        // Create a group will all paths that was created during text-to-path conversion.
-        let group = Node::new(NodeKind::Group(Group {
-            id: self.id.clone(),
-            transform: self.transform,
-            ..Group::default()
-        }));
+        None
+    }
 
-        let rendering_mode = resolve_rendering_mode(self);
-        for mut path in new_paths {
-            fix_obj_bounding_box(&mut path, bbox);
-            path.rendering_mode = rendering_mode;
-            group.append_kind(NodeKind::Path(path));
+    fn has_family(&self, name: &str) -> bool {
+        self.families
+            .iter()
+            .any(|family| family.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns a cache for a fonts database face.
+    ///
+    /// Document faces are never cached, since their IDs are not stable between documents.
+    fn face_cache(&self, id: ID) -> Option<&'a GlyphCache> {
+        self.cache.filter(|_| self.database.face(id).is_none())
+    }
+
+    fn load_font(&self, fontdb: &Database, id: ID) -> Option<ResolvedFont> {
+        if let Some(font) = self.svg_fonts.get(&id) {
+            return svg_font::resolve_font(font, id);
         }
 
-        Some(group)
+        match self.face_cache(id) {
+            Some(cache) => cache.load_font(fontdb, id),
+            None => self.database(fontdb, id).load_font(id),
+        }
     }
-}
 
-fn convert_text(root: Node, fontdb: &fontdb::Database) {
-    let mut text_nodes = Vec::new();
-    // We have to update text nodes in clipPaths, masks and patterns as well.
-    for node in root.descendants() {
-        if let NodeKind::Text(_) = *node.borrow() {
-            text_nodes.push(node.clone());
+    /// Checks that a face has all grapheme's characters.
+    fn has_grapheme(&self, fontdb: &Database, id: ID, grapheme: &str) -> bool {
+        // Joiners and selectors are usually not present in fonts and are ignored by shaping.
+        let chars: Vec<_> = grapheme
+            .chars()
+            .filter(|c| !is_default_ignorable(*c))
+            .collect();
+
+        match self.face_cache(id) {
+            Some(cache) => cache.has_chars(fontdb, id, &chars),
+            None => {
+                let res = self.database(fontdb, id).has_chars(id, &chars);
+                res.map(|res| res.into_iter().all(|v| v)) == Some(true)
+            }
         }
+    }
 
-        node.subroots(|subroot| convert_text(subroot, fontdb))
+    fn shape(
+        &self,
+        fontdb: &Database,
+        id: ID,
+        request: &ShapingRequest,
+    ) -> Option<Rc<[ShapedGlyph]>> {
+        match self.face_cache(id) {
+            Some(cache) => cache.shape(fontdb, id, request),
+            None => self
+                .database(fontdb, id)
+                .with_face_data(id, |data, face_index| shape_face(data, face_index, request))?
+                .map(Rc::from),
+        }
     }
 
-    if text_nodes.is_empty() {
-        return;
+    fn outline(
+        &self,
+        fontdb: &Database,
+        id: ID,
+        glyph_id: GlyphId,
+        variations: &[FontVariation],
+    ) -> Option<PathData> {
+        if let Some(font) = self.svg_fonts.get(&id) {
+            return svg_font::outline(font, glyph_id);
+        }
+
+        match self.face_cache(id) {
+            Some(cache) => cache.outline(fontdb, id, glyph_id, variations),
+            None => self.database(fontdb, id).outline(id, glyph_id, variations),
+        }
     }
+}
 
-    for node in &text_nodes {
-        let mut new_node = None;
-        if let NodeKind::Text(ref text) = *node.borrow() {
-            let mut absolute_ts = node.parent().unwrap().abs_transform();
-            absolute_ts.append(&text.transform);
-            new_node = text.convert(fontdb, absolute_ts);
+/// Adds a face to the document fonts database and returns its ID.
+///
+/// IDs of different databases can be equal. So when a new ID is present
+/// in the fonts database, the face is removed and added again,
+/// which produces a new ID, until it becomes unique across both databases.
+fn push_document_face(
+    database: &mut Database,
+    fontdb: &Database,
+    info: fontdb::FaceInfo,
+) -> Option<ID> {
+    loop {
+        let id = push_face(database, info.clone())?;
+        if fontdb.face(id).is_none() {
+            return Some(id);
         }
 
-        if let Some(new_node) = new_node {
-            node.insert_after(new_node);
+        database.remove_face(id);
+    }
+}
+
+/// Adds a face to the database and returns its ID.
+fn push_face(fontdb: &mut Database, info: fontdb::FaceInfo) -> Option<ID> {
+    // `push_face_info` doesn't return an ID, so find it by the new face source.
+    let ids_before: HashSet<ID> = fontdb.faces().map(|info| info.id).collect();
+    fontdb.push_face_info(info);
+    fontdb
+        .faces()
+        .find(|info| !ids_before.contains(&info.id))
+        .map(|info| info.id)
+}
+
+/// Generates unique IDs for gradients and clip paths created from color glyphs.
+#[derive(Default)]
+struct IdGenerator {
+    all_ids: HashSet<String>,
+    clip_path_index: usize,
+    gradient_index: usize,
+}
+
+impl IdGenerator {
+    fn new(tree: &usvg_tree::Tree) -> Self {
+        let mut all_ids = HashSet::new();
+        for node in tree.root.descendants() {
+            if !node.id().is_empty() {
+                all_ids.insert(node.id().to_string());
+            }
+        }
+
+        tree.paint_servers(|paint| match paint {
+            Paint::Color(_) => {}
+            Paint::LinearGradient(ref lg) => {
+                all_ids.insert(lg.id.clone());
+            }
+            Paint::RadialGradient(ref rg) => {
+                all_ids.insert(rg.id.clone());
+            }
+            Paint::Pattern(ref patt) => {
+                all_ids.insert(patt.id.clone());
+            }
+        });
+        tree.clip_paths(|clip| {
+            all_ids.insert(clip.id.clone());
+        });
+        tree.masks(|mask| {
+            all_ids.insert(mask.id.clone());
+        });
+        tree.filters(|filter| {
+            all_ids.insert(filter.id.clone());
+        });
+
+        IdGenerator {
+            all_ids,
+            ..IdGenerator::default()
         }
     }
 
-    text_nodes.iter().for_each(|n| n.detach());
+    fn gen_clip_path_id(&mut self) -> String {
+        loop {
+            self.clip_path_index += 1;
+            let new_id = format!("glyphClipPath{}", self.clip_path_index);
+            if self.all_ids.insert(new_id.clone()) {
+                return new_id;
+            }
+        }
+    }
+
+    fn gen_gradient_id(&mut self) -> String {
+        loop {
+            self.gradient_index += 1;
+            let new_id = format!("glyphGradient{}", self.gradient_index);
+            if self.all_ids.insert(new_id.clone()) {
+                return new_id;
+            }
+        }
+    }
 }
 
 trait DatabaseExt {
     fn load_font(&self, id: ID) -> Option<ResolvedFont>;
-    fn outline(&self, id: ID, glyph_id: GlyphId) -> Option<PathData>;
-    fn has_char(&self, id: ID, c: char) -> bool;
+    fn outline(&self, id: ID, glyph_id: GlyphId, variations: &[FontVariation]) -> Option<PathData>;
+    fn color_glyph(
+        &self,
+        id: ID,
+        glyph_id: GlyphId,
+        variations: &[FontVariation],
+        foreground: Option<&Fill>,
+        ids: &mut IdGenerator,
+    ) -> Option<Node>;
+    fn has_chars(&self, id: ID, chars: &[char]) -> Option<Vec<bool>>;
 }
 
 impl DatabaseExt for Database {
     #[inline(never)]
     fn load_font(&self, id: ID) -> Option<ResolvedFont> {
-        self.with_face_data(id, |data, face_index| -> Option<ResolvedFont> {
-            let font = ttf_parser::Face::parse(data, face_index).ok()?;
-
-            let units_per_em = NonZeroU16::new(font.units_per_em())?;
-
-            let ascent = font.ascender();
-            let descent = font.descender();
-
-            let x_height = font
-                .x_height()
-                .and_then(|x| u16::try_from(x).ok())
-                .and_then(NonZeroU16::new);
-            let x_height = match x_height {
-                Some(height) => height,
-                None => {
-                    // If not set - fallback to height * 45%.
-                    // 45% is what Firefox uses.
-                    u16::try_from((f32::from(ascent - descent) * 0.45) as i32)
-                        .ok()
-                        .and_then(NonZeroU16::new)?
-                }
-            };
-
-            let line_through = font.strikeout_metrics();
-            let line_through_position = match line_through {
-                Some(metrics) => metrics.position,
-                None => x_height.get() as i16 / 2,
-            };
+        self.with_face_data(id, |data, face_index| {
+            parse_font_metrics(id, data, face_index)
+        })?
+    }
 
-            let (underline_position, underline_thickness) = match font.underline_metrics() {
-                Some(metrics) => {
-                    let thickness = u16::try_from(metrics.thickness)
-                        .ok()
-                        .and_then(NonZeroU16::new)
-                        // `ttf_parser` guarantees that units_per_em is >= 16
-                        .unwrap_or_else(|| NonZeroU16::new(units_per_em.get() / 12).unwrap());
+    #[inline(never)]
+    fn outline(&self, id: ID, glyph_id: GlyphId, variations: &[FontVariation]) -> Option<PathData> {
+        self.with_face_data(id, |data, face_index| {
+            outline_glyph(data, face_index, glyph_id, variations)
+        })?
+    }
 
-                    (metrics.position, thickness)
+    #[inline(never)]
+    fn color_glyph(
+        &self,
+        id: ID,
+        glyph_id: GlyphId,
+        variations: &[FontVariation],
+        foreground: Option<&Fill>,
+        ids: &mut IdGenerator,
+    ) -> Option<Node> {
+        self.with_face_data(id, |data, face_index| -> Option<Node> {
+            let mut font = ttf_parser::Face::parse(data, face_index).ok()?;
+            if font.is_variable() {
+                for v in variations {
+                    font.set_variation(ttf_parser::Tag::from_bytes(&v.tag), v.value);
                 }
-                None => (
-                    -(units_per_em.get() as i16) / 9,
-                    NonZeroU16::new(units_per_em.get() / 12).unwrap(),
-                ),
-            };
-
-            // 0.2 and 0.4 are generic offsets used by some applications (Inkscape/librsvg).
-            let mut subscript_offset = (units_per_em.get() as f32 / 0.2).round() as i16;
-            let mut superscript_offset = (units_per_em.get() as f32 / 0.4).round() as i16;
-            if let Some(metrics) = font.subscript_metrics() {
-                subscript_offset = metrics.y_offset;
-            }
-
-            if let Some(metrics) = font.superscript_metrics() {
-                superscript_offset = metrics.y_offset;
             }
 
-            Some(ResolvedFont {
-                id,
-                units_per_em,
-                ascent,
-                descent,
-                x_height,
-                underline_position,
-                underline_thickness,
-                line_through_position,
-                subscript_offset,
-                superscript_offset,
-            })
+            colr::convert_glyph(&font, glyph_id, foreground, ids)
+                .or_else(|| svg_glyph(&font, glyph_id, foreground))
+                .or_else(|| raster_glyph(&font, glyph_id))
         })?
     }
 
     #[inline(never)]
-    fn outline(&self, id: ID, glyph_id: GlyphId) -> Option<PathData> {
-        self.with_face_data(id, |data, face_index| -> Option<PathData> {
-            let font = ttf_parser::Face::parse(data, face_index).ok()?;
+    fn has_chars(&self, id: ID, chars: &[char]) -> Option<Vec<bool>> {
+        self.with_face_data(id, |data, face_index| check_chars(data, face_index, chars))?
+    }
+}
 
-            let mut builder = PathBuilder {
-                path: PathData::new(),
-            };
-            font.outline_glyph(glyph_id, &mut builder)?;
-            Some(builder.path)
-        })?
+/// Resolves face metrics.
+fn parse_font_metrics(id: ID, data: &[u8], face_index: u32) -> Option<ResolvedFont> {
+    let font = ttf_parser::Face::parse(data, face_index).ok()?;
+
+    let units_per_em = NonZeroU16::new(font.units_per_em())?;
+
+    let ascent = font.ascender();
+    let descent = font.descender();
+    let line_gap = font.line_gap();
+
+    let x_height = font
+        .x_height()
+        .and_then(|x| u16::try_from(x).ok())
+        .and_then(NonZeroU16::new);
+    let x_height = match x_height {
+        Some(height) => height,
+        None => {
+            // If not set - fallback to height * 45%.
+            // 45% is what Firefox uses.
+            u16::try_from((f32::from(ascent - descent) * 0.45) as i32)
+                .ok()
+                .and_then(NonZeroU16::new)?
+        }
+    };
+
+    // If not set - fallback to 70% of em, which is typical for Latin fonts.
+    let cap_height = font
+        .capital_height()
+        .and_then(|x| u16::try_from(x).ok())
+        .and_then(NonZeroU16::new)
+        .unwrap_or_else(|| NonZeroU16::new(units_per_em.get() / 10 * 7).unwrap());
+
+    let line_through = font.strikeout_metrics();
+    let line_through_position = match line_through {
+        Some(metrics) => metrics.position,
+        None => x_height.get() as i16 / 2,
+    };
+
+    let (underline_position, underline_thickness) = match font.underline_metrics() {
+        Some(metrics) => {
+            let thickness = u16::try_from(metrics.thickness)
+                .ok()
+                .and_then(NonZeroU16::new)
+                // `ttf_parser` guarantees that units_per_em is >= 16
+                .unwrap_or_else(|| NonZeroU16::new(units_per_em.get() / 12).unwrap());
+
+            (metrics.position, thickness)
+        }
+        None => (
+            -(units_per_em.get() as i16) / 9,
+            NonZeroU16::new(units_per_em.get() / 12).unwrap(),
+        ),
+    };
+
+    // 0.2 and 0.4 are generic offsets used by some applications (Inkscape/librsvg).
+    let mut subscript_offset = (units_per_em.get() as f32 / 0.2).round() as i16;
+    let mut superscript_offset = (units_per_em.get() as f32 / 0.4).round() as i16;
+    if let Some(metrics) = font.subscript_metrics() {
+        subscript_offset = metrics.y_offset;
     }
 
-    #[inline(never)]
-    fn has_char(&self, id: ID, c: char) -> bool {
-        let res = self.with_face_data(id, |font_data, face_index| -> Option<bool> {
-            let font = ttf_parser::Face::parse(font_data, face_index).ok()?;
-            font.glyph_index(c)?;
-            Some(true)
-        });
+    if let Some(metrics) = font.superscript_metrics() {
+        superscript_offset = metrics.y_offset;
+    }
+
+    Some(ResolvedFont {
+        id,
+        units_per_em,
+        ascent,
+        descent,
+        line_gap,
+        x_height,
+        cap_height,
+        underline_position,
+        underline_thickness,
+        line_through_position,
+        subscript_offset,
+        superscript_offset,
+        synthetic_bold: false,
+        synthetic_oblique: false,
+    })
+}
+
+/// Outlines a glyph in font units.
+fn outline_glyph(
+    data: &[u8],
+    face_index: u32,
+    glyph_id: GlyphId,
+    variations: &[FontVariation],
+) -> Option<PathData> {
+    let mut font = ttf_parser::Face::parse(data, face_index).ok()?;
+    if font.is_variable() {
+        for v in variations {
+            // Axes that are not present in the font are ignored.
+            font.set_variation(ttf_parser::Tag::from_bytes(&v.tag), v.value);
+        }
+    }
 
-        res == Some(Some(true))
+    let mut builder = PathBuilder {
+        path: PathData::new(),
+    };
+    font.outline_glyph(glyph_id, &mut builder)?;
+    Some(builder.path)
+}
+
+/// Checks which characters are present in a face.
+fn check_chars(data: &[u8], face_index: u32, chars: &[char]) -> Option<Vec<bool>> {
+    let font = ttf_parser::Face::parse(data, face_index).ok()?;
+    Some(
+        chars
+            .iter()
+            .map(|c| font.glyph_index(*c).is_some())
+            .collect(),
+    )
+}
+
+/// Converts an OpenType-SVG glyph into an image.
+///
+/// The image is in font units with the Y axis pointing up, just like glyph outlines.
+fn svg_glyph(
+    face: &ttf_parser::Face,
+    glyph_id: GlyphId,
+    foreground: Option<&Fill>,
+) -> Option<Node> {
+    use usvg_parser::TreeParsing;
+
+    let data = face.glyph_svg_image(glyph_id)?;
+
+    // `currentColor` inside the glyph is the text color.
+    let color = match foreground {
+        Some(Fill {
+            paint: Paint::Color(c),
+            ..
+        }) => Some(format!("#{:02x}{:02x}{:02x}", c.red, c.green, c.blue)),
+        _ => None,
+    };
+    // Glyphs are untrusted and have no base location,
+    // so external images are never loaded. Data URLs are still allowed.
+    let opt = usvg_parser::Options {
+        resources_dir: None,
+        image_href_resolver: usvg_parser::ImageHrefResolver {
+            resolve_string: Box::new(|_, _| None),
+            ..usvg_parser::ImageHrefResolver::default()
+        },
+        color,
+        ..usvg_parser::Options::default()
+    };
+
+    let mut tree = usvg_tree::Tree::from_data(data, &opt).ok()?;
+
+    // A single document can contain multiple glyphs.
+    // When the requested one is missing, the outline should be used instead.
+    let glyph = tree.node_by_id(&format!("glyph{}", glyph_id.0))?;
+    let path: Vec<_> = glyph.ancestors().collect();
+    for node in path {
+        while let Some(sibling) = node.previous_sibling() {
+            sibling.detach();
+        }
+
+        while let Some(sibling) = node.next_sibling() {
+            sibling.detach();
+        }
+    }
+
+    // Glyph documents are in font units with the Y axis pointing down
+    // and the origin at the baseline. The document's own viewport is ignored.
+    let units_per_em = face.units_per_em() as f64;
+    let rect = Rect::new(0.0, -units_per_em, units_per_em, units_per_em)?;
+    let view_box = ViewBox {
+        rect,
+        aspect: AspectRatio::default(),
+    };
+    tree.size = rect.size();
+    tree.view_box = view_box;
+
+    Some(Node::new(NodeKind::Image(Image {
+        id: String::new(),
+        transform: Transform::new_scale(1.0, -1.0),
+        visibility: Visibility::Visible,
+        view_box,
+        rendering_mode: ImageRendering::default(),
+        kind: ImageKind::SVG(tree),
+    })))
+}
+
+/// Converts an `sbix` or `CBDT` glyph into an image.
+///
+/// The image is in font units with the Y axis pointing up, just like glyph outlines.
+fn raster_glyph(face: &ttf_parser::Face, glyph_id: GlyphId) -> Option<Node> {
+    // Use the largest strike, since we don't know the final scale.
+    let image = face.glyph_raster_image(glyph_id, u16::MAX)?;
+    if image.format != ttf_parser::RasterImageFormat::PNG || image.pixels_per_em == 0 {
+        return None;
     }
+
+    // Image metrics are in pixels and `y` is the bottom edge.
+    let scale = face.units_per_em() as f64 / image.pixels_per_em as f64;
+    let top = (image.y as f64 + image.height as f64) * scale;
+    let transform = Transform::new(scale, 0.0, 0.0, -scale, image.x as f64 * scale, top);
+
+    Some(Node::new(NodeKind::Image(Image {
+        id: String::new(),
+        transform,
+        visibility: Visibility::Visible,
+        view_box: ViewBox {
+            rect: Rect::new(0.0, 0.0, image.width as f64, image.height as f64)?,
+            aspect: AspectRatio::default(),
+        },
+        rendering_mode: ImageRendering::default(),
+        kind: ImageKind::PNG(std::sync::Arc::new(image.data.to_vec())),
+    })))
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -225,7 +1866,9 @@ struct ResolvedFont {
     // All values below are in font units.
     ascent: i16,
     descent: i16,
+    line_gap: i16,
     x_height: NonZeroU16,
+    cap_height: NonZeroU16,
 
     underline_position: i16,
     underline_thickness: NonZeroU16,
@@ -237,6 +1880,9 @@ struct ResolvedFont {
 
     subscript_offset: i16,
     superscript_offset: i16,
+
+    synthetic_bold: bool,
+    synthetic_oblique: bool,
 }
 
 impl ResolvedFont {
@@ -260,6 +1906,12 @@ impl ResolvedFont {
         self.ascent(font_size) - self.descent(font_size)
     }
 
+    /// Returns the `normal` line height.
+    #[inline]
+    fn line_height(&self, font_size: f64) -> f64 {
+        self.height(font_size) + self.line_gap as f64 * self.scale(font_size)
+    }
+
     #[inline]
     fn x_height(&self, font_size: f64) -> f64 {
         self.x_height.get() as f64 * self.scale(font_size)
@@ -423,211 +2075,132 @@ fn resolve_rendering_mode(text: &Text) -> ShapeRendering {
     }
 }
 
-fn chunk_span_at(chunk: &TextChunk, byte_offset: ByteIndex) -> Option<&TextSpan> {
-    chunk
-        .spans
-        .iter()
-        .find(|&span| span_contains(span, byte_offset))
-}
-
-fn span_contains(span: &TextSpan, byte_offset: ByteIndex) -> bool {
-    byte_offset.value() >= span.start && byte_offset.value() < span.end
-}
-
-// Baseline resolving in SVG is a mess.
-// Not only it's poorly documented, but as soon as you start mixing
-// `dominant-baseline` and `alignment-baseline` each application/browser will produce
-// different results.
-//
-// For now, resvg simply tries to match Chrome's output and not the mythical SVG spec output.
-//
-// See `alignment_baseline_shift` method comment for more details.
-fn resolve_baseline(span: &TextSpan, font: &ResolvedFont, writing_mode: WritingMode) -> f64 {
-    let mut shift = -resolve_baseline_shift(&span.baseline_shift, font, span.font_size.get());
-
-    // TODO: support vertical layout as well
-    if writing_mode == WritingMode::LeftToRight {
-        if span.alignment_baseline == AlignmentBaseline::Auto
-            || span.alignment_baseline == AlignmentBaseline::Baseline
-        {
-            shift += font.dominant_baseline_shift(span.dominant_baseline, span.font_size.get());
-        } else {
-            shift += font.alignment_baseline_shift(span.alignment_baseline, span.font_size.get());
-        }
-    }
-
-    shift
-}
-
-type FontsCache = HashMap<Font, Rc<ResolvedFont>>;
-
-fn text_to_paths(
-    text_node: &Text,
-    fontdb: &fontdb::Database,
-    abs_ts: Transform,
-) -> (Vec<Path>, PathBbox) {
-    let mut fonts_cache: FontsCache = HashMap::new();
-    for chunk in &text_node.chunks {
-        for span in &chunk.spans {
-            if !fonts_cache.contains_key(&span.font) {
-                if let Some(font) = resolve_font(&span.font, fontdb) {
-                    fonts_cache.insert(span.font.clone(), Rc::new(font));
-                }
-            }
-        }
-    }
-
-    let mut bbox = PathBbox::new_bbox();
-    let mut char_offset = 0;
-    let mut last_x = 0.0;
-    let mut last_y = 0.0;
-    let mut new_paths = Vec::new();
-    for chunk in &text_node.chunks {
-        let (x, y) = match chunk.text_flow {
-            TextFlow::Linear => (chunk.x.unwrap_or(last_x), chunk.y.unwrap_or(last_y)),
-            TextFlow::Path(_) => (0.0, 0.0),
-        };
-
-        let mut clusters = outline_chunk(chunk, &fonts_cache, fontdb);
-        if clusters.is_empty() {
-            char_offset += chunk.text.chars().count();
-            continue;
-        }
-
-        apply_writing_mode(text_node.writing_mode, &mut clusters);
-        apply_letter_spacing(chunk, &mut clusters);
-        apply_word_spacing(chunk, &mut clusters);
-        apply_length_adjust(chunk, &mut clusters);
-        let mut curr_pos = resolve_clusters_positions(
-            chunk,
-            char_offset,
-            &text_node.positions,
-            &text_node.rotate,
-            text_node.writing_mode,
-            abs_ts,
-            &fonts_cache,
-            &mut clusters,
-        );
-
-        let mut text_ts = Transform::default();
-        if text_node.writing_mode == WritingMode::TopToBottom {
-            if let TextFlow::Linear = chunk.text_flow {
-                text_ts.rotate_at(90.0, x, y);
-            }
-        }
-
-        for span in &chunk.spans {
-            let font = match fonts_cache.get(&span.font) {
-                Some(v) => v,
-                None => continue,
-            };
-
-            let decoration_spans = collect_decoration_spans(span, &clusters);
-
-            let mut span_ts = text_ts;
-            span_ts.translate(x, y);
-            if let TextFlow::Linear = chunk.text_flow {
-                let shift = resolve_baseline(span, font, text_node.writing_mode);
-
-                // In case of a horizontal flow, shift transform and not clusters,
-                // because clusters can be rotated and an additional shift will lead
-                // to invalid results.
-                span_ts.translate(0.0, shift);
-            }
-
-            if let Some(decoration) = span.decoration.underline.clone() {
-                // TODO: No idea what offset should be used for top-to-bottom layout.
-                // There is
-                // https://www.w3.org/TR/css-text-decor-3/#text-underline-position-property
-                // but it doesn't go into details.
-                let offset = match text_node.writing_mode {
-                    WritingMode::LeftToRight => -font.underline_position(span.font_size.get()),
-                    WritingMode::TopToBottom => font.height(span.font_size.get()) / 2.0,
-                };
+fn chunk_span_at(chunk: &TextChunk, byte_offset: ByteIndex) -> Option<&TextSpan> {
+    chunk
+        .spans
+        .iter()
+        .find(|&span| span_contains(span, byte_offset))
+}
 
-                let path =
-                    convert_decoration(offset, span, font, decoration, &decoration_spans, span_ts);
+fn span_contains(span: &TextSpan, byte_offset: ByteIndex) -> bool {
+    byte_offset.value() >= span.start && byte_offset.value() < span.end
+}
 
-                if let Some(r) = path.data.bbox() {
-                    bbox = bbox.expand(r);
-                }
+// Baseline resolving in SVG is a mess.
+// Not only it's poorly documented, but as soon as you start mixing
+// `dominant-baseline` and `alignment-baseline` each application/browser will produce
+// different results.
+//
+// For now, resvg simply tries to match Chrome's output and not the mythical SVG spec output.
+//
+// See `alignment_baseline_shift` method comment for more details.
+fn resolve_baseline(span: &TextSpan, font: &ResolvedFont, writing_mode: WritingMode) -> f64 {
+    let mut shift = -resolve_baseline_shift(&span.baseline_shift, font, span.font_size.get());
 
-                new_paths.push(path);
-            }
+    // TODO: support vertical layout as well
+    if writing_mode == WritingMode::LeftToRight {
+        if span.alignment_baseline == AlignmentBaseline::Auto
+            || span.alignment_baseline == AlignmentBaseline::Baseline
+        {
+            shift += font.dominant_baseline_shift(span.dominant_baseline, span.font_size.get());
+        } else {
+            shift += font.alignment_baseline_shift(span.alignment_baseline, span.font_size.get());
+        }
+    }
 
-            if let Some(decoration) = span.decoration.overline.clone() {
-                let offset = match text_node.writing_mode {
-                    WritingMode::LeftToRight => -font.ascent(span.font_size.get()),
-                    WritingMode::TopToBottom => -font.height(span.font_size.get()) / 2.0,
-                };
+    shift
+}
 
-                let path =
-                    convert_decoration(offset, span, font, decoration, &decoration_spans, span_ts);
+type FontsCache = HashMap<Font, Rc<ResolvedFont>>;
 
+fn text_to_nodes(
+    text_node: &Text,
+    layout: &TextLayout,
+    fontdb: &fontdb::Database,
+    fonts: &DocumentFonts,
+    hinting: Option<&Hinting>,
+    ids: &mut IdGenerator,
+) -> (Vec<Node>, PathBbox) {
+    let mut bbox = PathBbox::new_bbox();
+    let mut new_nodes = Vec::new();
+    for fragment in &layout.fragments {
+        let span = &text_node.chunks[fragment.chunk].spans[fragment.span];
+
+        let decorations = [
+            (&fragment.underline, &span.decoration.underline),
+            (&fragment.overline, &span.decoration.overline),
+        ];
+        for (data, decoration) in decorations.iter() {
+            if let (Some(data), Some(decoration)) = (data, decoration) {
+                let path = convert_decoration(data, span, decoration.clone());
                 if let Some(r) = path.data.bbox() {
                     bbox = bbox.expand(r);
                 }
 
-                new_paths.push(path);
+                new_nodes.push(Node::new(NodeKind::Path(path)));
             }
+        }
 
-            if let Some(path) = convert_span(span, &mut clusters, &span_ts) {
-                // Use `text_bbox` here and not `path.data.bbox()`.
-                if let Some(r) = path.text_bbox {
-                    bbox = bbox.expand(r.to_path_bbox());
-                }
-
-                new_paths.push(path);
+        let (path, color_glyphs) = convert_span(span, fragment, fontdb, fonts, hinting, ids);
+        if let Some(path) = path {
+            // Use `text_bbox` here and not `path.data.bbox()`.
+            if let Some(r) = path.text_bbox {
+                bbox = bbox.expand(r.to_path_bbox());
             }
 
-            if let Some(decoration) = span.decoration.line_through.clone() {
-                let offset = match text_node.writing_mode {
-                    WritingMode::LeftToRight => -font.line_through_position(span.font_size.get()),
-                    WritingMode::TopToBottom => 0.0,
-                };
-
-                let path =
-                    convert_decoration(offset, span, font, decoration, &decoration_spans, span_ts);
-
-                if let Some(r) = path.data.bbox() {
-                    bbox = bbox.expand(r);
-                }
-
-                new_paths.push(path);
-            }
+            new_nodes.push(Node::new(NodeKind::Path(path)));
         }
 
-        char_offset += chunk.text.chars().count();
+        new_nodes.extend(color_glyphs);
 
-        if text_node.writing_mode == WritingMode::TopToBottom {
-            if let TextFlow::Linear = chunk.text_flow {
-                std::mem::swap(&mut curr_pos.0, &mut curr_pos.1);
+        if let (Some(data), Some(decoration)) =
+            (&fragment.line_through, &span.decoration.line_through)
+        {
+            let path = convert_decoration(data, span, decoration.clone());
+            if let Some(r) = path.data.bbox() {
+                bbox = bbox.expand(r);
             }
-        }
 
-        last_x = x + curr_pos.0;
-        last_y = y + curr_pos.1;
+            new_nodes.push(Node::new(NodeKind::Path(path)));
+        }
     }
 
-    (new_paths, bbox)
+    (new_nodes, bbox)
 }
 
-fn resolve_font(font: &Font, fontdb: &fontdb::Database) -> Option<ResolvedFont> {
-    let mut name_list = Vec::new();
-    for family in &font.families {
-        name_list.push(match family.as_str() {
-            "serif" => fontdb::Family::Serif,
-            "sans-serif" => fontdb::Family::SansSerif,
-            "cursive" => fontdb::Family::Cursive,
-            "fantasy" => fontdb::Family::Fantasy,
-            "monospace" => fontdb::Family::Monospace,
-            _ => fontdb::Family::Name(family),
-        });
-    }
+fn resolve_font(
+    font: &Font,
+    fontdb: &fontdb::Database,
+    fonts: &DocumentFonts,
+) -> Option<ResolvedFont> {
+    let mut name_list: Vec<_> = font.families.iter().map(|f| family_from_str(f)).collect();
 
     // Use the default font as fallback.
     name_list.push(fontdb::Family::Serif);
 
+    let id = fonts.query(fontdb, &font_query(font, &name_list));
+    if id.is_none() {
+        log::warn!("No match for '{}' font-family.", font.families.join(", "));
+    }
+
+    let id = id?;
+    let mut resolved = fonts.load_font(fontdb, id)?;
+    synthesis::resolve(&mut resolved, font, fonts.database(fontdb, id));
+    Some(resolved)
+}
+
+fn family_from_str(family: &str) -> fontdb::Family<'_> {
+    match family {
+        "serif" => fontdb::Family::Serif,
+        "sans-serif" => fontdb::Family::SansSerif,
+        "cursive" => fontdb::Family::Cursive,
+        "fantasy" => fontdb::Family::Fantasy,
+        "monospace" => fontdb::Family::Monospace,
+        _ => fontdb::Family::Name(family),
+    }
+}
+
+fn font_query<'a>(font: &Font, families: &'a [fontdb::Family<'a>]) -> fontdb::Query<'a> {
     let stretch = match font.stretch {
         FontStretch::UltraCondensed => fontdb::Stretch::UltraCondensed,
         FontStretch::ExtraCondensed => fontdb::Stretch::ExtraCondensed,
@@ -640,62 +2213,99 @@ fn resolve_font(font: &Font, fontdb: &fontdb::Database) -> Option<ResolvedFont>
         FontStretch::UltraExpanded => fontdb::Stretch::UltraExpanded,
     };
 
-    let style = match font.style {
-        FontStyle::Normal => fontdb::Style::Normal,
-        FontStyle::Italic => fontdb::Style::Italic,
-        FontStyle::Oblique => fontdb::Style::Oblique,
-    };
-
-    let query = fontdb::Query {
-        families: &name_list,
+    fontdb::Query {
+        families,
         weight: fontdb::Weight(font.weight),
         stretch,
-        style,
-    };
-
-    let id = fontdb.query(&query);
-    if id.is_none() {
-        log::warn!("No match for '{}' font-family.", font.families.join(", "));
+        style: convert_style(font.style),
     }
+}
 
-    fontdb.load_font(id?)
+fn convert_style(style: FontStyle) -> fontdb::Style {
+    match style {
+        FontStyle::Normal => fontdb::Style::Normal,
+        FontStyle::Italic => fontdb::Style::Italic,
+        FontStyle::Oblique => fontdb::Style::Oblique,
+    }
 }
 
+/// Converts a span into a path and color glyphs.
+///
+/// Color glyphs are groups in the text element coordinates.
+/// The span's fill applies only to their foreground color layers.
 fn convert_span(
     span: &TextSpan,
-    clusters: &mut [OutlinedCluster],
-    text_ts: &Transform,
-) -> Option<Path> {
+    fragment: &TextFragment,
+    fontdb: &fontdb::Database,
+    fonts: &DocumentFonts,
+    hinting: Option<&Hinting>,
+    ids: &mut IdGenerator,
+) -> (Option<Path>, Vec<Node>) {
     let mut path_data = PathData::new();
+    let mut hinting_fonts: HashMap<ID, Option<ResolvedFont>> = HashMap::new();
     let mut bboxes_data = PathData::new();
+    let mut color_glyphs = Vec::new();
+
+    for cluster in &fragment.clusters {
+        for glyph in &cluster.glyphs {
+            let color_glyph = fonts.database(fontdb, glyph.font).color_glyph(
+                glyph.font,
+                glyph.id,
+                &fragment.variations,
+                span.fill.as_ref(),
+                ids,
+            );
+            if let Some(node) = color_glyph {
+                if span.visibility == Visibility::Visible {
+                    let group = Node::new(NodeKind::Group(Group {
+                        transform: glyph.transform,
+                        ..Group::default()
+                    }));
+                    group.append(node);
+                    color_glyphs.push(group);
+                }
 
-    for cluster in clusters {
-        if !cluster.visible {
-            continue;
-        }
-
-        if span_contains(span, cluster.byte_idx) {
-            let mut path = std::mem::replace(&mut cluster.path, PathData::new());
-            path.transform(cluster.transform);
+                continue;
+            }
 
-            path_data.push_path(&path);
+            let mut outline = fonts
+                .outline(fontdb, glyph.font, glyph.id, &fragment.variations)
+                .unwrap_or_default();
+            if glyph.embolden != 0.0 {
+                outline = synthesis::embolden(&outline, glyph.embolden);
+            }
 
-            // We have to calculate text bbox using font metrics and not glyph shape.
-            if let Some(r) = Rect::new(0.0, -cluster.ascent, cluster.advance, cluster.height()) {
-                if let Some(r) = r.transform(&cluster.transform) {
-                    bboxes_data.push_rect(r);
+            if let Some(hinting) = hinting {
+                let font = *hinting_fonts
+                    .entry(glyph.font)
+                    .or_insert_with(|| fonts.load_font(fontdb, glyph.font));
+                if let Some(ref font) = font {
+                    let mut ts = hinting.transform;
+                    ts.append(&glyph.transform);
+                    if let Some(hinted) =
+                        hinting::hint_outline(&outline, font, ts, hinting.max_font_size)
+                    {
+                        outline = hinted;
+                    }
                 }
             }
+
+            if !outline.is_empty() {
+                outline.transform(glyph.transform);
+                path_data.push_path(&outline);
+            }
+        }
+
+        // We have to calculate text bbox using font metrics and not glyph shape.
+        if let Some(r) = cluster.bbox.and_then(|r| r.transform(&cluster.transform)) {
+            bboxes_data.push_rect(r);
         }
     }
 
     if path_data.is_empty() {
-        return None;
+        return (None, color_glyphs);
     }
 
-    path_data.transform(*text_ts);
-    bboxes_data.transform(*text_ts);
-
     let mut fill = span.fill.clone();
     if let Some(ref mut fill) = fill {
         // The `fill-rule` should be ignored.
@@ -718,10 +2328,10 @@ fn convert_span(
         data: Rc::new(path_data),
     };
 
-    Some(path)
+    (Some(path), color_glyphs)
 }
 
-fn collect_decoration_spans(span: &TextSpan, clusters: &[OutlinedCluster]) -> Vec<DecorationSpan> {
+fn collect_decoration_spans(span: &TextSpan, clusters: &[GlyphCluster]) -> Vec<DecorationSpan> {
     let mut spans = Vec::new();
 
     let mut started = false;
@@ -754,43 +2364,66 @@ fn collect_decoration_spans(span: &TextSpan, clusters: &[OutlinedCluster]) -> Ve
     spans
 }
 
-fn convert_decoration(
+fn decoration_thickness(style: &TextDecorationStyle, span: &TextSpan, font: &ResolvedFont) -> f64 {
+    style
+        .thickness
+        .unwrap_or_else(|| font.underline_thickness(span.font_size.get()))
+}
+
+fn layout_decoration(
     dy: f64,
+    direction: f64,
+    style: &TextDecorationStyle,
     span: &TextSpan,
     font: &ResolvedFont,
-    mut decoration: TextDecorationStyle,
     decoration_spans: &[DecorationSpan],
     transform: Transform,
-) -> Path {
+) -> Option<PathData> {
     debug_assert!(!decoration_spans.is_empty());
 
-    let thickness = font.underline_thickness(span.font_size.get());
+    let thickness = decoration_thickness(style, span, font);
+    if thickness <= 0.0 || !thickness.is_finite() {
+        return None;
+    }
 
     let mut path = PathData::new();
-    for dec_span in decoration_spans {
-        let rect = match Rect::new(0.0, -thickness / 2.0, dec_span.width, thickness) {
-            Some(v) => v,
-            None => {
-                log::warn!("a decoration span has a malformed bbox");
-                continue;
-            }
-        };
 
+    let mut phase = 0.0;
+    for dec_span in decoration_spans {
         let start_idx = path.len();
-        path.push_rect(rect);
+        decoration::push_line(
+            style.line_style,
+            dec_span.width,
+            thickness,
+            direction,
+            phase,
+            &mut path,
+        );
+        phase += dec_span.width;
 
         let mut ts = dec_span.transform;
         ts.translate(0.0, dy);
         path.transform_from(start_idx, ts);
     }
 
+    if path.is_empty() {
+        return None;
+    }
+
     path.transform(transform);
+    Some(path)
+}
 
+fn convert_decoration(
+    data: &PathData,
+    span: &TextSpan,
+    mut decoration: TextDecorationStyle,
+) -> Path {
     Path {
         visibility: span.visibility,
         fill: decoration.fill.take(),
         stroke: decoration.stroke.take(),
-        data: Rc::new(path),
+        data: Rc::new(data.clone()),
         ..Path::default()
     }
 }
@@ -913,8 +2546,15 @@ struct Glyph {
     dy: i32,
 
     /// The glyph width / X-advance in font units.
+    ///
+    /// A Y-advance for upright glyphs.
     width: i32,
 
+    /// Indicates that the glyph was shaped top-to-bottom, for upright vertical text.
+    ///
+    /// Offsets of such glyphs are relative to the vertical origin.
+    upright: bool,
+
     /// Reference to the source font.
     ///
     /// Each glyph can have it's own source font.
@@ -927,16 +2567,35 @@ impl Glyph {
     }
 }
 
-/// An outlined cluster.
+/// A glyph inside a cluster.
+#[derive(Clone, Copy)]
+struct ClusterGlyph {
+    /// The glyph ID in the font.
+    id: GlyphId,
+
+    /// The source font.
+    font: ID,
+
+    /// The glyph advance in SVG coordinates.
+    advance: f64,
+
+    /// Maps a glyph outline in font units into the cluster coordinates.
+    transform: Transform,
+
+    /// An outline offset in font units.
+    embolden: f64,
+}
+
+/// A glyph cluster.
 ///
 /// Cluster/grapheme is a single, unbroken, renderable character.
 /// It can be positioned, rotated, spaced, etc.
 ///
 /// Let's say we have `й` which is *CYRILLIC SMALL LETTER I* and *COMBINING BREVE*.
 /// It consists of two code points, will be shaped (via harfbuzz) as two glyphs into one cluster,
-/// and then will be combined into the one `OutlinedCluster`.
+/// and then will be combined into the one `GlyphCluster`.
 #[derive(Clone)]
-struct OutlinedCluster {
+struct GlyphCluster {
     /// Position in bytes in the original string.
     ///
     /// We use it to match a cluster with a character in the text chunk and therefore with the style.
@@ -967,14 +2626,19 @@ struct OutlinedCluster {
     /// A x-height in SVG coordinates.
     x_height: f64,
 
+    /// Indicates that this cluster is upright in vertical text.
+    ///
+    /// Such clusters are already rotated and their advance is a vertical one.
+    upright: bool,
+
     /// Indicates that this cluster was affected by the relative shift (via dx/dy attributes)
     /// during the text layouting. Which breaks the `text-decoration` line.
     ///
     /// Used during the `text-decoration` processing.
     has_relative_shift: bool,
 
-    /// An actual outline.
-    path: PathData,
+    /// Cluster's glyphs.
+    glyphs: Vec<ClusterGlyph>,
 
     /// A cluster's transform that contains it's position, rotation, etc.
     transform: Transform,
@@ -985,7 +2649,7 @@ struct OutlinedCluster {
     visible: bool,
 }
 
-impl OutlinedCluster {
+impl GlyphCluster {
     fn height(&self) -> f64 {
         self.ascent - self.descent
     }
@@ -1021,257 +2685,698 @@ impl<'a> Iterator for GlyphClusters<'a> {
                 break;
             }
 
-            self.idx += 1;
+            self.idx += 1;
+        }
+
+        Some((start..self.idx, cluster))
+    }
+}
+
+/// Converts a text chunk into a list of glyph clusters.
+///
+/// This function will do the BIDI reordering and text shaping,
+/// but not the text layouting. So all clusters are in the 0x0 position.
+fn shape_chunk(
+    chunk: &TextChunk,
+    writing_mode: WritingMode,
+    fonts_cache: &FontsCache,
+    fontdb: &fontdb::Database,
+    fallback: &mut FallbackState,
+) -> Vec<GlyphCluster> {
+    let bidi_runs = resolve_bidi_runs(chunk, writing_mode);
+
+    let mut glyphs = Vec::new();
+    for span in &chunk.spans {
+        let font = match fonts_cache.get(&span.font) {
+            Some(v) => v.clone(),
+            None => continue,
+        };
+
+        let tmp_glyphs = shape_text(
+            &chunk.text,
+            &bidi_runs,
+            span,
+            writing_mode,
+            font,
+            &collect_features(span),
+            &collect_rb_variations(span),
+            fontdb,
+            fallback,
+        );
+
+        // Do nothing with the first run.
+        if glyphs.is_empty() {
+            glyphs = tmp_glyphs;
+            continue;
+        }
+
+        // We assume, that shaping with an any font will produce the same amount of glyphs.
+        // Otherwise an error.
+        if glyphs.len() != tmp_glyphs.len() {
+            log::warn!("Text layouting failed.");
+            return Vec::new();
+        }
+
+        // Copy span's glyphs.
+        for (i, glyph) in tmp_glyphs.iter().enumerate() {
+            if span_contains(span, glyph.byte_idx) {
+                glyphs[i] = glyph.clone();
+            }
+        }
+    }
+
+    // Convert glyphs to clusters.
+    let mut clusters = Vec::new();
+    for (range, byte_idx) in GlyphClusters::new(&glyphs) {
+        if let Some(span) = chunk_span_at(chunk, byte_idx) {
+            clusters.push(form_cluster(
+                &glyphs[range],
+                &chunk.text,
+                span.font_size.get(),
+            ));
+        }
+    }
+
+    clusters
+}
+
+/// A font fallback policy with a diagnostics storage.
+struct FallbackState<'a> {
+    policy: &'a FontFallback,
+    fonts: &'a DocumentFonts<'a>,
+    element_id: &'a str,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl FallbackState<'_> {
+    /// Checks that a font has a grapheme and that the grapheme is
+    /// inside the font's `unicode-range`.
+    fn has_grapheme(&self, fontdb: &Database, id: ID, grapheme: &str) -> bool {
+        if let Some(font) = self.fonts.svg_fonts.get(&id) {
+            return svg_font::has_grapheme(font, grapheme);
+        }
+
+        if let Some((_, ranges)) = self
+            .fonts
+            .unicode_ranges
+            .iter()
+            .find(|(face_id, _)| *face_id == id)
+        {
+            let c = u32::from(grapheme_base_char(grapheme));
+            if !ranges.iter().any(|range| range.contains(&c)) {
+                return false;
+            }
+        }
+
+        self.fonts.has_grapheme(fontdb, id, grapheme)
+    }
+
+    fn report(&mut self, diagnostic: Diagnostic) {
+        // Each span is shaped separately, so the same issue can be reported multiple times.
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+}
+
+/// A text run with the same bidi embedding level.
+struct BidiRun {
+    range: std::ops::Range<usize>,
+    is_rtl: bool,
+}
+
+/// Splits a text chunk into bidi runs in the visual order.
+///
+/// `unicode-bidi` is implemented by wrapping spans with explicit directional
+/// formatting characters, as described in the CSS Writing Modes spec.
+///
+/// Upright vertical text is always left-to-right, so such spans are wrapped
+/// with a left-to-right override instead.
+fn resolve_bidi_runs(chunk: &TextChunk, writing_mode: WritingMode) -> Vec<BidiRun> {
+    let is_upright = |span: &TextSpan| {
+        writing_mode == WritingMode::TopToBottom
+            && span.text_orientation == TextOrientation::Upright
+    };
+
+    fn push_text(text: &mut String, offsets: &mut Vec<usize>, s: &str) {
+        offsets.extend(text.len()..text.len() + s.len());
+        text.push_str(s);
+    }
+
+    // A text with formatting characters and positions of the original bytes in it.
+    let mut text = String::with_capacity(chunk.text.len());
+    let mut offsets = Vec::with_capacity(chunk.text.len());
+
+    let mut pos = 0;
+    let mut prev_bidi = (UnicodeBidi::Normal, TextDirection::LeftToRight);
+    for span in &chunk.spans {
+        push_text(&mut text, &mut offsets, &chunk.text[pos..span.start]);
+
+        let bidi = if is_upright(span) {
+            (UnicodeBidi::BidiOverride, TextDirection::LeftToRight)
+        } else {
+            (span.unicode_bidi, span.direction)
+        };
+        if bidi != prev_bidi {
+            text.push_str(bidi_close_chars(prev_bidi.0));
+            text.push_str(bidi_open_chars(bidi.0, bidi.1));
+            prev_bidi = bidi;
+        }
+
+        push_text(&mut text, &mut offsets, &chunk.text[span.start..span.end]);
+        pos = span.end;
+    }
+    text.push_str(bidi_close_chars(prev_bidi.0));
+    push_text(&mut text, &mut offsets, &chunk.text[pos..]);
+
+    let base_level = match chunk.direction {
+        _ if chunk.spans.iter().all(is_upright) => unicode_bidi::Level::ltr(),
+        TextDirection::LeftToRight => unicode_bidi::Level::ltr(),
+        TextDirection::RightToLeft => unicode_bidi::Level::rtl(),
+    };
+
+    let bidi_info = unicode_bidi::BidiInfo::new(&text, Some(base_level));
+    let mut runs = Vec::new();
+    for paragraph in &bidi_info.paragraphs {
+        let levels = bidi_info.reordered_levels(paragraph, paragraph.range.clone());
+
+        // Split the original text into level runs. Formatting characters are skipped.
+        let mut level_runs: Vec<(std::ops::Range<usize>, u8)> = Vec::new();
+        for (idx, offset) in offsets.iter().enumerate() {
+            if !paragraph.range.contains(offset) {
+                continue;
+            }
+
+            let level = levels[*offset].number();
+            match level_runs.last_mut() {
+                Some((range, run_level)) if *run_level == level && range.end == idx => {
+                    range.end = idx + 1
+                }
+                _ => level_runs.push((idx..idx + 1, level)),
+            }
+        }
+
+        // Reverse any contiguous sequence of runs at the given level or higher,
+        // from the highest level down to the lowest odd level.
+        // https://www.unicode.org/reports/tr9/#L2
+        let max_level = level_runs.iter().map(|run| run.1).max().unwrap_or(0);
+        let min_odd_level = level_runs
+            .iter()
+            .map(|run| run.1)
+            .filter(|level| level % 2 == 1)
+            .min();
+        if let Some(min_odd_level) = min_odd_level {
+            for level in (min_odd_level..=max_level).rev() {
+                let mut i = 0;
+                while i < level_runs.len() {
+                    let start = i;
+                    while i < level_runs.len() && level_runs[i].1 >= level {
+                        i += 1;
+                    }
+
+                    if start == i {
+                        i += 1;
+                    } else {
+                        level_runs[start..i].reverse();
+                    }
+                }
+            }
         }
 
-        Some((start..self.idx, cluster))
+        runs.extend(level_runs.into_iter().map(|(range, level)| BidiRun {
+            range,
+            is_rtl: level % 2 == 1,
+        }));
+    }
+
+    runs
+}
+
+fn bidi_open_chars(unicode_bidi: UnicodeBidi, direction: TextDirection) -> &'static str {
+    let is_rtl = direction == TextDirection::RightToLeft;
+    match unicode_bidi {
+        UnicodeBidi::Normal => "",
+        UnicodeBidi::Embed if is_rtl => "\u{202B}",
+        UnicodeBidi::Embed => "\u{202A}",
+        UnicodeBidi::Isolate if is_rtl => "\u{2067}",
+        UnicodeBidi::Isolate => "\u{2066}",
+        UnicodeBidi::BidiOverride if is_rtl => "\u{202E}",
+        UnicodeBidi::BidiOverride => "\u{202D}",
+        UnicodeBidi::IsolateOverride if is_rtl => "\u{2067}\u{202E}",
+        UnicodeBidi::IsolateOverride => "\u{2066}\u{202D}",
+        UnicodeBidi::Plaintext => "\u{2068}",
+    }
+}
+
+fn bidi_close_chars(unicode_bidi: UnicodeBidi) -> &'static str {
+    match unicode_bidi {
+        UnicodeBidi::Normal => "",
+        UnicodeBidi::Embed | UnicodeBidi::BidiOverride => "\u{202C}",
+        UnicodeBidi::Isolate | UnicodeBidi::Plaintext => "\u{2069}",
+        UnicodeBidi::IsolateOverride => "\u{202C}\u{2069}",
     }
 }
 
-/// Converts a text chunk into a list of outlined clusters.
+/// Text shaping with font fallback.
 ///
-/// This function will do the BIDI reordering, text shaping and glyphs outlining,
-/// but not the text layouting. So all clusters are in the 0x0 position.
-fn outline_chunk(
-    chunk: &TextChunk,
-    fonts_cache: &FontsCache,
+/// The text is already split into bidi runs, which are then split into runs
+/// of grapheme clusters supported by the same font. Each run is shaped separately.
+fn shape_text(
+    text: &str,
+    bidi_runs: &[BidiRun],
+    span: &TextSpan,
+    writing_mode: WritingMode,
+    font: Rc<ResolvedFont>,
+    features: &[rustybuzz::Feature],
+    variations: &[rustybuzz::Variation],
     fontdb: &fontdb::Database,
-) -> Vec<OutlinedCluster> {
-    let mut glyphs = Vec::new();
-    for span in &chunk.spans {
-        let font = match fonts_cache.get(&span.font) {
-            Some(v) => v.clone(),
-            None => continue,
-        };
+    fallback: &mut FallbackState,
+) -> Vec<Glyph> {
+    // Fonts that were used for this text already. The first one is the span's font.
+    let mut fonts = vec![font];
 
-        let tmp_glyphs = shape_text(
-            &chunk.text,
-            font,
-            span.small_caps,
-            span.apply_kerning,
-            fontdb,
-        );
+    let mut glyphs = Vec::new();
+    for run in bidi_runs {
+        let mut font_runs =
+            itemize_by_font(text, run.range.clone(), span, &mut fonts, fontdb, fallback);
 
-        // Do nothing with the first run.
-        if glyphs.is_empty() {
-            glyphs = tmp_glyphs;
-            continue;
+        // Font runs are in the logical order.
+        if run.is_rtl {
+            font_runs.reverse();
         }
 
-        // We assume, that shaping with an any font will produce the same amount of glyphs.
-        // Otherwise an error.
-        if glyphs.len() != tmp_glyphs.len() {
-            log::warn!("Text layouting failed.");
-            return Vec::new();
-        }
+        for (range, font) in font_runs {
+            let mut orientation_runs = itemize_by_orientation(text, range, span, writing_mode);
+            if run.is_rtl {
+                orientation_runs.reverse();
+            }
 
-        // Copy span's glyphs.
-        for (i, glyph) in tmp_glyphs.iter().enumerate() {
-            if span_contains(span, glyph.byte_idx) {
-                glyphs[i] = glyph.clone();
+            for (range, upright) in orientation_runs {
+                if let Some(run_glyphs) = shape_run(
+                    text,
+                    range,
+                    font.clone(),
+                    run.is_rtl,
+                    upright,
+                    span,
+                    features,
+                    variations,
+                    fontdb,
+                    fallback.fonts,
+                ) {
+                    glyphs.extend(run_glyphs);
+                }
             }
         }
     }
 
-    // Convert glyphs to clusters.
-    let mut clusters = Vec::new();
-    for (range, byte_idx) in GlyphClusters::new(&glyphs) {
-        if let Some(span) = chunk_span_at(chunk, byte_idx) {
-            clusters.push(outline_cluster(
-                &glyphs[range],
-                &chunk.text,
-                span.font_size.get(),
-                fontdb,
-            ));
+    // A synthetic bold is wider, except marks and other zero-width glyphs.
+    for glyph in &mut glyphs {
+        if glyph.width != 0 {
+            glyph.width += synthesis::bold_advance(&glyph.font);
         }
     }
 
-    clusters
+    // Report missing glyphs.
+    for glyph in &glyphs {
+        // Newlines and other control characters are not rendered anyway.
+        if glyph.is_missing() && !glyph.byte_idx.char_from(text).is_control() {
+            // TODO: report a full grapheme
+            fallback.report(Diagnostic::MissingGlyph {
+                element_id: fallback.element_id.to_string(),
+                character: glyph.byte_idx.char_from(text),
+            });
+        }
+    }
+
+    glyphs
 }
 
-/// Text shaping with font fallback.
-fn shape_text(
+/// Splits a text range into runs of grapheme clusters that are supported by the same font.
+///
+/// New fallback fonts will be appended to `fonts`.
+/// Clusters that are not supported by any font will use the span's font.
+fn itemize_by_font(
     text: &str,
-    font: Rc<ResolvedFont>,
-    small_caps: bool,
-    apply_kerning: bool,
+    range: std::ops::Range<usize>,
+    span: &TextSpan,
+    fonts: &mut Vec<Rc<ResolvedFont>>,
     fontdb: &fontdb::Database,
-) -> Vec<Glyph> {
-    let mut glyphs = shape_text_with_font(text, font.clone(), small_caps, apply_kerning, fontdb)
-        .unwrap_or_default();
-
-    // Remember all fonts used for shaping.
-    let mut used_fonts = vec![font.id];
-
-    // Loop until all glyphs become resolved or until no more fonts are left.
-    'outer: loop {
-        let mut missing = None;
-        for glyph in &glyphs {
-            if glyph.is_missing() {
-                missing = Some(glyph.byte_idx.char_from(text));
-                break;
+    fallback: &mut FallbackState,
+) -> Vec<(std::ops::Range<usize>, Rc<ResolvedFont>)> {
+    let mut runs: Vec<(std::ops::Range<usize>, Rc<ResolvedFont>)> = Vec::new();
+    for (idx, grapheme) in text[range.clone()].grapheme_indices(true) {
+        let grapheme = range.start + idx..range.start + idx + grapheme.len();
+        let grapheme_text = &text[grapheme.clone()];
+        let mut font = fonts
+            .iter()
+            .find(|font| fallback.has_grapheme(fontdb, font.id, grapheme_text))
+            .cloned();
+
+        if font.is_none() {
+            let exclude: Vec<_> = fonts.iter().map(|font| font.id).collect();
+            if let Some(mut new_font) =
+                find_font_for_grapheme(grapheme_text, span, &exclude, fontdb, fallback)
+            {
+                let database = fallback.fonts.database(fontdb, new_font.id);
+                synthesis::resolve(&mut new_font, &span.font, database);
+                let new_font = Rc::new(new_font);
+                let font_family = face_family(fallback.fonts.face(fontdb, fonts[0].id));
+                let fallback_family = face_family(fallback.fonts.face(fontdb, new_font.id));
+                // Faces of the same family split by `unicode-range` are not a fallback.
+                if font_family != fallback_family {
+                    fallback.report(Diagnostic::FontFallback {
+                        element_id: fallback.element_id.to_string(),
+                        character: grapheme_base_char(grapheme_text),
+                        font_family,
+                        fallback_family,
+                    });
+                }
+
+                fonts.push(new_font.clone());
+                font = Some(new_font);
             }
         }
 
-        if let Some(c) = missing {
-            let fallback_font = match find_font_for_char(c, &used_fonts, fontdb) {
-                Some(v) => Rc::new(v),
-                None => break 'outer,
-            };
-
-            // Shape again, using a new font.
-            let fallback_glyphs = shape_text_with_font(
-                text,
-                fallback_font.clone(),
-                small_caps,
-                apply_kerning,
-                fontdb,
-            )
-            .unwrap_or_default();
-
-            let all_matched = fallback_glyphs.iter().all(|g| !g.is_missing());
-            if all_matched {
-                // Replace all glyphs when all of them were matched.
-                glyphs = fallback_glyphs;
-                break 'outer;
-            }
+        let font = font.unwrap_or_else(|| fonts[0].clone());
+        match runs.last_mut() {
+            Some((run, run_font)) if run_font.id == font.id => run.end = grapheme.end,
+            _ => runs.push((grapheme, font)),
+        }
+    }
 
-            // We assume, that shaping with an any font will produce the same amount of glyphs.
-            // This is incorrect, but good enough for now.
-            if glyphs.len() != fallback_glyphs.len() {
-                break 'outer;
-            }
+    runs
+}
 
-            // TODO: Replace clusters and not glyphs. This should be more accurate.
+/// Splits a text range into runs of upright and sideways grapheme clusters.
+///
+/// In the `mixed` orientation, characters with the `U`, `Tu` and `Tr`
+/// [Unicode Vertical_Orientation](https://www.unicode.org/reports/tr50/) are upright.
+/// `Tr` characters rely on the `vert` feature to get a rotated glyph.
+/// Horizontal text has a single sideways run.
+fn itemize_by_orientation(
+    text: &str,
+    range: std::ops::Range<usize>,
+    span: &TextSpan,
+    writing_mode: WritingMode,
+) -> Vec<(std::ops::Range<usize>, bool)> {
+    if writing_mode == WritingMode::LeftToRight {
+        return vec![(range, false)];
+    }
 
-            // Copy new glyphs.
-            for i in 0..glyphs.len() {
-                if glyphs[i].is_missing() && !fallback_glyphs[i].is_missing() {
-                    glyphs[i] = fallback_glyphs[i].clone();
-                }
+    let mut runs: Vec<(std::ops::Range<usize>, bool)> = Vec::new();
+    for (idx, grapheme) in text[range.clone()].grapheme_indices(true) {
+        let grapheme = range.start + idx..range.start + idx + grapheme.len();
+        let upright = match span.text_orientation {
+            TextOrientation::Mixed => {
+                let c = grapheme_base_char(&text[grapheme.clone()]);
+                unicode_vo::char_orientation(c) != unicode_vo::Orientation::Rotated
             }
+            TextOrientation::Upright => true,
+            TextOrientation::Sideways => false,
+        };
 
-            // Remember this font.
-            used_fonts.push(fallback_font.id);
-        } else {
-            break 'outer;
+        match runs.last_mut() {
+            Some((run, run_upright)) if *run_upright == upright => run.end = grapheme.end,
+            _ => runs.push((grapheme, upright)),
         }
     }
 
-    // Warn about missing glyphs.
-    for glyph in &glyphs {
-        if glyph.is_missing() {
-            let c = glyph.byte_idx.char_from(text);
-            // TODO: print a full grapheme
-            log::warn!(
-                "No fonts with a {}/U+{:X} character were found.",
-                c,
-                c as u32
-            );
-        }
+    runs
+}
+
+/// Returns the first character of a grapheme cluster that is not a joiner or a selector.
+fn grapheme_base_char(grapheme: &str) -> char {
+    let mut chars = grapheme.chars();
+    let first = chars.clone().next().unwrap();
+    chars.find(|c| !is_default_ignorable(*c)).unwrap_or(first)
+}
+
+/// Checks for joiners, variation selectors and tags.
+fn is_default_ignorable(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x200C..=0x200D | 0xFE00..=0xFE0F | 0xE0020..=0xE007F | 0xE0100..=0xE01EF
+    )
+}
+
+/// Collects span's OpenType features.
+///
+/// Features that come later override the earlier ones.
+fn collect_features(span: &TextSpan) -> Vec<rustybuzz::Feature> {
+    let mut features = Vec::new();
+    if span.small_caps {
+        features.push(rustybuzz::Feature::new(
+            rustybuzz::Tag::from_bytes(b"smcp"),
+            1,
+            ..,
+        ));
     }
 
-    glyphs
+    if !span.apply_kerning {
+        features.push(rustybuzz::Feature::new(
+            rustybuzz::Tag::from_bytes(b"kern"),
+            0,
+            ..,
+        ));
+    }
+
+    for feature in &span.font_features {
+        features.push(rustybuzz::Feature::new(
+            rustybuzz::Tag::from_bytes(&feature.tag),
+            feature.value,
+            ..,
+        ));
+    }
+
+    features
+}
+
+/// Collects span's font variation axes values.
+///
+/// `font-weight`, `font-stretch` and `font-optical-sizing` are mapped to the registered axes,
+/// while `font-variation-settings` comes last and overrides them.
+fn collect_variations(span: &TextSpan) -> Vec<FontVariation> {
+    let stretch = match span.font.stretch {
+        FontStretch::UltraCondensed => 50.0,
+        FontStretch::ExtraCondensed => 62.5,
+        FontStretch::Condensed => 75.0,
+        FontStretch::SemiCondensed => 87.5,
+        FontStretch::Normal => 100.0,
+        FontStretch::SemiExpanded => 112.5,
+        FontStretch::Expanded => 125.0,
+        FontStretch::ExtraExpanded => 150.0,
+        FontStretch::UltraExpanded => 200.0,
+    };
+
+    let mut variations = vec![
+        FontVariation::new(b"wght", span.font.weight as f32),
+        FontVariation::new(b"wdth", stretch),
+    ];
+
+    if span.optical_sizing {
+        variations.push(FontVariation::new(b"opsz", span.font_size.get() as f32));
+    }
+
+    variations.extend_from_slice(&span.font_variations);
+    variations
+}
+
+fn collect_rb_variations(span: &TextSpan) -> Vec<rustybuzz::Variation> {
+    collect_variations(span)
+        .iter()
+        .map(|v| rustybuzz::Variation {
+            tag: rustybuzz::Tag::from_bytes(&v.tag),
+            value: v.value,
+        })
+        .collect()
 }
 
-/// Converts a text into a list of glyph IDs.
+/// Shapes a text run using a single font.
+///
+/// `range` must be inside a single bidi run. Glyphs are in the visual order.
 ///
-/// This function will do the BIDI reordering and text shaping.
-fn shape_text_with_font(
+/// Upright runs are shaped top-to-bottom, using the font's vertical metrics
+/// and the `vert` feature.
+fn shape_run(
     text: &str,
+    range: std::ops::Range<usize>,
     font: Rc<ResolvedFont>,
-    small_caps: bool,
-    apply_kerning: bool,
+    is_rtl: bool,
+    upright: bool,
+    span: &TextSpan,
+    features: &[rustybuzz::Feature],
+    variations: &[rustybuzz::Variation],
     fontdb: &fontdb::Database,
+    fonts: &DocumentFonts,
 ) -> Option<Vec<Glyph>> {
-    fontdb.with_face_data(font.id, |font_data, face_index| -> Option<Vec<Glyph>> {
-        let rb_font = rustybuzz::Face::from_slice(font_data, face_index)?;
+    if let Some(svg_font) = fonts.svg_fonts.get(&font.id) {
+        let mut glyphs = svg_font::shape(
+            svg_font,
+            text,
+            range,
+            font,
+            is_rtl,
+            span.lang.as_deref(),
+            span.apply_kerning,
+        );
 
-        let bidi_info = unicode_bidi::BidiInfo::new(text, Some(unicode_bidi::Level::ltr()));
-        let paragraph = &bidi_info.paragraphs[0];
-        let line = paragraph.range.clone();
+        if upright {
+            glyphs.iter_mut().for_each(synthesize_upright);
+        }
 
-        let mut glyphs = Vec::new();
+        return Some(glyphs);
+    }
 
-        let (levels, runs) = bidi_info.visual_runs(paragraph, line);
-        for run in runs.iter() {
-            let sub_text = &text[run.clone()];
-            if sub_text.is_empty() {
-                continue;
-            }
+    let direction = if upright {
+        rustybuzz::Direction::TopToBottom
+    } else if is_rtl {
+        rustybuzz::Direction::RightToLeft
+    } else {
+        rustybuzz::Direction::LeftToRight
+    };
 
-            let hb_direction = if levels[run.start].is_rtl() {
-                rustybuzz::Direction::RightToLeft
+    let mut run_features = Vec::new();
+    // `vert` is enabled by the vertical direction already.
+    // `vrt2` also rotates proportional glyphs, which is fine only in the `mixed`
+    // orientation, since sideways characters are not in upright runs anyway.
+    if upright && span.text_orientation == TextOrientation::Mixed {
+        run_features.push(rustybuzz::Feature::new(
+            rustybuzz::Tag::from_bytes(b"vrt2"),
+            1,
+            ..,
+        ));
+    }
+    run_features.extend_from_slice(features);
+
+    let request = ShapingRequest {
+        text: &text[range.clone()],
+        direction,
+        language: span.lang.as_deref(),
+        features: &run_features,
+        variations,
+    };
+    let shaped = fonts.shape(fontdb, font.id, &request)?;
+
+    let mut glyphs = Vec::with_capacity(shaped.len());
+    for shaped_glyph in shaped.iter() {
+        let idx = range.start + shaped_glyph.cluster as usize;
+        debug_assert!(text.get(idx..).is_some());
+
+        glyphs.push(Glyph {
+            byte_idx: ByteIndex::new(idx),
+            id: shaped_glyph.id,
+            dx: shaped_glyph.x_offset,
+            dy: shaped_glyph.y_offset,
+            // Vertical advances are negative, since the Y axis goes up in font units.
+            width: if upright {
+                -shaped_glyph.y_advance
             } else {
-                rustybuzz::Direction::LeftToRight
-            };
+                shaped_glyph.x_advance
+            },
+            upright,
+            font: font.clone(),
+        });
+    }
 
-            let mut buffer = rustybuzz::UnicodeBuffer::new();
-            buffer.push_str(sub_text);
-            buffer.set_direction(hb_direction);
-
-            let mut features = Vec::new();
-            if small_caps {
-                features.push(rustybuzz::Feature::new(
-                    rustybuzz::Tag::from_bytes(b"smcp"),
-                    1,
-                    ..,
-                ));
-            }
+    // Upright glyphs are always in the logical order.
+    if upright && is_rtl {
+        glyphs.reverse();
+    }
 
-            if !apply_kerning {
-                features.push(rustybuzz::Feature::new(
-                    rustybuzz::Tag::from_bytes(b"kern"),
-                    0,
-                    ..,
-                ));
-            }
+    Some(glyphs)
+}
+
+/// Shaping parameters of a text run.
+pub(crate) struct ShapingRequest<'a> {
+    pub text: &'a str,
+    pub direction: rustybuzz::Direction,
+    /// A BCP 47 language tag.
+    pub language: Option<&'a str>,
+    pub features: &'a [rustybuzz::Feature],
+    pub variations: &'a [rustybuzz::Variation],
+}
+
+/// A glyph produced by shaping, in font units.
+#[derive(Clone, Copy)]
+pub(crate) struct ShapedGlyph {
+    /// A cluster offset in the shaped text, in bytes.
+    pub cluster: u32,
+    pub id: GlyphId,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub x_advance: i32,
+    pub y_advance: i32,
+}
 
-            let output = rustybuzz::shape(&rb_font, &features, buffer);
+/// Shapes a text run using face data.
+fn shape_face(data: &[u8], face_index: u32, request: &ShapingRequest) -> Option<Vec<ShapedGlyph>> {
+    let mut rb_font = rustybuzz::Face::from_slice(data, face_index)?;
+    if rb_font.is_variable() {
+        rb_font.set_variations(request.variations);
+    }
 
-            let positions = output.glyph_positions();
-            let infos = output.glyph_infos();
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(request.text);
+    buffer.set_direction(request.direction);
 
-            for (pos, info) in positions.iter().zip(infos) {
-                let idx = run.start + info.cluster as usize;
-                debug_assert!(text.get(idx..).is_some());
+    // Selects language-specific glyphs, like Serbian Cyrillic forms.
+    if let Some(lang) = request.language.and_then(|lang| lang.parse().ok()) {
+        buffer.set_language(lang);
+    }
 
-                glyphs.push(Glyph {
-                    byte_idx: ByteIndex::new(idx),
-                    id: GlyphId(info.glyph_id as u16),
-                    dx: pos.x_offset,
-                    dy: pos.y_offset,
-                    width: pos.x_advance,
-                    font: font.clone(),
-                });
-            }
-        }
+    let output = rustybuzz::shape(&rb_font, request.features, buffer);
+    let glyphs = output
+        .glyph_positions()
+        .iter()
+        .zip(output.glyph_infos())
+        .map(|(pos, info)| ShapedGlyph {
+            cluster: info.cluster,
+            id: GlyphId(info.glyph_id as u16),
+            x_offset: pos.x_offset,
+            y_offset: pos.y_offset,
+            x_advance: pos.x_advance,
+            y_advance: pos.y_advance,
+        })
+        .collect();
+
+    Some(glyphs)
+}
 
-        Some(glyphs)
-    })?
+/// Positions a horizontal glyph as an upright one.
+///
+/// Used for fonts without vertical metrics, like SVG fonts.
+/// The glyph is centered on the vertical baseline, hangs from its ascent
+/// and advances by one em.
+fn synthesize_upright(glyph: &mut Glyph) {
+    glyph.dx -= glyph.width / 2;
+    glyph.dy -= i32::from(glyph.font.ascent);
+    glyph.width = i32::from(glyph.font.units_per_em.get());
+    glyph.upright = true;
 }
 
-/// Outlines a glyph cluster.
+/// Forms a glyph cluster.
 ///
-/// Uses one or more `Glyph`s to construct an `OutlinedCluster`.
-fn outline_cluster(
-    glyphs: &[Glyph],
-    text: &str,
-    font_size: f64,
-    db: &fontdb::Database,
-) -> OutlinedCluster {
+/// Uses one or more `Glyph`s to construct a `GlyphCluster`.
+fn form_cluster(glyphs: &[Glyph], text: &str, font_size: f64) -> GlyphCluster {
     debug_assert!(!glyphs.is_empty());
 
-    let mut path = PathData::new();
+    let mut cluster_glyphs = Vec::with_capacity(glyphs.len());
     let mut width = 0.0;
     let mut x = 0.0;
 
     for glyph in glyphs {
-        let mut outline = db.outline(glyph.font.id, glyph.id).unwrap_or_default();
-
         let sx = glyph.font.scale(font_size);
 
-        if !outline.is_empty() {
+        let mut ts = if glyph.upright {
+            // Vertical text is laid out horizontally and then rotated clockwise as a whole,
+            // so upright glyphs are rotated counterclockwise beforehand.
+            // This also mirrors them, since by default glyphs are upside-down.
+            let mut ts = Transform::new(0.0, -1.0, -1.0, 0.0, 0.0, 0.0);
+            ts.scale(sx, sx);
+            ts.translate(glyph.dx as f64, glyph.dy as f64 - x);
+            ts
+        } else {
             // By default, glyphs are upside-down, so we have to mirror them.
             let mut ts = Transform::new_scale(1.0, -1.0);
 
@@ -1285,15 +3390,24 @@ fn outline_cluster(
             // So we have to keep an advance.
             // TODO: should be done only inside a single text span
             ts.translate(x + glyph.dx as f64, glyph.dy as f64);
+            ts
+        };
 
-            outline.transform(ts);
-
-            path.push_path(&outline);
+        if glyph.font.synthetic_oblique {
+            ts.append(&synthesis::oblique_transform());
         }
 
+        let glyph_width = glyph.width as f64 * sx;
+        cluster_glyphs.push(ClusterGlyph {
+            id: glyph.id,
+            font: glyph.font.id,
+            advance: glyph_width,
+            transform: ts,
+            embolden: synthesis::bold_offset(&glyph.font),
+        });
+
         x += glyph.width as f64;
 
-        let glyph_width = glyph.width as f64 * sx;
         if glyph_width > width {
             width = glyph_width;
         }
@@ -1301,68 +3415,190 @@ fn outline_cluster(
 
     let byte_idx = glyphs[0].byte_idx;
     let font = glyphs[0].font.clone();
-    OutlinedCluster {
+    let upright = glyphs[0].upright;
+    GlyphCluster {
         byte_idx,
         codepoint: byte_idx.char_from(text),
         width,
         advance: width,
-        ascent: font.ascent(font_size),
-        descent: font.descent(font_size),
+        // Upright clusters are centered on the vertical baseline and are as high as wide.
+        ascent: if upright {
+            width / 2.0
+        } else {
+            font.ascent(font_size)
+        },
+        descent: if upright {
+            -width / 2.0
+        } else {
+            font.descent(font_size)
+        },
         x_height: font.x_height(font_size),
+        upright,
         has_relative_shift: false,
-        path,
+        glyphs: cluster_glyphs,
         transform: Transform::default(),
         visible: true,
     }
 }
 
-/// Finds a font with a specified char.
+/// Finds a font that supports a grapheme cluster using a fallback policy.
 ///
-/// This is a rudimentary font fallback algorithm.
-fn find_font_for_char(
-    c: char,
+/// `exclude_fonts` are fonts that were already checked.
+fn find_font_for_grapheme(
+    grapheme: &str,
+    span: &TextSpan,
     exclude_fonts: &[fontdb::ID],
     fontdb: &fontdb::Database,
+    fallback: &FallbackState,
 ) -> Option<ResolvedFont> {
-    let base_font_id = exclude_fonts[0];
+    let is_suitable =
+        |id: ID| !exclude_fonts.contains(&id) && fallback.has_grapheme(fontdb, id, grapheme);
+
+    // Document fonts split by `unicode-range` are parts of the span's family
+    // and must be checked before any fallback.
+    let fonts = fallback.fonts;
+    let base_family = face_family(fonts.face(fontdb, exclude_fonts[0]));
+    let same_family = fonts
+        .unicode_ranges
+        .iter()
+        .map(|(id, _)| *id)
+        .filter(|id| face_family(fonts.face(fontdb, *id)) == base_family);
+    if let Some(id) = pick_same_style(fontdb, fonts, exclude_fonts[0], same_family, &is_suitable) {
+        return fallback.fonts.load_font(fontdb, id);
+    }
 
-    // Iterate over fonts and check if any of them support the specified char.
-    for face in fontdb.faces() {
-        // Ignore fonts, that were used for shaping already.
-        if exclude_fonts.contains(&face.id) {
-            continue;
+    let policy = fallback.policy;
+    let span_font = &span.font;
+
+    let c = grapheme_base_char(grapheme);
+    let script = c.script();
+    if let Some(ref callback) = policy.callback {
+        if let Some(id) = callback(c, span_font, script) {
+            if is_suitable(id) {
+                return fallback.fonts.load_font(fontdb, id);
+            }
         }
+    }
 
-        // Check that the new face has the same style.
-        let base_face = fontdb.face(base_font_id)?;
-        if base_face.style != face.style
-            && base_face.weight != face.weight
-            && base_face.stretch != face.stretch
-        {
+    // The span's language is more specific than its script,
+    // like Japanese and Chinese for Han characters.
+    let mut family_lists = Vec::new();
+    if let Some(ref lang) = span.lang {
+        push_language_families(policy, lang, &mut family_lists);
+    }
+
+    if let Some(families) = policy.script_families.get(&script) {
+        family_lists.push(families);
+    }
+
+    for lang in &policy.languages {
+        push_language_families(policy, lang, &mut family_lists);
+    }
+
+    family_lists.push(&policy.families);
+
+    for family in family_lists.iter().flat_map(|list| list.iter()) {
+        let families = [family_from_str(family)];
+        if let Some(id) = fonts.query(fontdb, &font_query(span_font, &families)) {
+            if is_suitable(id) {
+                return fallback.fonts.load_font(fontdb, id);
+            }
+        }
+    }
+
+    if !policy.use_database {
+        return None;
+    }
+
+    let all_faces = fonts.faces(fontdb).map(|face| face.id);
+    let id = pick_same_style(fontdb, fonts, exclude_fonts[0], all_faces, &is_suitable)?;
+    fallback.fonts.load_font(fontdb, id)
+}
+
+fn push_language_families<'a>(
+    policy: &'a FontFallback,
+    lang: &str,
+    family_lists: &mut Vec<&'a Vec<String>>,
+) {
+    let mut matches: Vec<_> = policy
+        .language_families
+        .iter()
+        .filter(|(key, _)| language_matches(key, lang))
+        .collect();
+
+    // More specific tags first, like `zh-Hant` before `zh`.
+    // Hash map order is random, so ties are ordered by the tag itself.
+    matches.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(b.0)));
+    family_lists.extend(matches.into_iter().map(|(_, families)| families));
+}
+
+/// Picks a suitable face from `faces`.
+///
+/// Prefers faces with the same style as the base face,
+/// but fallbacks to any suitable face.
+fn pick_same_style(
+    fontdb: &fontdb::Database,
+    fonts: &DocumentFonts,
+    base_id: ID,
+    faces: impl Iterator<Item = ID>,
+    is_suitable: &dyn Fn(ID) -> bool,
+) -> Option<ID> {
+    let base_face = fonts.face(fontdb, base_id)?;
+
+    let mut other_style = None;
+    for id in faces {
+        let face = match fonts.face(fontdb, id) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let is_same_style = base_face.style == face.style
+            && base_face.weight == face.weight
+            && base_face.stretch == face.stretch;
+
+        // Checking for a char is expensive, so skip faces that cannot improve the result.
+        if !is_same_style && other_style.is_some() {
             continue;
         }
 
-        if !fontdb.has_char(face.id, c) {
+        if !is_suitable(face.id) {
             continue;
         }
 
-        let base_family = base_face
-            .families
-            .iter()
-            .find(|f| f.1 == fontdb::Language::English_UnitedStates)
-            .unwrap_or(&base_face.families[0]);
+        if is_same_style {
+            return Some(face.id);
+        }
 
-        let new_family = face
-            .families
-            .iter()
-            .find(|f| f.1 == fontdb::Language::English_UnitedStates)
-            .unwrap_or(&base_face.families[0]);
+        other_style = Some(face.id);
+    }
+
+    other_style
+}
 
-        log::warn!("Fallback from {} to {}.", base_family.0, new_family.0);
-        return fontdb.load_font(face.id);
+/// Checks that a language tag from `FontFallback::language_families` matches a language.
+fn language_matches(key: &str, lang: &str) -> bool {
+    if key.eq_ignore_ascii_case(lang) {
+        return true;
     }
 
-    None
+    lang.len() > key.len()
+        && lang.as_bytes()[key.len()] == b'-'
+        && lang[..key.len()].eq_ignore_ascii_case(key)
+}
+
+/// Returns a face's family name, preferably the English one.
+fn face_family(face: Option<&fontdb::FaceInfo>) -> String {
+    let face = match face {
+        Some(v) => v,
+        None => return String::new(),
+    };
+
+    face.families
+        .iter()
+        .find(|f| f.1 == fontdb::Language::English_UnitedStates)
+        .or_else(|| face.families.first())
+        .map(|f| f.0.clone())
+        .unwrap_or_default()
 }
 
 /// Resolves clusters positions.
@@ -1378,7 +3614,7 @@ fn resolve_clusters_positions(
     writing_mode: WritingMode,
     ts: Transform,
     fonts_cache: &FontsCache,
-    clusters: &mut [OutlinedCluster],
+    clusters: &mut [GlyphCluster],
 ) -> (f64, f64) {
     match chunk.text_flow {
         TextFlow::Linear => resolve_clusters_positions_horizontal(
@@ -1409,9 +3645,10 @@ fn resolve_clusters_positions_horizontal(
     pos_list: &[CharacterPosition],
     rotate_list: &[f64],
     writing_mode: WritingMode,
-    clusters: &mut [OutlinedCluster],
+    clusters: &mut [GlyphCluster],
 ) -> (f64, f64) {
-    let mut x = process_anchor(chunk.anchor, clusters_length(clusters));
+    let anchor = resolve_anchor(chunk, writing_mode);
+    let mut x = process_anchor(anchor, clusters_length(clusters));
     let mut y = 0.0;
 
     for cluster in clusters {
@@ -1451,7 +3688,7 @@ fn resolve_clusters_positions_path(
     writing_mode: WritingMode,
     ts: Transform,
     fonts_cache: &FontsCache,
-    clusters: &mut [OutlinedCluster],
+    clusters: &mut [GlyphCluster],
 ) -> (f64, f64) {
     let mut last_x = 0.0;
     let mut last_y = 0.0;
@@ -1465,8 +3702,9 @@ fn resolve_clusters_positions_path(
         WritingMode::TopToBottom => chunk.y.unwrap_or(0.0),
     };
 
+    let anchor = resolve_anchor(chunk, writing_mode);
     let start_offset =
-        chunk_offset + path.start_offset + process_anchor(chunk.anchor, clusters_length(clusters));
+        chunk_offset + path.start_offset + process_anchor(anchor, clusters_length(clusters));
 
     let normals = collect_normals(
         chunk,
@@ -1536,10 +3774,25 @@ fn resolve_clusters_positions_path(
     (last_x, last_y)
 }
 
-fn clusters_length(clusters: &[OutlinedCluster]) -> f64 {
+fn clusters_length(clusters: &[GlyphCluster]) -> f64 {
     clusters.iter().fold(0.0, |w, cluster| w + cluster.advance)
 }
 
+/// Resolves chunk's `text-anchor` in the visual order.
+///
+/// `start` and `end` are swapped for right-to-left chunks. Vertical text is not affected.
+fn resolve_anchor(chunk: &TextChunk, writing_mode: WritingMode) -> TextAnchor {
+    if chunk.direction == TextDirection::LeftToRight || writing_mode == WritingMode::TopToBottom {
+        return chunk.anchor;
+    }
+
+    match chunk.anchor {
+        TextAnchor::Start => TextAnchor::End,
+        TextAnchor::Middle => TextAnchor::Middle,
+        TextAnchor::End => TextAnchor::Start,
+    }
+}
+
 fn process_anchor(a: TextAnchor, text_width: f64) -> f64 {
     match a {
         TextAnchor::Start => 0.0, // Nothing.
@@ -1556,7 +3809,7 @@ struct PathNormal {
 
 fn collect_normals(
     chunk: &TextChunk,
-    clusters: &[OutlinedCluster],
+    clusters: &[GlyphCluster],
     path: &PathData,
     pos_list: &[CharacterPosition],
     char_offset: usize,
@@ -1565,32 +3818,80 @@ fn collect_normals(
 ) -> Vec<Option<PathNormal>> {
     debug_assert!(!path.is_empty());
 
-    let mut offsets = Vec::with_capacity(clusters.len());
+    let arclen_accuracy = {
+        let base_arclen_accuracy = 0.5;
+        // Accuracy depends on a current scale.
+        // When we have a tiny path scaled by a large value,
+        // we have to increase out accuracy accordingly.
+        let (sx, sy) = ts.get_scale();
+        // 1.0 acts as a threshold to prevent division by 0 and/or low accuracy.
+        base_arclen_accuracy / (sx * sy).sqrt().max(1.0)
+    };
+
+    // Each curve with its start offset and length.
+    let curves = collect_path_curves(path, arclen_accuracy);
+    let path_len = curves
+        .last()
+        .map(|(_, start, len)| start + len)
+        .unwrap_or(0.0);
+
+    // Text on a closed path wraps around instead of being cut at the path end.
+    let is_closed = is_closed_path(path) && path_len > 0.0;
+
     let mut normals = Vec::with_capacity(clusters.len());
-    {
-        let mut advance = offset;
-        for cluster in clusters {
-            // Clusters should be rotated by the x-midpoint x baseline position.
-            let half_width = cluster.width / 2.0;
-
-            // Include relative position.
-            let cp = char_offset + cluster.byte_idx.code_point_at(&chunk.text);
-            if let Some(pos) = pos_list.get(cp) {
-                advance += pos.dx.unwrap_or(0.0);
-            }
+    let mut advance = offset;
+    for cluster in clusters {
+        // Clusters should be rotated by the x-midpoint x baseline position.
+        let half_width = cluster.width / 2.0;
+
+        // Include relative position.
+        let cp = char_offset + cluster.byte_idx.code_point_at(&chunk.text);
+        if let Some(pos) = pos_list.get(cp) {
+            advance += pos.dx.unwrap_or(0.0);
+        }
 
-            let offset = advance + half_width;
+        let mut offset = advance + half_width;
+        advance += cluster.advance;
 
-            // Clusters outside the path have no normals.
-            if offset < 0.0 {
-                normals.push(None);
-            }
+        if is_closed {
+            offset = offset.rem_euclid(path_len);
+        }
 
-            offsets.push(offset);
-            advance += cluster.advance;
+        // Clusters outside the path have no normals.
+        if curves.is_empty() || offset < 0.0 || offset > path_len {
+            normals.push(None);
+            continue;
         }
+
+        let idx = curves
+            .partition_point(|(_, start, len)| start + len < offset)
+            .min(curves.len() - 1);
+        let (curve, start, _) = curves[idx];
+
+        let mut t = curve.inv_arclen(offset - start, arclen_accuracy);
+        // some rounding error may occur, so we give offset a little tolerance
+        debug_assert!((-1.0e-3..=1.0 + 1.0e-3).contains(&t));
+        t = t.clamp(0.0, 1.0);
+
+        let pos = curve.eval(t);
+        let d = curve.deriv().eval(t);
+        let d = kurbo::Vec2::new(-d.y, d.x); // tangent
+        let angle = d.atan2().to_degrees() - 90.0;
+
+        normals.push(Some(PathNormal {
+            x: pos.x,
+            y: pos.y,
+            angle,
+        }));
     }
 
+    normals
+}
+
+/// Converts path segments into cubic curves.
+///
+/// Returns each curve with its start offset along the path and its length.
+fn collect_path_curves(path: &PathData, arclen_accuracy: f64) -> Vec<(kurbo::CubicBez, f64, f64)> {
     let mut prev_mx = path.points()[0];
     let mut prev_my = path.points()[1];
     let mut prev_x = prev_mx;
@@ -1603,6 +3904,7 @@ fn collect_normals(
         cubic_from_points(px, py, p1.x, p1.y, p2.x, p2.y, x, y)
     }
 
+    let mut curves = Vec::new();
     let mut length = 0.0;
     for seg in path.segments() {
         let curve = match seg {
@@ -1625,59 +3927,32 @@ fn collect_normals(
             PathSegment::ClosePath => create_curve_from_line(prev_x, prev_y, prev_mx, prev_my),
         };
 
-        let arclen_accuracy = {
-            let base_arclen_accuracy = 0.5;
-            // Accuracy depends on a current scale.
-            // When we have a tiny path scaled by a large value,
-            // we have to increase out accuracy accordingly.
-            let (sx, sy) = ts.get_scale();
-            // 1.0 acts as a threshold to prevent division by 0 and/or low accuracy.
-            base_arclen_accuracy / (sx * sy).sqrt().max(1.0)
-        };
-
         let curve_len = curve.arclen(arclen_accuracy);
-
-        for offset in &offsets[normals.len()..] {
-            if *offset >= length && *offset <= length + curve_len {
-                let mut offset = curve.inv_arclen(offset - length, arclen_accuracy);
-                // some rounding error may occur, so we give offset a little tolerance
-                debug_assert!((-1.0e-3..=1.0 + 1.0e-3).contains(&offset));
-                offset = offset.min(1.0).max(0.0);
-
-                let pos = curve.eval(offset);
-                let d = curve.deriv().eval(offset);
-                let d = kurbo::Vec2::new(-d.y, d.x); // tangent
-                let angle = d.atan2().to_degrees() - 90.0;
-
-                normals.push(Some(PathNormal {
-                    x: pos.x,
-                    y: pos.y,
-                    angle,
-                }));
-
-                if normals.len() == offsets.len() {
-                    break;
-                }
-            }
-        }
+        curves.push((curve, length, curve_len));
 
         length += curve_len;
         prev_x = curve.p3.x;
         prev_y = curve.p3.y;
     }
 
-    // If path ended and we still have unresolved normals - set them to `None`.
-    for _ in 0..(offsets.len() - normals.len()) {
-        normals.push(None);
-    }
+    curves
+}
 
-    normals
+/// Checks that a path consists of a single closed subpath.
+fn is_closed_path(path: &PathData) -> bool {
+    let commands = path.commands();
+    commands.last() == Some(&PathCommand::ClosePath)
+        && commands
+            .iter()
+            .filter(|cmd| **cmd == PathCommand::MoveTo)
+            .count()
+            == 1
 }
 
 /// Applies the `letter-spacing` property to a text chunk clusters.
 ///
 /// [In the CSS spec](https://www.w3.org/TR/css-text-3/#letter-spacing-property).
-fn apply_letter_spacing(chunk: &TextChunk, clusters: &mut [OutlinedCluster]) {
+fn apply_letter_spacing(chunk: &TextChunk, clusters: &mut [GlyphCluster]) {
     // At least one span should have a non-zero spacing.
     if !chunk
         .spans
@@ -1707,7 +3982,7 @@ fn apply_letter_spacing(chunk: &TextChunk, clusters: &mut [OutlinedCluster]) {
                 if !cluster.advance.is_valid_length() {
                     cluster.width = 0.0;
                     cluster.advance = 0.0;
-                    cluster.path.clear();
+                    cluster.glyphs.clear();
                 }
             }
         }
@@ -1746,7 +4021,7 @@ fn script_supports_letter_spacing(script: unicode_script::Script) -> bool {
 /// Applies the `word-spacing` property to a text chunk clusters.
 ///
 /// [In the CSS spec](https://www.w3.org/TR/css-text-3/#propdef-word-spacing).
-fn apply_word_spacing(chunk: &TextChunk, clusters: &mut [OutlinedCluster]) {
+fn apply_word_spacing(chunk: &TextChunk, clusters: &mut [GlyphCluster]) {
     // At least one span should have a non-zero spacing.
     if !chunk
         .spans
@@ -1780,7 +4055,7 @@ fn is_word_separator_characters(c: char) -> bool {
     )
 }
 
-fn apply_length_adjust(chunk: &TextChunk, clusters: &mut [OutlinedCluster]) {
+fn apply_length_adjust(chunk: &TextChunk, clusters: &mut [GlyphCluster]) {
     let is_horizontal = matches!(chunk.text_flow, TextFlow::Linear);
 
     for span in &chunk.spans {
@@ -1841,35 +4116,19 @@ fn apply_length_adjust(chunk: &TextChunk, clusters: &mut [OutlinedCluster]) {
     }
 }
 
-/// Rotates clusters according to
-/// [Unicode Vertical_Orientation Property](https://www.unicode.org/reports/tr50/tr50-19.html).
-fn apply_writing_mode(writing_mode: WritingMode, clusters: &mut [OutlinedCluster]) {
+/// Centers sideways clusters in the top-to-bottom mode.
+///
+/// Upright clusters are positioned using vertical metrics during shaping.
+fn apply_writing_mode(writing_mode: WritingMode, clusters: &mut [GlyphCluster]) {
     if writing_mode != WritingMode::TopToBottom {
         return;
     }
 
-    for cluster in clusters {
-        let orientation = unicode_vo::char_orientation(cluster.codepoint);
-        if orientation == unicode_vo::Orientation::Upright {
-            // Additional offset. Not sure why.
-            let dy = cluster.width - cluster.height();
-
-            // Rotate a cluster 90deg counter clockwise by the center.
-            let mut ts = Transform::default();
-            ts.translate(cluster.width / 2.0, 0.0);
-            ts.rotate(-90.0);
-            ts.translate(-cluster.width / 2.0, -dy);
-            cluster.path.transform(ts);
-
-            // Move "baseline" to the middle and make height equal to width.
-            cluster.ascent = cluster.width / 2.0;
-            cluster.descent = -cluster.width / 2.0;
-        } else {
-            // Could not find a spec that explains this,
-            // but this is how other applications are shifting the "rotated" characters
-            // in the top-to-bottom mode.
-            cluster.transform.translate(0.0, cluster.x_height / 2.0);
-        }
+    for cluster in clusters.iter_mut().filter(|cluster| !cluster.upright) {
+        // Could not find a spec that explains this,
+        // but this is how other applications are shifting the "rotated" characters
+        // in the top-to-bottom mode.
+        cluster.transform.translate(0.0, cluster.x_height / 2.0);
     }
 }
 
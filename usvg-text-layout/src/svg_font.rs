@@ -0,0 +1,318 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! SVG fonts support.
+//!
+//! SVG fonts have no font data, so metrics, shaping and outlines are resolved
+//! from `usvg::SvgFont` directly. Only horizontal glyphs are supported.
+
+use std::convert::TryFrom;
+use std::num::NonZeroU16;
+use std::rc::Rc;
+
+use fontdb::ID;
+use usvg_tree::{ArabicForm, PathData, SvgFont, SvgGlyph};
+
+use crate::{is_default_ignorable, language_matches, ByteIndex, Glyph, GlyphId, ResolvedFont};
+
+/// Resolves SVG font metrics.
+///
+/// Metrics that SVG fonts don't have use the same defaults as OpenType fonts without them.
+pub(crate) fn resolve_font(font: &SvgFont, id: ID) -> Option<ResolvedFont> {
+    let units_per_em = NonZeroU16::new(font.units_per_em.round().min(u16::MAX as f64) as u16)?;
+
+    let ascent = font.ascent.round() as i16;
+    let descent = -font.descent.round() as i16;
+
+    let x_height = (f32::from(ascent.saturating_sub(descent)) * 0.45) as u16;
+    let x_height = NonZeroU16::new(x_height.max(1)).unwrap();
+
+    Some(ResolvedFont {
+        id,
+        units_per_em,
+        ascent,
+        descent,
+        line_gap: 0,
+        x_height,
+        cap_height: NonZeroU16::new((units_per_em.get() / 10 * 7).max(1)).unwrap(),
+        underline_position: -(units_per_em.get() as i16) / 9,
+        underline_thickness: NonZeroU16::new((units_per_em.get() / 12).max(1)).unwrap(),
+        line_through_position: x_height.get() as i16 / 2,
+        subscript_offset: (units_per_em.get() as f32 / 0.2).round() as i16,
+        superscript_offset: (units_per_em.get() as f32 / 0.4).round() as i16,
+        synthetic_bold: false,
+        synthetic_oblique: false,
+    })
+}
+
+/// Checks that a grapheme can be represented by the font's glyphs.
+pub(crate) fn has_grapheme(font: &SvgFont, grapheme: &str) -> bool {
+    // Joiners and selectors are ignored, just like in OpenType fonts.
+    let grapheme: String = grapheme
+        .chars()
+        .filter(|c| !is_default_ignorable(*c))
+        .collect();
+
+    let mut rest = grapheme.as_str();
+    while !rest.is_empty() {
+        let len = font
+            .glyphs
+            .iter()
+            .filter(|glyph| !glyph.unicode.is_empty() && rest.starts_with(&glyph.unicode))
+            .map(|glyph| glyph.unicode.len())
+            .max();
+
+        match len {
+            Some(len) => rest = &rest[len..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+/// Shapes a text run using an SVG font.
+///
+/// Each glyph is the longest `unicode` match at the current position that is suitable
+/// for the `lang` and the Arabic form. The first one in the document order is used
+/// when multiple glyphs have the same length.
+///
+/// `range` must be inside a single bidi run. Glyphs are in the visual order.
+/// Glyph IDs are indices in `SvgFont::glyphs` plus one. Zero is the missing glyph.
+pub(crate) fn shape(
+    svg_font: &SvgFont,
+    text: &str,
+    range: std::ops::Range<usize>,
+    font: Rc<ResolvedFont>,
+    is_rtl: bool,
+    lang: Option<&str>,
+    apply_kerning: bool,
+) -> Vec<Glyph> {
+    let run = &text[range.clone()];
+    let chars: Vec<(usize, char)> = run.char_indices().collect();
+    let forms = arabic_forms(&chars);
+
+    let mut glyphs: Vec<Glyph> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let rest = &run[pos..];
+
+        let mut best: Option<(u16, &SvgGlyph)> = None;
+        for (idx, glyph) in svg_font.glyphs.iter().enumerate() {
+            if glyph.unicode.is_empty() || !rest.starts_with(&glyph.unicode) {
+                continue;
+            }
+
+            if best.is_some_and(|(_, best)| best.unicode.len() >= glyph.unicode.len()) {
+                continue;
+            }
+
+            let chars_count = glyph.unicode.chars().count();
+            if !matches_form(glyph, &forms[i..i + chars_count]) || !matches_lang(glyph, lang) {
+                continue;
+            }
+
+            if let Ok(id) = u16::try_from(idx + 1) {
+                best = Some((id, glyph));
+            }
+        }
+
+        let (id, glyph, chars_count) = match best {
+            Some((id, glyph)) => (id, glyph, glyph.unicode.chars().count()),
+            None if is_default_ignorable(c) => {
+                // Ignorable characters without a glyph are not rendered.
+                i += 1;
+                continue;
+            }
+            None => (0, &svg_font.missing_glyph, 1),
+        };
+
+        glyphs.push(Glyph {
+            byte_idx: ByteIndex::new(range.start + pos),
+            id: GlyphId(id),
+            dx: 0,
+            dy: 0,
+            width: glyph.horiz_adv_x.round() as i32,
+            upright: false,
+            font: font.clone(),
+        });
+
+        i += chars_count;
+    }
+
+    if apply_kerning {
+        for i in 1..glyphs.len() {
+            let k = match find_kerning(svg_font, glyphs[i - 1].id, glyphs[i].id) {
+                Some(k) => k,
+                None => continue,
+            };
+
+            // Glyphs are placed in the visual order, so in the right-to-left text
+            // the space between a pair is defined by the second glyph.
+            let idx = if is_rtl { i } else { i - 1 };
+            glyphs[idx].width -= k.round() as i32;
+        }
+    }
+
+    if is_rtl {
+        glyphs.reverse();
+    }
+
+    glyphs
+}
+
+/// Returns a glyph outline in font units.
+pub(crate) fn outline(font: &SvgFont, glyph_id: GlyphId) -> Option<PathData> {
+    let glyph = match glyph_id.0 {
+        0 => &font.missing_glyph,
+        n => font.glyphs.get(n as usize - 1)?,
+    };
+
+    glyph.path.as_deref().cloned()
+}
+
+fn find_kerning(font: &SvgFont, first: GlyphId, second: GlyphId) -> Option<f64> {
+    let first = usize::from(first.0.checked_sub(1)?);
+    let second = usize::from(second.0.checked_sub(1)?);
+    font.kerning
+        .iter()
+        .find(|pair| pair.first.contains(&first) && pair.second.contains(&second))
+        .map(|pair| pair.k)
+}
+
+fn matches_lang(glyph: &SvgGlyph, lang: Option<&str>) -> bool {
+    if glyph.languages.is_empty() {
+        return true;
+    }
+
+    match lang {
+        Some(lang) => glyph
+            .languages
+            .iter()
+            .any(|key| language_matches(key, lang)),
+        None => false,
+    }
+}
+
+/// Checks that a glyph can be used for characters with the specified forms.
+///
+/// A ligature joins its neighbours the same way as its first and last characters.
+fn matches_form(glyph: &SvgGlyph, forms: &[ArabicForm]) -> bool {
+    let expected = match glyph.arabic_form {
+        Some(v) => v,
+        None => return true,
+    };
+
+    let joins_prev = matches!(
+        forms.first(),
+        Some(ArabicForm::Medial) | Some(ArabicForm::Terminal)
+    );
+    let joins_next = matches!(
+        forms.last(),
+        Some(ArabicForm::Medial) | Some(ArabicForm::Initial)
+    );
+
+    expected == form_from_joining(joins_prev, joins_next)
+}
+
+fn form_from_joining(joins_prev: bool, joins_next: bool) -> ArabicForm {
+    match (joins_prev, joins_next) {
+        (true, true) => ArabicForm::Medial,
+        (true, false) => ArabicForm::Terminal,
+        (false, true) => ArabicForm::Initial,
+        (false, false) => ArabicForm::Isolated,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum JoiningType {
+    Dual,
+    Right,
+    Causing,
+    Transparent,
+    NonJoining,
+}
+
+/// Resolves contextual forms of characters in the logical order.
+///
+/// Non-joining characters are always isolated.
+fn arabic_forms(chars: &[(usize, char)]) -> Vec<ArabicForm> {
+    let types: Vec<_> = chars.iter().map(|(_, c)| joining_type(*c)).collect();
+
+    let mut forms = Vec::with_capacity(chars.len());
+    for (i, t) in types.iter().enumerate() {
+        if !matches!(t, JoiningType::Dual | JoiningType::Right) {
+            forms.push(ArabicForm::Isolated);
+            continue;
+        }
+
+        let prev = types[..i]
+            .iter()
+            .rev()
+            .find(|t| **t != JoiningType::Transparent);
+        let next = types[i + 1..]
+            .iter()
+            .find(|t| **t != JoiningType::Transparent);
+
+        let joins_prev = matches!(prev, Some(JoiningType::Dual) | Some(JoiningType::Causing));
+        let joins_next = *t == JoiningType::Dual
+            && matches!(
+                next,
+                Some(JoiningType::Dual) | Some(JoiningType::Right) | Some(JoiningType::Causing)
+            );
+
+        forms.push(form_from_joining(joins_prev, joins_next));
+    }
+
+    forms
+}
+
+/// Returns a joining type of a character.
+///
+/// Only the Arabic block is supported.
+fn joining_type(c: char) -> JoiningType {
+    match c as u32 {
+        0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x0670
+        | 0x06D6..=0x06DC
+        | 0x06DF..=0x06E4
+        | 0x06E7..=0x06E8
+        | 0x06EA..=0x06ED => JoiningType::Transparent,
+        0x0640 | 0x200D => JoiningType::Causing,
+        0x0622..=0x0625
+        | 0x0627
+        | 0x0629
+        | 0x062F..=0x0632
+        | 0x0648
+        | 0x0671..=0x0673
+        | 0x0675..=0x0677
+        | 0x0688..=0x0699
+        | 0x06C0
+        | 0x06C3..=0x06CB
+        | 0x06CD
+        | 0x06CF
+        | 0x06D2..=0x06D3
+        | 0x06D5
+        | 0x06EE..=0x06EF => JoiningType::Right,
+        0x0620
+        | 0x0626
+        | 0x0628
+        | 0x062A..=0x062E
+        | 0x0633..=0x063F
+        | 0x0641..=0x0647
+        | 0x0649..=0x064A
+        | 0x066E..=0x066F
+        | 0x0678..=0x0687
+        | 0x069A..=0x06BF
+        | 0x06C1..=0x06C2
+        | 0x06CC
+        | 0x06CE
+        | 0x06D0..=0x06D1
+        | 0x06FA..=0x06FC
+        | 0x06FF => JoiningType::Dual,
+        _ => JoiningType::NonJoining,
+    }
+}
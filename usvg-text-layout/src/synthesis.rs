@@ -0,0 +1,193 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Synthetic bold and oblique faces.
+//!
+//! Mimics FreeType, which is what most browsers use on Linux:
+//! emboldened outlines are offset by 1/48 em and advances are increased by 1/24 em,
+//! while an oblique face is skewed by 14 degrees.
+
+use fontdb::Database;
+use rustybuzz::ttf_parser;
+use usvg_tree::{Font, FontStyle, PathCommand, PathData, Transform};
+
+use crate::ResolvedFont;
+
+/// The minimal weight that is considered bold.
+const BOLD_WEIGHT: u16 = 600;
+
+/// An oblique angle in degrees.
+const OBLIQUE_ANGLE: f64 = 14.0;
+
+/// Enables synthetic bold and oblique when the resolved face lacks the requested ones.
+///
+/// Variable fonts that have a matching axis are never synthesized.
+pub(crate) fn resolve(font: &mut ResolvedFont, request: &Font, fontdb: &Database) {
+    let face = match fontdb.face(font.id) {
+        Some(v) => v,
+        None => return,
+    };
+
+    let (has_weight_axis, has_slant_axis) = fontdb
+        .with_face_data(font.id, |data, face_index| {
+            let face = ttf_parser::Face::parse(data, face_index).ok()?;
+            let has_axis = |tags: &[&[u8; 4]]| {
+                face.variation_axes()
+                    .into_iter()
+                    .any(|axis| tags.iter().any(|tag| axis.tag.to_bytes() == **tag))
+            };
+
+            Some((has_axis(&[b"wght"]), has_axis(&[b"ital", b"slnt"])))
+        })
+        .flatten()
+        .unwrap_or((false, false));
+
+    font.synthetic_bold = request.synthesis.weight
+        && request.weight >= BOLD_WEIGHT
+        && face.weight.0 < BOLD_WEIGHT
+        && !has_weight_axis;
+
+    font.synthetic_oblique = request.synthesis.style
+        && request.style != FontStyle::Normal
+        && face.style == fontdb::Style::Normal
+        && !has_slant_axis;
+}
+
+/// Returns an advance increase in font units.
+pub(crate) fn bold_advance(font: &ResolvedFont) -> i32 {
+    if font.synthetic_bold {
+        i32::from(font.units_per_em.get()) / 24
+    } else {
+        0
+    }
+}
+
+/// Returns an outline offset in font units.
+pub(crate) fn bold_offset(font: &ResolvedFont) -> f64 {
+    if font.synthetic_bold {
+        font.units_per_em.get() as f64 / 48.0
+    } else {
+        0.0
+    }
+}
+
+/// Returns a skew transform in font units.
+pub(crate) fn oblique_transform() -> Transform {
+    Transform::new(1.0, 0.0, OBLIQUE_ANGLE.to_radians().tan(), 1.0, 0.0, 0.0)
+}
+
+/// Offsets a glyph outline outwards.
+///
+/// Each point is moved along the bisector of its neighbouring edges,
+/// so straight edges are moved exactly by `offset`.
+/// Control points are treated like on-curve ones.
+pub(crate) fn embolden(path: &PathData, offset: f64) -> PathData {
+    let mut points: Vec<(f64, f64)> = path.points().chunks(2).map(|p| (p[0], p[1])).collect();
+
+    // Collect contours as ranges of points.
+    let mut contours = Vec::new();
+    let mut start = 0;
+    let mut idx = 0;
+    for cmd in path.commands() {
+        match cmd {
+            PathCommand::MoveTo => {
+                if idx > start {
+                    contours.push(start..idx);
+                }
+                start = idx;
+                idx += 1;
+            }
+            PathCommand::LineTo => idx += 1,
+            PathCommand::CurveTo => idx += 3,
+            PathCommand::ClosePath => {}
+        }
+    }
+    if idx > start {
+        contours.push(start..idx);
+    }
+
+    // Outer contours and holes have opposite directions,
+    // so the global orientation is enough to find the outside.
+    let mut area = 0.0;
+    for range in &contours {
+        let contour = &points[range.clone()];
+        for (i, p) in contour.iter().enumerate() {
+            let next = contour[(i + 1) % contour.len()];
+            area += p.0 * next.1 - next.0 * p.1;
+        }
+    }
+    let sign = if area < 0.0 { -1.0 } else { 1.0 };
+
+    for range in contours {
+        let original = points[range.clone()].to_vec();
+
+        // A closing point that duplicates the first one is moved the same way.
+        let mut len = original.len();
+        if len > 1 && original[0] == original[len - 1] {
+            len -= 1;
+        }
+
+        for i in 0..len {
+            let p = original[i];
+            let prev = (1..len)
+                .map(|n| original[(i + len - n) % len])
+                .find(|q| *q != p);
+            let next = (1..len).map(|n| original[(i + n) % len]).find(|q| *q != p);
+            let (prev, next) = match (prev, next) {
+                (Some(prev), Some(next)) => (prev, next),
+                _ => continue,
+            };
+
+            let n_in = normal(prev, p, sign);
+            let n_out = normal(p, next, sign);
+
+            // Don't move spikes too far away.
+            let d = 1.0 + n_in.0 * n_out.0 + n_in.1 * n_out.1;
+            if d < 0.0625 {
+                continue;
+            }
+
+            let shift = (
+                (n_in.0 + n_out.0) / d * offset,
+                (n_in.1 + n_out.1) / d * offset,
+            );
+            points[range.start + i] = (p.0 + shift.0, p.1 + shift.1);
+        }
+
+        if len != original.len() {
+            points[range.end - 1] = points[range.start];
+        }
+    }
+
+    let mut new_path = PathData::new();
+    let mut points = points.into_iter();
+    for cmd in path.commands() {
+        match cmd {
+            PathCommand::MoveTo => {
+                let p = points.next().unwrap();
+                new_path.push_move_to(p.0, p.1);
+            }
+            PathCommand::LineTo => {
+                let p = points.next().unwrap();
+                new_path.push_line_to(p.0, p.1);
+            }
+            PathCommand::CurveTo => {
+                let p1 = points.next().unwrap();
+                let p2 = points.next().unwrap();
+                let p = points.next().unwrap();
+                new_path.push_curve_to(p1.0, p1.1, p2.0, p2.1, p.0, p.1);
+            }
+            PathCommand::ClosePath => new_path.push_close_path(),
+        }
+    }
+
+    new_path
+}
+
+/// Returns an outward unit normal of an edge.
+fn normal(a: (f64, f64), b: (f64, f64), sign: f64) -> (f64, f64) {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len = (dx * dx + dy * dy).sqrt();
+    (sign * dy / len, -sign * dx / len)
+}
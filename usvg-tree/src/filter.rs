@@ -7,7 +7,9 @@
 use strict_num::PositiveF64;
 use svgtypes::AspectRatio;
 
-use crate::{BlendMode, Color, ImageRendering, Node, NonZeroF64, Opacity, Point, Rect, Units};
+use crate::{
+    BlendMode, Color, ImageRendering, Node, NonZeroF64, Opacity, Point, Rect, SpecifiedColor, Units,
+};
 
 /// A filter element.
 ///
@@ -465,6 +467,9 @@ pub struct DropShadow {
     ///
     /// `flood-opacity` in the SVG.
     pub opacity: Opacity,
+
+    /// The original `flood-color` when it was specified outside of sRGB.
+    pub specified_color: Option<SpecifiedColor>,
 }
 
 /// A flood filter primitive.
@@ -481,6 +486,9 @@ pub struct Flood {
     ///
     /// `flood-opacity` in the SVG.
     pub opacity: Opacity,
+
+    /// The original `flood-color` when it was specified outside of sRGB.
+    pub specified_color: Option<SpecifiedColor>,
 }
 
 /// A Gaussian blur filter primitive.
@@ -559,6 +567,9 @@ pub struct DiffuseLighting {
     /// `lighting-color` in the SVG.
     pub lighting_color: Color,
 
+    /// The original `lighting-color` when it was specified outside of sRGB.
+    pub specified_lighting_color: Option<SpecifiedColor>,
+
     /// A light source.
     pub light_source: LightSource,
 }
@@ -595,6 +606,9 @@ pub struct SpecularLighting {
     /// `lighting-color` in the SVG.
     pub lighting_color: Color,
 
+    /// The original `lighting-color` when it was specified outside of sRGB.
+    pub specified_lighting_color: Option<SpecifiedColor>,
+
     /// A light source.
     pub light_source: LightSource,
 }
@@ -21,6 +21,7 @@
 pub mod filter;
 mod geom;
 mod pathdata;
+mod recolor;
 mod text;
 pub mod utils;
 
@@ -338,6 +339,9 @@ pub struct Stop {
     ///
     /// `stop-opacity` in SVG.
     pub opacity: Opacity,
+
+    /// The original `stop-color` when it was specified outside of sRGB.
+    pub specified_color: Option<SpecifiedColor>,
 }
 
 /// A pattern element.
@@ -466,9 +470,11 @@ impl Default for LineJoin {
 
 /// A stroke style.
 #[allow(missing_docs)]
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Stroke {
     pub paint: Paint,
+    /// The original color when `paint` is a color specified outside of sRGB.
+    pub specified_color: Option<SpecifiedColor>,
     pub dasharray: Option<Vec<f64>>,
     pub dashoffset: f32, // f32 and not f64 to reduce the struct size.
     pub miterlimit: StrokeMiterlimit,
@@ -484,6 +490,7 @@ impl Default for Stroke {
             // The actual default color is `none`,
             // but to simplify the `Stroke` object creation we use `black`.
             paint: Paint::Color(Color::black()),
+            specified_color: None,
             dasharray: None,
             dashoffset: 0.0,
             miterlimit: StrokeMiterlimit::default(),
@@ -513,9 +520,11 @@ impl Default for FillRule {
 
 /// A fill style.
 #[allow(missing_docs)]
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Fill {
     pub paint: Paint,
+    /// The original color when `paint` is a color specified outside of sRGB.
+    pub specified_color: Option<SpecifiedColor>,
     pub opacity: Opacity,
     pub rule: FillRule,
 }
@@ -536,6 +545,7 @@ impl Default for Fill {
     fn default() -> Self {
         Fill {
             paint: Paint::Color(Color::black()),
+            specified_color: None,
             opacity: Opacity::ONE,
             rule: FillRule::default(),
         }
@@ -571,6 +581,57 @@ impl Color {
     }
 }
 
+/// A color space of a [`SpecifiedColor`].
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorSpace {
+    HWB,
+    Lab,
+    LCH,
+    Oklab,
+    Oklch,
+    DisplayP3,
+}
+
+/// A color in its original color space.
+///
+/// Colors like `lab()` or `color(display-p3 ...)` are always converted into
+/// an sRGB [`Color`], but the original value is preserved as well,
+/// so it can be used by wide gamut outputs.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpecifiedColor {
+    /// A color space.
+    pub space: ColorSpace,
+
+    /// Color components with percentages already resolved.
+    ///
+    /// - `HWB`: hue in degrees, whiteness and blackness in a 0..1 range.
+    /// - `Lab`: lightness in a 0..100 range, a and b axes.
+    /// - `LCH`: lightness in a 0..100 range, chroma and hue in degrees.
+    /// - `Oklab`: lightness in a 0..1 range, a and b axes.
+    /// - `Oklch`: lightness in a 0..1 range, chroma and hue in degrees.
+    /// - `DisplayP3`: red, green and blue in a 0..1 range.
+    pub components: [f64; 3],
+}
+
+impl std::fmt::Display for SpecifiedColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Round to get rid of floating point noise.
+        let n = |v: f64| (v * 10_000.0).round() / 10_000.0;
+        let [c1, c2, c3] = self.components;
+        match self.space {
+            ColorSpace::HWB => write!(f, "hwb({} {}% {}%)", n(c1), n(c2 * 100.0), n(c3 * 100.0)),
+            ColorSpace::Lab => write!(f, "lab({} {} {})", n(c1), n(c2), n(c3)),
+            ColorSpace::LCH => write!(f, "lch({} {} {})", n(c1), n(c2), n(c3)),
+            ColorSpace::Oklab => write!(f, "oklab({} {} {})", n(c1), n(c2), n(c3)),
+            ColorSpace::Oklch => write!(f, "oklch({} {} {})", n(c1), n(c2), n(c3)),
+            ColorSpace::DisplayP3 => {
+                write!(f, "color(display-p3 {} {} {})", n(c1), n(c2), n(c3))
+            }
+        }
+    }
+}
+
 /// A paint style.
 ///
 /// `paint` value type in the SVG.
@@ -991,6 +1052,106 @@ pub struct Tree {
     ///
     /// The root node is always `Group`.
     pub root: Node,
+
+    /// Font faces defined by the document's `@font-face` rules.
+    ///
+    /// Used during text-to-path conversion.
+    pub font_faces: Vec<FontFace>,
+
+    /// SVG fonts defined by the document's `font` elements.
+    ///
+    /// Used during text-to-path conversion.
+    pub svg_fonts: Vec<SvgFont>,
+
+    /// Non-fatal issues found during the tree processing.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A non-fatal issue found during the tree processing.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Diagnostic {
+    /// A color is outside of the sRGB gamut and was mapped into it.
+    ColorOutOfGamut {
+        /// An ID of the element that uses this color. Can be empty.
+        element_id: String,
+        /// The original color.
+        color: SpecifiedColor,
+        /// The resulting sRGB color.
+        mapped: Color,
+    },
+    /// A character is missing in the span's font and a fallback font was used instead.
+    FontFallback {
+        /// An ID of the text element. Can be empty.
+        element_id: String,
+        /// The first character that triggered the fallback.
+        character: char,
+        /// The span's font family.
+        font_family: String,
+        /// The family of the fallback font.
+        fallback_family: String,
+    },
+    /// None of the available fonts have a character.
+    MissingGlyph {
+        /// An ID of the text element. Can be empty.
+        element_id: String,
+        /// The missing character.
+        character: char,
+    },
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Diagnostic::ColorOutOfGamut {
+                element_id,
+                color,
+                mapped,
+            } => {
+                write!(f, "'{}' ", color)?;
+                if !element_id.is_empty() {
+                    write!(f, "in '{}' ", element_id)?;
+                }
+
+                write!(
+                    f,
+                    "is outside of the sRGB gamut. Mapped to #{:02x}{:02x}{:02x}.",
+                    mapped.red, mapped.green, mapped.blue
+                )
+            }
+            Diagnostic::FontFallback {
+                element_id,
+                character,
+                font_family,
+                fallback_family,
+            } => {
+                write!(
+                    f,
+                    "Fallback from {} to {} for U+{:04X}",
+                    font_family, fallback_family, *character as u32
+                )?;
+                if !element_id.is_empty() {
+                    write!(f, " in '{}'", element_id)?;
+                }
+
+                write!(f, ".")
+            }
+            Diagnostic::MissingGlyph {
+                element_id,
+                character,
+            } => {
+                write!(
+                    f,
+                    "No fonts with a {}/U+{:04X} character were found",
+                    character, *character as u32
+                )?;
+                if !element_id.is_empty() {
+                    write!(f, " in '{}'", element_id)?;
+                }
+
+                write!(f, ".")
+            }
+        }
+    }
 }
 
 impl Tree {
@@ -1039,6 +1200,41 @@ impl Tree {
     pub fn filters<F: FnMut(Rc<filter::Filter>)>(&self, mut f: F) {
         loop_over_filters(&self.root, &mut f)
     }
+
+    /// Replaces each color in the tree with the one returned by a closure.
+    ///
+    /// Affects fill and stroke paints, gradient stops, patterns content,
+    /// filter colors and nested SVG images.
+    /// Clip paths are left untouched, since their colors are never painted.
+    /// Masks are left untouched as well, even though their colors do matter:
+    /// a mask's luminance defines which parts of an element are visible,
+    /// so recoloring it, for example to a monochrome tint, would change the element's shape
+    /// instead of its colors.
+    /// Opacity is preserved.
+    ///
+    /// When a color is changed, the corresponding `specified_color` is reset to `None`,
+    /// since it no longer describes the new color.
+    ///
+    /// Gradients and filters are shared via `Rc`, so they will be replaced with
+    /// recolored copies. Objects shared by multiple nodes will stay shared
+    /// and each of them will be processed only once.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut tree: usvg_tree::Tree = unimplemented!();
+    /// // Replace black with a brand color.
+    /// tree.recolor(|c| {
+    ///     if c == usvg_tree::Color::black() {
+    ///         usvg_tree::Color::new_rgb(0, 90, 200)
+    ///     } else {
+    ///         c
+    ///     }
+    /// });
+    /// ```
+    pub fn recolor<F: FnMut(Color) -> Color>(&mut self, mut f: F) {
+        recolor::recolor_tree(&self.root, &mut f)
+    }
 }
 
 fn has_text_nodes(root: &Node) -> bool {
@@ -0,0 +1,215 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::{
+    filter, Color, Fill, ImageKind, LinearGradient, Node, NodeKind, Paint, Pattern, RadialGradient,
+    SpecifiedColor, Stop, Stroke,
+};
+
+/// Already processed shared objects.
+///
+/// Original objects are kept alive, so their addresses cannot be reused
+/// by newly allocated ones.
+#[derive(Default)]
+struct Cache {
+    linear_gradients: HashMap<*const LinearGradient, (Rc<LinearGradient>, Rc<LinearGradient>)>,
+    radial_gradients: HashMap<*const RadialGradient, (Rc<RadialGradient>, Rc<RadialGradient>)>,
+    patterns: HashMap<*const Pattern, Rc<Pattern>>,
+    filters: HashMap<*const filter::Filter, (Rc<filter::Filter>, Rc<filter::Filter>)>,
+}
+
+pub(crate) fn recolor_tree(root: &Node, f: &mut dyn FnMut(Color) -> Color) {
+    recolor_nodes(root, f, &mut Cache::default());
+}
+
+fn recolor_nodes(root: &Node, f: &mut dyn FnMut(Color) -> Color, cache: &mut Cache) {
+    for node in root.descendants() {
+        // Subroots must be processed after the current node is released.
+        let mut subroots = Vec::new();
+
+        match *node.borrow_mut() {
+            NodeKind::Group(ref mut group) => {
+                // Clip paths are ignored, because their colors are never painted.
+                // Masks are ignored, because their colors define visibility, not appearance.
+                if let Some(ref mut paint) = group.filter_fill {
+                    recolor_paint(paint, f, cache, &mut subroots);
+                }
+
+                if let Some(ref mut paint) = group.filter_stroke {
+                    recolor_paint(paint, f, cache, &mut subroots);
+                }
+
+                for filter in &mut group.filters {
+                    *filter = recolor_filter(filter, f, cache, &mut subroots);
+                }
+            }
+            NodeKind::Path(ref mut path) => {
+                recolor_fill(path.fill.as_mut(), f, cache, &mut subroots);
+                recolor_stroke(path.stroke.as_mut(), f, cache, &mut subroots);
+            }
+            NodeKind::Image(ref image) => {
+                if let ImageKind::SVG(ref tree) = image.kind {
+                    subroots.push(tree.root.clone());
+                }
+            }
+            NodeKind::Text(ref mut text) => {
+                for chunk in &mut text.chunks {
+                    for span in &mut chunk.spans {
+                        recolor_fill(span.fill.as_mut(), f, cache, &mut subroots);
+                        recolor_stroke(span.stroke.as_mut(), f, cache, &mut subroots);
+
+                        let decoration = &mut span.decoration;
+                        for style in [
+                            decoration.underline.as_mut(),
+                            decoration.overline.as_mut(),
+                            decoration.line_through.as_mut(),
+                        ]
+                        .iter_mut()
+                        .flatten()
+                        {
+                            recolor_fill(style.fill.as_mut(), f, cache, &mut subroots);
+                            recolor_stroke(style.stroke.as_mut(), f, cache, &mut subroots);
+                        }
+                    }
+                }
+            }
+        }
+
+        for subroot in subroots {
+            recolor_nodes(&subroot, f, cache);
+        }
+    }
+}
+
+fn recolor_fill(
+    fill: Option<&mut Fill>,
+    f: &mut dyn FnMut(Color) -> Color,
+    cache: &mut Cache,
+    subroots: &mut Vec<Node>,
+) {
+    if let Some(fill) = fill {
+        if let Paint::Color(ref mut color) = fill.paint {
+            recolor_color(color, &mut fill.specified_color, f);
+        } else {
+            recolor_paint(&mut fill.paint, f, cache, subroots);
+        }
+    }
+}
+
+fn recolor_stroke(
+    stroke: Option<&mut Stroke>,
+    f: &mut dyn FnMut(Color) -> Color,
+    cache: &mut Cache,
+    subroots: &mut Vec<Node>,
+) {
+    if let Some(stroke) = stroke {
+        if let Paint::Color(ref mut color) = stroke.paint {
+            recolor_color(color, &mut stroke.specified_color, f);
+        } else {
+            recolor_paint(&mut stroke.paint, f, cache, subroots);
+        }
+    }
+}
+
+fn recolor_paint(
+    paint: &mut Paint,
+    f: &mut dyn FnMut(Color) -> Color,
+    cache: &mut Cache,
+    subroots: &mut Vec<Node>,
+) {
+    match paint {
+        Paint::Color(ref mut color) => *color = f(*color),
+        Paint::LinearGradient(ref mut lg) => {
+            let (_, new) = cache
+                .linear_gradients
+                .entry(Rc::as_ptr(lg))
+                .or_insert_with(|| {
+                    let mut new = LinearGradient::clone(lg);
+                    recolor_stops(&mut new.base.stops, f);
+                    (lg.clone(), Rc::new(new))
+                });
+            *lg = new.clone();
+        }
+        Paint::RadialGradient(ref mut rg) => {
+            let (_, new) = cache
+                .radial_gradients
+                .entry(Rc::as_ptr(rg))
+                .or_insert_with(|| {
+                    let mut new = RadialGradient::clone(rg);
+                    recolor_stops(&mut new.base.stops, f);
+                    (rg.clone(), Rc::new(new))
+                });
+            *rg = new.clone();
+        }
+        Paint::Pattern(ref patt) => {
+            // Pattern children can be modified in place,
+            // but they still must be processed only once.
+            if let Entry::Vacant(entry) = cache.patterns.entry(Rc::as_ptr(patt)) {
+                entry.insert(patt.clone());
+                subroots.push(patt.root.clone());
+            }
+        }
+    }
+}
+
+fn recolor_stops(stops: &mut [Stop], f: &mut dyn FnMut(Color) -> Color) {
+    for stop in stops {
+        recolor_color(&mut stop.color, &mut stop.specified_color, f);
+    }
+}
+
+fn recolor_color(
+    color: &mut Color,
+    specified_color: &mut Option<SpecifiedColor>,
+    f: &mut dyn FnMut(Color) -> Color,
+) {
+    let new_color = f(*color);
+    if new_color != *color {
+        // The original color is no longer valid.
+        *specified_color = None;
+    }
+
+    *color = new_color;
+}
+
+fn recolor_filter(
+    filter: &Rc<filter::Filter>,
+    f: &mut dyn FnMut(Color) -> Color,
+    cache: &mut Cache,
+    subroots: &mut Vec<Node>,
+) -> Rc<filter::Filter> {
+    let (_, new) = cache.filters.entry(Rc::as_ptr(filter)).or_insert_with(|| {
+        let mut new = filter::Filter::clone(filter);
+        for primitive in &mut new.primitives {
+            match primitive.kind {
+                filter::Kind::DiffuseLighting(ref mut fe) => {
+                    recolor_color(&mut fe.lighting_color, &mut fe.specified_lighting_color, f);
+                }
+                filter::Kind::DropShadow(ref mut fe) => {
+                    recolor_color(&mut fe.color, &mut fe.specified_color, f);
+                }
+                filter::Kind::Flood(ref mut fe) => {
+                    recolor_color(&mut fe.color, &mut fe.specified_color, f);
+                }
+                filter::Kind::Image(ref fe) => {
+                    if let filter::ImageKind::Use(ref node) = fe.data {
+                        subroots.push(node.clone());
+                    }
+                }
+                filter::Kind::SpecularLighting(ref mut fe) => {
+                    recolor_color(&mut fe.lighting_color, &mut fe.specified_lighting_color, f);
+                }
+                _ => {}
+            }
+        }
+
+        (filter.clone(), Rc::new(new))
+    });
+
+    new.clone()
+}
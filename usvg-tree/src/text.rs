@@ -3,6 +3,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::rc::Rc;
+use std::sync::Arc;
 
 use strict_num::NonZeroPositiveF64;
 
@@ -59,8 +60,170 @@ pub struct Font {
     pub style: FontStyle,
     /// A font stretch.
     pub stretch: FontStretch,
-    /// A font width.
+    /// A font weight.
+    ///
+    /// In a 1..=1000 range.
+    pub weight: u16,
+    /// Allowed synthetic faces.
+    ///
+    /// `font-synthesis` in SVG.
+    pub synthesis: FontSynthesis,
+}
+
+/// A `font-synthesis` property.
+///
+/// Controls whether a missing bold or italic face can be synthesized
+/// from the one that was matched.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct FontSynthesis {
+    /// Allows synthetic bold.
+    pub weight: bool,
+    /// Allows synthetic oblique.
+    pub style: bool,
+}
+
+impl Default for FontSynthesis {
+    #[inline]
+    fn default() -> Self {
+        FontSynthesis {
+            weight: true,
+            style: true,
+        }
+    }
+}
+
+/// A font face defined by an `@font-face` rule.
+///
+/// Text nodes use such faces instead of the fonts database ones with the same family.
+#[derive(Clone, Debug)]
+pub struct FontFace {
+    /// A family name.
+    pub family: String,
+    /// A font style.
+    pub style: FontStyle,
+    /// A font weight.
+    ///
+    /// The lower bound when the rule declares a range.
     pub weight: u16,
+    /// Code points supported by the face.
+    ///
+    /// Empty when the face can be used for any character.
+    pub unicode_range: Vec<std::ops::RangeInclusive<u32>>,
+    /// A TrueType or an OpenType font data.
+    ///
+    /// WOFF and WOFF2 fonts are already decoded.
+    pub data: Arc<Vec<u8>>,
+}
+
+/// An SVG font defined by a `font` element.
+///
+/// Text nodes use such fonts instead of the fonts database ones with the same family.
+///
+/// All values are in font units.
+#[derive(Clone, Debug)]
+pub struct SvgFont {
+    /// Element's ID.
+    ///
+    /// Can be empty.
+    pub id: String,
+    /// A family name.
+    ///
+    /// `font-family` of the `font-face` element in SVG.
+    pub family: String,
+    /// A font style.
+    pub style: FontStyle,
+    /// A font weight.
+    pub weight: u16,
+    /// `units-per-em` in SVG.
+    pub units_per_em: f64,
+    /// A distance from the baseline to the top of the font.
+    ///
+    /// `ascent` in SVG.
+    pub ascent: f64,
+    /// A distance from the baseline to the bottom of the font.
+    ///
+    /// `descent` in SVG. Always positive.
+    pub descent: f64,
+    /// A default glyph advance.
+    ///
+    /// `horiz-adv-x` of the `font` element in SVG.
+    pub horiz_adv_x: f64,
+    /// A glyph that is used for characters without a glyph.
+    ///
+    /// `missing-glyph` in SVG.
+    pub missing_glyph: SvgGlyph,
+    /// Glyphs in the document order.
+    pub glyphs: Vec<SvgGlyph>,
+    /// Horizontal kerning pairs in the document order.
+    ///
+    /// `hkern` in SVG.
+    pub kerning: Vec<SvgKerning>,
+}
+
+/// A glyph of an [`SvgFont`].
+#[derive(Clone, Debug)]
+pub struct SvgGlyph {
+    /// Characters represented by the glyph.
+    ///
+    /// Contains multiple characters for ligatures. Empty when not set.
+    ///
+    /// `unicode` in SVG.
+    pub unicode: String,
+    /// Glyph names.
+    ///
+    /// `glyph-name` in SVG.
+    pub names: Vec<String>,
+    /// A contextual form of an Arabic glyph.
+    ///
+    /// `None` when the glyph can be used in any form.
+    ///
+    /// `arabic-form` in SVG.
+    pub arabic_form: Option<ArabicForm>,
+    /// Languages that the glyph can be used for.
+    ///
+    /// Empty when the glyph can be used for any language.
+    ///
+    /// `lang` in SVG.
+    pub languages: Vec<String>,
+    /// A glyph advance.
+    ///
+    /// `horiz-adv-x` in SVG. Resolved from the font when not set.
+    pub horiz_adv_x: f64,
+    /// A glyph outline.
+    ///
+    /// Uses a coordinate system with the Y axis pointing up, just like other fonts.
+    ///
+    /// `d` in SVG.
+    pub path: Option<Rc<PathData>>,
+}
+
+/// An `arabic-form` attribute value.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArabicForm {
+    Isolated,
+    Initial,
+    Medial,
+    Terminal,
+}
+
+/// A kerning pair of an [`SvgFont`].
+///
+/// Glyphs are indices in `SvgFont::glyphs`.
+#[derive(Clone, Debug)]
+pub struct SvgKerning {
+    /// Glyphs that can be the first in the pair.
+    ///
+    /// Resolved from `u1` and `g1` in SVG.
+    pub first: Vec<usize>,
+    /// Glyphs that can be the second in the pair.
+    ///
+    /// Resolved from `u2` and `g2` in SVG.
+    pub second: Vec<usize>,
+    /// An amount to decrease the spacing between glyphs by.
+    ///
+    /// `k` in SVG.
+    pub k: f64,
 }
 
 /// A dominant baseline property.
@@ -148,16 +311,58 @@ impl Default for LengthAdjust {
 /// So you can have black text and green underline.
 ///
 /// Also, in SVG you can specify text decoration stroking.
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct TextDecorationStyle {
     /// A fill style.
     pub fill: Option<Fill>,
     /// A stroke style.
     pub stroke: Option<Stroke>,
+    /// A `text-decoration-style` property.
+    pub line_style: TextDecorationLineStyle,
+    /// A `text-decoration-thickness` property.
+    ///
+    /// `None` indicates that the font thickness should be used.
+    pub thickness: Option<f64>,
+    /// A `text-underline-offset` property.
+    ///
+    /// `None` indicates that the font position should be used.
+    /// Used only by underlines.
+    pub underline_offset: Option<f64>,
+    /// A `text-underline-position` property.
+    ///
+    /// Used only by underlines.
+    pub underline_position: TextUnderlinePosition,
+}
+
+/// A `text-decoration-style` property.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextDecorationLineStyle {
+    Solid,
+    Double,
+    Dotted,
+    Dashed,
+    Wavy,
+}
+
+impl Default for TextDecorationLineStyle {
+    #[inline]
+    fn default() -> Self {
+        Self::Solid
+    }
+}
+
+/// A `text-underline-position` property.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct TextUnderlinePosition {
+    /// Places an underline below the text descenders instead of the font position.
+    pub under: bool,
+    /// Places an underline on the right side of vertical text.
+    pub right: bool,
 }
 
 /// A text span decoration.
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct TextDecoration {
     /// An optional underline and its style.
     pub underline: Option<TextDecorationStyle>,
@@ -167,6 +372,42 @@ pub struct TextDecoration {
     pub line_through: Option<TextDecorationStyle>,
 }
 
+/// An OpenType font feature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FontFeature {
+    /// A four-letter feature tag, like `liga`.
+    pub tag: [u8; 4],
+    /// A feature value. `0` disables the feature.
+    ///
+    /// Values greater than `1` select an alternate glyph.
+    pub value: u32,
+}
+
+impl FontFeature {
+    /// Creates a new feature.
+    #[inline]
+    pub fn new(tag: &[u8; 4], value: u32) -> Self {
+        FontFeature { tag: *tag, value }
+    }
+}
+
+/// A font variation axis value.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FontVariation {
+    /// A four-letter axis tag, like `wght`.
+    pub tag: [u8; 4],
+    /// An axis value in the axis units.
+    pub value: f32,
+}
+
+impl FontVariation {
+    /// Creates a new variation.
+    #[inline]
+    pub fn new(tag: &[u8; 4], value: f32) -> Self {
+        FontVariation { tag: *tag, value }
+    }
+}
+
 /// A text style span.
 ///
 /// Spans do not overlap inside a text chunk.
@@ -192,12 +433,29 @@ pub struct TextSpan {
     pub font_size: NonZeroPositiveF64,
     /// Indicates that small caps should be used.
     ///
-    /// Set by `font-variant="small-caps"`
+    /// Set by `font-variant="small-caps"` and `font-variant-caps="small-caps"`.
     pub small_caps: bool,
     /// Indicates that a kerning should be applied.
     ///
     /// Supports both `kerning` and `font-kerning` properties.
     pub apply_kerning: bool,
+    /// OpenType features set by `font-variant-*` and `font-feature-settings`.
+    ///
+    /// Ordered by precedence, so a feature overrides the same one that came before it.
+    /// Doesn't include `small_caps` and `apply_kerning`.
+    pub font_features: Vec<FontFeature>,
+    /// Font variation axes values set by `font-variation-settings`.
+    ///
+    /// Also contains the `wdth` axis when `font-stretch` is a percentage
+    /// that doesn't match any keyword, since `Font::stretch` stores only the closest keyword.
+    ///
+    /// The `wght`, `wdth` and `opsz` axes are set from the font properties during text layout
+    /// and are overridden by these values.
+    pub font_variations: Vec<FontVariation>,
+    /// Indicates that the `opsz` axis should be set to the font size.
+    ///
+    /// Set by `font-optical-sizing`.
+    pub optical_sizing: bool,
     /// A span decorations.
     pub decoration: TextDecoration,
     /// A span dominant baseline.
@@ -218,6 +476,75 @@ pub struct TextSpan {
     pub text_length: Option<f64>,
     /// A length adjust property.
     pub length_adjust: LengthAdjust,
+    /// An embedding direction.
+    ///
+    /// Used only when `unicode_bidi` is not `Normal`.
+    pub direction: TextDirection,
+    /// A bidi embedding mode.
+    ///
+    /// Resolved from the closest element with a non-`normal` `unicode-bidi`.
+    pub unicode_bidi: UnicodeBidi,
+    /// A line height.
+    ///
+    /// `None` indicates `normal`, which is resolved using font metrics.
+    pub line_height: Option<f64>,
+    /// A content language.
+    ///
+    /// Resolved from the closest element with `xml:lang` or `lang`.
+    /// Used to select glyphs of SVG fonts.
+    pub lang: Option<String>,
+    /// A glyph orientation in vertical text.
+    ///
+    /// Set by `text-orientation` or the deprecated `glyph-orientation-vertical`.
+    /// Ignored in horizontal text.
+    pub text_orientation: TextOrientation,
+}
+
+/// A text direction.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Default for TextDirection {
+    fn default() -> Self {
+        Self::LeftToRight
+    }
+}
+
+/// A `unicode-bidi` property.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UnicodeBidi {
+    Normal,
+    Embed,
+    Isolate,
+    BidiOverride,
+    IsolateOverride,
+    Plaintext,
+}
+
+impl Default for UnicodeBidi {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// A `text-orientation` property.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextOrientation {
+    Mixed,
+    Upright,
+    Sideways,
+}
+
+impl Default for TextOrientation {
+    fn default() -> Self {
+        Self::Mixed
+    }
 }
 
 /// A text chunk anchor property.
@@ -238,12 +565,21 @@ impl Default for TextAnchor {
 /// A path used by text-on-path.
 #[derive(Clone, Debug)]
 pub struct TextPath {
+    /// Element's ID.
+    ///
+    /// Unlike other IDs, it's always generated and cannot be empty.
+    /// Used only during SVG writing.
+    pub id: String,
+
     /// A text offset in SVG coordinates.
     ///
     /// Percentage values already resolved.
     pub start_offset: f64,
 
     /// A path.
+    ///
+    /// Either an inline `path` or a referenced path or basic shape,
+    /// with `side=right` already applied by reversing its direction.
     pub path: Rc<PathData>,
 }
 
@@ -269,6 +605,10 @@ pub struct TextChunk {
     pub y: Option<f64>,
     /// A text anchor.
     pub anchor: TextAnchor,
+    /// A base text direction.
+    ///
+    /// Affects BIDI reordering and `anchor`.
+    pub direction: TextDirection,
     /// A list of text chunk style spans.
     pub spans: Vec<TextSpan>,
     /// A text chunk flow.
@@ -300,6 +640,75 @@ pub enum WritingMode {
     TopToBottom,
 }
 
+/// A shape used by `shape-inside` and `shape-subtract`.
+#[derive(Debug)]
+pub struct TextShape {
+    /// Element's ID.
+    ///
+    /// Unlike other elements, shapes do not preserve their IDs,
+    /// because they can be referenced multiple times with different transforms.
+    /// Generated automatically. Cannot be empty.
+    pub id: String,
+
+    /// A shape's path in the text element coordinates.
+    pub path: Rc<PathData>,
+}
+
+/// A text flow area.
+///
+/// `shape-inside` and related properties in SVG 2.
+#[derive(Clone, Debug)]
+pub struct ShapeInside {
+    /// Shapes to flow text into.
+    ///
+    /// Text that doesn't fit the first shape continues in the next one.
+    /// Never empty.
+    pub shapes: Vec<Rc<TextShape>>,
+
+    /// Shapes that should be excluded from the flow area.
+    ///
+    /// `shape-subtract` in SVG.
+    pub subtract: Vec<Rc<TextShape>>,
+
+    /// An inner padding of `shapes`.
+    ///
+    /// `shape-padding` in SVG.
+    pub padding: f64,
+
+    /// An outer margin of `subtract` shapes.
+    ///
+    /// `shape-margin` in SVG.
+    pub margin: f64,
+}
+
+/// A `white-space` property.
+///
+/// Whitespace collapsing is done during parsing,
+/// so this property only affects line wrapping.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WhiteSpace {
+    Normal,
+    Pre,
+    NoWrap,
+    PreWrap,
+    BreakSpaces,
+    PreLine,
+}
+
+impl Default for WhiteSpace {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl WhiteSpace {
+    /// Checks that lines can be wrapped at soft wrap opportunities.
+    pub fn allows_wrapping(&self) -> bool {
+        !matches!(self, WhiteSpace::Pre | WhiteSpace::NoWrap)
+    }
+}
+
 /// A text element.
 ///
 /// `text` element in SVG.
@@ -333,6 +742,20 @@ pub struct Text {
     /// A writing mode.
     pub writing_mode: WritingMode,
 
+    /// An inline size.
+    ///
+    /// When set, text chunks will be wrapped into lines of the specified size.
+    /// `inline-size` in SVG 2.
+    pub inline_size: Option<f64>,
+
+    /// A white space handling mode.
+    pub white_space: WhiteSpace,
+
+    /// A text flow area.
+    ///
+    /// Takes precedence over `inline_size`.
+    pub shape_inside: Option<ShapeInside>,
+
     /// A list of text chunks.
     pub chunks: Vec<TextChunk>,
 }
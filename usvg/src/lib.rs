@@ -39,10 +39,12 @@ and can focus just on the rendering part.
 ## Limitations
 
 - Unsupported SVG features will be ignored
-- CSS support is minimal
+- CSS support is limited to selectors, custom properties, `@media` and `@namespace` rules
 - Only [static](http://www.w3.org/TR/SVG11/feature#SVG-static) SVG features,
   e.g. no `a`, `view`, `cursor`, `script`, no events and no animations
-- Text elements must be converted into paths before writing to SVG.
+- Text elements that were not converted into paths will be written as resolved
+  `text`, `tspan` and `textPath` elements. Decorations declared by a `tspan`
+  will be painted using the `tspan` fill and stroke after such round-trip.
 
 [SVG]: https://en.wikipedia.org/wiki/Scalable_Vector_Graphics
 */
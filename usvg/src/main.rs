@@ -50,6 +50,20 @@ OPTIONS:
                                     contains the SVG file, but can be set to any.
                                     [default: input file directory
                                     or none when reading from stdin]
+  --stylesheet PATH                 Applies a CSS style sheet to the input SVG.
+                                    This option can be set multiple times
+  --stylesheet-origin ORIGIN        Sets the cascade origin of the '--stylesheet' files.
+                                    'user-agent' style sheets are applied before
+                                    the document styles, 'author' ones - after
+                                    [default: author] [possible values: user-agent, author]
+  --color-scheme SCHEME             Sets the preferred color scheme used by
+                                    'prefers-color-scheme' CSS media queries
+                                    [default: light] [possible values: light, dark]
+  --css-variable NAME=VALUE         Sets a CSS custom property on the root element,
+                                    like '--css-variable --main-color=green'.
+                                    This option can be set multiple times
+  --current-color COLOR             Sets the initial 'color' property value
+                                    used by 'currentColor' [default: black]
 
   --font-family FAMILY              Sets the default font family that will be
                                     used when no 'font-family' is present
@@ -91,6 +105,9 @@ OPTIONS:
                                     Refer to the explanation of the '--default-width'
                                     option. [values: 1..4294967295 (inclusive)] [default: 100]
 
+  --preserve-text                   Keeps text elements as text instead of
+                                    converting them into paths
+
   --id-prefix                       Adds a prefix to each ID attribute
   --indent INDENT                   Sets the XML nodes indent
                                     [values: none, 0, 1, 2, 3, 4, tabs] [default: 4]
@@ -117,6 +134,11 @@ struct Args {
     text_rendering: usvg_tree::TextRendering,
     image_rendering: usvg_tree::ImageRendering,
     resources_dir: Option<PathBuf>,
+    style_sheets: Vec<PathBuf>,
+    style_sheet_origin: usvg_parser::StyleSheetOrigin,
+    color_scheme: usvg_parser::ColorScheme,
+    custom_properties: Vec<(String, String)>,
+    current_color: Option<String>,
 
     font_family: Option<String>,
     font_size: u32,
@@ -132,6 +154,8 @@ struct Args {
     default_width: u32,
     default_height: u32,
 
+    preserve_text: bool,
+
     id_prefix: Option<String>,
     indent: xmlwriter::Indent,
     attrs_indent: xmlwriter::Indent,
@@ -174,6 +198,15 @@ fn collect_args() -> Result<Args, pico_args::Error> {
         resources_dir: input
             .opt_value_from_str("--resources-dir")
             .unwrap_or_default(),
+        style_sheets: input.values_from_str("--stylesheet")?,
+        style_sheet_origin: input
+            .opt_value_from_fn("--stylesheet-origin", parse_style_sheet_origin)?
+            .unwrap_or_default(),
+        color_scheme: input
+            .opt_value_from_fn("--color-scheme", parse_color_scheme)?
+            .unwrap_or_default(),
+        custom_properties: input.values_from_fn("--css-variable", parse_custom_property)?,
+        current_color: input.opt_value_from_str("--current-color")?,
 
         font_family: input.opt_value_from_str("--font-family")?,
         font_size: input
@@ -195,6 +228,8 @@ fn collect_args() -> Result<Args, pico_args::Error> {
             .opt_value_from_fn("--default-height", parse_length)?
             .unwrap_or(100),
 
+        preserve_text: input.contains("--preserve-text"),
+
         id_prefix: input.opt_value_from_str("--id-prefix")?,
         indent: input
             .opt_value_from_fn("--indent", parse_indent)?
@@ -246,6 +281,29 @@ fn parse_languages(s: &str) -> Result<Vec<String>, String> {
     Ok(langs)
 }
 
+fn parse_style_sheet_origin(s: &str) -> Result<usvg_parser::StyleSheetOrigin, String> {
+    match s {
+        "user-agent" => Ok(usvg_parser::StyleSheetOrigin::UserAgent),
+        "author" => Ok(usvg_parser::StyleSheetOrigin::Author),
+        _ => Err("invalid ORIGIN value".to_string()),
+    }
+}
+
+fn parse_color_scheme(s: &str) -> Result<usvg_parser::ColorScheme, String> {
+    match s {
+        "light" => Ok(usvg_parser::ColorScheme::Light),
+        "dark" => Ok(usvg_parser::ColorScheme::Dark),
+        _ => Err("invalid SCHEME value".to_string()),
+    }
+}
+
+fn parse_custom_property(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => Ok((name.to_string(), value.to_string())),
+        _ => Err("invalid NAME=VALUE value".to_string()),
+    }
+}
+
 fn parse_indent(s: &str) -> Result<xmlwriter::Indent, String> {
     let indent = match s {
         "none" => xmlwriter::Indent::None,
@@ -399,6 +457,13 @@ fn process(args: Args) -> Result<(), String> {
         }
     };
 
+    let mut style_sheet = Vec::new();
+    for path in &args.style_sheets {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read '{}' cause {}", path.display(), e))?;
+        style_sheet.push(text);
+    }
+
     let re_opt = usvg_parser::Options {
         resources_dir,
         dpi: args.dpi as f64,
@@ -415,6 +480,11 @@ fn process(args: Args) -> Result<(), String> {
         default_size: usvg_tree::Size::new(args.default_width as f64, args.default_height as f64)
             .unwrap(),
         image_href_resolver: usvg_parser::ImageHrefResolver::default(),
+        style_sheet,
+        style_sheet_origin: args.style_sheet_origin,
+        color_scheme: args.color_scheme,
+        custom_properties: args.custom_properties,
+        color: args.current_color,
     };
 
     let input_svg = match in_svg {
@@ -423,7 +493,13 @@ fn process(args: Args) -> Result<(), String> {
     }?;
 
     let mut tree = usvg_tree::Tree::from_data(&input_svg, &re_opt).map_err(|e| format!("{}", e))?;
-    tree.convert_text(&fontdb);
+    if !args.preserve_text {
+        tree.convert_text(&fontdb);
+    }
+
+    for diagnostic in &tree.diagnostics {
+        log::warn!("{}", diagnostic);
+    }
 
     let xml_opt = usvg::XmlOptions {
         id_prefix: args.id_prefix,
@@ -6,6 +6,8 @@ use std::fmt::Display;
 use std::io::Write;
 use std::rc::Rc;
 
+use base64::Engine;
+
 use crate::TreeWriting;
 use usvg_parser::rosvgtree::{AttributeId as AId, ElementId as EId};
 use usvg_tree::*;
@@ -72,6 +74,8 @@ pub(crate) fn convert(tree: &Tree, opt: &XmlOptions) -> String {
     }
 
     xml.start_svg_element(EId::Defs);
+    write_font_faces(&tree.font_faces, &mut xml);
+    write_svg_fonts(&tree.svg_fonts, opt, &mut xml);
     conv_defs(tree, opt, &mut xml);
     xml.end_element();
 
@@ -439,6 +443,190 @@ fn conv_filters(tree: &Tree, opt: &XmlOptions, xml: &mut XmlWriter) {
     }
 }
 
+/// Writes `@font-face` rules with embedded font data.
+fn write_font_faces(faces: &[FontFace], xml: &mut XmlWriter) {
+    if faces.is_empty() {
+        return;
+    }
+
+    let mut css = String::new();
+    for face in faces {
+        css.push_str("@font-face{font-family:\"");
+        css.push_str(&face.family);
+        css.push_str("\";font-style:");
+        css.push_str(match face.style {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        });
+        css.push_str(&format!(";font-weight:{}", face.weight));
+
+        if !face.unicode_range.is_empty() {
+            css.push_str(";unicode-range:");
+            for (i, range) in face.unicode_range.iter().enumerate() {
+                if i != 0 {
+                    css.push(',');
+                }
+                css.push_str(&format!("U+{:X}-{:X}", range.start(), range.end()));
+            }
+        }
+
+        let mime = if face.data.starts_with(b"OTTO") {
+            "otf"
+        } else {
+            "ttf"
+        };
+        css.push_str(&format!(";src:url(data:font/{};base64,", mime));
+        base64::engine::general_purpose::STANDARD.encode_string(face.data.as_slice(), &mut css);
+        css.push_str(")}");
+    }
+
+    xml.start_svg_element(EId::Style);
+    xml.write_text(&css);
+    xml.end_element();
+}
+
+fn write_svg_fonts(fonts: &[SvgFont], opt: &XmlOptions, xml: &mut XmlWriter) {
+    for font in fonts {
+        xml.start_svg_element(EId::Font);
+        if !font.id.is_empty() {
+            xml.write_id_attribute(&font.id, opt);
+        }
+        xml.write_svg_attribute(AId::HorizAdvX, &font.horiz_adv_x);
+
+        xml.start_svg_element(EId::FontFace);
+        xml.write_svg_attribute(AId::FontFamily, &font.family);
+        match font.style {
+            FontStyle::Normal => {}
+            FontStyle::Italic => xml.write_svg_attribute(AId::FontStyle, "italic"),
+            FontStyle::Oblique => xml.write_svg_attribute(AId::FontStyle, "oblique"),
+        }
+        xml.write_svg_attribute(AId::FontWeight, &font.weight);
+        xml.write_svg_attribute(AId::UnitsPerEm, &font.units_per_em);
+        xml.write_svg_attribute(AId::Ascent, &font.ascent);
+        xml.write_svg_attribute(AId::Descent, &font.descent);
+        xml.end_element();
+
+        let names = kerning_glyph_names(font);
+
+        xml.start_svg_element(EId::MissingGlyph);
+        write_svg_glyph(&font.missing_glyph, None, opt, xml);
+        xml.end_element();
+
+        for (idx, glyph) in font.glyphs.iter().enumerate() {
+            xml.start_svg_element(EId::Glyph);
+            write_svg_glyph(glyph, names[idx].as_deref(), opt, xml);
+            xml.end_element();
+        }
+
+        for pair in &font.kerning {
+            let join_names = |glyphs: &[usize]| {
+                glyphs
+                    .iter()
+                    .filter_map(|idx| names[*idx].as_deref())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+
+            xml.start_svg_element(EId::Hkern);
+            xml.write_svg_attribute(AId::G1, &join_names(&pair.first));
+            xml.write_svg_attribute(AId::G2, &join_names(&pair.second));
+            xml.write_svg_attribute(AId::K, &pair.k);
+            xml.end_element();
+        }
+
+        xml.end_element();
+    }
+}
+
+/// Returns unique names for glyphs referenced by kerning pairs.
+///
+/// A glyph's own name is used when possible. Otherwise a new one is generated.
+fn kerning_glyph_names(font: &SvgFont) -> Vec<Option<String>> {
+    let mut names = vec![None; font.glyphs.len()];
+    for pair in &font.kerning {
+        for idx in pair.first.iter().chain(&pair.second) {
+            if names[*idx].is_some() {
+                continue;
+            }
+
+            let is_unique = |name: &str| {
+                font.glyphs
+                    .iter()
+                    .filter(|glyph| glyph.names.iter().any(|n| n == name))
+                    .count()
+                    == 1
+            };
+
+            let name = match font.glyphs[*idx].names.iter().find(|n| is_unique(n)) {
+                Some(name) => name.clone(),
+                None => {
+                    // Stepping by the glyphs count keeps generated names unique.
+                    let mut n = *idx + 1;
+                    loop {
+                        let name = format!("glyph{}", n);
+                        if !font.glyphs.iter().any(|glyph| glyph.names.contains(&name)) {
+                            break name;
+                        }
+                        n += font.glyphs.len();
+                    }
+                }
+            };
+
+            names[*idx] = Some(name);
+        }
+    }
+
+    names
+}
+
+/// Writes glyph attributes.
+///
+/// `kerning_name` is added to the glyph's names when it's not one of them.
+fn write_svg_glyph(
+    glyph: &SvgGlyph,
+    kerning_name: Option<&str>,
+    opt: &XmlOptions,
+    xml: &mut XmlWriter,
+) {
+    if !glyph.unicode.is_empty() {
+        let unicode = glyph.unicode.replace('&', "&amp;").replace('<', "&lt;");
+        xml.write_svg_attribute(AId::Unicode, &unicode);
+    }
+
+    let mut names = glyph.names.clone();
+    if let Some(name) = kerning_name {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    if !names.is_empty() {
+        xml.write_svg_attribute(AId::GlyphName, &names.join(","));
+    }
+
+    if let Some(form) = glyph.arabic_form {
+        xml.write_svg_attribute(
+            AId::ArabicForm,
+            match form {
+                ArabicForm::Isolated => "isolated",
+                ArabicForm::Initial => "initial",
+                ArabicForm::Medial => "medial",
+                ArabicForm::Terminal => "terminal",
+            },
+        );
+    }
+
+    if !glyph.languages.is_empty() {
+        xml.write_svg_attribute(AId::Lang, &glyph.languages.join(","));
+    }
+
+    xml.write_svg_attribute(AId::HorizAdvX, &glyph.horiz_adv_x);
+
+    if let Some(ref path) = glyph.path {
+        write_path_data(path, opt, xml);
+    }
+}
+
 fn conv_defs(tree: &Tree, opt: &XmlOptions, xml: &mut XmlWriter) {
     let mut paint_servers: Vec<Paint> = Vec::new();
     tree.paint_servers(|paint| {
@@ -542,6 +730,64 @@ fn conv_defs(tree: &Tree, opt: &XmlOptions, xml: &mut XmlWriter) {
 
         xml.end_element();
     }
+
+    for text_path in collect_text_paths(tree) {
+        xml.start_svg_element(EId::Path);
+        xml.write_id_attribute(&text_path.id, opt);
+        write_path_data(&text_path.path, opt, xml);
+        xml.end_element();
+    }
+
+    for text_shape in collect_text_shapes(tree) {
+        xml.start_svg_element(EId::Path);
+        xml.write_id_attribute(&text_shape.id, opt);
+        write_path_data(&text_shape.path, opt, xml);
+        xml.end_element();
+    }
+}
+
+fn collect_text_paths(tree: &Tree) -> Vec<Rc<TextPath>> {
+    fn collect(root: &Node, list: &mut Vec<Rc<TextPath>>) {
+        for node in root.descendants() {
+            if let NodeKind::Text(ref text) = *node.borrow() {
+                for chunk in &text.chunks {
+                    if let TextFlow::Path(ref text_path) = chunk.text_flow {
+                        if !list.iter().any(|other| Rc::ptr_eq(text_path, other)) {
+                            list.push(text_path.clone());
+                        }
+                    }
+                }
+            }
+
+            node.subroots(|subroot| collect(&subroot, list));
+        }
+    }
+
+    let mut list = Vec::new();
+    collect(&tree.root, &mut list);
+    list
+}
+
+fn collect_text_shapes(tree: &Tree) -> Vec<Rc<TextShape>> {
+    fn collect(root: &Node, list: &mut Vec<Rc<TextShape>>) {
+        for node in root.descendants() {
+            if let NodeKind::Text(ref text) = *node.borrow() {
+                if let Some(ref shape_inside) = text.shape_inside {
+                    for shape in shape_inside.shapes.iter().chain(&shape_inside.subtract) {
+                        if !list.iter().any(|other| Rc::ptr_eq(shape, other)) {
+                            list.push(shape.clone());
+                        }
+                    }
+                }
+            }
+
+            node.subroots(|subroot| collect(&subroot, list));
+        }
+    }
+
+    let mut list = Vec::new();
+    collect(&tree.root, &mut list);
+    list
 }
 
 fn conv_elements(parent: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut XmlWriter) {
@@ -668,8 +914,8 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
 
             xml.end_element();
         }
-        NodeKind::Text(_) => {
-            log::warn!("text must be converted into paths");
+        NodeKind::Text(ref text) => {
+            write_text(text, is_clip_path, opt, xml);
         }
     }
 }
@@ -979,7 +1225,7 @@ fn has_xlink(tree: &Tree) -> bool {
         }
     }
 
-    false
+    !collect_text_paths(tree).is_empty()
 }
 
 fn write_base_grad(g: &BaseGradient, xml: &mut XmlWriter, opt: &XmlOptions) {
@@ -1039,8 +1285,14 @@ fn write_path(
 
     xml.write_transform(AId::Transform, path.transform, opt);
 
+    write_path_data(&path.data, opt, xml);
+
+    xml.end_element();
+}
+
+fn write_path_data(data: &PathData, opt: &XmlOptions, xml: &mut XmlWriter) {
     xml.write_attribute_raw("d", |buf| {
-        for seg in path.data.segments() {
+        for seg in data.segments() {
             match seg {
                 PathSegment::MoveTo { x, y } => {
                     buf.extend_from_slice(b"M ");
@@ -1084,12 +1336,535 @@ fn write_path(
             }
         }
 
-        if !path.data.is_empty() {
+        if !data.is_empty() {
             buf.pop();
         }
     });
+}
+
+fn write_text_shapes(aid: AId, shapes: &[Rc<TextShape>], opt: &XmlOptions, xml: &mut XmlWriter) {
+    if shapes.is_empty() {
+        return;
+    }
+
+    let prefix = opt.id_prefix.as_deref().unwrap_or_default();
+    xml.write_attribute_raw(aid.to_str(), |buf| {
+        for (i, shape) in shapes.iter().enumerate() {
+            if i != 0 {
+                buf.push(b' ');
+            }
+
+            buf.extend_from_slice(format!("url(#{}{})", prefix, shape.id).as_bytes());
+        }
+    });
+}
+
+fn write_text(text: &Text, is_clip_path: bool, opt: &XmlOptions, xml: &mut XmlWriter) {
+    xml.start_svg_element(EId::Text);
+    if !text.id.is_empty() {
+        xml.write_id_attribute(&text.id, opt);
+    }
+
+    match text.rendering_mode {
+        TextRendering::OptimizeSpeed => {
+            xml.write_svg_attribute(AId::TextRendering, "optimizeSpeed");
+        }
+        TextRendering::OptimizeLegibility => {}
+        TextRendering::GeometricPrecision => {
+            xml.write_svg_attribute(AId::TextRendering, "geometricPrecision");
+        }
+    }
+
+    if text.writing_mode == WritingMode::TopToBottom {
+        xml.write_svg_attribute(AId::WritingMode, "tb");
+    }
+
+    if let Some(inline_size) = text.inline_size {
+        xml.write_svg_attribute(AId::InlineSize, &inline_size);
+    }
+
+    if let Some(ref shape_inside) = text.shape_inside {
+        write_text_shapes(AId::ShapeInside, &shape_inside.shapes, opt, xml);
+        write_text_shapes(AId::ShapeSubtract, &shape_inside.subtract, opt, xml);
+
+        if shape_inside.padding != 0.0 {
+            xml.write_svg_attribute(AId::ShapePadding, &shape_inside.padding);
+        }
+
+        if shape_inside.margin != 0.0 {
+            xml.write_svg_attribute(AId::ShapeMargin, &shape_inside.margin);
+        }
+    }
+
+    match text.white_space {
+        WhiteSpace::Normal => {}
+        WhiteSpace::Pre => xml.write_svg_attribute(AId::WhiteSpace, "pre"),
+        WhiteSpace::NoWrap => xml.write_svg_attribute(AId::WhiteSpace, "nowrap"),
+        WhiteSpace::PreWrap => xml.write_svg_attribute(AId::WhiteSpace, "pre-wrap"),
+        WhiteSpace::BreakSpaces => xml.write_svg_attribute(AId::WhiteSpace, "break-spaces"),
+        WhiteSpace::PreLine => xml.write_svg_attribute(AId::WhiteSpace, "pre-line"),
+    }
+
+    xml.write_transform(AId::Transform, text.transform, opt);
+
+    // The base direction is resolved from the `text` element, so it's the same for all chunks.
+    let is_rtl = text
+        .chunks
+        .first()
+        .map(|chunk| chunk.direction == TextDirection::RightToLeft)
+        .unwrap_or(false);
+    if is_rtl {
+        xml.write_svg_attribute(AId::Direction, "rtl");
+    }
+
+    // Relative positions and rotation are stored per character,
+    // so we can write them as is on the `text` element itself.
+    if text.positions.iter().any(|p| p.dx.is_some()) {
+        let list: Vec<_> = text.positions.iter().map(|p| p.dx.unwrap_or(0.0)).collect();
+        xml.write_numbers(AId::Dx, &list);
+    }
+
+    if text.positions.iter().any(|p| p.dy.is_some()) {
+        let list: Vec<_> = text.positions.iter().map(|p| p.dy.unwrap_or(0.0)).collect();
+        xml.write_numbers(AId::Dy, &list);
+    }
+
+    if text.rotate.iter().any(|a| !a.is_fuzzy_zero()) {
+        xml.write_numbers(AId::Rotate, &text.rotate);
+    }
+
+    // A decoration shared by all spans is written on the `text` element,
+    // otherwise it will be written on each `tspan` and painted using its fill and stroke.
+    let text_decoration = text
+        .chunks
+        .first()
+        .and_then(|chunk| chunk.spans.first())
+        .map(|span| &span.decoration)
+        .filter(|dec| {
+            text.chunks
+                .iter()
+                .flat_map(|chunk| &chunk.spans)
+                .all(|span| span.decoration == **dec)
+        })
+        .and_then(|dec| {
+            let styles = [&dec.underline, &dec.overline, &dec.line_through];
+            let style = styles.iter().find_map(|s| s.as_ref())?;
+            if styles.iter().flat_map(|s| s.as_ref()).all(|s| s == style) {
+                Some((dec, style))
+            } else {
+                None
+            }
+        });
+
+    if let Some((decoration, style)) = text_decoration {
+        write_text_decoration(decoration, xml);
+        write_fill(&style.fill, is_clip_path, opt, xml);
+        write_stroke(&style.stroke, opt, xml);
+    }
+
+    xml.write_attribute("xml:space", "preserve");
+
+    // Any whitespace inside a `text` element is meaningful.
+    xml.set_preserve_whitespaces(true);
+
+    let prefix = opt.id_prefix.as_deref().unwrap_or_default();
+    let mut current_path: Option<&Rc<TextPath>> = None;
+    for chunk in &text.chunks {
+        let text_path = match chunk.text_flow {
+            TextFlow::Linear => None,
+            TextFlow::Path(ref path) => Some(path),
+        };
+
+        // Each `textPath` element starts a new chunk,
+        // so we can merge consecutive chunks that are using the same path.
+        let is_same_path = match (current_path, text_path) {
+            (Some(prev), Some(next)) => Rc::ptr_eq(prev, next),
+            _ => false,
+        };
+
+        if !is_same_path {
+            if current_path.is_some() {
+                xml.end_element();
+            }
+
+            if let Some(text_path) = text_path {
+                xml.start_svg_element(EId::TextPath);
+                xml.write_attribute_fmt("xlink:href", format_args!("#{}{}", prefix, text_path.id));
+
+                if !text_path.start_offset.is_fuzzy_zero() {
+                    xml.write_svg_attribute(AId::StartOffset, &text_path.start_offset);
+                }
+            }
+
+            current_path = text_path;
+        }
+
+        for (i, span) in chunk.spans.iter().enumerate() {
+            // `baseline-shift` values are accumulated from all the ancestors,
+            // with the closest one being first.
+            let mut shifts = span.baseline_shift.iter().rev();
+            let wrappers_count = span.baseline_shift.len().saturating_sub(1);
+            for shift in shifts.by_ref().take(wrappers_count) {
+                xml.start_svg_element(EId::Tspan);
+                write_baseline_shift(*shift, xml);
+            }
+
+            xml.start_svg_element(EId::Tspan);
+            if let Some(shift) = shifts.next() {
+                write_baseline_shift(*shift, xml);
+            }
+
+            // Absolute coordinates are set only on the first character of a chunk.
+            if i == 0 {
+                if let Some(x) = chunk.x {
+                    xml.write_svg_attribute(AId::X, &x);
+                }
+
+                if let Some(y) = chunk.y {
+                    xml.write_svg_attribute(AId::Y, &y);
+                }
+            }
+
+            match chunk.anchor {
+                TextAnchor::Start => {}
+                TextAnchor::Middle => xml.write_svg_attribute(AId::TextAnchor, "middle"),
+                TextAnchor::End => xml.write_svg_attribute(AId::TextAnchor, "end"),
+            }
+
+            write_unicode_bidi(span, xml);
+
+            write_text_span(span, text_decoration.is_none(), is_clip_path, opt, xml);
+
+            xml.write_text(&chunk.text[span.start..span.end].replace('&', "&amp;"));
+
+            for _ in 0..span.baseline_shift.len().max(1) {
+                xml.end_element();
+            }
+        }
+    }
+
+    if current_path.is_some() {
+        xml.end_element();
+    }
 
     xml.end_element();
+    xml.set_preserve_whitespaces(false);
+}
+
+fn write_unicode_bidi(span: &TextSpan, xml: &mut XmlWriter) {
+    let unicode_bidi = match span.unicode_bidi {
+        UnicodeBidi::Normal => return,
+        UnicodeBidi::Embed => "embed",
+        UnicodeBidi::Isolate => "isolate",
+        UnicodeBidi::BidiOverride => "bidi-override",
+        UnicodeBidi::IsolateOverride => "isolate-override",
+        UnicodeBidi::Plaintext => "plaintext",
+    };
+
+    xml.write_svg_attribute(AId::UnicodeBidi, unicode_bidi);
+    match span.direction {
+        TextDirection::LeftToRight => xml.write_svg_attribute(AId::Direction, "ltr"),
+        TextDirection::RightToLeft => xml.write_svg_attribute(AId::Direction, "rtl"),
+    }
+}
+
+/// Quotes a font family name as a CSS string.
+///
+/// Double quotes are used when the name contains single quotes,
+/// otherwise quotes and backslashes are escaped.
+fn quote_font_family(family: &str) -> String {
+    let quote = if family.contains('\'') && !family.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+
+    let mut s = String::with_capacity(family.len() + 2);
+    s.push(quote);
+    for c in family.chars() {
+        if c == quote || c == '\\' {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push(quote);
+    s
+}
+
+fn write_text_span(
+    span: &TextSpan,
+    with_decoration: bool,
+    is_clip_path: bool,
+    opt: &XmlOptions,
+    xml: &mut XmlWriter,
+) {
+    write_fill(&span.fill, is_clip_path, opt, xml);
+    write_stroke(&span.stroke, opt, xml);
+
+    if span.paint_order == PaintOrder::StrokeAndFill {
+        xml.write_svg_attribute(AId::PaintOrder, "stroke");
+    }
+
+    let families: Vec<_> = span
+        .font
+        .families
+        .iter()
+        .map(|family| match family.as_str() {
+            "serif" | "sans-serif" | "cursive" | "fantasy" | "monospace" => family.clone(),
+            _ => quote_font_family(family),
+        })
+        .collect();
+    xml.write_svg_attribute(AId::FontFamily, &families.join(","));
+    xml.write_svg_attribute(AId::FontSize, &span.font_size.get());
+
+    match span.font.style {
+        FontStyle::Normal => {}
+        FontStyle::Italic => xml.write_svg_attribute(AId::FontStyle, "italic"),
+        FontStyle::Oblique => xml.write_svg_attribute(AId::FontStyle, "oblique"),
+    }
+
+    let stretch = match span.font.stretch {
+        FontStretch::UltraCondensed => "ultra-condensed",
+        FontStretch::ExtraCondensed => "extra-condensed",
+        FontStretch::Condensed => "condensed",
+        FontStretch::SemiCondensed => "semi-condensed",
+        FontStretch::Normal => "",
+        FontStretch::SemiExpanded => "semi-expanded",
+        FontStretch::Expanded => "expanded",
+        FontStretch::ExtraExpanded => "extra-expanded",
+        FontStretch::UltraExpanded => "ultra-expanded",
+    };
+    if !stretch.is_empty() {
+        xml.write_svg_attribute(AId::FontStretch, stretch);
+    }
+
+    if span.font.weight != 400 {
+        xml.write_svg_attribute(AId::FontWeight, &span.font.weight);
+    }
+
+    match (span.font.synthesis.weight, span.font.synthesis.style) {
+        (true, true) => {}
+        (true, false) => xml.write_svg_attribute(AId::FontSynthesis, "weight"),
+        (false, true) => xml.write_svg_attribute(AId::FontSynthesis, "style"),
+        (false, false) => xml.write_svg_attribute(AId::FontSynthesis, "none"),
+    }
+
+    if span.small_caps {
+        xml.write_svg_attribute(AId::FontVariant, "small-caps");
+    }
+
+    if !span.apply_kerning {
+        xml.write_svg_attribute(AId::FontKerning, "none");
+    }
+
+    // All `font-variant-*` properties are already resolved into features.
+    if !span.font_features.is_empty() {
+        xml.write_attribute_raw(AId::FontFeatureSettings.to_str(), |buf| {
+            for (i, feature) in span.font_features.iter().enumerate() {
+                if i != 0 {
+                    buf.extend_from_slice(b", ");
+                }
+
+                write_quoted_tag(&feature.tag, buf);
+                buf.extend_from_slice(format!(" {}", feature.value).as_bytes());
+            }
+        });
+    }
+
+    // Also preserves a `font-stretch` percentage that has no matching keyword.
+    if !span.font_variations.is_empty() {
+        xml.write_attribute_raw(AId::FontVariationSettings.to_str(), |buf| {
+            for (i, variation) in span.font_variations.iter().enumerate() {
+                if i != 0 {
+                    buf.extend_from_slice(b", ");
+                }
+
+                write_quoted_tag(&variation.tag, buf);
+                buf.extend_from_slice(format!(" {}", variation.value).as_bytes());
+            }
+        });
+    }
+
+    if !span.optical_sizing {
+        xml.write_svg_attribute(AId::FontOpticalSizing, "none");
+    }
+
+    if with_decoration {
+        write_text_decoration(&span.decoration, xml);
+        write_text_decoration_paint(span, opt, xml);
+    }
+
+    let dominant_baseline = match span.dominant_baseline {
+        DominantBaseline::Auto => "",
+        DominantBaseline::UseScript => "use-script",
+        DominantBaseline::NoChange => "no-change",
+        DominantBaseline::ResetSize => "reset-size",
+        DominantBaseline::Ideographic => "ideographic",
+        DominantBaseline::Alphabetic => "alphabetic",
+        DominantBaseline::Hanging => "hanging",
+        DominantBaseline::Mathematical => "mathematical",
+        DominantBaseline::Central => "central",
+        DominantBaseline::Middle => "middle",
+        DominantBaseline::TextAfterEdge => "text-after-edge",
+        DominantBaseline::TextBeforeEdge => "text-before-edge",
+    };
+    if !dominant_baseline.is_empty() {
+        xml.write_svg_attribute(AId::DominantBaseline, dominant_baseline);
+    }
+
+    let alignment_baseline = match span.alignment_baseline {
+        AlignmentBaseline::Auto => "",
+        AlignmentBaseline::Baseline => "baseline",
+        AlignmentBaseline::BeforeEdge => "before-edge",
+        AlignmentBaseline::TextBeforeEdge => "text-before-edge",
+        AlignmentBaseline::Middle => "middle",
+        AlignmentBaseline::Central => "central",
+        AlignmentBaseline::AfterEdge => "after-edge",
+        AlignmentBaseline::TextAfterEdge => "text-after-edge",
+        AlignmentBaseline::Ideographic => "ideographic",
+        AlignmentBaseline::Alphabetic => "alphabetic",
+        AlignmentBaseline::Hanging => "hanging",
+        AlignmentBaseline::Mathematical => "mathematical",
+    };
+    if !alignment_baseline.is_empty() {
+        xml.write_svg_attribute(AId::AlignmentBaseline, alignment_baseline);
+    }
+
+    xml.write_visibility(span.visibility);
+
+    if !span.letter_spacing.is_fuzzy_zero() {
+        xml.write_svg_attribute(AId::LetterSpacing, &span.letter_spacing);
+    }
+
+    if !span.word_spacing.is_fuzzy_zero() {
+        xml.write_svg_attribute(AId::WordSpacing, &span.word_spacing);
+    }
+
+    if let Some(ref lang) = span.lang {
+        xml.write_attribute("xml:lang", lang);
+    }
+
+    match span.text_orientation {
+        TextOrientation::Mixed => {}
+        TextOrientation::Upright => xml.write_svg_attribute(AId::TextOrientation, "upright"),
+        TextOrientation::Sideways => xml.write_svg_attribute(AId::TextOrientation, "sideways"),
+    }
+
+    // Written as a number, which is relative to the span's font size.
+    if let Some(line_height) = span.line_height {
+        xml.write_svg_attribute(AId::LineHeight, &(line_height / span.font_size.get()));
+    }
+
+    if let Some(text_length) = span.text_length {
+        xml.write_svg_attribute(AId::TextLength, &text_length);
+    }
+
+    if span.length_adjust == LengthAdjust::SpacingAndGlyphs {
+        xml.write_svg_attribute(AId::LengthAdjust, "spacingAndGlyphs");
+    }
+}
+
+fn write_quoted_tag(tag: &[u8; 4], buf: &mut Vec<u8>) {
+    let quote = if tag.contains(&b'"') { b'\'' } else { b'"' };
+    buf.push(quote);
+    buf.extend_from_slice(tag);
+    buf.push(quote);
+}
+
+fn write_text_decoration(decoration: &TextDecoration, xml: &mut XmlWriter) {
+    let mut values = Vec::new();
+    if decoration.underline.is_some() {
+        values.push("underline");
+    }
+
+    if decoration.overline.is_some() {
+        values.push("overline");
+    }
+
+    if decoration.line_through.is_some() {
+        values.push("line-through");
+    }
+
+    if !values.is_empty() {
+        xml.write_svg_attribute(AId::TextDecoration, &values.join(" "));
+    }
+
+    // Only a single style per element can be stored, so we are using the first one.
+    let style = match first_decoration_style(decoration) {
+        Some(v) => v,
+        None => return,
+    };
+
+    let line_style = match style.line_style {
+        TextDecorationLineStyle::Solid => "",
+        TextDecorationLineStyle::Double => "double",
+        TextDecorationLineStyle::Dotted => "dotted",
+        TextDecorationLineStyle::Dashed => "dashed",
+        TextDecorationLineStyle::Wavy => "wavy",
+    };
+    if !line_style.is_empty() {
+        xml.write_svg_attribute(AId::TextDecorationStyle, line_style);
+    }
+
+    if let Some(thickness) = style.thickness {
+        xml.write_svg_attribute(AId::TextDecorationThickness, &thickness);
+    }
+
+    if let Some(offset) = style.underline_offset {
+        xml.write_svg_attribute(AId::TextUnderlineOffset, &offset);
+    }
+
+    let position = match (
+        style.underline_position.under,
+        style.underline_position.right,
+    ) {
+        (false, false) => "",
+        (true, false) => "under",
+        (false, true) => "right",
+        (true, true) => "under right",
+    };
+    if !position.is_empty() {
+        xml.write_svg_attribute(AId::TextUnderlinePosition, position);
+    }
+}
+
+/// Writes decoration paints that are different from the span ones.
+fn write_text_decoration_paint(span: &TextSpan, opt: &XmlOptions, xml: &mut XmlWriter) {
+    let style = match first_decoration_style(&span.decoration) {
+        Some(v) => v,
+        None => return,
+    };
+
+    if style.fill != span.fill {
+        match style.fill {
+            Some(ref fill) => write_paint(AId::TextDecorationFill, &fill.paint, opt, xml),
+            None => xml.write_svg_attribute(AId::TextDecorationFill, "none"),
+        }
+    }
+
+    if style.stroke != span.stroke {
+        match style.stroke {
+            Some(ref stroke) => write_paint(AId::TextDecorationStroke, &stroke.paint, opt, xml),
+            None => xml.write_svg_attribute(AId::TextDecorationStroke, "none"),
+        }
+    }
+}
+
+fn first_decoration_style(decoration: &TextDecoration) -> Option<&TextDecorationStyle> {
+    decoration
+        .underline
+        .as_ref()
+        .or(decoration.overline.as_ref())
+        .or(decoration.line_through.as_ref())
+}
+
+fn write_baseline_shift(shift: BaselineShift, xml: &mut XmlWriter) {
+    match shift {
+        BaselineShift::Baseline => xml.write_svg_attribute(AId::BaselineShift, "baseline"),
+        BaselineShift::Subscript => xml.write_svg_attribute(AId::BaselineShift, "sub"),
+        BaselineShift::Superscript => xml.write_svg_attribute(AId::BaselineShift, "super"),
+        BaselineShift::Number(n) => xml.write_svg_attribute(AId::BaselineShift, &n),
+    }
 }
 
 fn write_fill(fill: &Option<Fill>, is_clip_path: bool, opt: &XmlOptions, xml: &mut XmlWriter) {
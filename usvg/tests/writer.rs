@@ -0,0 +1,577 @@
+use usvg::{NodeKind, TextFlow, TreeParsing, TreeWriting};
+
+fn first_text(tree: &usvg::Tree) -> usvg::Text {
+    for node in tree.root.descendants() {
+        if let NodeKind::Text(ref text) = *node.borrow() {
+            return text.clone();
+        }
+    }
+
+    panic!("no text");
+}
+
+#[test]
+fn text_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'
+         xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <path id='path1' d='M 20 150 L 180 150'/>
+        <text x='10 20' y='30' dy='0 0 5' rotate='15' font-family='Noto Sans, serif'
+              font-size='20' text-anchor='middle' text-decoration='underline'>
+            A &amp; <tspan fill='green' font-weight='bold' baseline-shift='sub'>B</tspan>
+            <textPath xlink:href='#path1' startOffset='10'>C</textPath>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    assert_eq!(text.chunks.len(), text2.chunks.len());
+    assert_eq!(text.rotate, text2.rotate);
+    assert_eq!(text.positions.len(), text2.positions.len());
+    for (p1, p2) in text.positions.iter().zip(&text2.positions) {
+        assert_eq!(p1.x, p2.x);
+        assert_eq!(p1.y, p2.y);
+        assert_eq!(p1.dy.unwrap_or(0.0), p2.dy.unwrap_or(0.0));
+    }
+
+    for (c1, c2) in text.chunks.iter().zip(&text2.chunks) {
+        assert_eq!(c1.text, c2.text);
+        assert_eq!(c1.x, c2.x);
+        assert_eq!(c1.y, c2.y);
+        assert_eq!(c1.anchor, c2.anchor);
+
+        match (&c1.text_flow, &c2.text_flow) {
+            (TextFlow::Linear, TextFlow::Linear) => {}
+            (TextFlow::Path(p1), TextFlow::Path(p2)) => {
+                assert_eq!(p1.start_offset, p2.start_offset);
+                assert_eq!(p1.path.points(), p2.path.points());
+            }
+            _ => panic!("text flow mismatch"),
+        }
+
+        assert_eq!(c1.spans.len(), c2.spans.len());
+        for (s1, s2) in c1.spans.iter().zip(&c2.spans) {
+            assert_eq!((s1.start, s1.end), (s2.start, s2.end));
+            assert_eq!(s1.font, s2.font);
+            assert_eq!(s1.font_size, s2.font_size);
+            assert_eq!(s1.fill, s2.fill);
+            assert_eq!(s1.stroke, s2.stroke);
+            assert_eq!(s1.decoration, s2.decoration);
+            assert_eq!(s1.baseline_shift, s2.baseline_shift);
+        }
+    }
+}
+
+#[test]
+fn bidi_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' direction='rtl'>
+            A <tspan unicode-bidi='isolate' direction='ltr'>B <tspan>C</tspan></tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    assert_eq!(text.chunks.len(), text2.chunks.len());
+    for (c1, c2) in text.chunks.iter().zip(&text2.chunks) {
+        assert_eq!(c1.direction, usvg::TextDirection::RightToLeft);
+        assert_eq!(c1.direction, c2.direction);
+
+        assert_eq!(c1.spans.len(), c2.spans.len());
+        for (s1, s2) in c1.spans.iter().zip(&c2.spans) {
+            assert_eq!(s1.direction, s2.direction);
+            assert_eq!(s1.unicode_bidi, s2.unicode_bidi);
+        }
+    }
+}
+
+#[test]
+fn wrapped_text_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' inline-size='100' white-space='pre-line' line-height='1.5'>A
+B <tspan font-size='20'>C</tspan></text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+    assert_eq!(text.chunks[0].text, "A\nB C");
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    assert_eq!(text.inline_size, text2.inline_size);
+    assert_eq!(text.white_space, text2.white_space);
+    assert_eq!(text.chunks[0].text, text2.chunks[0].text);
+
+    let spans = text.chunks[0].spans.iter();
+    for (s1, s2) in spans.zip(&text2.chunks[0].spans) {
+        assert_eq!(s1.line_height, s2.line_height);
+    }
+}
+
+#[test]
+fn shape_inside_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <rect id='rect1' x='20' y='20' width='160' height='160'/>
+        <circle id='circle1' cx='100' cy='100' r='20' transform='translate(10 0)'/>
+        <text shape-inside='url(#rect1)' shape-subtract='url(#circle1)'
+              shape-padding='5' shape-margin='2'>Some text</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+    let shape_inside = text.shape_inside.clone().unwrap();
+    assert_eq!(shape_inside.shapes.len(), 1);
+    assert_eq!(shape_inside.subtract.len(), 1);
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+    let shape_inside2 = text2.shape_inside.unwrap();
+
+    assert_eq!(shape_inside.padding, shape_inside2.padding);
+    assert_eq!(shape_inside.margin, shape_inside2.margin);
+
+    let shapes = shape_inside.shapes.iter().chain(&shape_inside.subtract);
+    let shapes2 = shape_inside2.shapes.iter().chain(&shape_inside2.subtract);
+    assert_eq!(shapes.clone().count(), shapes2.clone().count());
+    for (s1, s2) in shapes.zip(shapes2) {
+        assert_eq!(s1.path.points().len(), s2.path.points().len());
+        for (p1, p2) in s1.path.points().iter().zip(s2.path.points()) {
+            assert!((p1 - p2).abs() < 0.001);
+        }
+    }
+}
+
+#[test]
+fn font_features_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' font-variant='small-caps oldstyle-nums'
+              font-feature-settings='\"liga\" 0, \"ss01\"'>
+            A <tspan font-variant-numeric='normal' font-variant-position='super'>B</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    let spans = &text.chunks[0].spans;
+    assert!(spans[0].small_caps);
+    assert_eq!(
+        spans[0].font_features,
+        vec![
+            usvg::FontFeature::new(b"onum", 1),
+            usvg::FontFeature::new(b"liga", 0),
+            usvg::FontFeature::new(b"ss01", 1),
+        ]
+    );
+    assert_eq!(
+        spans[1].font_features,
+        vec![
+            usvg::FontFeature::new(b"sups", 1),
+            usvg::FontFeature::new(b"liga", 0),
+            usvg::FontFeature::new(b"ss01", 1),
+        ]
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    for (s1, s2) in spans.iter().zip(&text2.chunks[0].spans) {
+        assert_eq!(s1.small_caps, s2.small_caps);
+        assert_eq!(s1.font_features, s2.font_features);
+    }
+}
+
+#[test]
+fn font_variations_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' font-weight='650' font-stretch='170%'
+              font-variation-settings='\"wght\" 300, \"XHGT\" 0.5'>
+            A <tspan font-optical-sizing='none' font-variation-settings='normal'>B</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    let spans = &text.chunks[0].spans;
+    assert_eq!(spans[0].font.weight, 650);
+    assert_eq!(spans[0].font.stretch, usvg::FontStretch::ExtraExpanded);
+    assert!(spans[0].optical_sizing);
+    assert_eq!(
+        spans[0].font_variations,
+        vec![
+            usvg::FontVariation::new(b"wdth", 170.0),
+            usvg::FontVariation::new(b"wght", 300.0),
+            usvg::FontVariation::new(b"XHGT", 0.5),
+        ]
+    );
+    assert!(!spans[1].optical_sizing);
+    assert_eq!(
+        spans[1].font_variations,
+        vec![usvg::FontVariation::new(b"wdth", 170.0)]
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    for (s1, s2) in spans.iter().zip(&text2.chunks[0].spans) {
+        assert_eq!(s1.font, s2.font);
+        assert_eq!(s1.optical_sizing, s2.optical_sizing);
+        assert_eq!(s1.font_variations, s2.font_variations);
+    }
+}
+
+#[test]
+fn font_synthesis_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' font-synthesis='style small-caps'>
+            A <tspan font-synthesis='none'>B</tspan> <tspan font-synthesis='weight style'>C</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    let synthesis: Vec<_> = text.chunks[0]
+        .spans
+        .iter()
+        .map(|span| (span.font.synthesis.weight, span.font.synthesis.style))
+        .collect();
+    assert_eq!(
+        synthesis,
+        vec![(false, true), (false, false), (false, true), (true, true),]
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    for (s1, s2) in text.chunks[0].spans.iter().zip(&text2.chunks[0].spans) {
+        assert_eq!(s1.font, s2.font);
+    }
+}
+
+#[test]
+fn text_transform_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' dx='1 2 3 4 5' rotate='5 10' text-transform='uppercase'>straße
+            <tspan xml:lang='tr' x='50'>istanbul</tspan>
+            <tspan text-transform='capitalize'>don't ǆungla</tspan>
+            <tspan text-transform='lowercase'>ΟΔΟΣ</tspan>
+            <tspan text-transform='full-width'>A1</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    assert_eq!(text.chunks[0].text, "STRASSE ");
+    assert_eq!(text.chunks[1].text, "İSTANBUL Don't ǅungla οδος Ａ１");
+
+    // `ß` is replaced by two characters, so later positions are shifted by one.
+    let chars_count: usize = text.chunks.iter().map(|c| c.text.chars().count()).sum();
+    assert_eq!(text.positions.len(), chars_count);
+    assert_eq!(text.positions[4].dx, Some(5.0));
+    assert_eq!(text.positions[5].dx, None);
+    assert_eq!(text.positions[8].x, Some(50.0));
+    assert_eq!(text.rotate.len(), chars_count);
+    assert_eq!(
+        &text.rotate[..7],
+        &[5.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0]
+    );
+
+    let spans = &text.chunks[1].spans;
+    assert_eq!(
+        &text.chunks[1].text[spans[0].start..spans[0].end],
+        "İSTANBUL"
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    for (c1, c2) in text.chunks.iter().zip(&text2.chunks) {
+        assert_eq!(c1.text, c2.text);
+    }
+    assert_eq!(text.positions.len(), text2.positions.len());
+}
+
+#[test]
+fn font_faces_round_trip() {
+    use base64::Engine;
+
+    let font = include_bytes!("../../tests/fonts/WebTest-Regular.woff2");
+    let svg = format!(
+        "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <style>
+            @font-face {{
+                font-family: 'My Font';
+                font-style: italic;
+                font-weight: bold;
+                unicode-range: U+41-43, U+2??;
+                src: url(missing.woff2), url(data:font/woff2;base64,{}) format('woff2');
+            }}
+        </style>
+        <text x='10' y='30' font-family='My Font'>A</text>
+    </svg>
+    ",
+        base64::engine::general_purpose::STANDARD.encode(font)
+    );
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(tree.font_faces.len(), 1);
+
+    let face = &tree.font_faces[0];
+    assert_eq!(face.family, "My Font");
+    assert_eq!(face.style, usvg::FontStyle::Italic);
+    assert_eq!(face.weight, 700);
+    assert_eq!(face.unicode_range, vec![0x41..=0x43, 0x200..=0x2FF]);
+    // Decoded into TrueType.
+    assert!(face.data.starts_with(&[0, 1, 0, 0]));
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    assert_eq!(tree2.font_faces.len(), 1);
+
+    let face2 = &tree2.font_faces[0];
+    assert_eq!(face.family, face2.family);
+    assert_eq!(face.style, face2.style);
+    assert_eq!(face.weight, face2.weight);
+    assert_eq!(face.unicode_range, face2.unicode_range);
+    assert_eq!(face.data, face2.data);
+}
+
+#[test]
+fn svg_fonts_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <font id='font1' horiz-adv-x='500'>
+            <font-face font-family='Shapes' font-weight='bold' units-per-em='2048' descent='-400'/>
+            <glyph unicode='&amp;' glyph-name='amp' d='M 0 0 L 100 0 L 100 100 Z'/>
+            <glyph unicode='fi' arabic-form='initial' lang='en, de' horiz-adv-x='700'/>
+            <glyph unicode='B' glyph-name='amp'/>
+            <hkern u1='&amp;,U+0066-0067' g2='amp' k='50'/>
+        </font>
+        <text x='10' y='30' font-family='Shapes' xml:lang='de'>fi</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    assert_eq!(tree.svg_fonts.len(), 1);
+
+    let font = &tree.svg_fonts[0];
+    assert_eq!(font.family, "Shapes");
+    assert_eq!(font.weight, 700);
+    assert_eq!(font.units_per_em, 2048.0);
+    assert_eq!(font.ascent, 2048.0);
+    assert_eq!(font.descent, 400.0);
+    assert_eq!(font.glyphs.len(), 3);
+    assert_eq!(font.glyphs[1].unicode, "fi");
+    assert_eq!(font.glyphs[1].arabic_form, Some(usvg::ArabicForm::Initial));
+    assert_eq!(font.glyphs[1].languages, vec!["en", "de"]);
+    assert_eq!(font.glyphs[1].horiz_adv_x, 700.0);
+    assert_eq!(font.glyphs[2].horiz_adv_x, 500.0);
+    assert_eq!(font.kerning.len(), 1);
+    // A ligature doesn't match a unicode range.
+    assert_eq!(font.kerning[0].first, vec![0]);
+    assert_eq!(font.kerning[0].second, vec![0, 2]);
+    assert_eq!(
+        first_text(&tree).chunks[0].spans[0].lang.as_deref(),
+        Some("de")
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    assert_eq!(tree2.svg_fonts.len(), 1);
+
+    let font2 = &tree2.svg_fonts[0];
+    assert_eq!(font.family, font2.family);
+    assert_eq!(font.weight, font2.weight);
+    assert_eq!(font.descent, font2.descent);
+    assert_eq!(font.glyphs.len(), font2.glyphs.len());
+    for (glyph, glyph2) in font.glyphs.iter().zip(&font2.glyphs) {
+        assert_eq!(glyph.unicode, glyph2.unicode);
+        assert_eq!(glyph.arabic_form, glyph2.arabic_form);
+        assert_eq!(glyph.languages, glyph2.languages);
+        assert_eq!(glyph.horiz_adv_x, glyph2.horiz_adv_x);
+        assert_eq!(glyph.path.is_some(), glyph2.path.is_some());
+    }
+    // Glyph names are not unique, so kerning uses generated ones.
+    assert_eq!(font2.kerning[0].first, font.kerning[0].first);
+    assert_eq!(font2.kerning[0].second, font.kerning[0].second);
+    assert_eq!(
+        first_text(&tree2).chunks[0].spans[0].lang.as_deref(),
+        Some("de")
+    );
+}
+
+#[test]
+fn text_decoration_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' font-size='20'><tspan
+            text-decoration='underline wavy green 10%'
+            text-underline-offset='2' text-underline-position='under'>A</tspan><tspan
+            text-decoration-line='overline line-through' text-decoration-style='dotted'
+            text-decoration-fill='blue' text-decoration-stroke='red'>B</tspan><tspan
+            text-decoration='underline rgb(0, 0, 255)'
+            text-decoration-color='yellow' text-decoration-style='bogus'>C</tspan></text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    let spans: Vec<_> = text.chunks.iter().flat_map(|chunk| &chunk.spans).collect();
+
+    let underline = spans[0].decoration.underline.as_ref().unwrap();
+    assert_eq!(underline.line_style, usvg::TextDecorationLineStyle::Wavy);
+    assert_eq!(underline.thickness, Some(2.0));
+    assert_eq!(underline.underline_offset, Some(2.0));
+    assert!(underline.underline_position.under);
+    assert_eq!(
+        underline.fill.as_ref().map(|f| &f.paint),
+        Some(&usvg::Paint::Color(usvg::Color::new_rgb(0, 128, 0)))
+    );
+
+    let overline = spans[1].decoration.overline.as_ref().unwrap();
+    assert!(spans[1].decoration.underline.is_none());
+    assert_eq!(overline.line_style, usvg::TextDecorationLineStyle::Dotted);
+    assert_eq!(
+        overline.fill.as_ref().map(|f| &f.paint),
+        Some(&usvg::Paint::Color(usvg::Color::new_rgb(0, 0, 255)))
+    );
+    assert_eq!(
+        overline.stroke.as_ref().map(|s| &s.paint),
+        Some(&usvg::Paint::Color(usvg::Color::new_rgb(255, 0, 0)))
+    );
+
+    // Longhands override the shorthand, while invalid longhands are ignored.
+    let underline = spans[2].decoration.underline.as_ref().unwrap();
+    assert_eq!(underline.line_style, usvg::TextDecorationLineStyle::Solid);
+    assert_eq!(
+        underline.fill.as_ref().map(|f| &f.paint),
+        Some(&usvg::Paint::Color(usvg::Color::new_rgb(255, 255, 0)))
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    let spans2: Vec<_> = text2.chunks.iter().flat_map(|chunk| &chunk.spans).collect();
+    assert_eq!(spans.len(), spans2.len());
+    for (s1, s2) in spans.iter().zip(spans2) {
+        assert_eq!(s1.decoration, s2.decoration);
+    }
+}
+
+#[test]
+fn text_orientation_round_trip() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='100' y='20' writing-mode='tb' text-orientation='upright'>
+            A <tspan text-orientation='sideways'>B</tspan> <tspan glyph-orientation-vertical='auto'>C</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+
+    let orientations: Vec<_> = text.chunks[0]
+        .spans
+        .iter()
+        .map(|span| span.text_orientation)
+        .collect();
+    assert_eq!(
+        orientations,
+        vec![
+            usvg::TextOrientation::Upright,
+            usvg::TextOrientation::Sideways,
+            usvg::TextOrientation::Upright,
+            usvg::TextOrientation::Mixed,
+        ]
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+
+    for (s1, s2) in text.chunks[0].spans.iter().zip(&text2.chunks[0].spans) {
+        assert_eq!(s1.text_orientation, s2.text_orientation);
+    }
+}
+
+#[test]
+fn font_family_quoting_round_trip() {
+    let svg = r#"
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <text x='10' y='30' font-family="'Font, Inc.', &quot;Tom's Font&quot;, 'A \'B\' &quot;C&quot;', Noto   Sans, serif">Text</text>
+    </svg>
+    "#;
+
+    let opt = usvg::Options::default();
+    let tree = usvg::Tree::from_str(svg, &opt).unwrap();
+    let text = first_text(&tree);
+    assert_eq!(
+        text.chunks[0].spans[0].font.families,
+        vec![
+            "Font, Inc.",
+            "Tom's Font",
+            "A 'B' \"C\"",
+            "Noto Sans",
+            "serif"
+        ]
+    );
+
+    let output = tree.to_string(&usvg::XmlOptions::default());
+    let tree2 = usvg::Tree::from_str(&output, &opt).unwrap();
+    let text2 = first_text(&tree2);
+    assert_eq!(
+        text.chunks[0].spans[0].font.families,
+        text2.chunks[0].spans[0].font.families
+    );
+}